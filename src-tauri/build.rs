@@ -1,5 +1,6 @@
+use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use tauri_build::{AppManifest, Attributes};
 
@@ -138,6 +139,54 @@ fn write_capability(commands: &[String]) {
     }
 }
 
+/// 托盘翻译目录，路径相对于 src-tauri/。
+const TRAY_LOCALES_DIR: &str = "../src/i18n/tray";
+
+/// 为托盘翻译目录中的每个 `<code>.json` 生成一项 `TrayLocale`，写入
+/// `$OUT_DIR/tray_locales.rs`，由 `src/tray/i18n.rs` 通过 `include!` 引入。
+///
+/// 新增语言只需放入新的 JSON 文件，无需修改 Rust 代码。
+fn write_tray_locales() {
+    let dir = Path::new(&env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is not set"))
+        .join(TRAY_LOCALES_DIR);
+    let mut codes: Vec<String> = fs::read_dir(&dir)
+        .expect("failed to read the tray locale directory")
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            path.file_stem()?.to_str().map(str::to_string)
+        })
+        .collect();
+    codes.sort();
+
+    assert!(
+        codes.iter().any(|code| code == "en"),
+        "the English tray catalog is the reference and must exist"
+    );
+    let entries = codes
+        .iter()
+        .map(|code| {
+            assert!(
+                code.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+                "tray locale file name `{code}.json` is not a locale code"
+            );
+            let path = dir.join(format!("{code}.json"));
+            format!(
+                "    TrayLocale {{\n        code: {:?},\n        source: include_str!({:?}),\n    }},",
+                code,
+                path.display().to_string()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let out =
+        PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR is not set")).join("tray_locales.rs");
+    fs::write(out, format!("&[\n{entries}\n]\n")).expect("failed to write tray locales");
+}
+
 fn main() {
     // 告诉 Cargo 当这些文件变化时需要重新编译
     // 这样 include_str! 宏就能读取到最新版本的文件
@@ -146,9 +195,12 @@ fn main() {
     println!("cargo:rerun-if-changed=error-404.html");
     // 命令清单从 main.rs 解析，main.rs 变化需重跑 build.rs
     println!("cargo:rerun-if-changed=src/main.rs");
+    // 目录变化（新增或修改翻译文件）时重新生成托盘语言列表
+    println!("cargo:rerun-if-changed={}", TRAY_LOCALES_DIR);
 
     let commands = extract_commands();
     write_capability(&commands);
+    write_tray_locales();
 
     // AppManifest::commands 需要 &'static [&'static str]，泄漏成静态生命周期
     let leaked: Vec<&'static str> = commands
@@ -33,6 +33,7 @@ pub fn setup_application(app: &mut App) -> Result<(), Box<dyn std::error::Error>
         .map_or(false, |arg| arg.contains("/pin"));
 
     let app_handle = app.handle().clone();
+    crate::host_registry::restore_active_host();
     windows::register_agent_restart();
     crate::desktop_settings::apply_startup_settings(&app_handle, &desktop_settings);
     // 更新偏好属于应用级状态，必须在 `--hidden` 的纯托盘代理模式下也立即注册。
@@ -87,6 +88,11 @@ pub fn setup_application(app: &mut App) -> Result<(), Box<dyn std::error::Error>
         crate::file_mapping::dispatch_cli_quick_share(quick_share_folder_paths);
     }
 
+    crate::host_registry::start_health_monitor(app.handle().clone());
+    crate::session_history::start_detail_poller();
+    crate::bitrate_controller::start(app.handle().clone());
+    crate::core_watchdog::start(app.handle().clone());
+
     // 启动 WebView 心跳监控（检测渲染进程崩溃并自动恢复）
     windows::start_heartbeat_monitor(app.handle().clone());
 
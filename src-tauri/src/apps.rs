@@ -0,0 +1,491 @@
+//! Typed view of the Core's `apps.json`.
+//!
+//! Only the fields the GUI acts on are typed; everything else is kept in
+//! `extra` and written back untouched, so Core options the GUI does not know
+//! about survive an edit. Flags keep the spelling they were read with because
+//! the WebUI stores them as `"true"`/`"false"` strings.
+//!
+//! Edits go through the local Core's `/api/apps`, like the WebUI's, so the
+//! running Core picks them up and the simulated Core never touches the real
+//! file. Apps are addressed by name; indexes shift under concurrent edits.
+
+use log::info;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config_snapshots::{self, SnapshotReason};
+use crate::core_api::{CoreApiError, CoreClient, Envelope};
+use crate::sunshine;
+
+const APPS_FILE: &str = "apps.json";
+
+/// Serializes the GUI's fetch-edit-save cycles against the Core.
+static APPS_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// A boolean stored either as a JSON bool or as a string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Flag {
+    pub value: bool,
+    as_string: bool,
+}
+
+impl Flag {
+    pub fn is_set(flag: &Option<Flag>) -> bool {
+        flag.is_some_and(|flag| flag.value)
+    }
+}
+
+impl Serialize for Flag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.as_string {
+            serializer.serialize_str(if self.value { "true" } else { "false" })
+        } else {
+            serializer.serialize_bool(self.value)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Flag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (value, as_string) = match Value::deserialize(deserializer)? {
+            Value::Bool(value) => (value, false),
+            Value::Number(value) => (value.as_i64().unwrap_or(0) != 0, false),
+            Value::String(value) => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "enabled" => (true, true),
+                "false" | "0" | "no" | "disabled" | "" => (false, true),
+                other => {
+                    return Err(serde::de::Error::custom(format!(
+                        "expected a boolean, got '{other}'"
+                    )));
+                }
+            },
+            other => {
+                return Err(serde::de::Error::custom(format!(
+                    "expected a boolean, got {other}"
+                )));
+            }
+        };
+        Ok(Self { value, as_string })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrepCmd {
+    #[serde(rename = "do", default)]
+    pub do_cmd: String,
+    #[serde(default)]
+    pub undo: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevated: Option<Flag>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MenuCmd {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub cmd: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevated: Option<Flag>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct App {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cmd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// A cover in the covers directory, an absolute path, or `desktop`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevated: Option<Flag>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_detach: Option<Flag>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_all: Option<Flag>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_global_prep_cmd: Option<Flag>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prep_cmd: Option<Vec<PrepCmd>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detached: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub menu_cmd: Option<Vec<MenuCmd>>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl App {
+    pub fn cmd(&self) -> &str {
+        self.cmd.as_deref().map(str::trim).unwrap_or_default()
+    }
+
+    pub fn working_dir(&self) -> Option<&str> {
+        self.working_dir
+            .as_deref()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+    }
+
+    pub fn prep_cmds(&self) -> &[PrepCmd] {
+        self.prep_cmd.as_deref().unwrap_or_default()
+    }
+
+    /// Detached commands that are not blank.
+    pub fn detached_cmds(&self) -> impl Iterator<Item = &str> {
+        self.detached
+            .iter()
+            .flatten()
+            .map(|cmd| cmd.trim())
+            .filter(|cmd| !cmd.is_empty())
+    }
+
+    /// The cover file name and, when the path has directories, the full path.
+    pub fn cover_references(&self) -> Vec<String> {
+        let Some(image_path) = self
+            .image_path
+            .as_deref()
+            .filter(|path| !path.is_empty() && *path != "desktop")
+        else {
+            return Vec::new();
+        };
+        let filename = image_path.rsplit(['/', '\\']).next().unwrap_or(image_path);
+        if filename.is_empty() || filename == "desktop" {
+            return Vec::new();
+        }
+
+        let mut references = vec![filename.to_string()];
+        if filename != image_path {
+            references.push(image_path.to_string());
+        }
+        references
+    }
+
+    /// Check the fields the Core would otherwise only reject at launch time.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("App name cannot be empty".to_string());
+        }
+        check_command("cmd", self.cmd())?;
+        for cmd in self.detached_cmds() {
+            check_command("detached", cmd)?;
+        }
+        for prep in self.prep_cmds() {
+            check_command("prep-cmd do", &prep.do_cmd)?;
+            check_command("prep-cmd undo", &prep.undo)?;
+        }
+        for menu in self.menu_cmd.iter().flatten() {
+            if menu.name.trim().is_empty() {
+                return Err("menu-cmd name cannot be empty".to_string());
+            }
+            check_command("menu-cmd", &menu.cmd)?;
+        }
+
+        if let Some(dir) = self.working_dir().filter(|dir| !Path::new(dir).is_dir()) {
+            return Err(format!("working-dir does not exist: {dir}"));
+        }
+        let missing_image = self
+            .image_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|image| Path::new(image).is_absolute() && !Path::new(image).is_file());
+        if let Some(image) = missing_image {
+            return Err(format!("image-path does not exist: {image}"));
+        }
+        Ok(())
+    }
+}
+
+/// Reject commands the Core's argument splitter would mangle.
+fn check_command(field: &str, cmd: &str) -> Result<(), String> {
+    if cmd.contains('\0') || cmd.contains('\n') {
+        return Err(format!("{field} must be a single line: {cmd}"));
+    }
+    if cmd.matches('"').count() % 2 != 0 {
+        return Err(format!("{field} has an unclosed quote: {cmd}"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppsFile {
+    #[serde(default)]
+    pub apps: Vec<App>,
+    /// `env` and any other top-level keys.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+pub fn apps_path() -> PathBuf {
+    sunshine::config_dir().join(APPS_FILE)
+}
+
+/// A missing or blank file reads as no apps.
+pub fn load_apps_file(path: &Path) -> Result<AppsFile, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AppsFile::default()),
+        Err(e) => return Err(format!("读取 apps.json 失败: {}", e)),
+    };
+    if content.trim().is_empty() {
+        return Ok(AppsFile::default());
+    }
+    serde_json::from_str(&content).map_err(|e| format!("解析 apps.json 失败: {}", e))
+}
+
+/// An app the WebUI reports as edited: `index` is its position in `apps`, or
+/// -1 to append it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct EditedApp {
+    #[serde(flatten)]
+    app: App,
+    index: i64,
+}
+
+/// Body of the Core's `POST /api/apps`, in the shape the WebUI sends. Without
+/// `editApp` the Core replaces its list with `apps`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveAppsRequest<'a> {
+    apps: &'a [App],
+    edit_app: Option<EditedApp>,
+}
+
+async fn fetch_apps(client: &CoreClient) -> Result<AppsFile, CoreApiError> {
+    client.get("api/apps").send().await
+}
+
+/// Fetch the local Core's apps, let `edit` turn them into a save request and
+/// hand it back to the Core, which rewrites `apps.json` and refreshes the
+/// list it serves. The config is snapshotted right before the save.
+async fn edit_apps(
+    detail: String,
+    edit: impl FnOnce(&mut Vec<App>) -> Result<Option<EditedApp>, String>,
+) -> Result<(), String> {
+    let _guard = APPS_LOCK.lock().await;
+    let client = CoreClient::local().await?;
+    let mut apps = fetch_apps(&client).await?.apps;
+    let edit_app = edit(&mut apps)?;
+    config_snapshots::snapshot_before_write(SnapshotReason::AppsEdit, Some(detail))?;
+    client
+        .post("api/apps")
+        .json(&SaveAppsRequest {
+            apps: &apps,
+            edit_app,
+        })?
+        .envelope(Envelope::Status)
+        .send_value()
+        .await?;
+    Ok(())
+}
+
+fn find_app(apps: &[App], name: &str) -> Result<usize, String> {
+    apps.iter()
+        .position(|app| app.name == name)
+        .ok_or_else(|| format!("App '{name}' does not exist"))
+}
+
+/// Names identify apps, so a second app may not take one already in use.
+fn check_name_free(apps: &[App], name: &str, except: Option<usize>) -> Result<(), String> {
+    let taken = apps
+        .iter()
+        .enumerate()
+        .any(|(index, app)| app.name == name && Some(index) != except);
+    if taken {
+        Err(format!("An app named '{name}' already exists"))
+    } else {
+        Ok(())
+    }
+}
+
+fn reorder(apps: &mut Vec<App>, order: &[String]) -> Result<(), String> {
+    let mut slots: Vec<Option<App>> = apps.drain(..).map(Some).collect();
+    let mut reordered = Vec::with_capacity(slots.len());
+    for name in order {
+        let slot = slots
+            .iter_mut()
+            .find(|slot| slot.as_ref().is_some_and(|app| &app.name == name));
+        match slot.and_then(Option::take) {
+            Some(app) => reordered.push(app),
+            None => break,
+        }
+    }
+    let complete = reordered.len() == order.len() && slots.iter().all(Option::is_none);
+    if !complete {
+        apps.extend(reordered);
+        apps.extend(slots.into_iter().flatten());
+        return Err("Reorder must list every app name exactly once".to_string());
+    }
+    *apps = reordered;
+    Ok(())
+}
+
+/// The apps the local Core currently serves.
+#[tauri::command]
+pub async fn list_apps() -> Result<Vec<App>, String> {
+    let _guard = APPS_LOCK.lock().await;
+    Ok(fetch_apps(&CoreClient::local().await?).await?.apps)
+}
+
+#[tauri::command]
+pub async fn create_app(app: App) -> Result<(), String> {
+    app.validate()?;
+    let name = app.name.clone();
+    edit_apps(format!("add {name}"), |apps| {
+        check_name_free(apps, &app.name, None)?;
+        Ok(Some(EditedApp { app, index: -1 }))
+    })
+    .await?;
+    info!("➕ 已添加应用 {}", name);
+    Ok(())
+}
+
+/// Replace the app called `name`, which `app` may rename.
+#[tauri::command]
+pub async fn update_app(name: String, app: App) -> Result<(), String> {
+    app.validate()?;
+    edit_apps(format!("update {name}"), |apps| {
+        let index = find_app(apps, &name)?;
+        check_name_free(apps, &app.name, Some(index))?;
+        Ok(Some(EditedApp {
+            app,
+            index: index as i64,
+        }))
+    })
+    .await?;
+    info!("✏️ 已更新应用 {}", name);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_app(name: String) -> Result<(), String> {
+    edit_apps(format!("delete {name}"), |apps| {
+        apps.remove(find_app(apps, &name)?);
+        Ok(None)
+    })
+    .await?;
+    info!("🗑️ 已删除应用 {}", name);
+    Ok(())
+}
+
+/// `order` lists every app name in the new order.
+#[tauri::command]
+pub async fn reorder_apps(order: Vec<String>) -> Result<(), String> {
+    edit_apps("reorder".to_string(), |apps| {
+        reorder(apps, &order)?;
+        Ok(None)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+  "env": { "PATH": "$(PATH)" },
+  "apps": [
+    {
+      "name": "Desktop",
+      "image-path": "desktop.png",
+      "elevated": "false",
+      "uuid": "A1",
+      "prep-cmd": [{ "do": "a.bat", "undo": "b.bat", "elevated": false, "id": 7 }]
+    },
+    { "name": "Steam", "detached": ["steam://open/bigpicture"], "auto-detach": true }
+  ]
+}"#;
+
+    #[test]
+    fn apps_round_trip_keeps_unknown_fields_and_flag_spelling() {
+        let file: AppsFile = serde_json::from_str(SAMPLE).unwrap();
+        assert_eq!(file.apps.len(), 2);
+        assert_eq!(file.apps[0].elevated.map(|flag| flag.value), Some(false));
+        assert!(Flag::is_set(&file.apps[1].auto_detach));
+
+        let original: Value = serde_json::from_str(SAMPLE).unwrap();
+        assert_eq!(serde_json::to_value(&file).unwrap(), original);
+    }
+
+    #[test]
+    fn app_validation_checks_commands_and_paths() {
+        let mut app = App {
+            name: "Game".to_string(),
+            cmd: Some("\"C:\\Games\\game.exe\" -windowed".to_string()),
+            ..App::default()
+        };
+        assert!(app.validate().is_ok());
+
+        app.cmd = Some("\"C:\\Games\\game.exe -windowed".to_string());
+        assert!(app.validate().unwrap_err().contains("unclosed quote"));
+
+        app.cmd = Some("game.exe".to_string());
+        app.working_dir = Some(
+            std::env::temp_dir()
+                .join(format!("sunshine-missing-{}", uuid::Uuid::new_v4()))
+                .display()
+                .to_string(),
+        );
+        assert!(app.validate().unwrap_err().contains("working-dir"));
+
+        app.working_dir = Some(std::env::temp_dir().display().to_string());
+        assert!(app.validate().is_ok());
+
+        app.name = " ".to_string();
+        assert!(app.validate().unwrap_err().contains("name"));
+    }
+
+    #[test]
+    fn apps_are_addressed_by_name() {
+        let file: AppsFile = serde_json::from_str(SAMPLE).unwrap();
+        let mut apps = file.apps;
+        let order = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+
+        reorder(&mut apps, &order(&["Steam", "Desktop"])).unwrap();
+        assert_eq!(apps[0].name, "Steam");
+        assert!(reorder(&mut apps, &order(&["Steam", "Steam"])).is_err());
+        assert!(reorder(&mut apps, &order(&["Steam"])).is_err());
+        assert!(reorder(&mut apps, &order(&["Steam", "Missing"])).is_err());
+        assert_eq!(apps[0].name, "Steam");
+        assert_eq!(apps[1].cover_references(), vec!["desktop.png"]);
+
+        assert_eq!(find_app(&apps, "Desktop"), Ok(1));
+        assert!(find_app(&apps, "desktop").is_err());
+        assert!(check_name_free(&apps, "Desktop", Some(1)).is_ok());
+        assert!(check_name_free(&apps, "Desktop", Some(0)).is_err());
+    }
+
+    #[test]
+    fn edits_are_sent_in_the_webui_shape() {
+        let app = App {
+            name: "Game".to_string(),
+            ..App::default()
+        };
+        let request = SaveAppsRequest {
+            apps: std::slice::from_ref(&app),
+            edit_app: Some(EditedApp {
+                app: app.clone(),
+                index: -1,
+            }),
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "apps": [{ "name": "Game" }],
+                "editApp": { "name": "Game", "index": -1 },
+            })
+        );
+    }
+}
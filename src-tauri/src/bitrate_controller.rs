@@ -0,0 +1,299 @@
+//! Opt-in controller that adjusts each client's bitrate from host load.
+//!
+//! While enabled it polls `/api/perf/current` and `/api/runtime/sessions` on
+//! this machine's Core, feeds the joined samples to [`BitrateController`] and
+//! applies its decisions through the runtime bitrate API. Settings live in
+//! `<config>/Sunshine GUI/bitrate-controller.json`, and the recent changes in
+//! `bitrate-controller-history.json` next to it so they survive a restart.
+
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+mod policy;
+
+pub(crate) use policy::BitrateControllerSettings;
+use policy::{BitrateController, BitrateDecision, MetricSample};
+
+const SETTINGS_FILE: &str = "bitrate-controller.json";
+const HISTORY_FILE: &str = "bitrate-controller-history.json";
+const CHANGE_EVENT: &str = "bitrate-controller-change";
+const MAX_RECENT_CHANGES: usize = 50;
+/// How often a disabled controller checks whether it was turned on.
+const DISABLED_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+static SETTINGS: Lazy<RwLock<BitrateControllerSettings>> =
+    Lazy::new(|| RwLock::new(load_settings()));
+static RECENT_CHANGES: Lazy<Mutex<VecDeque<AppliedChange>>> =
+    Lazy::new(|| Mutex::new(load_history()));
+static CONTROLLER_STARTED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedChange {
+    pub timestamp: String,
+    pub client_name: String,
+    pub from_kbps: u32,
+    pub to_kbps: u32,
+    pub reason: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitrateControllerStatus {
+    settings: BitrateControllerSettings,
+    /// Newest first.
+    recent_changes: Vec<AppliedChange>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PerfLatency {
+    p95_ms: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PerfPipeline {
+    encode: PerfLatency,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PerfSession {
+    session_id: u64,
+    active: bool,
+    fps: Option<f64>,
+    host_latency: PerfLatency,
+    pipeline: PerfPipeline,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PerfSnapshot {
+    sessions: Vec<PerfSession>,
+}
+
+impl PerfSession {
+    fn budget_usage_percent(&self) -> Option<f64> {
+        let frame_budget_ms = 1000.0 / self.fps.unwrap_or(60.0).max(1.0);
+        [self.host_latency.p95_ms, self.pipeline.encode.p95_ms]
+            .into_iter()
+            .flatten()
+            .map(|ms| ms / frame_budget_ms * 100.0)
+            .reduce(f64::max)
+    }
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(SETTINGS_FILE))
+}
+
+fn load_settings() -> BitrateControllerSettings {
+    let Ok(path) = settings_path() else {
+        return BitrateControllerSettings::default();
+    };
+    let Ok(text) = fs::read_to_string(path) else {
+        return BitrateControllerSettings::default();
+    };
+    serde_json::from_str::<BitrateControllerSettings>(&text)
+        .map_err(|e| e.to_string())
+        .and_then(BitrateControllerSettings::validated)
+        .unwrap_or_else(|e| {
+            warn!("Invalid bitrate controller settings, using defaults: {}", e);
+            BitrateControllerSettings::default()
+        })
+}
+
+fn save_settings(settings: &BitrateControllerSettings) -> Result<(), String> {
+    let path = settings_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+fn history_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(HISTORY_FILE))
+}
+
+/// Recent changes saved by an earlier run, newest first.
+fn load_history() -> VecDeque<AppliedChange> {
+    let Ok(path) = history_path() else {
+        return VecDeque::new();
+    };
+    let Ok(text) = fs::read_to_string(path) else {
+        return VecDeque::new();
+    };
+    let mut history: VecDeque<AppliedChange> = serde_json::from_str(&text).unwrap_or_else(|e| {
+        warn!("Invalid bitrate controller history, starting empty: {}", e);
+        VecDeque::new()
+    });
+    history.truncate(MAX_RECENT_CHANGES);
+    history
+}
+
+fn save_history(history: &VecDeque<AppliedChange>) -> Result<(), String> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(history).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+fn current_settings() -> BitrateControllerSettings {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Join runtime sessions with their perf entries.
+async fn collect_samples() -> Result<Vec<MetricSample>, String> {
+    let perf: PerfSnapshot = crate::core_api::CoreClient::local()
+        .await?
+        .with_retry(crate::core_api::RetryPolicy::NONE)
+        .get("api/perf/current")
+        .send()
+        .await?;
+    let sessions = crate::sunshine::get_local_sessions().await?;
+
+    Ok(sessions
+        .into_iter()
+        .filter(|session| session.state.eq_ignore_ascii_case("RUNNING"))
+        .map(|session| {
+            let usage = perf
+                .sessions
+                .iter()
+                .find(|perf| {
+                    perf.active && u64::try_from(session.session_id).ok() == Some(perf.session_id)
+                })
+                .and_then(PerfSession::budget_usage_percent);
+            MetricSample {
+                client_name: session.client_name,
+                bitrate_kbps: session.bitrate,
+                budget_usage_percent: usage,
+            }
+        })
+        .collect())
+}
+
+fn record_change(change: AppliedChange) {
+    let mut recent = RECENT_CHANGES.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() == MAX_RECENT_CHANGES {
+        recent.pop_back();
+    }
+    recent.push_front(change);
+    if let Err(e) = save_history(&recent) {
+        warn!("Failed to save bitrate controller history: {}", e);
+    }
+}
+
+/// Apply `decision` and report whether the Core accepted it.
+async fn apply_decision(app: &AppHandle, decision: BitrateDecision) -> bool {
+    let result =
+        crate::sunshine::change_local_bitrate(&decision.client_name, decision.to_kbps).await;
+    match &result {
+        Ok(()) => info!(
+            "📶 自动码率: {} {} -> {} Kbps ({})",
+            decision.client_name, decision.from_kbps, decision.to_kbps, decision.reason
+        ),
+        Err(e) => warn!(
+            "⚠️ 自动码率调整失败: {} {} -> {} Kbps: {}",
+            decision.client_name, decision.from_kbps, decision.to_kbps, e
+        ),
+    }
+
+    let change = AppliedChange {
+        timestamp: chrono::Local::now().to_rfc3339(),
+        client_name: decision.client_name,
+        from_kbps: decision.from_kbps,
+        to_kbps: decision.to_kbps,
+        reason: decision.reason,
+        error: result.err().map(|e| e.to_string()),
+    };
+    if let Err(e) = app.emit(CHANGE_EVENT, &change) {
+        debug!("Failed to emit bitrate controller change: {}", e);
+    }
+    let applied = change.error.is_none();
+    record_change(change);
+    applied
+}
+
+pub fn start(app: AppHandle) {
+    use std::sync::atomic::Ordering;
+
+    if CONTROLLER_STARTED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let mut controller = BitrateController::default();
+        loop {
+            let settings = current_settings();
+            if !settings.enabled {
+                controller = BitrateController::default();
+                tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+                continue;
+            }
+
+            match collect_samples().await {
+                Ok(samples) => {
+                    let now = started.elapsed().as_secs_f64();
+                    for decision in controller.step(&settings, &samples, now) {
+                        if !apply_decision(&app, decision.clone()).await {
+                            controller.change_failed(&decision);
+                        }
+                    }
+                }
+                Err(e) => debug!("Bitrate controller poll skipped: {}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(settings.poll_interval_secs)).await;
+        }
+    });
+}
+
+fn status() -> BitrateControllerStatus {
+    BitrateControllerStatus {
+        settings: current_settings(),
+        recent_changes: RECENT_CHANGES
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect(),
+    }
+}
+
+#[tauri::command]
+pub fn get_bitrate_controller() -> BitrateControllerStatus {
+    status()
+}
+
+#[tauri::command]
+pub fn save_bitrate_controller_settings(
+    settings: BitrateControllerSettings,
+) -> Result<BitrateControllerStatus, String> {
+    let settings = settings.validated()?;
+    save_settings(&settings)?;
+    info!(
+        "自动码率控制已{}: {}-{} Kbps",
+        if settings.enabled { "启用" } else { "停用" },
+        settings.min_kbps,
+        settings.max_kbps
+    );
+    if let Ok(mut current) = SETTINGS.write() {
+        *current = settings;
+    }
+    Ok(status())
+}
@@ -0,0 +1,369 @@
+//! Decision logic for the adaptive bitrate controller, kept free of I/O so it
+//! can be replayed against recorded metric traces.
+//!
+//! The controller only steps a bitrate it set itself or found in place. When
+//! a client's bitrate changes behind its back (a tray preset, a client
+//! profile or the main window), it leaves that client alone for
+//! `manual_hold_secs`, and it never pulls a bitrate outside `[min, max]`
+//! back into range on its own.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Bitrate range the Core's runtime API accepts.
+const CORE_BITRATE_RANGE: std::ops::RangeInclusive<u32> = 1..=800_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct BitrateControllerSettings {
+    pub enabled: bool,
+    pub min_kbps: u32,
+    pub max_kbps: u32,
+    /// Frame budget usage above which the bitrate is lowered.
+    pub high_load_percent: f64,
+    /// Frame budget usage below which the bitrate may be raised again.
+    pub low_load_percent: f64,
+    pub step_down_percent: u32,
+    pub step_up_percent: u32,
+    /// Consecutive samples a condition must hold before acting. Raising waits
+    /// twice as long as lowering.
+    pub sustain_samples: u32,
+    /// Minimum time between two changes for the same client.
+    pub min_change_interval_secs: u64,
+    /// How long a bitrate changed by someone else is left untouched.
+    pub manual_hold_secs: u64,
+    pub poll_interval_secs: u64,
+}
+
+impl Default for BitrateControllerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_kbps: 5_000,
+            max_kbps: 80_000,
+            high_load_percent: 90.0,
+            low_load_percent: 60.0,
+            step_down_percent: 20,
+            step_up_percent: 10,
+            sustain_samples: 3,
+            min_change_interval_secs: 15,
+            manual_hold_secs: 600,
+            poll_interval_secs: 2,
+        }
+    }
+}
+
+impl BitrateControllerSettings {
+    pub fn validated(self) -> Result<Self, String> {
+        if !CORE_BITRATE_RANGE.contains(&self.min_kbps)
+            || !CORE_BITRATE_RANGE.contains(&self.max_kbps)
+        {
+            return Err("Bitrate bounds must be between 1 and 800000 Kbps".to_string());
+        }
+        if self.min_kbps > self.max_kbps {
+            return Err("Minimum bitrate cannot exceed the maximum".to_string());
+        }
+        if !(0.0..=1000.0).contains(&self.low_load_percent)
+            || !(0.0..=1000.0).contains(&self.high_load_percent)
+            || self.low_load_percent >= self.high_load_percent
+        {
+            return Err("Low load threshold must be below the high load threshold".to_string());
+        }
+        if !(1..=90).contains(&self.step_down_percent) || !(1..=100).contains(&self.step_up_percent)
+        {
+            return Err("Step sizes must be between 1% and 90% (down) or 100% (up)".to_string());
+        }
+        if self.sustain_samples == 0 || self.poll_interval_secs == 0 {
+            return Err("Sustain samples and poll interval must be at least 1".to_string());
+        }
+        Ok(self)
+    }
+}
+
+/// One client's metrics at one poll.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MetricSample {
+    pub client_name: String,
+    pub bitrate_kbps: u32,
+    /// Share of the frame budget spent on the host, from the p95 host latency
+    /// or the p95 encode time, whichever is higher. `None` when the Core has
+    /// no perf data for the session.
+    pub budget_usage_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BitrateDecision {
+    pub client_name: String,
+    pub from_kbps: u32,
+    pub to_kbps: u32,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+struct ClientState {
+    high_streak: u32,
+    low_streak: u32,
+    last_change_secs: Option<f64>,
+    /// Bitrate seen at the last poll, or set by the controller since.
+    known_kbps: Option<u32>,
+    /// Set when the bitrate changed without the controller.
+    manual_since_secs: Option<f64>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct BitrateController {
+    clients: HashMap<String, ClientState>,
+}
+
+impl BitrateController {
+    /// Feed one poll worth of samples taken at `now_secs` and return the
+    /// changes to apply.
+    pub fn step(
+        &mut self,
+        settings: &BitrateControllerSettings,
+        samples: &[MetricSample],
+        now_secs: f64,
+    ) -> Vec<BitrateDecision> {
+        self.clients
+            .retain(|name, _| samples.iter().any(|sample| &sample.client_name == name));
+
+        let mut decisions = Vec::new();
+        for sample in samples {
+            let state = self.clients.entry(sample.client_name.clone()).or_default();
+            let decision = Self::decide(settings, state, sample, now_secs);
+            state.known_kbps = Some(sample.bitrate_kbps);
+            if let Some(decision) = decision {
+                state.high_streak = 0;
+                state.low_streak = 0;
+                state.last_change_secs = Some(now_secs);
+                state.known_kbps = Some(decision.to_kbps);
+                decisions.push(decision);
+            }
+        }
+        decisions
+    }
+
+    /// Forget a decision the Core did not apply, so the unchanged bitrate at
+    /// the next poll is not mistaken for a manual change.
+    pub fn change_failed(&mut self, decision: &BitrateDecision) {
+        if let Some(state) = self.clients.get_mut(&decision.client_name) {
+            state.known_kbps = Some(decision.from_kbps);
+        }
+    }
+
+    fn decide(
+        settings: &BitrateControllerSettings,
+        state: &mut ClientState,
+        sample: &MetricSample,
+        now_secs: f64,
+    ) -> Option<BitrateDecision> {
+        let current = sample.bitrate_kbps;
+        if current == 0 {
+            return None;
+        }
+        if state.known_kbps.is_some_and(|known| known != current) {
+            state.manual_since_secs = Some(now_secs);
+            state.high_streak = 0;
+            state.low_streak = 0;
+        }
+        let held = state
+            .manual_since_secs
+            .is_some_and(|since| now_secs - since < settings.manual_hold_secs as f64);
+        if held {
+            return None;
+        }
+
+        match sample.budget_usage_percent {
+            Some(usage) if usage > settings.high_load_percent => {
+                state.high_streak += 1;
+                state.low_streak = 0;
+            }
+            Some(usage) if usage < settings.low_load_percent => {
+                state.low_streak += 1;
+                state.high_streak = 0;
+            }
+            // Inside the dead band, or no data: start counting again.
+            _ => {
+                state.high_streak = 0;
+                state.low_streak = 0;
+            }
+        }
+
+        let rate_limited = state
+            .last_change_secs
+            .is_some_and(|last| now_secs - last < settings.min_change_interval_secs as f64);
+        if rate_limited {
+            return None;
+        }
+
+        // Steps stop at the bounds but never move a bitrate set outside them
+        // the wrong way.
+        let (target, reason) = if state.high_streak >= settings.sustain_samples {
+            let lowered = current - current * settings.step_down_percent / 100;
+            (
+                lowered.max(settings.min_kbps).min(current),
+                format!(
+                    "frame budget usage {:.0}% above {:.0}%",
+                    sample.budget_usage_percent.unwrap_or_default(),
+                    settings.high_load_percent
+                ),
+            )
+        } else if state.low_streak >= settings.sustain_samples * 2 {
+            let raised = current.saturating_add(current * settings.step_up_percent / 100);
+            (
+                raised.min(settings.max_kbps).max(current),
+                format!(
+                    "frame budget usage {:.0}% below {:.0}%",
+                    sample.budget_usage_percent.unwrap_or_default(),
+                    settings.low_load_percent
+                ),
+            )
+        } else {
+            return None;
+        };
+
+        (target != current).then(|| BitrateDecision {
+            client_name: sample.client_name.clone(),
+            from_kbps: current,
+            to_kbps: target,
+            reason,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A recorded trace: `(seconds, usage percent)` polls for one client.
+    fn replay(
+        settings: &BitrateControllerSettings,
+        start_kbps: u32,
+        trace: &[(f64, Option<f64>)],
+    ) -> Vec<BitrateDecision> {
+        let mut controller = BitrateController::default();
+        let mut bitrate = start_kbps;
+        let mut decisions = Vec::new();
+        for (time, usage) in trace {
+            let sample = MetricSample {
+                client_name: "Deck".to_string(),
+                bitrate_kbps: bitrate,
+                budget_usage_percent: *usage,
+            };
+            for decision in controller.step(settings, &[sample], *time) {
+                bitrate = decision.to_kbps;
+                decisions.push(decision);
+            }
+        }
+        decisions
+    }
+
+    fn trace(usages: &[f64], interval: f64) -> Vec<(f64, Option<f64>)> {
+        usages
+            .iter()
+            .enumerate()
+            .map(|(i, usage)| (i as f64 * interval, Some(*usage)))
+            .collect()
+    }
+
+    #[test]
+    fn sustained_load_lowers_bitrate_and_spikes_are_ignored() {
+        let settings = BitrateControllerSettings::default();
+
+        let spikes = trace(&[95.0, 70.0, 97.0, 75.0, 99.0, 80.0], 2.0);
+        assert!(replay(&settings, 40_000, &spikes).is_empty());
+
+        let sustained = trace(&[95.0, 96.0, 97.0], 2.0);
+        let decisions = replay(&settings, 40_000, &sustained);
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].to_kbps, 32_000);
+        assert!(decisions[0].reason.contains("97%"));
+    }
+
+    #[test]
+    fn changes_are_rate_limited_and_stop_at_bounds() {
+        let settings = BitrateControllerSettings {
+            min_kbps: 20_000,
+            ..BitrateControllerSettings::default()
+        };
+        // Overloaded for 40 s: one change per 15 s, never below the minimum.
+        let overloaded = trace(&[99.0; 21], 2.0);
+        let decisions = replay(&settings, 40_000, &overloaded);
+        assert_eq!(
+            decisions
+                .iter()
+                .map(|decision| decision.to_kbps)
+                .collect::<Vec<_>>(),
+            vec![32_000, 25_600, 20_480]
+        );
+
+        // A bitrate set above the maximum is not pulled down while idle.
+        assert!(replay(&settings, 100_000, &trace(&[40.0; 12], 2.0)).is_empty());
+        assert!(replay(&settings, 10_000, &trace(&[99.0; 3], 2.0)).is_empty());
+    }
+
+    #[test]
+    fn manual_changes_are_held_off() {
+        let settings = BitrateControllerSettings::default();
+        let mut controller = BitrateController::default();
+        let sample = |bitrate_kbps| MetricSample {
+            client_name: "Deck".to_string(),
+            bitrate_kbps,
+            budget_usage_percent: Some(99.0),
+        };
+
+        controller.step(&settings, &[sample(40_000)], 0.0);
+        controller.step(&settings, &[sample(40_000)], 2.0);
+        // A tray preset switched the client to 60 Mbps.
+        for time in 2..=100 {
+            let now = time as f64 * 2.0;
+            assert!(
+                controller
+                    .step(&settings, &[sample(60_000)], now)
+                    .is_empty()
+            );
+        }
+        let resumed: Vec<_> = (301..=304)
+            .flat_map(|time| controller.step(&settings, &[sample(60_000)], time as f64 * 2.0))
+            .collect();
+        assert_eq!(resumed.len(), 1);
+        assert_eq!(resumed[0].to_kbps, 48_000);
+
+        controller.change_failed(&resumed[0]);
+        assert!(
+            controller
+                .step(&settings, &[sample(60_000)], 700.0)
+                .is_empty()
+        );
+        assert_eq!(controller.clients["Deck"].manual_since_secs, Some(4.0));
+    }
+
+    #[test]
+    fn raising_needs_a_longer_quiet_period_than_lowering() {
+        let settings = BitrateControllerSettings::default();
+        let idle = trace(&[40.0; 5], 2.0);
+        assert!(replay(&settings, 20_000, &idle).is_empty());
+
+        let idle = trace(&[40.0; 6], 2.0);
+        let decisions = replay(&settings, 20_000, &idle);
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].to_kbps, 22_000);
+    }
+
+    #[test]
+    fn settings_validation_rejects_inverted_bounds() {
+        assert!(BitrateControllerSettings::default().validated().is_ok());
+        let inverted = BitrateControllerSettings {
+            min_kbps: 90_000,
+            ..BitrateControllerSettings::default()
+        };
+        assert!(inverted.validated().is_err());
+        let band = BitrateControllerSettings {
+            low_load_percent: 95.0,
+            ..BitrateControllerSettings::default()
+        };
+        assert!(band.validated().is_err());
+    }
+}
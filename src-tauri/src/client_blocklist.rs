@@ -0,0 +1,374 @@
+//! Local blocklist of client fingerprints.
+//!
+//! A client blocked from the tray or a suspicious-client notification is
+//! disconnected through the Core's `session_disconnect` tray action every time
+//! it shows up again in the tray state. A disconnect that fails is retried on
+//! the next tray state. Each rejected attempt is counted and logged; the list
+//! lives in `<config>/Sunshine GUI/client-blocklist.json`.
+//! Cores without the `session-actions-v1` capability cannot disconnect a
+//! session, so blocking is refused there and the list is only advisory.
+
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::sunshine::{TrayActionResponse, TrayClientSession, TrayState};
+
+const BLOCKLIST_FILE: &str = "client-blocklist.json";
+const ACTIONS_CAPABILITY: &str = "session-actions-v1";
+
+static BLOCKLIST: Lazy<Mutex<Blocklist>> = Lazy::new(|| Mutex::new(load()));
+/// Sessions being or already disconnected, so each attempt is rejected once.
+static REJECTED: Lazy<Mutex<HashSet<u32>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+/// Whether the last accepted tray state came from a Core that can disconnect
+/// sessions.
+static ENFORCEABLE: AtomicBool = AtomicBool::new(true);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockedClient {
+    pub fingerprint: String,
+    /// Client name when it was blocked, for display only.
+    #[serde(default)]
+    pub name: String,
+    pub blocked_at: String,
+    /// Connections rejected since the client was blocked.
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_attempt_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlocklistStatus {
+    pub clients: Vec<BlockedClient>,
+    /// `false` while the Core cannot disconnect sessions, so blocked clients
+    /// still get in.
+    pub enforceable: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Blocklist {
+    clients: Vec<BlockedClient>,
+}
+
+impl Blocklist {
+    fn find_mut(&mut self, fingerprint: &str) -> Option<&mut BlockedClient> {
+        self.clients
+            .iter_mut()
+            .find(|client| same_fingerprint(&client.fingerprint, fingerprint))
+    }
+
+    fn contains(&self, fingerprint: &str) -> bool {
+        self.clients
+            .iter()
+            .any(|client| same_fingerprint(&client.fingerprint, fingerprint))
+    }
+
+    /// Add `fingerprint`; returns `false` when it is empty or already blocked.
+    fn block(&mut self, fingerprint: &str, name: &str) -> bool {
+        if fingerprint.trim().is_empty() || self.contains(fingerprint) {
+            return false;
+        }
+        self.clients.push(BlockedClient {
+            fingerprint: fingerprint.trim().to_string(),
+            name: name.trim().to_string(),
+            blocked_at: chrono::Local::now().to_rfc3339(),
+            attempts: 0,
+            last_attempt_at: None,
+        });
+        true
+    }
+
+    fn unblock(&mut self, fingerprint: &str) -> bool {
+        let before = self.clients.len();
+        self.clients
+            .retain(|client| !same_fingerprint(&client.fingerprint, fingerprint));
+        self.clients.len() != before
+    }
+}
+
+fn same_fingerprint(a: &str, b: &str) -> bool {
+    let (a, b) = (a.trim(), b.trim());
+    !a.is_empty() && a.eq_ignore_ascii_case(b)
+}
+
+fn blocklist_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(BLOCKLIST_FILE))
+}
+
+fn load() -> Blocklist {
+    let Ok(path) = blocklist_path() else {
+        return Blocklist::default();
+    };
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return Blocklist::default();
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        warn!("Invalid client blocklist, starting empty: {}", e);
+        Blocklist::default()
+    })
+}
+
+fn save(blocklist: &Blocklist) -> Result<(), String> {
+    let path = blocklist_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(blocklist).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Sessions of blocked clients not rejected yet. Forgets sessions that have
+/// ended, so session ids reused after a Core restart are checked again.
+fn sessions_to_reject<'a>(
+    blocklist: &Blocklist,
+    rejected: &mut HashSet<u32>,
+    sessions: &'a [TrayClientSession],
+) -> Vec<&'a TrayClientSession> {
+    rejected.retain(|id| sessions.iter().any(|session| session.id == *id));
+    sessions
+        .iter()
+        .filter(|session| blocklist.contains(&session.client_fingerprint))
+        .filter(|session| rejected.insert(session.id))
+        .collect()
+}
+
+/// Keep a session marked as rejected only if the Core disconnected it, so a
+/// failed attempt is retried instead of leaving the client streaming.
+fn settle_disconnect(
+    rejected: &mut HashSet<u32>,
+    session_id: u32,
+    result: &Result<TrayActionResponse, String>,
+) {
+    if !result.as_ref().is_ok_and(|response| response.status) {
+        rejected.remove(&session_id);
+    }
+}
+
+fn record_attempt(fingerprint: &str) {
+    let mut blocklist = BLOCKLIST.lock().unwrap();
+    let Some(client) = blocklist.find_mut(fingerprint) else {
+        return;
+    };
+    client.attempts += 1;
+    client.last_attempt_at = Some(chrono::Local::now().to_rfc3339());
+    if let Err(e) = save(&blocklist) {
+        warn!("Failed to save client blocklist: {}", e);
+    }
+}
+
+fn disconnect(session: TrayClientSession) {
+    tauri::async_runtime::spawn(async move {
+        match disconnect_session(session.id).await {
+            Ok(response) if response.status => {
+                warn!(
+                    "🚫 已拒绝被屏蔽的客户端 {} ({})，会话 {}",
+                    session.client_name, session.client_fingerprint, session.id
+                );
+                record_attempt(&session.client_fingerprint);
+            }
+            Ok(response) => warn!(
+                "🚫 断开被屏蔽的会话 {} 失败: {}",
+                session.id, response.error
+            ),
+            Err(e) => warn!("🚫 断开被屏蔽的会话 {} 失败: {}", session.id, e),
+        }
+    });
+}
+
+/// Disconnect a session of a blocked client. A failed disconnect is forgotten,
+/// so the next tray state tries again.
+pub async fn disconnect_session(session_id: u32) -> Result<TrayActionResponse, String> {
+    let result =
+        crate::sunshine::post_tray_session_action("session_disconnect", session_id, None).await;
+    settle_disconnect(&mut REJECTED.lock().unwrap(), session_id, &result);
+    result
+}
+
+pub fn is_blocked(fingerprint: &str) -> bool {
+    BLOCKLIST.lock().unwrap().contains(fingerprint)
+}
+
+pub fn can_enforce(state: &TrayState) -> bool {
+    state
+        .capabilities
+        .iter()
+        .any(|capability| capability == ACTIONS_CAPABILITY)
+}
+
+/// Whether the connected Core lets blocked clients be disconnected.
+pub fn is_enforceable() -> bool {
+    ENFORCEABLE.load(Ordering::Acquire)
+}
+
+/// Disconnect blocked clients in a tray state the monitor accepted.
+pub fn enforce(state: &TrayState) {
+    let enforceable = can_enforce(state);
+    ENFORCEABLE.store(enforceable, Ordering::Release);
+    let targets: Vec<TrayClientSession> = {
+        let blocklist = BLOCKLIST.lock().unwrap();
+        let mut rejected = REJECTED.lock().unwrap();
+        sessions_to_reject(&blocklist, &mut rejected, &state.sessions)
+            .into_iter()
+            .cloned()
+            .collect()
+    };
+    if targets.is_empty() {
+        return;
+    }
+    if !enforceable {
+        for session in targets {
+            warn!(
+                "🚫 Core 不支持 {}，无法断开被屏蔽的客户端 {} ({})",
+                ACTIONS_CAPABILITY, session.client_name, session.client_fingerprint
+            );
+        }
+        return;
+    }
+
+    for session in targets {
+        disconnect(session);
+    }
+}
+
+/// Block the client of `session`. The caller disconnects the session itself
+/// through [`disconnect_session`]; later sessions of the client are rejected
+/// by [`enforce`].
+pub fn block_session(session: &TrayClientSession) -> Result<(), String> {
+    if session.client_fingerprint.trim().is_empty() {
+        return Err("This client has no fingerprint to block".to_string());
+    }
+    if !is_enforceable() {
+        return Err(
+            "This Sunshine version cannot disconnect sessions, so blocking cannot be enforced"
+                .to_string(),
+        );
+    }
+    let mut blocklist = BLOCKLIST.lock().unwrap();
+    if blocklist.block(&session.client_fingerprint, &session.client_name) {
+        save(&blocklist)?;
+        info!(
+            "🚫 已屏蔽客户端 {} ({})",
+            session.client_name, session.client_fingerprint
+        );
+    }
+    REJECTED.lock().unwrap().insert(session.id);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_blocked_clients() -> BlocklistStatus {
+    BlocklistStatus {
+        clients: BLOCKLIST.lock().unwrap().clients.clone(),
+        enforceable: is_enforceable(),
+    }
+}
+
+#[tauri::command]
+pub fn unblock_client(fingerprint: String) -> Result<bool, String> {
+    let mut blocklist = BLOCKLIST.lock().unwrap();
+    if !blocklist.unblock(&fingerprint) {
+        return Ok(false);
+    }
+    save(&blocklist)?;
+    info!("✅ 已解除屏蔽客户端 {}", fingerprint);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: u32, fingerprint: &str) -> TrayClientSession {
+        TrayClientSession {
+            id,
+            client_fingerprint: fingerprint.to_string(),
+            ..TrayClientSession::default()
+        }
+    }
+
+    #[test]
+    fn fingerprints_match_case_insensitively_and_never_when_empty() {
+        let mut blocklist = Blocklist::default();
+        assert!(blocklist.block(" ABC-1 ", "Phone"));
+        assert!(!blocklist.block("abc-1", "Phone"));
+        assert!(blocklist.contains("abc-1"));
+        assert!(!blocklist.contains(""));
+        assert!(!blocklist.block(" ", "Unknown"));
+
+        assert!(blocklist.unblock("ABC-1"));
+        assert!(!blocklist.unblock("abc-1"));
+    }
+
+    #[test]
+    fn enforcement_needs_session_actions() {
+        let mut state = TrayState::default();
+        assert!(!can_enforce(&state));
+        state.capabilities = vec!["sessions-v1".to_string(), ACTIONS_CAPABILITY.to_string()];
+        assert!(can_enforce(&state));
+    }
+
+    #[test]
+    fn failed_disconnects_are_retried() {
+        let mut blocklist = Blocklist::default();
+        blocklist.block("bad", "Phone");
+        let mut rejected = HashSet::new();
+        let sessions = [session(2, "bad")];
+        let rejected_response = TrayActionResponse {
+            status: false,
+            error: "session not found".to_string(),
+            ..TrayActionResponse::default()
+        };
+
+        for failure in [Err("connection refused".to_string()), Ok(rejected_response)] {
+            assert_eq!(
+                sessions_to_reject(&blocklist, &mut rejected, &sessions).len(),
+                1
+            );
+            settle_disconnect(&mut rejected, 2, &failure);
+            assert!(!rejected.contains(&2));
+        }
+
+        assert_eq!(
+            sessions_to_reject(&blocklist, &mut rejected, &sessions).len(),
+            1
+        );
+        let disconnected = Ok(TrayActionResponse {
+            status: true,
+            ..TrayActionResponse::default()
+        });
+        settle_disconnect(&mut rejected, 2, &disconnected);
+        assert!(sessions_to_reject(&blocklist, &mut rejected, &sessions).is_empty());
+    }
+
+    #[test]
+    fn each_blocked_session_is_rejected_once() {
+        let mut blocklist = Blocklist::default();
+        blocklist.block("bad", "Phone");
+        let mut rejected = HashSet::new();
+
+        let sessions = [session(1, "good"), session(2, "bad")];
+        let ids = |targets: Vec<&TrayClientSession>| -> Vec<u32> {
+            targets.iter().map(|session| session.id).collect()
+        };
+        assert_eq!(
+            ids(sessions_to_reject(&blocklist, &mut rejected, &sessions)),
+            vec![2]
+        );
+        assert!(sessions_to_reject(&blocklist, &mut rejected, &sessions).is_empty());
+
+        // The session ended; the same id later belongs to a new attempt.
+        assert!(sessions_to_reject(&blocklist, &mut rejected, &sessions[..1]).is_empty());
+        assert_eq!(
+            ids(sessions_to_reject(&blocklist, &mut rejected, &sessions)),
+            vec![2]
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//! Named streaming profiles applied when a matching client connects.
+//!
+//! A profile matches a client by fingerprint when the Core reports one, or by
+//! client name. On connect its bitrate is applied to the new session and its
+//! VDD mode and RTSS frame rate limit replace the current ones; when the last
+//! profiled session ends, or the Core goes away or restarts, the VDD settings
+//! and limit in effect before the first profile are restored. Connects,
+//! disconnects and Core restarts are handled one at a time in the order the
+//! tray reported them. Profiles live in `<config>/Sunshine GUI/client-profiles.json`.
+
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::vdd::{Resolution, VddSettings};
+
+const PROFILES_FILE: &str = "client-profiles.json";
+/// A new session may not accept bitrate changes until it is running.
+const BITRATE_ATTEMPTS: u32 = 5;
+const BITRATE_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Serializes read-modify-write cycles on `client-profiles.json`.
+static PROFILES_LOCK: Mutex<()> = Mutex::new(());
+/// Instance id of the Core whose sessions the applied profiles belong to.
+static CORE_INSTANCE: Mutex<Option<String>> = Mutex::new(None);
+/// Queue feeding the single task that applies and reverts profiles.
+static EVENTS: Lazy<tokio::sync::mpsc::UnboundedSender<ProfileEvent>> = Lazy::new(|| {
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    tauri::async_runtime::spawn(run_events(receiver));
+    sender
+});
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ClientProfile {
+    pub id: String,
+    pub name: String,
+    /// Matched case-insensitively against the session's client name.
+    pub client_name: Option<String>,
+    /// Matched exactly against the fingerprint the Core reports.
+    pub client_fingerprint: Option<String>,
+    pub bitrate_kbps: Option<u32>,
+    /// Moved to the front of the VDD resolution list.
+    pub vdd_resolution: Option<Resolution>,
+    /// Moved to the front of the VDD refresh rate list.
+    pub vdd_refresh_rate: Option<u32>,
+    /// Global RTSS frame rate limit; 0 removes the limit.
+    pub rtss_framerate_limit: Option<i32>,
+}
+
+impl ClientProfile {
+    fn validated(mut self) -> Result<Self, String> {
+        self.name = self.name.trim().to_string();
+        self.client_name = self
+            .client_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        self.client_fingerprint = self
+            .client_fingerprint
+            .map(|fingerprint| fingerprint.trim().to_string())
+            .filter(|fingerprint| !fingerprint.is_empty());
+
+        if self.name.is_empty() {
+            return Err("Profile name cannot be empty".to_string());
+        }
+        if self.client_name.is_none() && self.client_fingerprint.is_none() {
+            return Err("A profile needs a client name or fingerprint to match".to_string());
+        }
+        if self
+            .bitrate_kbps
+            .is_some_and(|bitrate| !(1..=800_000).contains(&bitrate))
+        {
+            return Err("Bitrate must be between 1 and 800000 Kbps".to_string());
+        }
+        if self
+            .vdd_resolution
+            .as_ref()
+            .is_some_and(|resolution| resolution.width == 0 || resolution.height == 0)
+        {
+            return Err("VDD resolution must be non-zero".to_string());
+        }
+        if self.vdd_refresh_rate == Some(0) {
+            return Err("VDD refresh rate must be non-zero".to_string());
+        }
+        if self.rtss_framerate_limit.is_some_and(|limit| limit < 0) {
+            return Err("RTSS frame rate limit cannot be negative".to_string());
+        }
+        Ok(self)
+    }
+
+    fn changes_vdd(&self) -> bool {
+        self.vdd_resolution.is_some() || self.vdd_refresh_rate.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ProfileStore {
+    profiles: Vec<ClientProfile>,
+}
+
+impl ProfileStore {
+    /// Fingerprint matches win over name matches.
+    fn find(&self, client_name: Option<&str>, fingerprint: Option<&str>) -> Option<&ClientProfile> {
+        let by_fingerprint = fingerprint.and_then(|fingerprint| {
+            self.profiles
+                .iter()
+                .find(|profile| profile.client_fingerprint.as_deref() == Some(fingerprint))
+        });
+        by_fingerprint.or_else(|| {
+            let client_name = client_name?.trim();
+            self.profiles.iter().find(|profile| {
+                profile
+                    .client_name
+                    .as_deref()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(client_name))
+            })
+        })
+    }
+
+    fn upsert(&mut self, profile: ClientProfile) -> ClientProfile {
+        let mut profile = profile;
+        if profile.id.is_empty() {
+            profile.id = uuid::Uuid::new_v4().to_string();
+        }
+        match self.profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => *existing = profile.clone(),
+            None => self.profiles.push(profile.clone()),
+        }
+        profile
+    }
+}
+
+/// Global settings a profile replaced, captured before the first one applied.
+#[derive(Debug, Clone, Default)]
+struct Baseline {
+    vdd: Option<VddSettings>,
+    rtss_framerate_limit: Option<i32>,
+}
+
+#[derive(Debug)]
+enum ProfileEvent {
+    Connected {
+        client_name: Option<String>,
+        fingerprint: Option<String>,
+    },
+    Disconnected(Option<String>),
+    /// The Core stopped or restarted, ending every session at once.
+    CoreGone,
+}
+
+#[derive(Debug, Default)]
+struct AppliedState {
+    /// Profiled sessions by client name, oldest first.
+    active: Vec<(String, ClientProfile)>,
+    baseline: Baseline,
+}
+
+fn profiles_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(PROFILES_FILE))
+}
+
+fn load_store(path: &Path) -> ProfileStore {
+    let Ok(text) = fs::read_to_string(path) else {
+        return ProfileStore::default();
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        warn!("Invalid client profiles, ignoring them: {}", e);
+        ProfileStore::default()
+    })
+}
+
+fn save_store(path: &Path, store: &ProfileStore) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+fn read_store() -> ProfileStore {
+    let _guard = PROFILES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    profiles_path()
+        .map(|path| load_store(&path))
+        .unwrap_or_default()
+}
+
+fn update_store<T>(edit: impl FnOnce(&mut ProfileStore) -> T) -> Result<(T, ProfileStore), String> {
+    let _guard = PROFILES_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = profiles_path()?;
+    let mut store = load_store(&path);
+    let output = edit(&mut store);
+    save_store(&path, &store)?;
+    Ok((output, store))
+}
+
+/// Put `value` first in `list`, keeping the rest in order.
+fn move_to_front<T: PartialEq>(list: &mut Vec<T>, value: T) {
+    list.retain(|item| *item != value);
+    list.insert(0, value);
+}
+
+fn vdd_for_profile(mut settings: VddSettings, profile: &ClientProfile) -> VddSettings {
+    if let Some(resolution) = &profile.vdd_resolution {
+        move_to_front(&mut settings.resolutions.resolution, resolution.clone());
+    }
+    if let Some(refresh_rate) = profile.vdd_refresh_rate {
+        move_to_front(
+            &mut settings.global.g_refresh_rate,
+            refresh_rate.to_string(),
+        );
+    }
+    settings
+}
+
+async fn apply_bitrate(client_name: &str, bitrate: u32) {
+    for attempt in 1..=BITRATE_ATTEMPTS {
+        match crate::sunshine::change_local_bitrate(client_name, bitrate).await {
+            Ok(()) => {
+                info!("🎛️ 客户端配置: {} 码率 -> {} Kbps", client_name, bitrate);
+                return;
+            }
+            Err(e) if attempt < BITRATE_ATTEMPTS => {
+                debug!("Profile bitrate not applied yet ({}): {}", attempt, e);
+                tokio::time::sleep(BITRATE_RETRY_DELAY).await;
+            }
+            Err(e) => warn!("⚠️ 客户端配置码率应用失败 {}: {}", client_name, e),
+        }
+    }
+}
+
+async fn apply_global(profile: &ClientProfile, baseline: &Baseline) {
+    if profile.changes_vdd() {
+        match &baseline.vdd {
+            Some(vdd) => {
+                let settings = vdd_for_profile(vdd.clone(), profile);
+                if let Err(e) = crate::vdd::apply_profile_vdd_settings(settings).await {
+                    warn!("⚠️ 客户端配置 VDD 应用失败: {}", e);
+                }
+            }
+            None => warn!("⚠️ VDD 设置不可读，跳过配置 {} 的 VDD 项", profile.name),
+        }
+    }
+    if let Some(limit) = profile.rtss_framerate_limit {
+        if let Err(e) = crate::rtss::rtss_set_framerate_limit(limit, None).await {
+            warn!("⚠️ 客户端配置 RTSS 帧率限制应用失败: {}", e);
+        }
+    }
+}
+
+/// Restore what `profile` changed, either to the newest remaining profile
+/// that sets the same thing or to the baseline.
+async fn restore_global(profile: &ClientProfile, state: &AppliedState) {
+    let remaining = || state.active.iter().rev().map(|(_, profile)| profile);
+    if profile.changes_vdd() {
+        if let Some(vdd) = &state.baseline.vdd {
+            let settings = match remaining().find(|other| other.changes_vdd()) {
+                Some(other) => vdd_for_profile(vdd.clone(), other),
+                None => vdd.clone(),
+            };
+            if let Err(e) = crate::vdd::apply_profile_vdd_settings(settings).await {
+                warn!("⚠️ 恢复 VDD 设置失败: {}", e);
+            }
+        }
+    }
+    if profile.rtss_framerate_limit.is_some() {
+        let limit = remaining()
+            .find_map(|other| other.rtss_framerate_limit)
+            .or(state.baseline.rtss_framerate_limit);
+        if let Some(limit) = limit {
+            if let Err(e) = crate::rtss::rtss_set_framerate_limit(limit, None).await {
+                warn!("⚠️ 恢复 RTSS 帧率限制失败: {}", e);
+            }
+        }
+    }
+}
+
+async fn capture_baseline(profile: &ClientProfile) -> Baseline {
+    Baseline {
+        vdd: if profile.changes_vdd() {
+            crate::vdd::load_vdd_settings()
+                .await
+                .map_err(|e| warn!("⚠️ 读取 VDD 设置失败: {}", e))
+                .ok()
+        } else {
+            None
+        },
+        rtss_framerate_limit: if profile.rtss_framerate_limit.is_some() {
+            crate::rtss::rtss_get_framerate_limit(None).await.ok()
+        } else {
+            None
+        },
+    }
+}
+
+async fn apply_on_connect(
+    state: &mut AppliedState,
+    client_name: Option<String>,
+    fingerprint: Option<String>,
+) {
+    let Some(profile) = read_store()
+        .find(client_name.as_deref(), fingerprint.as_deref())
+        .cloned()
+    else {
+        return;
+    };
+    let client_name = client_name.unwrap_or_default();
+    info!("🎛️ 应用客户端配置 '{}' -> {}", profile.name, client_name);
+
+    if state.active.is_empty() {
+        state.baseline = capture_baseline(&profile).await;
+    } else {
+        // A later profile may touch settings the first one left alone.
+        let missing = capture_baseline(&ClientProfile {
+            vdd_resolution: state
+                .baseline
+                .vdd
+                .is_none()
+                .then(|| profile.vdd_resolution.clone())
+                .flatten(),
+            vdd_refresh_rate: state
+                .baseline
+                .vdd
+                .is_none()
+                .then_some(profile.vdd_refresh_rate)
+                .flatten(),
+            rtss_framerate_limit: state
+                .baseline
+                .rtss_framerate_limit
+                .is_none()
+                .then_some(profile.rtss_framerate_limit)
+                .flatten(),
+            ..ClientProfile::default()
+        })
+        .await;
+        state.baseline.vdd = state.baseline.vdd.take().or(missing.vdd);
+        state.baseline.rtss_framerate_limit = state
+            .baseline
+            .rtss_framerate_limit
+            .or(missing.rtss_framerate_limit);
+    }
+
+    apply_global(&profile, &state.baseline).await;
+    state.active.push((client_name.clone(), profile.clone()));
+
+    // Retries wait for the session to run; later events need not wait too.
+    if let Some(bitrate) = profile.bitrate_kbps.filter(|_| !client_name.is_empty()) {
+        tauri::async_runtime::spawn(async move { apply_bitrate(&client_name, bitrate).await });
+    }
+}
+
+async fn revert_on_disconnect(state: &mut AppliedState, client_name: Option<String>) {
+    let position = match client_name.as_deref() {
+        Some(name) => state.active.iter().rposition(|(active, _)| active == name),
+        // Cores without session snapshots only report one session.
+        None => (state.active.len() == 1).then_some(0),
+    };
+    let Some(position) = position else {
+        return;
+    };
+
+    let (client_name, profile) = state.active.remove(position);
+    info!("🎛️ 还原客户端配置 '{}' ({})", profile.name, client_name);
+    restore_global(&profile, state).await;
+    if state.active.is_empty() {
+        state.baseline = Baseline::default();
+    }
+}
+
+/// Put back the baseline at once; no session survives a Core restart.
+async fn revert_all(state: &mut AppliedState) {
+    let applied = std::mem::take(&mut state.active);
+    let baseline = std::mem::take(&mut state.baseline);
+    if applied.is_empty() {
+        return;
+    }
+    info!("🎛️ Core 已断开，还原 {} 个客户端配置", applied.len());
+
+    let changed_vdd = applied.iter().any(|(_, profile)| profile.changes_vdd());
+    if let Some(vdd) = baseline.vdd.filter(|_| changed_vdd) {
+        if let Err(e) = crate::vdd::apply_profile_vdd_settings(vdd).await {
+            warn!("⚠️ 恢复 VDD 设置失败: {}", e);
+        }
+    }
+    let changed_rtss = applied
+        .iter()
+        .any(|(_, profile)| profile.rtss_framerate_limit.is_some());
+    if let Some(limit) = baseline.rtss_framerate_limit.filter(|_| changed_rtss) {
+        if let Err(e) = crate::rtss::rtss_set_framerate_limit(limit, None).await {
+            warn!("⚠️ 恢复 RTSS 帧率限制失败: {}", e);
+        }
+    }
+}
+
+async fn run_events(mut receiver: tokio::sync::mpsc::UnboundedReceiver<ProfileEvent>) {
+    let mut state = AppliedState::default();
+    while let Some(event) = receiver.recv().await {
+        match event {
+            ProfileEvent::Connected {
+                client_name,
+                fingerprint,
+            } => apply_on_connect(&mut state, client_name, fingerprint).await,
+            ProfileEvent::Disconnected(client_name) => {
+                revert_on_disconnect(&mut state, client_name).await
+            }
+            ProfileEvent::CoreGone => revert_all(&mut state).await,
+        }
+    }
+}
+
+/// Simulated sessions must not touch the real VDD and RTSS settings.
+fn is_simulated() -> bool {
+    crate::simulated_core::url().is_some()
+}
+
+fn queue(event: ProfileEvent) {
+    if is_simulated() {
+        return;
+    }
+    if EVENTS.send(event).is_err() {
+        warn!("Client profile queue is closed");
+    }
+}
+
+/// Apply the matching profile for a client the tray state reported as new.
+pub fn on_client_connected(client_name: Option<String>, fingerprint: Option<String>) {
+    queue(ProfileEvent::Connected {
+        client_name,
+        fingerprint,
+    });
+}
+
+/// Revert the profile applied for a client whose session ended.
+pub fn on_client_disconnected(client_name: Option<String>) {
+    queue(ProfileEvent::Disconnected(client_name));
+}
+
+/// Revert every applied profile when the Core stops answering.
+pub fn observe_core_disconnected() {
+    *CORE_INSTANCE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    queue(ProfileEvent::CoreGone);
+}
+
+/// Revert every applied profile when a tray state comes from a new Core
+/// instance that restarted before it was seen disconnected.
+pub fn observe_core_instance(instance_id: &str) {
+    let previous = CORE_INSTANCE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(instance_id.to_string());
+    if previous.is_some_and(|previous| previous != instance_id) {
+        queue(ProfileEvent::CoreGone);
+    }
+}
+
+#[tauri::command]
+pub fn list_client_profiles() -> Vec<ClientProfile> {
+    read_store().profiles
+}
+
+/// Create a profile, or replace the one with the same `id`.
+#[tauri::command]
+pub fn save_client_profile(profile: ClientProfile) -> Result<ClientProfile, String> {
+    let profile = profile.validated()?;
+    let (saved, _) = update_store(|store| store.upsert(profile))?;
+    info!("💾 已保存客户端配置 '{}'", saved.name);
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_client_profile(id: String) -> Result<Vec<ClientProfile>, String> {
+    let (_, store) = update_store(|store| store.profiles.retain(|profile| profile.id != id))?;
+    Ok(store.profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, client_name: Option<&str>, fingerprint: Option<&str>) -> ClientProfile {
+        ClientProfile {
+            id: name.to_string(),
+            name: name.to_string(),
+            client_name: client_name.map(str::to_string),
+            client_fingerprint: fingerprint.map(str::to_string),
+            ..ClientProfile::default()
+        }
+    }
+
+    #[test]
+    fn profiles_match_fingerprint_before_name() {
+        let store = ProfileStore {
+            profiles: vec![
+                profile("tv", Some("Living Room TV"), None),
+                profile("deck", None, Some("fp-deck")),
+            ],
+        };
+        assert_eq!(
+            store
+                .find(Some("living room tv"), None)
+                .map(|p| p.id.as_str()),
+            Some("tv")
+        );
+        assert_eq!(
+            store
+                .find(Some("Living Room TV"), Some("fp-deck"))
+                .map(|p| p.id.as_str()),
+            Some("deck")
+        );
+        assert!(store.find(Some("Laptop"), Some("fp-other")).is_none());
+        assert!(store.find(None, None).is_none());
+    }
+
+    #[test]
+    fn profile_validation_requires_a_match_key() {
+        assert!(profile("empty", None, None).validated().is_err());
+        assert!(profile("blank", Some("  "), None).validated().is_err());
+        let invalid = ClientProfile {
+            bitrate_kbps: Some(0),
+            ..profile("tv", Some("TV"), None)
+        };
+        assert!(invalid.validated().is_err());
+        assert!(profile("tv", Some(" TV "), None).validated().is_ok());
+    }
+
+    #[test]
+    fn vdd_profile_moves_mode_to_the_front() {
+        let mut list = vec![1, 2, 3];
+        move_to_front(&mut list, 3);
+        assert_eq!(list, vec![3, 1, 2]);
+        move_to_front(&mut list, 4);
+        assert_eq!(list, vec![4, 3, 1, 2]);
+    }
+}
@@ -131,6 +131,15 @@ fn encode_frame(f: &Frame) -> Vec<u8> {
     out
 }
 
+/// Wire frame for a text item, as a streaming client would send it.
+pub(crate) fn text_frame(text: &str) -> Vec<u8> {
+    encode_frame(&Frame {
+        kind: Kind::Text,
+        token: 0,
+        payload: text.as_bytes().to_vec(),
+    })
+}
+
 fn decode_frame(bytes: &[u8]) -> Option<Frame> {
     if bytes.len() < 10 || bytes[0] != WIRE_VERSION {
         return None;
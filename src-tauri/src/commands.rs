@@ -1,8 +1,10 @@
-use crate::{toolbar, windows};
+use crate::{
+    apps::{App, Flag},
+    toolbar, windows,
+};
 use base64::Engine as _;
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use std::sync::{
     OnceLock,
     atomic::{AtomicBool, Ordering},
@@ -135,48 +137,20 @@ pub async fn open_tool_window(app: AppHandle, tool_name: String) -> Result<(), S
     Ok(())
 }
 
-fn value_string(value: &Value, key: &str) -> Option<String> {
-    value
-        .get(key)
-        .and_then(Value::as_str)
-        .map(str::trim)
-        .filter(|value| !value.is_empty())
-        .map(ToOwned::to_owned)
-}
-
-fn value_bool(value: Option<&Value>) -> Option<bool> {
-    match value? {
-        Value::Bool(value) => Some(*value),
-        Value::String(value) => match value.trim().to_ascii_lowercase().as_str() {
-            "true" | "1" | "yes" | "enabled" => Some(true),
-            "false" | "0" | "no" | "disabled" | "" => Some(false),
-            _ => None,
-        },
-        Value::Number(value) => Some(value.as_i64().unwrap_or(0) != 0),
-        _ => None,
-    }
-}
-
 #[tauri::command]
 pub async fn launch_app(
     cmd: Option<String>,
     working_dir: Option<String>,
     elevated: Option<bool>,
-    app: Option<Value>,
+    app: Option<App>,
 ) -> Result<(), String> {
     let cmd = cmd
-        .or_else(|| app.as_ref().and_then(|app| value_string(app, "cmd")))
+        .filter(|cmd| !cmd.trim().is_empty())
+        .or_else(|| app.as_ref().map(|app| app.cmd().to_string()))
         .unwrap_or_default();
     let has_detached = app
         .as_ref()
-        .and_then(|app| app.get("detached"))
-        .and_then(Value::as_array)
-        .is_some_and(|items| {
-            items
-                .iter()
-                .filter_map(Value::as_str)
-                .any(|cmd| !cmd.trim().is_empty())
-        });
+        .is_some_and(|app| app.detached_cmds().next().is_some());
 
     if cmd.trim().is_empty() && !has_detached {
         return Err("Launch command cannot be empty".to_string());
@@ -185,10 +159,13 @@ pub async fn launch_app(
 
     let working_dir = working_dir.or_else(|| {
         app.as_ref()
-            .and_then(|app| value_string(app, "working-dir"))
+            .and_then(|app| app.working_dir().map(ToOwned::to_owned))
     });
     let is_elevated = elevated
-        .or_else(|| app.as_ref().and_then(|app| value_bool(app.get("elevated"))))
+        .or_else(|| {
+            app.as_ref()
+                .and_then(|app| app.elevated.map(|flag| flag.value))
+        })
         .unwrap_or(false);
 
     tokio::task::spawn_blocking(move || {
@@ -314,23 +291,17 @@ pub async fn launch_app(
             .map(|d| to_wide(d));
 
         if let Some(app) = app.as_ref() {
-            if let Some(prep_cmds) = app.get("prep-cmd").and_then(Value::as_array) {
-                for prep in prep_cmds {
-                    let Some(do_cmd) = value_string(prep, "do") else {
-                        continue;
-                    };
-                    let elevated = value_bool(prep.get("elevated")).unwrap_or(false);
-                    run_command(&do_cmd, elevated, dir_wide.as_ref(), "prep-cmd")?;
+            for prep in app.prep_cmds() {
+                let do_cmd = prep.do_cmd.trim();
+                if do_cmd.is_empty() {
+                    continue;
                 }
+                let elevated = Flag::is_set(&prep.elevated);
+                run_command(do_cmd, elevated, dir_wide.as_ref(), "prep-cmd")?;
             }
 
-            if let Some(detached_cmds) = app.get("detached").and_then(Value::as_array) {
-                for detached in detached_cmds {
-                    let Some(detached_cmd) = detached.as_str() else {
-                        continue;
-                    };
-                    run_command(detached_cmd, is_elevated, dir_wide.as_ref(), "detached")?;
-                }
+            for detached_cmd in app.detached_cmds() {
+                run_command(detached_cmd, is_elevated, dir_wide.as_ref(), "detached")?;
             }
         }
 
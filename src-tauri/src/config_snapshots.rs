@@ -0,0 +1,553 @@
+//! Copies of `sunshine.conf` and `apps.json` taken before the GUI writes them.
+//!
+//! Snapshots live in `<config>/Sunshine GUI/config-snapshots/<id>/` next to an
+//! `index.json` that records when and why each one was taken. Only the newest
+//! [`MAX_SNAPSHOTS`] are kept. Snapshots always copy this machine's files, so
+//! every write they guard goes to the local Core through
+//! [`sunshine::save_sunshine_config`], whichever host the main window shows.
+//! Restoring hands `sunshine.conf` to the same function, writes `apps.json`
+//! back like the app editor does, and offers to restart the Core so both take
+//! effect. A stopped Core gets `sunshine.conf` written directly and reads it
+//! when it starts.
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::core_api::CoreApiError;
+use crate::sunshine::{self, conf::SunshineConf};
+
+const SNAPSHOTS_DIR: &str = "config-snapshots";
+const INDEX_FILE: &str = "index.json";
+const MAX_SNAPSHOTS: usize = 20;
+const CONF_FILE: &str = "sunshine.conf";
+const SNAPSHOT_FILES: &[&str] = &[CONF_FILE, "apps.json"];
+
+/// Serializes snapshot creation, pruning and restores.
+static SNAPSHOT_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotReason {
+    Import,
+    Reset,
+    VddSync,
+    LocaleChange,
+    AppsEdit,
+    /// Taken right before another snapshot is restored.
+    Restore,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSnapshot {
+    pub id: String,
+    pub created_at: String,
+    pub reason: SnapshotReason,
+    pub detail: Option<String>,
+    /// Files that existed when the snapshot was taken.
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SnapshotIndex {
+    /// Oldest first.
+    snapshots: Vec<ConfigSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigValueDiff {
+    pub key: String,
+    pub snapshot: Option<String>,
+    pub current: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSnapshotDiff {
+    /// `sunshine.conf` keys whose snapshot value differs from the current one.
+    pub config: Vec<ConfigValueDiff>,
+    /// Apps, by name, that differ between the snapshot and the current `apps.json`.
+    pub apps: Vec<ConfigValueDiff>,
+}
+
+struct SnapshotStore {
+    root: PathBuf,
+    config_dir: PathBuf,
+}
+
+impl SnapshotStore {
+    fn open() -> Result<Self, String> {
+        Ok(Self {
+            root: crate::desktop_settings::settings_dir()?.join(SNAPSHOTS_DIR),
+            config_dir: sunshine::config_dir(),
+        })
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.root.join(INDEX_FILE)
+    }
+
+    fn load_index(&self) -> SnapshotIndex {
+        let Ok(text) = fs::read_to_string(self.index_path()) else {
+            return SnapshotIndex::default();
+        };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            warn!("Invalid config snapshot index, starting over: {}", e);
+            SnapshotIndex::default()
+        })
+    }
+
+    fn save_index(&self, index: &SnapshotIndex) -> Result<(), String> {
+        fs::create_dir_all(&self.root).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+        write_atomically(&self.index_path(), json.as_bytes())
+    }
+
+    fn snapshot_dir(&self, id: &str) -> Result<PathBuf, String> {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid snapshot id: {id}"));
+        }
+        Ok(self.root.join(id))
+    }
+
+    fn read_current(&self, file: &str) -> Option<Vec<u8>> {
+        fs::read(self.config_dir.join(file)).ok()
+    }
+
+    fn read_snapshot(
+        &self,
+        snapshot: &ConfigSnapshot,
+        file: &str,
+    ) -> Result<Option<Vec<u8>>, String> {
+        if !snapshot.files.iter().any(|name| name == file) {
+            return Ok(None);
+        }
+        let path = self.snapshot_dir(&snapshot.id)?.join(file);
+        fs::read(&path)
+            .map(Some)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    }
+
+    fn matches_current(&self, snapshot: &ConfigSnapshot) -> bool {
+        SNAPSHOT_FILES
+            .iter()
+            .all(|file| match self.read_snapshot(snapshot, file) {
+                Ok(saved) => saved == self.read_current(file),
+                Err(_) => false,
+            })
+    }
+
+    fn take(
+        &self,
+        reason: SnapshotReason,
+        detail: Option<String>,
+    ) -> Result<ConfigSnapshot, String> {
+        let mut index = self.load_index();
+        // Repeated writes without an edit in between would only add copies of
+        // the same files and push older, useful snapshots out. The newest
+        // reason still replaces the old one, since it names the write about
+        // to happen.
+        if let Some(latest) = index
+            .snapshots
+            .last_mut()
+            .filter(|latest| self.matches_current(latest))
+        {
+            latest.reason = reason;
+            latest.detail = detail;
+            let latest = latest.clone();
+            self.save_index(&index)?;
+            return Ok(latest);
+        }
+
+        let now = chrono::Local::now();
+        let id = format!(
+            "{}-{}",
+            now.format("%Y%m%d-%H%M%S"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        );
+        let dir = self.snapshot_dir(&id)?;
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+        let mut files = Vec::new();
+        for file in SNAPSHOT_FILES {
+            if let Some(content) = self.read_current(file) {
+                fs::write(dir.join(file), content)
+                    .map_err(|e| format!("Failed to copy {}: {}", file, e))?;
+                files.push(file.to_string());
+            }
+        }
+
+        let snapshot = ConfigSnapshot {
+            id,
+            created_at: now.to_rfc3339(),
+            reason,
+            detail,
+            files,
+        };
+        index.snapshots.push(snapshot.clone());
+        self.prune(&mut index);
+        self.save_index(&index)?;
+        Ok(snapshot)
+    }
+
+    fn prune(&self, index: &mut SnapshotIndex) {
+        let excess = index.snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+        for snapshot in index.snapshots.drain(..excess) {
+            let removed = self
+                .snapshot_dir(&snapshot.id)
+                .and_then(|dir| fs::remove_dir_all(dir).map_err(|e| e.to_string()));
+            if let Err(e) = removed {
+                warn!("Failed to remove config snapshot {}: {}", snapshot.id, e);
+            }
+        }
+    }
+
+    fn find(&self, id: &str) -> Result<ConfigSnapshot, String> {
+        self.load_index()
+            .snapshots
+            .into_iter()
+            .find(|snapshot| snapshot.id == id)
+            .ok_or_else(|| format!("Config snapshot not found: {id}"))
+    }
+
+    fn diff(&self, id: &str) -> Result<ConfigSnapshotDiff, String> {
+        let snapshot = self.find(id)?;
+        let text = |content: Option<Vec<u8>>| {
+            content
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_default()
+        };
+
+        let saved_conf = text(self.read_snapshot(&snapshot, "sunshine.conf")?);
+        let current_conf = text(self.read_current("sunshine.conf"));
+        let saved_apps = text(self.read_snapshot(&snapshot, "apps.json")?);
+        let current_apps = text(self.read_current("apps.json"));
+
+        Ok(ConfigSnapshotDiff {
+            config: diff_entries(&conf_entries(&saved_conf), &conf_entries(&current_conf)),
+            apps: diff_entries(&app_entries(&saved_apps), &app_entries(&current_apps)),
+        })
+    }
+
+    /// Read every file of snapshot `id`, then snapshot the current files.
+    /// Reading first lets a damaged snapshot fail before anything changes.
+    fn prepare_restore(&self, id: &str) -> Result<RestorePlan, String> {
+        let snapshot = self.find(id)?;
+        let mut contents = Vec::new();
+        for file in SNAPSHOT_FILES {
+            contents.push((*file, self.read_snapshot(&snapshot, file)?));
+        }
+
+        self.take(SnapshotReason::Restore, Some(snapshot.id.clone()))?;
+        Ok(RestorePlan { snapshot, contents })
+    }
+
+    /// Replace a current file, or remove it when the snapshot had none.
+    fn write_current(&self, file: &str, content: Option<&[u8]>) -> Result<(), String> {
+        let path = self.config_dir.join(file);
+        match content {
+            Some(content) => write_atomically(&path, content),
+            None if path.exists() => fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e)),
+            None => Ok(()),
+        }
+    }
+}
+
+struct RestorePlan {
+    snapshot: ConfigSnapshot,
+    contents: Vec<(&'static str, Option<Vec<u8>>)>,
+}
+
+impl RestorePlan {
+    fn content(&self, file: &str) -> Option<&[u8]> {
+        self.contents
+            .iter()
+            .find(|(name, _)| *name == file)
+            .and_then(|(_, content)| content.as_deref())
+    }
+}
+
+fn write_atomically(path: &Path, content: &[u8]) -> Result<(), String> {
+    let temp = path.with_extension("snapshot.tmp");
+    fs::write(&temp, content).map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+    fs::rename(&temp, path).map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        format!("Failed to replace {}: {}", path.display(), e)
+    })
+}
+
+fn conf_entries(content: &str) -> Vec<(String, String)> {
+    SunshineConf::parse(content)
+        .entries()
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+fn app_entries(content: &str) -> Vec<(String, String)> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+    json.get("apps")
+        .and_then(|apps| apps.as_array())
+        .map(|apps| {
+            apps.iter()
+                .map(|app| {
+                    let name = app.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+                    (name.to_string(), app.to_string())
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn diff_entries(saved: &[(String, String)], current: &[(String, String)]) -> Vec<ConfigValueDiff> {
+    let lookup = |entries: &[(String, String)], key: &str| {
+        entries
+            .iter()
+            .rev()
+            .find(|(candidate, _)| candidate == key)
+            .map(|(_, value)| value.clone())
+    };
+    let keys: BTreeSet<&String> = saved.iter().chain(current).map(|(key, _)| key).collect();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let snapshot = lookup(saved, key);
+            let current = lookup(current, key);
+            (snapshot != current).then(|| ConfigValueDiff {
+                key: key.clone(),
+                snapshot,
+                current,
+            })
+        })
+        .collect()
+}
+
+/// Snapshot the local Core config files before a GUI-initiated write. A
+/// failure here aborts the write: without a snapshot it could not be rolled
+/// back. The guarded write must target this machine's Core, never the host
+/// selected in the main window.
+pub fn snapshot_before_write(reason: SnapshotReason, detail: Option<String>) -> Result<(), String> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let snapshot = SnapshotStore::open()?
+        .take(reason, detail)
+        .map_err(|e| format!("Failed to snapshot config before writing: {}", e))?;
+    info!("📸 配置快照 {} ({:?})", snapshot.id, snapshot.reason);
+    Ok(())
+}
+
+/// Snapshots, newest first.
+#[tauri::command]
+pub fn list_config_snapshots() -> Result<Vec<ConfigSnapshot>, String> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut snapshots = SnapshotStore::open()?.load_index().snapshots;
+    snapshots.reverse();
+    Ok(snapshots)
+}
+
+#[tauri::command]
+pub fn diff_config_snapshot(id: String) -> Result<ConfigSnapshotDiff, String> {
+    let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    SnapshotStore::open()?.diff(&id)
+}
+
+/// Send a snapshot's `sunshine.conf` to the local Core along the same path as
+/// the writes snapshots guard. Returns `false` when the Core is not running
+/// and the file was written directly instead.
+async fn restore_conf(store: &SnapshotStore, plan: &RestorePlan) -> Result<bool, String> {
+    let content = plan.content(CONF_FILE);
+    let text = content
+        .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+        .unwrap_or_default();
+    let config: serde_json::Map<String, serde_json::Value> = conf_entries(&text)
+        .into_iter()
+        .map(|(key, value)| (key, serde_json::Value::String(value)))
+        .collect();
+
+    match sunshine::save_sunshine_config(&config).await {
+        Ok(()) => Ok(true),
+        // The request never reached the Core; a stopped Core reads the file
+        // when it starts.
+        Err(CoreApiError::Transport {
+            message,
+            delivery_unknown: false,
+        }) => {
+            warn!(
+                "Core unreachable, restoring sunshine.conf directly: {}",
+                message
+            );
+            let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            store.write_current(CONF_FILE, content)?;
+            Ok(false)
+        }
+        Err(error) => Err(format!("Failed to restore sunshine.conf: {}", error)),
+    }
+}
+
+fn prompt_restart(app: &AppHandle, snapshot: &ConfigSnapshot) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    app.dialog()
+        .message(format!(
+            "Config snapshot {} was restored. Restart Sunshine now so it loads the restored settings and apps?",
+            snapshot.id
+        ))
+        .title("Restore Config Snapshot")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::YesNo)
+        .show(|confirmed| {
+            if !confirmed {
+                return;
+            }
+            tauri::async_runtime::spawn(async {
+                if let Err(e) = sunshine::post_tray_restart_action().await {
+                    warn!("Restart after restoring a config snapshot failed: {}", e);
+                }
+            });
+        });
+}
+
+/// Restore a snapshot over the current config, snapshotting it first, then
+/// ask whether to restart the Core.
+#[tauri::command]
+pub async fn restore_config_snapshot(app: AppHandle, id: String) -> Result<ConfigSnapshot, String> {
+    let (store, plan) = {
+        let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let store = SnapshotStore::open()?;
+        let plan = store.prepare_restore(&id)?;
+        (store, plan)
+    };
+
+    let core_running = restore_conf(&store, &plan).await?;
+    {
+        let _guard = SNAPSHOT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        for (file, content) in plan.contents.iter().filter(|(file, _)| *file != CONF_FILE) {
+            store.write_current(file, content.as_deref())?;
+        }
+    }
+    info!("⏪ 已恢复配置快照 {}", plan.snapshot.id);
+    if core_running {
+        prompt_restart(&app, &plan.snapshot);
+    }
+    Ok(plan.snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> SnapshotStore {
+        let root =
+            std::env::temp_dir().join(format!("sunshine-snapshots-{}", uuid::Uuid::new_v4()));
+        let config_dir = root.join("config");
+        fs::create_dir_all(&config_dir).unwrap();
+        SnapshotStore {
+            root: root.join("snapshots"),
+            config_dir,
+        }
+    }
+
+    fn write_config(store: &SnapshotStore, conf: &str, apps: &str) {
+        fs::write(store.config_dir.join("sunshine.conf"), conf).unwrap();
+        fs::write(store.config_dir.join("apps.json"), apps).unwrap();
+    }
+
+    #[test]
+    fn snapshots_skip_duplicates_and_prune_oldest() {
+        let store = temp_store();
+        write_config(&store, "port = 47989\n", r#"{"apps":[]}"#);
+        let first = store.take(SnapshotReason::Import, None).unwrap();
+        let again = store
+            .take(SnapshotReason::Reset, Some("defaults".to_string()))
+            .unwrap();
+        assert_eq!(first.id, again.id);
+        let latest = store.load_index().snapshots.pop().unwrap();
+        assert_eq!(latest.reason, SnapshotReason::Reset);
+        assert_eq!(latest.detail.as_deref(), Some("defaults"));
+
+        for port in 0..MAX_SNAPSHOTS {
+            write_config(
+                &store,
+                &format!("port = {}\n", 48000 + port),
+                r#"{"apps":[]}"#,
+            );
+            store.take(SnapshotReason::LocaleChange, None).unwrap();
+        }
+        let index = store.load_index();
+        assert_eq!(index.snapshots.len(), MAX_SNAPSHOTS);
+        assert!(
+            index
+                .snapshots
+                .iter()
+                .all(|snapshot| snapshot.id != first.id)
+        );
+        assert!(!store.root.join(&first.id).exists());
+
+        let _ = fs::remove_dir_all(store.config_dir.parent().unwrap());
+    }
+
+    #[test]
+    fn snapshot_diff_and_restore_round_trip() {
+        let store = temp_store();
+        write_config(
+            &store,
+            "# keep\nport = 47989\nlocale = en\n",
+            r#"{"apps":[{"name":"Desktop"}]}"#,
+        );
+        let snapshot = store
+            .take(SnapshotReason::Import, Some("a.conf".to_string()))
+            .unwrap();
+
+        write_config(
+            &store,
+            "port = 48000\n",
+            r#"{"apps":[{"name":"Desktop"},{"name":"Steam"}]}"#,
+        );
+        let diff = store.diff(&snapshot.id).unwrap();
+        assert_eq!(
+            diff.config,
+            vec![
+                ConfigValueDiff {
+                    key: "locale".to_string(),
+                    snapshot: Some("en".to_string()),
+                    current: None,
+                },
+                ConfigValueDiff {
+                    key: "port".to_string(),
+                    snapshot: Some("47989".to_string()),
+                    current: Some("48000".to_string()),
+                },
+            ]
+        );
+        assert_eq!(diff.apps.len(), 1);
+        assert_eq!(diff.apps[0].key, "Steam");
+
+        let plan = store.prepare_restore(&snapshot.id).unwrap();
+        for (file, content) in &plan.contents {
+            store.write_current(file, content.as_deref()).unwrap();
+        }
+        assert_eq!(
+            fs::read_to_string(store.config_dir.join("sunshine.conf")).unwrap(),
+            "# keep\nport = 47989\nlocale = en\n"
+        );
+        let index = store.load_index();
+        assert_eq!(
+            index.snapshots.last().unwrap().reason,
+            SnapshotReason::Restore
+        );
+        assert!(store.diff(&snapshot.id).unwrap().config.is_empty());
+        assert!(store.prepare_restore("../escape").is_err());
+
+        let _ = fs::remove_dir_all(store.config_dir.parent().unwrap());
+    }
+}
@@ -0,0 +1,699 @@
+//! Typed client for the Sunshine Core REST API.
+//!
+//! Every Core call goes through [`CoreClient`] so URL building, timeouts,
+//! retries, response envelopes and error classification live in one place.
+//! Callers that still return `Result<_, String>` can use `?` directly because
+//! [`CoreApiError`] converts into the user-facing message from
+//! [`CoreApiError::user_message`]; `Display` stays English for logs.
+
+use log::{debug, warn};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+const MAX_ERROR_DETAIL_CHARS: usize = 300;
+
+/// Failure of a Core API call, classified by what the caller can do about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoreApiError {
+    /// HTTP 401: the Core wants (other) credentials.
+    Auth(String),
+    /// HTTP 403: the Core refuses this caller, e.g. non-localhost access.
+    Forbidden(String),
+    /// HTTP 404, or a Core that does not know the endpoint.
+    NotFound(String),
+    /// The request did not complete. `delivery_unknown` is set when the Core
+    /// may already have acted on it, so callers must not assume a rollback.
+    Transport {
+        message: String,
+        delivery_unknown: bool,
+    },
+    /// The Core understood the request and reported a failure.
+    Rejected {
+        status: Option<u16>,
+        message: String,
+    },
+    /// The Core answered outside the documented shape.
+    Contract(String),
+}
+
+impl fmt::Display for CoreApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auth(detail) => write!(f, "Sunshine authentication failed: {detail}"),
+            Self::Forbidden(detail) => write!(f, "Sunshine denied access: {detail}"),
+            Self::NotFound(detail) => write!(f, "Sunshine resource not found: {detail}"),
+            Self::Transport { message, .. } => write!(f, "Sunshine request failed: {message}"),
+            Self::Rejected {
+                status: Some(status),
+                message,
+            } => write!(f, "Sunshine rejected the request ({status}): {message}"),
+            Self::Rejected {
+                status: None,
+                message,
+            } => write!(f, "Sunshine rejected the request: {message}"),
+            Self::Contract(detail) => write!(f, "Unexpected Sunshine response: {detail}"),
+        }
+    }
+}
+
+impl std::error::Error for CoreApiError {}
+
+impl From<CoreApiError> for String {
+    fn from(error: CoreApiError) -> Self {
+        error.user_message()
+    }
+}
+
+impl CoreApiError {
+    /// The message shown to the user when a command fails.
+    pub fn user_message(&self) -> String {
+        match self {
+            Self::Auth(detail) => format!("Sunshine 身份验证失败: {detail}"),
+            Self::Forbidden(detail) => format!("Sunshine 拒绝访问: {detail}"),
+            Self::NotFound(detail) => format!("Sunshine 找不到请求的资源: {detail}"),
+            Self::Transport { message, .. } => format!("无法连接 Sunshine: {message}"),
+            Self::Rejected {
+                status: Some(status),
+                message,
+            } => format!("Sunshine 拒绝了请求 ({status}): {message}"),
+            Self::Rejected {
+                status: None,
+                message,
+            } => format!("Sunshine 拒绝了请求: {message}"),
+            Self::Contract(detail) => format!("Sunshine 返回了无法识别的响应: {detail}"),
+        }
+    }
+
+    /// The message reported by the Core or transport, without the category
+    /// prefix used by `Display`.
+    pub fn detail(&self) -> &str {
+        match self {
+            Self::Auth(detail)
+            | Self::Forbidden(detail)
+            | Self::NotFound(detail)
+            | Self::Contract(detail) => detail,
+            Self::Transport { message, .. } | Self::Rejected { message, .. } => message,
+        }
+    }
+
+    fn from_reqwest(error: reqwest::Error) -> Self {
+        Self::Transport {
+            message: error.to_string(),
+            // A refused connection never reached the Core; anything later
+            // (timeouts, resets) may have been processed.
+            delivery_unknown: !error.is_connect() && !error.is_builder(),
+        }
+    }
+}
+
+/// Success envelope the endpoint wraps its payload in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Envelope {
+    /// Plain payload without a success flag.
+    None,
+    /// `{"success": bool, "status_code": n, "status_message": "..."}` used by
+    /// `/api/runtime/*`.
+    Success,
+    /// `{"ok": bool, "error": "..."}` used by `/api/v1/*`.
+    Ok,
+    /// `{"status": true | "true", "error": "..."}` used by `/api/config` and
+    /// the tray endpoints.
+    Status,
+}
+
+fn str_field<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    value
+        .get(key)
+        .and_then(serde_json::Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+/// Reject a decoded body whose envelope reports failure or is malformed.
+pub fn check_envelope(envelope: Envelope, body: &serde_json::Value) -> Result<(), CoreApiError> {
+    match envelope {
+        Envelope::None => Ok(()),
+        Envelope::Success => match body.get("success").and_then(serde_json::Value::as_bool) {
+            Some(true) => Ok(()),
+            Some(false) => {
+                let message = str_field(body, "status_message")
+                    .unwrap_or("unknown error")
+                    .to_string();
+                let status = body
+                    .get("status_code")
+                    .and_then(serde_json::Value::as_u64)
+                    .and_then(|code| u16::try_from(code).ok());
+                Err(match status {
+                    Some(401) => CoreApiError::Auth(message),
+                    Some(403) => CoreApiError::Forbidden(message),
+                    Some(404) => CoreApiError::NotFound(message),
+                    status => CoreApiError::Rejected { status, message },
+                })
+            }
+            None => Err(CoreApiError::Contract(
+                "response is missing the success flag".to_string(),
+            )),
+        },
+        Envelope::Ok => match body.get("ok").and_then(serde_json::Value::as_bool) {
+            Some(true) => Ok(()),
+            Some(false) => Err(CoreApiError::Rejected {
+                status: None,
+                message: str_field(body, "error")
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            }),
+            None => Err(CoreApiError::Contract(
+                "response is missing the ok flag".to_string(),
+            )),
+        },
+        Envelope::Status => {
+            let succeeded = match body.get("status") {
+                Some(serde_json::Value::Bool(status)) => *status,
+                Some(serde_json::Value::String(status)) => status.eq_ignore_ascii_case("true"),
+                _ => {
+                    return Err(CoreApiError::Contract(
+                        "response is missing a valid status".to_string(),
+                    ));
+                }
+            };
+            if succeeded {
+                return Ok(());
+            }
+            Err(CoreApiError::Rejected {
+                status: None,
+                message: str_field(body, "error")
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            })
+        }
+    }
+}
+
+fn truncate_detail(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(MAX_ERROR_DETAIL_CHARS) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
+    }
+}
+
+/// Prefer the message the Core put in a JSON error body over the raw body.
+fn error_detail(status: reqwest::StatusCode, body: &str) -> String {
+    let from_json = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|json| {
+            ["status_message", "error", "message"]
+                .into_iter()
+                .find_map(|key| str_field(&json, key).map(str::to_string))
+        });
+    match from_json {
+        Some(message) => message,
+        None if body.trim().is_empty() => status.to_string(),
+        None => truncate_detail(body),
+    }
+}
+
+fn is_xml_body(content_type: &str, body: &str) -> bool {
+    content_type.contains("xml") || body.trim_start().starts_with("<?xml")
+}
+
+/// Map a non-success HTTP status to an error.
+pub fn error_for_status(status: reqwest::StatusCode, body: &str) -> CoreApiError {
+    let detail = error_detail(status, body);
+    match status.as_u16() {
+        401 => CoreApiError::Auth(detail),
+        403 => CoreApiError::Forbidden(detail),
+        404 => CoreApiError::NotFound(detail),
+        502..=504 => CoreApiError::Transport {
+            message: format!("{status}: {detail}"),
+            delivery_unknown: true,
+        },
+        code => CoreApiError::Rejected {
+            status: Some(code),
+            message: detail,
+        },
+    }
+}
+
+/// When a failed attempt may be repeated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first one.
+    pub max_attempts: u32,
+    /// Delay before the second attempt; doubled for every further attempt.
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub const NONE: Self = Self {
+        max_attempts: 1,
+        initial_backoff: Duration::ZERO,
+    };
+
+    /// Idempotent requests retry any transport failure. Other requests only
+    /// retry when the Core provably never received them.
+    fn should_retry(
+        &self,
+        method: &reqwest::Method,
+        error: &CoreApiError,
+        attempt: u32,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let CoreApiError::Transport {
+            delivery_unknown, ..
+        } = error
+        else {
+            return None;
+        };
+        if *delivery_unknown && !method.is_idempotent() {
+            return None;
+        }
+        Some(self.initial_backoff * 2u32.saturating_pow(attempt - 1))
+    }
+}
+
+/// Which Core instance a client talks to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Direct,
+    /// Through the loopback proxy, which needs the session access token.
+    Proxy,
+}
+
+#[derive(Debug, Clone)]
+pub struct CoreClient {
+    base_url: String,
+    http: reqwest::Client,
+    route: Route,
+    /// Basic credentials of a registered remote host.
+    authorization: Option<String>,
+    timeout: Duration,
+    retry: RetryPolicy,
+}
+
+impl CoreClient {
+    fn new(base_url: &str, route: Route) -> Result<Self, CoreApiError> {
+        let http =
+            crate::sunshine::create_https_client().map_err(|message| CoreApiError::Transport {
+                message,
+                delivery_unknown: false,
+            })?;
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+            route,
+            authorization: None,
+            timeout: DEFAULT_TIMEOUT,
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    fn unresolved(message: String) -> CoreApiError {
+        CoreApiError::Transport {
+            message: format!("cannot resolve Sunshine URL: {message}"),
+            delivery_unknown: false,
+        }
+    }
+
+    /// The Core the main window currently targets, which may be remote. A
+    /// remote host from the host registry is called with its stored
+    /// credentials, like the proxy does for the WebView.
+    pub async fn target() -> Result<Self, CoreApiError> {
+        let url = crate::sunshine::get_sunshine_url()
+            .await
+            .map_err(Self::unresolved)?;
+        let mut client = Self::new(&url, Route::Direct)?;
+        client.authorization = crate::proxy_server::sunshine_target_authorization();
+        Ok(client)
+    }
+
+    /// The Core installed on this machine. Tray traffic always uses this.
+    pub async fn local() -> Result<Self, CoreApiError> {
+        let url = crate::sunshine::get_local_sunshine_url()
+            .await
+            .map_err(Self::unresolved)?;
+        Self::new(&url, Route::Direct)
+    }
+
+    /// The Core behind the loopback proxy, for pollers that should share the
+    /// proxy's fast-fail state with the WebView.
+    pub fn via_proxy() -> Result<Self, CoreApiError> {
+        Self::new(&crate::proxy_server::get_proxy_url(), Route::Proxy)
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn request(&self, method: reqwest::Method, path: &str) -> CoreRequest<'_> {
+        CoreRequest {
+            client: self,
+            method,
+            path: path.trim_start_matches('/').to_string(),
+            query: Vec::new(),
+            body: None,
+            envelope: Envelope::None,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+        }
+    }
+
+    pub fn get(&self, path: &str) -> CoreRequest<'_> {
+        self.request(reqwest::Method::GET, path)
+    }
+
+    pub fn post(&self, path: &str) -> CoreRequest<'_> {
+        self.request(reqwest::Method::POST, path)
+    }
+
+    pub fn patch(&self, path: &str) -> CoreRequest<'_> {
+        self.request(reqwest::Method::PATCH, path)
+    }
+
+    pub fn delete(&self, path: &str) -> CoreRequest<'_> {
+        self.request(reqwest::Method::DELETE, path)
+    }
+}
+
+#[must_use]
+pub struct CoreRequest<'a> {
+    client: &'a CoreClient,
+    method: reqwest::Method,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Option<serde_json::Value>,
+    envelope: Envelope,
+    max_response_bytes: usize,
+}
+
+impl CoreRequest<'_> {
+    pub fn query(mut self, key: &str, value: impl ToString) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn json(mut self, body: &impl Serialize) -> Result<Self, CoreApiError> {
+        let body = serde_json::to_value(body)
+            .map_err(|e| CoreApiError::Contract(format!("cannot encode request body: {e}")))?;
+        self.body = Some(body);
+        Ok(self)
+    }
+
+    pub fn envelope(mut self, envelope: Envelope) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    pub fn max_response_bytes(mut self, limit: usize) -> Self {
+        self.max_response_bytes = limit;
+        self
+    }
+
+    fn url(&self) -> Result<url::Url, CoreApiError> {
+        let mut url = url::Url::parse(&format!("{}/{}", self.client.base_url, self.path))
+            .map_err(|e| CoreApiError::Contract(format!("invalid endpoint {}: {e}", self.path)))?;
+        if !self.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query);
+        }
+        Ok(url)
+    }
+
+    async fn attempt(&self, url: &url::Url) -> Result<serde_json::Value, CoreApiError> {
+        let mut builder = self
+            .client
+            .http
+            .request(self.method.clone(), url.as_str())
+            .timeout(self.client.timeout);
+        if let Some(body) = &self.body {
+            builder = builder.json(body);
+        }
+        if let Some(authorization) = &self.client.authorization {
+            builder = builder.header(reqwest::header::AUTHORIZATION, authorization);
+        }
+        if self.client.route == Route::Proxy {
+            builder = crate::proxy_server::with_proxy_access(builder, url.as_str());
+        }
+
+        let mut response = builder.send().await.map_err(CoreApiError::from_reqwest)?;
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        if response
+            .content_length()
+            .is_some_and(|size| size > self.max_response_bytes as u64)
+        {
+            return Err(CoreApiError::Contract(format!(
+                "response exceeds {} bytes",
+                self.max_response_bytes
+            )));
+        }
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| CoreApiError::Transport {
+                message: format!("reading response failed: {e}"),
+                delivery_unknown: true,
+            })?
+        {
+            if bytes.len().saturating_add(chunk.len()) > self.max_response_bytes {
+                return Err(CoreApiError::Contract(format!(
+                    "response exceeds {} bytes",
+                    self.max_response_bytes
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        let text = String::from_utf8(bytes)
+            .map_err(|e| CoreApiError::Contract(format!("response is not UTF-8: {e}")))?;
+
+        // Older Core builds answer unknown routes with an XML error document.
+        if is_xml_body(&content_type, &text) {
+            return Err(CoreApiError::NotFound(format!(
+                "{} is not supported",
+                self.path
+            )));
+        }
+        if !status.is_success() {
+            return Err(error_for_status(status, &text));
+        }
+
+        let body = serde_json::from_str(&text).map_err(|e| {
+            CoreApiError::Contract(format!(
+                "invalid JSON: {e}; body: {}",
+                truncate_detail(&text)
+            ))
+        })?;
+        check_envelope(self.envelope, &body)?;
+        Ok(body)
+    }
+
+    /// Send the request and return the raw JSON body once its envelope has
+    /// been checked.
+    pub async fn send_value(self) -> Result<serde_json::Value, CoreApiError> {
+        let url = self.url()?;
+        let mut attempt = 1;
+        loop {
+            match self.attempt(&url).await {
+                Ok(body) => return Ok(body),
+                Err(error) => {
+                    let Some(delay) = self
+                        .client
+                        .retry
+                        .should_retry(&self.method, &error, attempt)
+                    else {
+                        debug!("Core API {} {} failed: {}", self.method, self.path, error);
+                        return Err(error);
+                    };
+                    warn!(
+                        "Core API {} {} failed (attempt {}), retrying in {:?}: {}",
+                        self.method, self.path, attempt, delay, error
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Send the request and decode the body into `T`.
+    pub async fn send<T: DeserializeOwned>(self) -> Result<T, CoreApiError> {
+        let path = self.path.clone();
+        let body = self.send_value().await?;
+        serde_json::from_value(body)
+            .map_err(|e| CoreApiError::Contract(format!("cannot decode {path} response: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_envelope_requires_business_success() {
+        assert!(check_envelope(Envelope::Status, &serde_json::json!({"status": "true"})).is_ok());
+        assert!(check_envelope(Envelope::Status, &serde_json::json!({"status": true})).is_ok());
+
+        let error = check_envelope(
+            Envelope::Status,
+            &serde_json::json!({"status": "false", "error": "failed to persist configuration"}),
+        )
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("failed to persist configuration")
+        );
+
+        assert!(matches!(
+            check_envelope(Envelope::Status, &serde_json::json!({})),
+            Err(CoreApiError::Contract(_))
+        ));
+    }
+
+    #[test]
+    fn commands_report_the_user_message() {
+        let error = CoreApiError::Rejected {
+            status: Some(400),
+            message: "invalid key".to_string(),
+        };
+        assert_eq!(String::from(error.clone()), error.user_message());
+        assert_eq!(
+            String::from(error),
+            "Sunshine 拒绝了请求 (400): invalid key"
+        );
+        assert_eq!(
+            String::from(CoreApiError::Transport {
+                message: "connection refused".to_string(),
+                delivery_unknown: false,
+            }),
+            "无法连接 Sunshine: connection refused"
+        );
+    }
+
+    #[test]
+    fn success_envelope_maps_embedded_status_codes() {
+        let body = serde_json::json!({
+            "success": false,
+            "status_code": 404,
+            "status_message": "client not found"
+        });
+        assert_eq!(
+            check_envelope(Envelope::Success, &body),
+            Err(CoreApiError::NotFound("client not found".to_string()))
+        );
+        assert!(matches!(
+            check_envelope(Envelope::Success, &serde_json::json!({"sessions": []})),
+            Err(CoreApiError::Contract(_))
+        ));
+        assert_eq!(
+            check_envelope(
+                Envelope::Ok,
+                &serde_json::json!({"ok": false, "error": "path denied"})
+            ),
+            Err(CoreApiError::Rejected {
+                status: None,
+                message: "path denied".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn http_status_maps_to_error_categories() {
+        use reqwest::StatusCode;
+
+        assert_eq!(
+            error_for_status(StatusCode::UNAUTHORIZED, ""),
+            CoreApiError::Auth("401 Unauthorized".to_string())
+        );
+        assert_eq!(
+            error_for_status(StatusCode::FORBIDDEN, r#"{"error":"localhost only"}"#),
+            CoreApiError::Forbidden("localhost only".to_string())
+        );
+        assert!(matches!(
+            error_for_status(StatusCode::NOT_FOUND, "missing"),
+            CoreApiError::NotFound(_)
+        ));
+        assert!(matches!(
+            error_for_status(StatusCode::BAD_GATEWAY, ""),
+            CoreApiError::Transport { .. }
+        ));
+        assert_eq!(
+            error_for_status(StatusCode::BAD_REQUEST, "bad value"),
+            CoreApiError::Rejected {
+                status: Some(400),
+                message: "bad value".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn retries_only_safe_transport_failures() {
+        let policy = RetryPolicy::default();
+        let refused = CoreApiError::Transport {
+            message: "refused".to_string(),
+            delivery_unknown: false,
+        };
+        let interrupted = CoreApiError::Transport {
+            message: "reset".to_string(),
+            delivery_unknown: true,
+        };
+
+        assert_eq!(
+            policy.should_retry(&reqwest::Method::POST, &refused, 1),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            policy.should_retry(&reqwest::Method::GET, &interrupted, 2),
+            Some(Duration::from_millis(400))
+        );
+        assert_eq!(
+            policy.should_retry(&reqwest::Method::POST, &interrupted, 1),
+            None
+        );
+        assert_eq!(
+            policy.should_retry(&reqwest::Method::GET, &refused, 3),
+            None
+        );
+        assert_eq!(
+            policy.should_retry(&reqwest::Method::GET, &CoreApiError::Auth(String::new()), 1),
+            None
+        );
+        assert_eq!(
+            RetryPolicy::NONE.should_retry(&reqwest::Method::GET, &refused, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn legacy_xml_answers_are_detected() {
+        assert!(is_xml_body("application/xml", ""));
+        assert!(is_xml_body("", "  <?xml version=\"1.0\"?><root/>"));
+        assert!(!is_xml_body("application/json", "{}"));
+    }
+}
@@ -0,0 +1,413 @@
+//! Watchdog that restarts this machine's Core when it dies or stops answering.
+//!
+//! The tray connection state is the primary signal; once the tray reports the
+//! Core as gone, a direct health probe confirms it before [`RestartPolicy`]
+//! decides whether to restart. The Core is restarted the way it was last seen
+//! running, as the Windows service or as a user-mode process, and never with
+//! elevation, so an automatic restart cannot raise a UAC prompt. A Core the
+//! GUI lacks the rights to restart is reported instead of counted as a
+//! restart, and the service is left to its own recovery options. The watchdog
+//! is off until the user enables it and only arms after the Core was reachable
+//! once, so a Core that was never started is left alone. A deliberate stop or
+//! a Core it may not restart disarms it again: the service in the stopped
+//! state, or a Core that announced a shutdown before going away. Settings
+//! live in `<config>/Sunshine GUI/core-watchdog.json` and every intervention
+//! is appended to `core-watchdog-history.json`.
+
+use log::{debug, error, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::sunshine::UnattendedRestartError;
+
+mod policy;
+
+pub(crate) use policy::WatchdogSettings;
+use policy::{RestartPolicy, WatchdogAction};
+
+const SETTINGS_FILE: &str = "core-watchdog.json";
+const HISTORY_FILE: &str = "core-watchdog-history.json";
+const INTERVENTION_EVENT: &str = "core-watchdog-intervention";
+const MAX_HISTORY: usize = 200;
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Manual restarts and mode switches get this long before the watchdog acts.
+const MANUAL_HOLD_OFF: Duration = Duration::from_secs(90);
+
+static SETTINGS: Lazy<RwLock<WatchdogSettings>> = Lazy::new(|| RwLock::new(load_settings()));
+static POLICY: Lazy<Mutex<RestartPolicy>> = Lazy::new(|| Mutex::new(RestartPolicy::default()));
+static HOLD_OFF_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+static CORE_MODE: Mutex<Option<CoreMode>> = Mutex::new(None);
+static WATCHDOG_STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+/// The connected Core reported a shutdown operation, from any of its frontends.
+static SHUTDOWN_ANNOUNCED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CoreMode {
+    Service,
+    User,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InterventionKind {
+    Restart,
+    CrashLoop,
+    /// The Core runs elevated and the watchdog may not restart it.
+    NotPermitted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Intervention {
+    pub timestamp: String,
+    pub kind: InterventionKind,
+    pub mode: Option<CoreMode>,
+    pub attempt: u32,
+    pub reason: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoreWatchdogStatus {
+    settings: WatchdogSettings,
+    /// Restarts are suspended after a crash loop.
+    halted: bool,
+    /// Restarts since the Core was last stable.
+    consecutive_restarts: u32,
+    /// Newest first.
+    interventions: Vec<Intervention>,
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(SETTINGS_FILE))
+}
+
+fn history_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(HISTORY_FILE))
+}
+
+fn load_settings() -> WatchdogSettings {
+    let Ok(path) = settings_path() else {
+        return WatchdogSettings::default();
+    };
+    let Ok(text) = fs::read_to_string(path) else {
+        return WatchdogSettings::default();
+    };
+    serde_json::from_str::<WatchdogSettings>(&text)
+        .map_err(|e| e.to_string())
+        .and_then(WatchdogSettings::validated)
+        .unwrap_or_else(|e| {
+            warn!("Invalid Core watchdog settings, using defaults: {}", e);
+            WatchdogSettings::default()
+        })
+}
+
+fn save_settings(settings: &WatchdogSettings) -> Result<(), String> {
+    let path = settings_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+fn current_settings() -> WatchdogSettings {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Oldest first.
+fn read_history(path: &Path) -> Vec<Intervention> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn record_intervention(app: &AppHandle, intervention: Intervention) {
+    if let Err(e) = app.emit(INTERVENTION_EVENT, &intervention) {
+        debug!("Failed to emit Core watchdog intervention: {}", e);
+    }
+
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let result = history_path().and_then(|path| {
+        let mut history = read_history(&path);
+        history.push(intervention);
+        let overflow = history.len().saturating_sub(MAX_HISTORY);
+        history.drain(..overflow);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let text = serde_json::to_string_pretty(&history).map_err(|e| e.to_string())?;
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, text).map_err(|e| e.to_string())?;
+        fs::rename(&temp, &path).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        warn!("⚠️ 保存看门狗记录失败: {}", e);
+    }
+}
+
+/// Pause the watchdog while a user-requested restart or mode switch runs.
+pub fn hold_off() {
+    *HOLD_OFF_UNTIL.lock().unwrap_or_else(|e| e.into_inner()) =
+        Some(Instant::now() + MANUAL_HOLD_OFF);
+}
+
+/// Note a Core shutdown requested through its own tray, the WebUI or the GUI.
+pub fn observe_tray_state(state: &crate::sunshine::TrayState) {
+    if state.operation.action == "shutdown" {
+        SHUTDOWN_ANNOUNCED.store(true, std::sync::atomic::Ordering::Release);
+    }
+}
+
+fn is_held_off() -> bool {
+    HOLD_OFF_UNTIL
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some_and(|until| Instant::now() < until)
+}
+
+/// Whether the Core still answers at all, bypassing the tray monitor.
+async fn probe_core() -> bool {
+    let Ok(client) = crate::core_api::CoreClient::local().await else {
+        return false;
+    };
+    client
+        .with_timeout(PROBE_TIMEOUT)
+        .with_retry(crate::core_api::RetryPolicy::NONE)
+        .get("api/tray/state")
+        .send_value()
+        .await
+        .is_ok()
+}
+
+async fn detect_mode() -> Option<CoreMode> {
+    match tokio::task::spawn_blocking(crate::sunshine::is_sunshine_running_in_user_mode_impl).await
+    {
+        Ok(Ok(true)) => Some(CoreMode::User),
+        Ok(Ok(false)) => Some(CoreMode::Service),
+        _ => None,
+    }
+}
+
+/// Whether the Core went away because someone stopped it on purpose.
+async fn stopped_deliberately(mode: Option<CoreMode>) -> bool {
+    SHUTDOWN_ANNOUNCED.load(std::sync::atomic::Ordering::Acquire) || service_stopped(mode).await
+}
+
+async fn service_stopped(mode: Option<CoreMode>) -> bool {
+    // A crashed or hung service is still reported as running.
+    mode == Some(CoreMode::Service)
+        && tokio::task::spawn_blocking(crate::sunshine::is_sunshine_service_stopped_impl)
+            .await
+            .unwrap_or(false)
+}
+
+/// Whether a lost Core connection was a restart or stop somebody asked for,
+/// rather than a crash worth telling the user about.
+pub(crate) async fn core_stop_expected() -> bool {
+    if is_held_off() || crate::tray::is_core_recovery_in_progress() {
+        return true;
+    }
+    let mode = *CORE_MODE.lock().unwrap_or_else(|e| e.into_inner());
+    service_stopped(mode).await
+}
+
+async fn restart_core(mode: Option<CoreMode>) -> Result<(), UnattendedRestartError> {
+    crate::sunshine::restart_sunshine_unattended(mode == Some(CoreMode::User)).await
+}
+
+/// Carry out `action` and return whether the watchdog stays armed.
+async fn intervene(app: &AppHandle, action: WatchdogAction, mode: Option<CoreMode>) -> bool {
+    match action {
+        WatchdogAction::None => {}
+        WatchdogAction::Restart {
+            attempt,
+            unhealthy_secs,
+            backoff_secs,
+        } => {
+            let reason = format!("Core unreachable for {:.0}s", unhealthy_secs);
+            warn!(
+                "🐕 看门狗: {}，第 {} 次重启 ({:?})，下次至少间隔 {}s",
+                reason, attempt, mode, backoff_secs
+            );
+            let (kind, error) = match restart_core(mode).await {
+                Ok(()) => (InterventionKind::Restart, None),
+                Err(UnattendedRestartError::NotPermitted(message)) => {
+                    // Not a restart: nothing ran, so it must not push the
+                    // Core toward a crash-loop halt.
+                    warn!("🐕 看门狗: {}，等待 Sunshine 重新连接后再监控", message);
+                    POLICY
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .forget_restart();
+                    (InterventionKind::NotPermitted, Some(message))
+                }
+                Err(UnattendedRestartError::Failed(message)) => {
+                    error!("看门狗重启 Sunshine 失败: {}", message);
+                    (InterventionKind::Restart, Some(message))
+                }
+            };
+            record_intervention(
+                app,
+                Intervention {
+                    timestamp: chrono::Local::now().to_rfc3339(),
+                    kind,
+                    mode,
+                    attempt,
+                    reason,
+                    error,
+                },
+            );
+            return kind != InterventionKind::NotPermitted;
+        }
+        WatchdogAction::CrashLoop { restarts } => {
+            let settings = current_settings();
+            let minutes = settings.window_secs.div_ceil(60);
+            error!(
+                "🐕 看门狗: Sunshine 在 {} 分钟内重启了 {} 次，暂停自动重启",
+                minutes, restarts
+            );
+            let notify_handle = app.clone();
+            if let Err(e) = app.run_on_main_thread(move || {
+                crate::tray::notify_core_crash_loop(&notify_handle, restarts, minutes);
+            }) {
+                debug!("Failed to schedule crash loop notification: {}", e);
+            }
+            record_intervention(
+                app,
+                Intervention {
+                    timestamp: chrono::Local::now().to_rfc3339(),
+                    kind: InterventionKind::CrashLoop,
+                    mode,
+                    attempt: u32::try_from(restarts).unwrap_or(u32::MAX),
+                    reason: format!("{} restarts within {} minutes", restarts, minutes),
+                    error: None,
+                },
+            );
+        }
+    }
+    true
+}
+
+pub fn start(app: AppHandle) {
+    use std::sync::atomic::Ordering;
+
+    if !cfg!(target_os = "windows") {
+        debug!("Core watchdog is only available on Windows");
+        return;
+    }
+    // The simulated Core must never restart the real one.
+    if crate::simulated_core::url().is_some() {
+        info!("Core watchdog disabled while the simulated Core runs");
+        return;
+    }
+    if WATCHDOG_STARTED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let started = Instant::now();
+        let mut armed = false;
+        let mut was_connected = false;
+        let mut mode = None;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let settings = current_settings();
+            let connected = crate::tray::is_core_connected();
+            if connected && !was_connected {
+                armed = true;
+                SHUTDOWN_ANNOUNCED.store(false, Ordering::Release);
+                mode = detect_mode().await.or(mode);
+                *CORE_MODE.lock().unwrap_or_else(|e| e.into_inner()) = mode;
+            }
+            was_connected = connected;
+
+            if !settings.enabled || !armed {
+                continue;
+            }
+            if !connected && (is_held_off() || crate::tray::is_core_recovery_in_progress()) {
+                POLICY.lock().unwrap_or_else(|e| e.into_inner()).pause();
+                continue;
+            }
+            if !connected && stopped_deliberately(mode).await {
+                info!("🐕 看门狗: Sunshine 已被手动停止，等待其重新启动后再监控");
+                armed = false;
+                POLICY.lock().unwrap_or_else(|e| e.into_inner()).pause();
+                continue;
+            }
+
+            let healthy = connected || probe_core().await;
+            let now = started.elapsed().as_secs_f64();
+            let action = POLICY
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .observe(&settings, healthy, now);
+            if !intervene(&app, action, mode).await {
+                armed = false;
+                POLICY.lock().unwrap_or_else(|e| e.into_inner()).pause();
+            }
+        }
+    });
+}
+
+fn status() -> CoreWatchdogStatus {
+    let mut interventions = {
+        let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        history_path()
+            .map(|path| read_history(&path))
+            .unwrap_or_default()
+    };
+    interventions.reverse();
+    let policy = POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    CoreWatchdogStatus {
+        settings: current_settings(),
+        halted: policy.is_halted(),
+        consecutive_restarts: policy.attempt(),
+        interventions,
+    }
+}
+
+#[tauri::command]
+pub fn get_core_watchdog() -> CoreWatchdogStatus {
+    status()
+}
+
+#[tauri::command]
+pub fn save_core_watchdog_settings(
+    settings: WatchdogSettings,
+) -> Result<CoreWatchdogStatus, String> {
+    let settings = settings.validated()?;
+    save_settings(&settings)?;
+    info!(
+        "Sunshine 看门狗已{}: {} 分钟内最多重启 {} 次",
+        if settings.enabled { "启用" } else { "停用" },
+        settings.window_secs.div_ceil(60),
+        settings.max_restarts
+    );
+    if let Ok(mut current) = SETTINGS.write() {
+        *current = settings;
+    }
+    Ok(status())
+}
+
+/// Clear the backoff and a crash-loop halt so restarts resume immediately.
+#[tauri::command]
+pub fn reset_core_watchdog() -> CoreWatchdogStatus {
+    *POLICY.lock().unwrap_or_else(|e| e.into_inner()) = RestartPolicy::default();
+    info!("Sunshine 看门狗已重置");
+    status()
+}
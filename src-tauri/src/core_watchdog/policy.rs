@@ -0,0 +1,261 @@
+//! Restart policy for the Core watchdog, kept free of I/O so outage traces
+//! can be replayed in tests.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct WatchdogSettings {
+    /// Off until the user opts in.
+    pub enabled: bool,
+    /// How long the Core must stay unreachable before the first restart.
+    pub unhealthy_grace_secs: u64,
+    /// Delay before the next restart; doubles with every consecutive restart.
+    pub initial_backoff_secs: u64,
+    pub max_backoff_secs: u64,
+    /// Restarts allowed within `window_secs` before the watchdog gives up.
+    pub max_restarts: u32,
+    pub window_secs: u64,
+    /// Time the Core must stay healthy before the backoff and a crash-loop
+    /// halt are cleared.
+    pub stable_secs: u64,
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            unhealthy_grace_secs: 20,
+            initial_backoff_secs: 15,
+            max_backoff_secs: 300,
+            max_restarts: 3,
+            window_secs: 600,
+            stable_secs: 120,
+        }
+    }
+}
+
+impl WatchdogSettings {
+    pub fn validated(self) -> Result<Self, String> {
+        if self.unhealthy_grace_secs < 5 {
+            return Err("Grace period must be at least 5 seconds".to_string());
+        }
+        if self.initial_backoff_secs == 0 || self.initial_backoff_secs > self.max_backoff_secs {
+            return Err("Initial backoff must be between 1 second and the maximum".to_string());
+        }
+        if self.max_restarts == 0 || self.window_secs == 0 {
+            return Err("Restart limit and window must be at least 1".to_string());
+        }
+        Ok(self)
+    }
+
+    fn backoff_secs(&self, attempt: u32) -> u64 {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        self.initial_backoff_secs
+            .saturating_mul(factor)
+            .min(self.max_backoff_secs)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WatchdogAction {
+    None,
+    Restart {
+        attempt: u32,
+        unhealthy_secs: f64,
+        backoff_secs: u64,
+    },
+    /// The restart limit was reached; no more restarts until the Core is
+    /// stable again or the watchdog is reset.
+    CrashLoop {
+        restarts: usize,
+    },
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct RestartPolicy {
+    unhealthy_since: Option<f64>,
+    healthy_since: Option<f64>,
+    restarts: VecDeque<f64>,
+    attempt: u32,
+    next_restart_at: f64,
+    halted: bool,
+}
+
+impl RestartPolicy {
+    /// Feed one health observation taken at `now_secs`.
+    pub fn observe(
+        &mut self,
+        settings: &WatchdogSettings,
+        healthy: bool,
+        now_secs: f64,
+    ) -> WatchdogAction {
+        if healthy {
+            self.unhealthy_since = None;
+            let since = *self.healthy_since.get_or_insert(now_secs);
+            if now_secs - since >= settings.stable_secs as f64 {
+                self.attempt = 0;
+                self.halted = false;
+            }
+            return WatchdogAction::None;
+        }
+
+        self.healthy_since = None;
+        let since = *self.unhealthy_since.get_or_insert(now_secs);
+        let unhealthy_secs = now_secs - since;
+        if self.halted
+            || unhealthy_secs < settings.unhealthy_grace_secs as f64
+            || now_secs < self.next_restart_at
+        {
+            return WatchdogAction::None;
+        }
+
+        let window = settings.window_secs as f64;
+        self.restarts.retain(|at| now_secs - at < window);
+        if self.restarts.len() >= settings.max_restarts as usize {
+            self.halted = true;
+            return WatchdogAction::CrashLoop {
+                restarts: self.restarts.len(),
+            };
+        }
+
+        self.restarts.push_back(now_secs);
+        self.attempt += 1;
+        let backoff_secs = settings.backoff_secs(self.attempt);
+        self.next_restart_at = now_secs + backoff_secs as f64;
+        // The restarted Core gets a fresh grace period to come up.
+        self.unhealthy_since = None;
+        WatchdogAction::Restart {
+            attempt: self.attempt,
+            unhealthy_secs,
+            backoff_secs,
+        }
+    }
+
+    /// Take back the restart just handed out when it could not be attempted
+    /// at all, so it neither backs off nor counts toward a crash loop.
+    pub fn forget_restart(&mut self) {
+        if self.restarts.pop_back().is_some() {
+            self.attempt = self.attempt.saturating_sub(1);
+            self.next_restart_at = 0.0;
+        }
+    }
+
+    /// Forget the current outage, e.g. while a manual restart is in flight.
+    pub fn pause(&mut self) {
+        self.unhealthy_since = None;
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Replay `(seconds, healthy)` observations and return the restart times
+    /// and the time a crash loop was reported, if any.
+    fn replay(settings: &WatchdogSettings, trace: &[(f64, bool)]) -> (Vec<f64>, Option<f64>) {
+        let mut policy = RestartPolicy::default();
+        let mut restarts = Vec::new();
+        let mut crash_loop = None;
+        for (time, healthy) in trace {
+            match policy.observe(settings, *healthy, *time) {
+                WatchdogAction::Restart { .. } => restarts.push(*time),
+                WatchdogAction::CrashLoop { .. } => crash_loop = crash_loop.or(Some(*time)),
+                WatchdogAction::None => {}
+            }
+        }
+        (restarts, crash_loop)
+    }
+
+    fn outage(from: f64, to: f64) -> Vec<(f64, bool)> {
+        (0..)
+            .map(|i| from + i as f64 * 5.0)
+            .take_while(|time| *time < to)
+            .map(|time| (time, false))
+            .collect()
+    }
+
+    #[test]
+    fn short_outages_within_the_grace_period_are_ignored() {
+        let settings = WatchdogSettings::default();
+        let mut trace = outage(0.0, 15.0);
+        trace.push((15.0, true));
+        trace.extend(outage(20.0, 35.0));
+        assert_eq!(replay(&settings, &trace), (Vec::new(), None));
+    }
+
+    #[test]
+    fn restarts_back_off_and_stop_in_a_crash_loop() {
+        let settings = WatchdogSettings::default();
+        let (restarts, crash_loop) = replay(&settings, &outage(0.0, 600.0));
+        // Each restart waits for a fresh grace period and the doubled backoff.
+        assert_eq!(restarts, vec![20.0, 45.0, 75.0]);
+        assert_eq!(crash_loop, Some(135.0));
+    }
+
+    #[test]
+    fn stable_health_resets_backoff_and_halt() {
+        let settings = WatchdogSettings::default();
+        let mut policy = RestartPolicy::default();
+        for (time, healthy) in outage(0.0, 200.0) {
+            policy.observe(&settings, healthy, time);
+        }
+        assert!(policy.is_halted());
+
+        policy.observe(&settings, true, 200.0);
+        policy.observe(&settings, true, 330.0);
+        assert!(!policy.is_halted());
+        assert_eq!(policy.attempt(), 0);
+
+        let mut action = WatchdogAction::None;
+        for (time, healthy) in outage(900.0, 925.0) {
+            action = policy.observe(&settings, healthy, time);
+        }
+        assert!(matches!(action, WatchdogAction::Restart { attempt: 1, .. }));
+    }
+
+    #[test]
+    fn forgotten_restarts_do_not_count_toward_a_crash_loop() {
+        let settings = WatchdogSettings::default();
+        let mut policy = RestartPolicy::default();
+        let mut restarts = 0;
+        for (time, healthy) in outage(0.0, 600.0) {
+            if let WatchdogAction::Restart { attempt, .. } =
+                policy.observe(&settings, healthy, time)
+            {
+                assert_eq!(attempt, 1);
+                restarts += 1;
+                policy.forget_restart();
+            }
+        }
+        assert!(restarts > settings.max_restarts);
+        assert!(!policy.is_halted());
+        assert_eq!(policy.attempt(), 0);
+    }
+
+    #[test]
+    fn backoff_is_capped_and_settings_are_validated() {
+        let settings = WatchdogSettings::default();
+        assert!(!settings.enabled);
+        assert_eq!(settings.backoff_secs(1), 15);
+        assert_eq!(settings.backoff_secs(3), 60);
+        assert_eq!(settings.backoff_secs(10), 300);
+        assert_eq!(settings.backoff_secs(40), 300);
+
+        assert!(settings.clone().validated().is_ok());
+        let invalid = WatchdogSettings {
+            initial_backoff_secs: 600,
+            ..settings
+        };
+        assert!(invalid.validated().is_err());
+    }
+}
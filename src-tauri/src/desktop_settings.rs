@@ -45,11 +45,19 @@ pub struct DesktopSettings {
     pub auto_start_sunshine: bool,
     pub file_mapping_menu_enabled: bool,
     pub notifications: bool,
-    pub connection_notify: bool,
-    pub update_notify: bool,
     pub dev_mode: bool,
     pub log_level: String,
+    /// Level written to the rotating log files, independent of `log_level`.
+    pub file_log_level: String,
+    /// Start a new log file once the current one reaches this size.
+    pub log_file_max_size_mb: u32,
+    /// Start a new log file once the current one is this old.
+    pub log_file_max_age_days: u32,
+    /// Rotated log files kept next to the current one.
+    pub log_file_retention: u32,
     pub toolbar_shortcut_enabled: bool,
+    /// Draw the streaming bitrate as a bar on the tray icon.
+    pub tray_bitrate_bar: bool,
 }
 
 impl Default for DesktopSettings {
@@ -60,15 +68,29 @@ impl Default for DesktopSettings {
             auto_start_sunshine: true,
             file_mapping_menu_enabled: true,
             notifications: true,
-            connection_notify: true,
-            update_notify: true,
             dev_mode: false,
             log_level: "info".to_string(),
+            file_log_level: "info".to_string(),
+            log_file_max_size_mb: 10,
+            log_file_max_age_days: 1,
+            log_file_retention: 7,
             toolbar_shortcut_enabled: true,
+            tray_bitrate_bar: false,
         }
     }
 }
 
+/// Per-kind notification switches older versions stored in the desktop
+/// settings. The notification rules take them over once; `None` when the file
+/// never had the switch.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LegacyNotificationSwitches {
+    pub connection_notify: Option<bool>,
+    pub update_notify: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DesktopSettingsStatus {
@@ -90,7 +112,7 @@ pub struct ToolbarShortcutStatus {
     pub registered: bool,
 }
 
-fn settings_dir() -> Result<PathBuf, String> {
+pub(crate) fn settings_dir() -> Result<PathBuf, String> {
     dirs::config_dir()
         .map(|dir| dir.join("Sunshine GUI"))
         .ok_or_else(|| "Cannot resolve user config directory".to_string())
@@ -106,6 +128,14 @@ fn normalize(settings: &mut DesktopSettings) {
         "error" | "warn" | "info" | "debug" | "trace" => level,
         _ => "info".to_string(),
     };
+    let file_level = settings.file_log_level.trim().to_ascii_lowercase();
+    settings.file_log_level = match file_level.as_str() {
+        "off" | "error" | "warn" | "info" | "debug" | "trace" => file_level,
+        _ => "info".to_string(),
+    };
+    settings.log_file_max_size_mb = settings.log_file_max_size_mb.clamp(1, 100);
+    settings.log_file_max_age_days = settings.log_file_max_age_days.clamp(1, 30);
+    settings.log_file_retention = settings.log_file_retention.clamp(1, 50);
 }
 
 pub fn load_desktop_settings_from_disk() -> DesktopSettings {
@@ -120,6 +150,14 @@ pub fn load_desktop_settings_from_disk() -> DesktopSettings {
     settings
 }
 
+pub(crate) fn legacy_notification_switches() -> LegacyNotificationSwitches {
+    settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
 fn save_desktop_settings_to_disk(settings: &DesktopSettings) -> Result<(), String> {
     let dir = settings_dir()?;
     fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
@@ -391,7 +429,9 @@ pub async fn save_desktop_settings(
     }
     apply_auto_start(&settings)?;
     crate::tray::refresh_menu(&app);
+    crate::tray::set_bitrate_bar(&app, settings.tray_bitrate_bar);
     crate::logger::set_log_level(&settings.log_level);
+    crate::logger::configure_file_log(&settings);
     apply_dev_mode(&app, settings.dev_mode);
 
     if settings.auto_start_sunshine {
@@ -549,8 +589,6 @@ mod tests {
         let settings = DesktopSettings::default();
 
         assert!(settings.notifications);
-        assert!(settings.connection_notify);
-        assert!(settings.update_notify);
     }
 
     #[test]
@@ -559,8 +597,20 @@ mod tests {
             .expect("legacy desktop settings should deserialize");
 
         assert!(settings.notifications);
-        assert!(settings.connection_notify);
-        assert!(settings.update_notify);
+    }
+
+    #[test]
+    fn legacy_notification_switches_are_read_for_migration() {
+        let legacy: LegacyNotificationSwitches =
+            serde_json::from_str(r#"{"notifications":true,"connectionNotify":false}"#).unwrap();
+        assert_eq!(legacy.connection_notify, Some(false));
+        assert_eq!(legacy.update_notify, None);
+
+        let settings: DesktopSettings =
+            serde_json::from_str(r#"{"connectionNotify":false,"updateNotify":false}"#).unwrap();
+        let saved = serde_json::to_value(&settings).unwrap();
+        assert!(saved.get("connectionNotify").is_none());
+        assert!(saved.get("updateNotify").is_none());
     }
 
     #[test]
@@ -383,26 +383,13 @@ fn config_path() -> PathBuf {
         .join("sunshine.conf")
 }
 
-fn read_config_value(key: &str) -> Option<String> {
-    let content = fs::read_to_string(config_path()).ok()?;
-    content.lines().find_map(|line| {
-        let line = line.trim();
-        if line.starts_with('#') || line.is_empty() {
-            return None;
-        }
-        let (candidate, value) = line.split_once('=')?;
-        (candidate.trim() == key).then(|| value.trim().to_string())
-    })
+fn read_config() -> Option<crate::sunshine::conf::SunshineConf> {
+    crate::sunshine::conf::SunshineConf::load(&config_path()).ok()
 }
 
 fn config_bool(key: &str, default_value: bool) -> bool {
-    read_config_value(key)
-        .map(|value| {
-            matches!(
-                value.to_ascii_lowercase().as_str(),
-                "true" | "yes" | "1" | "enabled"
-            )
-        })
+    read_config()
+        .and_then(|conf| conf.get_bool(key))
         .unwrap_or(default_value)
 }
 
@@ -710,7 +697,10 @@ pub async fn dualsense_get_status() -> Result<DualSenseStatus, String> {
     let installed = executable.is_file();
     let in_use = has_active_session().await;
     let enabled = config_bool("ds5_enabled", false)
-        && read_config_value("gamepad").is_some_and(|gamepad| gamepad.eq_ignore_ascii_case("ds5"));
+        && read_config().is_some_and(|conf| {
+            conf.gamepad()
+                .is_some_and(|gamepad| gamepad.eq_ignore_ascii_case("ds5"))
+        });
     let audio_haptics = config_bool("ds5_audio_haptics", false);
     let genshin_compatibility = config_bool("ds5_genshin_compatibility", false);
     let probe = if installed {
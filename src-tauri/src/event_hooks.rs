@@ -0,0 +1,703 @@
+//! User hooks that run on Core events.
+//!
+//! The tray state stream is turned into events (client connected or
+//! disconnected, stream started or ended, pairing requested, Core connected
+//! or disconnected), and every enabled hook subscribed to an event either runs
+//! a command with the event data in `SUNSHINE_*` environment variables or
+//! posts the event as JSON to a webhook. Each hook has its own queue, so its
+//! runs happen one at a time in event order; runs are bounded by a per-hook
+//! timeout and a global concurrency limit, and the latest results are kept for
+//! the settings page. Hooks live in `<config>/Sunshine GUI/event-hooks.json`.
+
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{Semaphore, mpsc, oneshot};
+
+use crate::sunshine::{TrayClientSession, TrayState};
+
+mod runner;
+
+const SETTINGS_FILE: &str = "event-hooks.json";
+const MAX_RUNS: usize = 50;
+const MAX_CONCURRENT_LIMIT: usize = 16;
+const MAX_TIMEOUT_SECS: u64 = 600;
+/// Key for the single session reported by Cores without session snapshots.
+const LEGACY_SESSION_ID: u32 = 0;
+
+static SETTINGS: Lazy<Mutex<EventHooksSettings>> = Lazy::new(|| Mutex::new(load_settings()));
+static LIMITER: Lazy<Mutex<Arc<Semaphore>>> = Lazy::new(|| {
+    let permits = SETTINGS.lock().unwrap().max_concurrent;
+    Mutex::new(Arc::new(Semaphore::new(permits)))
+});
+static OBSERVER: Lazy<Mutex<Observer>> = Lazy::new(|| Mutex::new(Observer::default()));
+static RUNS: Mutex<VecDeque<HookRun>> = Mutex::new(VecDeque::new());
+static QUEUES: Lazy<Mutex<HashMap<String, mpsc::UnboundedSender<QueuedRun>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HookEvent {
+    ClientConnected,
+    ClientDisconnected,
+    StreamStarted,
+    StreamEnded,
+    PairingRequested,
+    CoreConnected,
+    CoreDisconnected,
+}
+
+impl HookEvent {
+    /// Name passed to hooks in `SUNSHINE_EVENT` and the webhook body.
+    fn name(self) -> &'static str {
+        match self {
+            Self::ClientConnected => "client_connected",
+            Self::ClientDisconnected => "client_disconnected",
+            Self::StreamStarted => "stream_started",
+            Self::StreamEnded => "stream_ended",
+            Self::PairingRequested => "pairing_requested",
+            Self::CoreConnected => "core_connected",
+            Self::CoreDisconnected => "core_disconnected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum HookAction {
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+        /// Empty runs in the GUI's working directory.
+        #[serde(default)]
+        working_dir: String,
+    },
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventHook {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub events: Vec<HookEvent>,
+    pub action: HookAction,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventHooksSettings {
+    pub hooks: Vec<EventHook>,
+    /// Hook runs allowed at the same time; later runs wait for a slot.
+    pub max_concurrent: usize,
+}
+
+impl Default for EventHooksSettings {
+    fn default() -> Self {
+        Self {
+            hooks: Vec::new(),
+            max_concurrent: 4,
+        }
+    }
+}
+
+impl EventHooksSettings {
+    fn validated(mut self) -> Result<Self, String> {
+        if !(1..=MAX_CONCURRENT_LIMIT).contains(&self.max_concurrent) {
+            return Err(format!(
+                "maxConcurrent 必须在 1 到 {} 之间",
+                MAX_CONCURRENT_LIMIT
+            ));
+        }
+        let mut names = HashSet::new();
+        for hook in &mut self.hooks {
+            hook.name = hook.name.trim().to_string();
+            if hook.name.is_empty() {
+                return Err("钩子名称不能为空".to_string());
+            }
+            if !names.insert(hook.name.to_lowercase()) {
+                return Err(format!("钩子名称重复: '{}'", hook.name));
+            }
+            if hook.events.is_empty() {
+                return Err(format!("钩子 '{}' 未选择任何事件", hook.name));
+            }
+            if !(1..=MAX_TIMEOUT_SECS).contains(&hook.timeout_secs) {
+                return Err(format!(
+                    "钩子 '{}': timeoutSecs 必须在 1 到 {} 之间",
+                    hook.name, MAX_TIMEOUT_SECS
+                ));
+            }
+            match &mut hook.action {
+                HookAction::Command { program, .. } => {
+                    *program = program.trim().to_string();
+                    if program.is_empty() {
+                        return Err(format!("钩子 '{}' 未指定程序", hook.name));
+                    }
+                }
+                HookAction::Webhook { url, .. } => {
+                    *url = url.trim().to_string();
+                    let parsed = url::Url::parse(url)
+                        .map_err(|e| format!("钩子 '{}': URL 无效: {}", hook.name, e))?;
+                    if !matches!(parsed.scheme(), "http" | "https") {
+                        return Err(format!(
+                            "钩子 '{}': webhook 必须使用 http 或 https",
+                            hook.name
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// What a hook receives for one event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Payload {
+    event: HookEvent,
+    timestamp: String,
+    data: BTreeMap<&'static str, String>,
+}
+
+impl Payload {
+    fn new(event: HookEvent, data: BTreeMap<&'static str, String>) -> Self {
+        Self {
+            event,
+            timestamp: chrono::Local::now().to_rfc3339(),
+            data,
+        }
+    }
+
+    /// The webhook body: `{"event": "client_connected", "timestamp", "data"}`.
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "event": self.event.name(),
+            "timestamp": self.timestamp,
+            "data": self.data,
+        })
+    }
+
+    /// `SUNSHINE_EVENT`, `SUNSHINE_EVENT_TIME`, `SUNSHINE_EVENT_JSON` and one
+    /// `SUNSHINE_<KEY>` per data field.
+    fn env(&self) -> Vec<(String, String)> {
+        let mut env = vec![
+            ("SUNSHINE_EVENT".to_string(), self.event.name().to_string()),
+            ("SUNSHINE_EVENT_TIME".to_string(), self.timestamp.clone()),
+            (
+                "SUNSHINE_EVENT_JSON".to_string(),
+                self.to_json().to_string(),
+            ),
+        ];
+        env.extend(self.data.iter().map(|(key, value)| {
+            (
+                format!("SUNSHINE_{}", key.to_ascii_uppercase()),
+                value.clone(),
+            )
+        }));
+        env
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookRun {
+    pub hook: String,
+    pub event: HookEvent,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub success: bool,
+    pub message: String,
+}
+
+type Occurrence = (HookEvent, BTreeMap<&'static str, String>);
+
+/// A run waiting in its hook's queue, with a reply for runs someone awaits.
+struct QueuedRun {
+    hook: EventHook,
+    payload: Payload,
+    reply: Option<oneshot::Sender<HookRun>>,
+}
+
+/// Turns successive tray states into hook events.
+#[derive(Default)]
+struct Observer {
+    connected: bool,
+    /// Whether client and stream events were established from a first state.
+    baseline: bool,
+    instance_id: String,
+    sessions: Vec<TrayClientSession>,
+    pairing: bool,
+}
+
+impl Observer {
+    fn observe(&mut self, state: &TrayState) -> Vec<Occurrence> {
+        let mut events = Vec::new();
+        let instance = |data: &mut BTreeMap<&'static str, String>| {
+            data.insert("instance_id", state.instance_id.clone());
+        };
+        if !self.connected {
+            self.connected = true;
+            let mut data = BTreeMap::new();
+            instance(&mut data);
+            events.push((HookEvent::CoreConnected, data));
+        }
+
+        let current = open_sessions(state);
+        let previous = std::mem::take(&mut self.sessions);
+        let same_instance = self.instance_id == state.instance_id;
+        let is_open = |sessions: &[TrayClientSession], id: u32| {
+            same_instance && sessions.iter().any(|session| session.id == id)
+        };
+        let pairing = state.status == "pairing" || !state.pairing_client_name.trim().is_empty();
+
+        // The first state after startup only sets the baseline; streams that
+        // were already running do not re-run their hooks.
+        if self.baseline {
+            for session in previous
+                .iter()
+                .filter(|session| !is_open(&current, session.id))
+            {
+                let mut data = session_data(session, state);
+                instance(&mut data);
+                events.push((HookEvent::ClientDisconnected, data));
+            }
+            if !previous.is_empty() && current.is_empty() {
+                let mut data = BTreeMap::new();
+                instance(&mut data);
+                events.push((HookEvent::StreamEnded, data));
+            }
+            for session in current
+                .iter()
+                .filter(|session| !is_open(&previous, session.id))
+            {
+                let mut data = session_data(session, state);
+                instance(&mut data);
+                events.push((HookEvent::ClientConnected, data));
+            }
+            if previous.is_empty() && !current.is_empty() {
+                let mut data = BTreeMap::from([
+                    ("app_name", state.app_name.clone()),
+                    ("session_count", current.len().to_string()),
+                ]);
+                instance(&mut data);
+                events.push((HookEvent::StreamStarted, data));
+            }
+            if pairing && !self.pairing {
+                let mut data =
+                    BTreeMap::from([("client_name", state.pairing_client_name.trim().to_string())]);
+                instance(&mut data);
+                events.push((HookEvent::PairingRequested, data));
+            }
+        }
+
+        self.baseline = true;
+        self.instance_id = state.instance_id.clone();
+        self.sessions = current;
+        self.pairing = pairing;
+        events
+    }
+
+    fn core_disconnected(&mut self) -> Vec<Occurrence> {
+        if !self.connected {
+            return Vec::new();
+        }
+        self.connected = false;
+        self.pairing = false;
+        let instance = BTreeMap::from([("instance_id", self.instance_id.clone())]);
+        let sessions = std::mem::take(&mut self.sessions);
+        let mut events: Vec<Occurrence> = sessions
+            .iter()
+            .map(|session| {
+                let mut data = BTreeMap::from([
+                    ("client_name", session.client_name.clone()),
+                    ("client_fingerprint", session.client_fingerprint.clone()),
+                    ("session_id", session.id.to_string()),
+                ]);
+                data.extend(instance.clone());
+                (HookEvent::ClientDisconnected, data)
+            })
+            .collect();
+        if !sessions.is_empty() {
+            events.push((HookEvent::StreamEnded, instance.clone()));
+        }
+        events.push((HookEvent::CoreDisconnected, instance));
+        events
+    }
+}
+
+fn open_sessions(state: &TrayState) -> Vec<TrayClientSession> {
+    let snapshots = state
+        .capabilities
+        .iter()
+        .any(|capability| capability == "sessions-v1");
+    if snapshots {
+        state.sessions.clone()
+    } else if state.status == "streaming" {
+        vec![TrayClientSession {
+            id: LEGACY_SESSION_ID,
+            ..TrayClientSession::default()
+        }]
+    } else {
+        Vec::new()
+    }
+}
+
+fn session_data(session: &TrayClientSession, state: &TrayState) -> BTreeMap<&'static str, String> {
+    let app_name = if session.app_name.is_empty() {
+        &state.app_name
+    } else {
+        &session.app_name
+    };
+    BTreeMap::from([
+        ("client_name", session.client_name.clone()),
+        ("client_fingerprint", session.client_fingerprint.clone()),
+        ("session_id", session.id.to_string()),
+        ("app_name", app_name.clone()),
+        (
+            "suspicious",
+            session.highly_suspected_unknown_client.to_string(),
+        ),
+    ])
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(SETTINGS_FILE))
+}
+
+fn load_settings() -> EventHooksSettings {
+    let Ok(path) = settings_path() else {
+        return EventHooksSettings::default();
+    };
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return EventHooksSettings::default();
+    };
+    serde_json::from_str::<EventHooksSettings>(&text)
+        .map_err(|e| e.to_string())
+        .and_then(EventHooksSettings::validated)
+        .unwrap_or_else(|e| {
+            warn!("Invalid event hooks, disabling them: {}", e);
+            EventHooksSettings::default()
+        })
+}
+
+fn dispatch(events: Vec<Occurrence>) {
+    if events.is_empty() {
+        return;
+    }
+    let hooks = SETTINGS.lock().unwrap().hooks.clone();
+    for (event, data) in events {
+        let payload = Payload::new(event, data);
+        for hook in hooks
+            .iter()
+            .filter(|hook| hook.enabled && hook.events.contains(&event))
+        {
+            enqueue(QueuedRun {
+                hook: hook.clone(),
+                payload: payload.clone(),
+                reply: None,
+            });
+        }
+    }
+}
+
+/// Queue a run behind the hook's earlier runs, starting its worker if needed.
+fn enqueue(run: QueuedRun) {
+    let mut queues = QUEUES.lock().unwrap();
+    let key = run.hook.name.to_lowercase();
+    let run = match queues.get(&key) {
+        Some(queue) => match queue.send(run) {
+            Ok(()) => return,
+            // The worker is gone; start a new one below.
+            Err(mpsc::error::SendError(run)) => run,
+        },
+        None => run,
+    };
+    let (queue, mut runs) = mpsc::unbounded_channel::<QueuedRun>();
+    let _ = queue.send(run);
+    queues.insert(key, queue);
+    tauri::async_runtime::spawn(async move {
+        while let Some(QueuedRun {
+            hook,
+            payload,
+            reply,
+        }) = runs.recv().await
+        {
+            let run = run_hook(hook, payload).await;
+            if let Some(reply) = reply {
+                let _ = reply.send(run);
+            }
+        }
+    });
+}
+
+async fn run_hook(hook: EventHook, payload: Payload) -> HookRun {
+    let limiter = LIMITER.lock().unwrap().clone();
+    let _permit = limiter.acquire_owned().await;
+    let started_at = chrono::Local::now().to_rfc3339();
+    let started = Instant::now();
+    let result = runner::run(&hook, &payload).await;
+    let run = HookRun {
+        hook: hook.name.clone(),
+        event: payload.event,
+        started_at,
+        duration_ms: started.elapsed().as_millis() as u64,
+        success: result.is_ok(),
+        message: result.unwrap_or_else(|e| e),
+    };
+    if run.success {
+        info!(
+            "🪝 钩子 {} ({}) 完成: {}",
+            run.hook,
+            payload.event.name(),
+            run.message
+        );
+    } else {
+        warn!(
+            "🪝 钩子 {} ({}) 失败: {}",
+            run.hook,
+            payload.event.name(),
+            run.message
+        );
+    }
+    let mut runs = RUNS.lock().unwrap();
+    runs.push_front(run.clone());
+    runs.truncate(MAX_RUNS);
+    run
+}
+
+/// Feed a tray state the monitor accepted.
+pub fn observe_tray_state(state: &TrayState) {
+    let events = OBSERVER.lock().unwrap().observe(state);
+    dispatch(events);
+}
+
+/// Report the Core as gone when it stops answering.
+pub fn observe_core_disconnected() {
+    let events = OBSERVER.lock().unwrap().core_disconnected();
+    dispatch(events);
+}
+
+#[tauri::command]
+pub fn get_event_hooks() -> EventHooksSettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub fn save_event_hooks(settings: EventHooksSettings) -> Result<EventHooksSettings, String> {
+    let settings = settings.validated()?;
+    let path = settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())?;
+    // Runs in flight keep their permits from the old limiter.
+    *LIMITER.lock().unwrap() = Arc::new(Semaphore::new(settings.max_concurrent));
+    // Workers of removed hooks finish what is queued and then stop.
+    let names: HashSet<String> = settings
+        .hooks
+        .iter()
+        .map(|hook| hook.name.to_lowercase())
+        .collect();
+    QUEUES
+        .lock()
+        .unwrap()
+        .retain(|name, _| names.contains(name));
+    *SETTINGS.lock().unwrap() = settings.clone();
+    info!("🪝 事件钩子已保存: {} 个", settings.hooks.len());
+    Ok(settings)
+}
+
+/// Latest hook runs, newest first.
+#[tauri::command]
+pub fn list_event_hook_runs() -> Vec<HookRun> {
+    RUNS.lock().unwrap().iter().cloned().collect()
+}
+
+/// Run a saved hook now with sample data for its first event.
+#[tauri::command]
+pub async fn test_event_hook(name: String) -> Result<HookRun, String> {
+    let hook = SETTINGS
+        .lock()
+        .unwrap()
+        .hooks
+        .iter()
+        .find(|hook| hook.name == name)
+        .cloned()
+        .ok_or_else(|| format!("找不到钩子 '{}'", name))?;
+    let event = hook.events[0];
+    let data = BTreeMap::from([
+        ("test", "true".to_string()),
+        ("client_name", "Sunshine Test Client".to_string()),
+    ]);
+    let (reply, run) = oneshot::channel();
+    enqueue(QueuedRun {
+        hook,
+        payload: Payload::new(event, data),
+        reply: Some(reply),
+    });
+    run.await.map_err(|_| "钩子运行被中断".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(instance_id: &str, sessions: &[(u32, &str)]) -> TrayState {
+        TrayState {
+            instance_id: instance_id.to_string(),
+            capabilities: vec!["sessions-v1".to_string()],
+            status: if sessions.is_empty() {
+                "idle"
+            } else {
+                "streaming"
+            }
+            .to_string(),
+            app_name: "Desktop".to_string(),
+            sessions: sessions
+                .iter()
+                .map(|(id, name)| TrayClientSession {
+                    id: *id,
+                    client_name: name.to_string(),
+                    ..TrayClientSession::default()
+                })
+                .collect(),
+            ..TrayState::default()
+        }
+    }
+
+    fn kinds(events: &[Occurrence]) -> Vec<HookEvent> {
+        events.iter().map(|(event, _)| *event).collect()
+    }
+
+    #[test]
+    fn observer_reports_clients_and_streams() {
+        let mut observer = Observer::default();
+        assert_eq!(
+            kinds(&observer.observe(&state("core-1", &[(1, "TV")]))),
+            vec![HookEvent::CoreConnected]
+        );
+
+        let events = observer.observe(&state("core-1", &[(1, "TV"), (2, "Deck")]));
+        assert_eq!(kinds(&events), vec![HookEvent::ClientConnected]);
+        assert_eq!(events[0].1["client_name"], "Deck");
+        assert_eq!(events[0].1["app_name"], "Desktop");
+
+        assert_eq!(
+            kinds(&observer.observe(&state("core-1", &[]))),
+            vec![
+                HookEvent::ClientDisconnected,
+                HookEvent::ClientDisconnected,
+                HookEvent::StreamEnded
+            ]
+        );
+        assert_eq!(
+            kinds(&observer.observe(&state("core-1", &[(3, "TV")]))),
+            vec![HookEvent::ClientConnected, HookEvent::StreamStarted]
+        );
+    }
+
+    #[test]
+    fn observer_reports_pairing_restarts_and_disconnects() {
+        let mut observer = Observer::default();
+        observer.observe(&state("core-1", &[(1, "TV")]));
+
+        let mut pairing = state("core-1", &[(1, "TV")]);
+        pairing.pairing_client_name = "Phone".to_string();
+        let events = observer.observe(&pairing);
+        assert_eq!(kinds(&events), vec![HookEvent::PairingRequested]);
+        assert_eq!(events[0].1["client_name"], "Phone");
+        assert!(observer.observe(&pairing).is_empty());
+
+        // Session ids restart with the Core.
+        assert_eq!(
+            kinds(&observer.observe(&state("core-2", &[(1, "TV")]))),
+            vec![HookEvent::ClientDisconnected, HookEvent::ClientConnected]
+        );
+
+        assert_eq!(
+            kinds(&observer.core_disconnected()),
+            vec![
+                HookEvent::ClientDisconnected,
+                HookEvent::StreamEnded,
+                HookEvent::CoreDisconnected
+            ]
+        );
+        assert!(observer.core_disconnected().is_empty());
+        assert_eq!(
+            kinds(&observer.observe(&state("core-3", &[]))),
+            vec![HookEvent::CoreConnected]
+        );
+    }
+
+    #[test]
+    fn payload_exposes_data_as_environment() {
+        let payload = Payload {
+            event: HookEvent::ClientConnected,
+            timestamp: "2026-01-01T00:00:00+00:00".to_string(),
+            data: BTreeMap::from([("client_name", "TV".to_string())]),
+        };
+        let env: BTreeMap<_, _> = payload.env().into_iter().collect();
+
+        assert_eq!(env["SUNSHINE_EVENT"], "client_connected");
+        assert_eq!(env["SUNSHINE_CLIENT_NAME"], "TV");
+        let json: serde_json::Value = serde_json::from_str(&env["SUNSHINE_EVENT_JSON"]).unwrap();
+        assert_eq!(json["event"], "client_connected");
+        assert_eq!(json["data"]["client_name"], "TV");
+    }
+
+    #[test]
+    fn settings_are_validated() {
+        let parsed: EventHooksSettings = serde_json::from_str(
+            r#"{"hooks": [{
+                "name": " audio ",
+                "events": ["streamStarted"],
+                "action": {"type": "command", "program": "switch-audio", "args": ["hdmi"]}
+            }]}"#,
+        )
+        .unwrap();
+        let settings = parsed.validated().unwrap();
+        assert_eq!(settings.hooks[0].name, "audio");
+        assert!(settings.hooks[0].enabled);
+        assert_eq!(settings.hooks[0].timeout_secs, 30);
+
+        let mut duplicate = settings.clone();
+        duplicate.hooks.push(settings.hooks[0].clone());
+        assert!(duplicate.validated().is_err());
+
+        let mut webhook = settings.clone();
+        webhook.hooks[0].action = HookAction::Webhook {
+            url: "file:///etc/passwd".to_string(),
+            headers: BTreeMap::new(),
+        };
+        assert!(webhook.validated().is_err());
+
+        let mut no_events = settings;
+        no_events.hooks[0].events.clear();
+        assert!(no_events.validated().is_err());
+    }
+}
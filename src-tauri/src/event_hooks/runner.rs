@@ -0,0 +1,193 @@
+//! Runs one hook: a command with the event in its environment, or a webhook
+//! POST with the event as JSON.
+//!
+//! Command output is captured like `bat_runner` does for driver scripts: a
+//! temp log is created before the run, stdout and stderr are appended after
+//! it, and the log is removed on success but kept (and named in the error)
+//! when the command fails or times out. A command that times out is killed
+//! together with every process it started, so a shell or batch file cannot
+//! leave its children running.
+
+use log::{debug, warn};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{EventHook, HookAction, Payload};
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+/// Longest response body kept in a webhook error.
+const MAX_BODY_CHARS: usize = 200;
+
+fn webhook_client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .expect("Failed to create webhook HTTP client")
+    })
+}
+
+/// Run `hook` for `payload`; the message describes the result either way.
+pub(super) async fn run(hook: &EventHook, payload: &Payload) -> Result<String, String> {
+    let timeout = Duration::from_secs(hook.timeout_secs);
+    match &hook.action {
+        HookAction::Command {
+            program,
+            args,
+            working_dir,
+        } => run_command(hook, program, args, working_dir, payload, timeout).await,
+        HookAction::Webhook { url, headers } => {
+            let mut request = webhook_client()
+                .post(url)
+                .timeout(timeout)
+                .json(&payload.to_json());
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("webhook 请求失败: {e}"))?;
+            let status = response.status();
+            if status.is_success() {
+                return Ok(format!("HTTP {}", status.as_u16()));
+            }
+            let body = response.text().await.unwrap_or_default();
+            let body: String = body.trim().chars().take(MAX_BODY_CHARS).collect();
+            Err(format!("webhook 返回 HTTP {}: {body}", status.as_u16()))
+        }
+    }
+}
+
+async fn run_command(
+    hook: &EventHook,
+    program: &str,
+    args: &[String],
+    working_dir: &str,
+    payload: &Payload,
+    timeout: Duration,
+) -> Result<String, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let stem: String = hook
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let log_path = std::env::temp_dir().join(format!(
+        "sunshine-hook-{stem}-{}-{timestamp}.log",
+        std::process::id()
+    ));
+    let log_str = log_path.to_string_lossy().into_owned();
+    writeln!(
+        File::create(&log_path).map_err(|e| format!("创建钩子日志失败: {e}"))?,
+        "Sunshine event hook: {} ({}) {} {}",
+        hook.name,
+        payload.event.name(),
+        program,
+        args.join(" ")
+    )
+    .map_err(|e| format!("初始化钩子日志失败: {e}"))?;
+
+    let mut command = tokio::process::Command::new(program);
+    command
+        .args(args)
+        .envs(payload.env())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if !working_dir.trim().is_empty() {
+        command.current_dir(working_dir.trim());
+    }
+    #[cfg(target_os = "windows")]
+    command.creation_flags(CREATE_NO_WINDOW);
+    // Its own process group, so a timeout can take down the whole tree.
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let child = command
+        .spawn()
+        .map_err(|e| format!("启动钩子失败: {e}，日志: {log_str}"))?;
+    let pid = child.id();
+    // Dropping the future on timeout kills the child through `kill_on_drop`;
+    // the processes it started are killed separately.
+    let output = match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(output) => output.map_err(|e| format!("等待钩子失败: {e}，日志: {log_str}"))?,
+        Err(_) => {
+            if let Some(pid) = pid {
+                kill_process_tree(pid).await;
+            }
+            append_line(
+                &log_path,
+                &format!("timed out after {}s", timeout.as_secs()),
+            );
+            return Err(format!(
+                "钩子超时 ({}s)，日志: {log_str}",
+                timeout.as_secs()
+            ));
+        }
+    };
+
+    append_process_output(&log_path, &output.stdout, &output.stderr);
+    if output.status.success() {
+        let _ = std::fs::remove_file(&log_path);
+        return Ok("exit 0".to_string());
+    }
+    let code = output
+        .status
+        .code()
+        .map_or_else(|| "signal".to_string(), |code| code.to_string());
+    Err(format!("钩子执行失败 (exit {code})，日志: {log_str}"))
+}
+
+/// Kill `pid` and every process it started.
+async fn kill_process_tree(pid: u32) {
+    #[cfg(target_os = "windows")]
+    let result = tokio::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+    // The hook leads its own process group, whose id is its pid.
+    #[cfg(not(target_os = "windows"))]
+    let result = tokio::process::Command::new("kill")
+        .args(["-KILL", "--", &format!("-{pid}")])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+    match result {
+        Ok(status) if status.success() => {}
+        Ok(status) => debug!("Killing hook process tree {} exited with {}", pid, status),
+        Err(e) => warn!("结束钩子进程树失败: {}", e),
+    }
+}
+
+fn append_process_output(log_path: &Path, stdout: &[u8], stderr: &[u8]) {
+    let Ok(mut log) = OpenOptions::new().append(true).open(log_path) else {
+        return;
+    };
+    if !stdout.is_empty() {
+        let _ = log.write_all(stdout);
+    }
+    if !stderr.is_empty() {
+        let _ = log.write_all(stderr);
+    }
+}
+
+fn append_line(log_path: &Path, line: &str) {
+    if let Ok(mut log) = OpenOptions::new().append(true).open(log_path) {
+        let _ = writeln!(log, "{line}");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::sunshine::{create_https_client, get_sunshine_url};
+use crate::core_api::{CoreClient, Envelope};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -16,24 +16,31 @@ pub struct FileMappingInfo {
     pub clients: Vec<String>,
 }
 
+const MAPPINGS_ENDPOINT: &str = "api/v1/file-mapping/mappings";
+
+#[derive(Debug, Serialize)]
+struct CreateMappingRequest {
+    path: String,
+}
+
 #[derive(Debug, Deserialize)]
-struct CreateMappingResponse {
-    ok: bool,
+struct MappingResponse {
     mapping: Option<FileMappingInfo>,
-    error: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct ListMappingsResponse {
-    ok: bool,
     mappings: Option<Vec<FileMappingInfo>>,
-    error: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
-struct DeleteMappingResponse {
-    ok: bool,
-    error: Option<String>,
+fn mapping_endpoint(id: &str) -> String {
+    format!("{MAPPINGS_ENDPOINT}/{id}")
+}
+
+fn require_mapping(response: MappingResponse) -> Result<FileMappingInfo, String> {
+    response
+        .mapping
+        .ok_or_else(|| "Sunshine mapping API response did not include a mapping".to_string())
 }
 
 pub fn parse_quick_share_folder_args(args: &[String]) -> Vec<String> {
@@ -168,37 +175,16 @@ fn canonicalize_directory(path: &str) -> Result<PathBuf, String> {
 }
 
 async fn create_quick_share(path: &PathBuf) -> Result<FileMappingInfo, String> {
-    let url = get_sunshine_url().await?;
-    let endpoint = format!("{}/api/v1/file-mapping/mappings", url.trim_end_matches('/'));
-    let client = create_https_client()?;
-    let body = serde_json::json!({
-        "path": path_for_sunshine_api(path),
-    });
-
-    let resp = client
-        .post(endpoint)
-        .json(&body)
+    let response: MappingResponse = CoreClient::target()
+        .await?
+        .post(MAPPINGS_ENDPOINT)
+        .json(&CreateMappingRequest {
+            path: path_for_sunshine_api(path),
+        })?
+        .envelope(Envelope::Ok)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let status = resp.status();
-    let text = resp.text().await.map_err(|e| e.to_string())?;
-    if !status.is_success() {
-        return Err(format!("Sunshine mapping API failed: HTTP {status} {text}"));
-    }
-
-    let parsed: CreateMappingResponse = serde_json::from_str(&text)
-        .map_err(|e| format!("failed to parse Sunshine mapping API response: {e}; body={text}"))?;
-    if !parsed.ok {
-        return Err(parsed
-            .error
-            .unwrap_or_else(|| "Sunshine mapping API returned an error".to_string()));
-    }
-
-    parsed
-        .mapping
-        .ok_or_else(|| "Sunshine mapping API response did not include a mapping".to_string())
+        .await?;
+    require_mapping(response)
 }
 
 fn path_for_sunshine_api(path: &PathBuf) -> String {
@@ -270,96 +256,32 @@ mod tests {
 }
 
 async fn list_mappings() -> Result<Vec<FileMappingInfo>, String> {
-    let url = get_sunshine_url().await?;
-    let endpoint = format!("{}/api/v1/file-mapping/mappings", url.trim_end_matches('/'));
-    let client = create_https_client()?;
-
-    let resp = client
-        .get(endpoint)
+    let response: ListMappingsResponse = CoreClient::target()
+        .await?
+        .get(MAPPINGS_ENDPOINT)
+        .envelope(Envelope::Ok)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let status = resp.status();
-    let text = resp.text().await.map_err(|e| e.to_string())?;
-    if !status.is_success() {
-        return Err(format!("Sunshine mapping API failed: HTTP {status} {text}"));
-    }
-
-    let parsed: ListMappingsResponse = serde_json::from_str(&text)
-        .map_err(|e| format!("failed to parse Sunshine mapping API response: {e}; body={text}"))?;
-    if !parsed.ok {
-        return Err(parsed
-            .error
-            .unwrap_or_else(|| "Sunshine mapping API returned an error".to_string()));
-    }
-
-    Ok(parsed.mappings.unwrap_or_default())
+        .await?;
+    Ok(response.mappings.unwrap_or_default())
 }
 
 async fn delete_mapping(id: &str) -> Result<(), String> {
-    let url = get_sunshine_url().await?;
-    let endpoint = format!(
-        "{}/api/v1/file-mapping/mappings/{}",
-        url.trim_end_matches('/'),
-        id
-    );
-    let client = create_https_client()?;
-
-    let resp = client
-        .delete(endpoint)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let status = resp.status();
-    let text = resp.text().await.map_err(|e| e.to_string())?;
-    if !status.is_success() {
-        return Err(format!("Sunshine mapping API failed: HTTP {status} {text}"));
-    }
-
-    let parsed: DeleteMappingResponse = serde_json::from_str(&text)
-        .map_err(|e| format!("failed to parse Sunshine mapping API response: {e}; body={text}"))?;
-    if !parsed.ok {
-        return Err(parsed
-            .error
-            .unwrap_or_else(|| "Sunshine mapping API returned an error".to_string()));
-    }
-
+    CoreClient::target()
+        .await?
+        .delete(&mapping_endpoint(id))
+        .envelope(Envelope::Ok)
+        .send_value()
+        .await?;
     Ok(())
 }
 
 async fn update_mapping(id: &str, patch: serde_json::Value) -> Result<FileMappingInfo, String> {
-    let url = get_sunshine_url().await?;
-    let endpoint = format!(
-        "{}/api/v1/file-mapping/mappings/{}",
-        url.trim_end_matches('/'),
-        id
-    );
-    let client = create_https_client()?;
-
-    let resp = client
-        .patch(endpoint)
-        .json(&patch)
+    let response: MappingResponse = CoreClient::target()
+        .await?
+        .patch(&mapping_endpoint(id))
+        .json(&patch)?
+        .envelope(Envelope::Ok)
         .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let status = resp.status();
-    let text = resp.text().await.map_err(|e| e.to_string())?;
-    if !status.is_success() {
-        return Err(format!("Sunshine mapping API failed: HTTP {status} {text}"));
-    }
-
-    let parsed: CreateMappingResponse = serde_json::from_str(&text)
-        .map_err(|e| format!("failed to parse Sunshine mapping API response: {e}; body={text}"))?;
-    if !parsed.ok {
-        return Err(parsed
-            .error
-            .unwrap_or_else(|| "Sunshine mapping API returned an error".to_string()));
-    }
-
-    parsed
-        .mapping
-        .ok_or_else(|| "Sunshine mapping API response did not include a mapping".to_string())
+        .await?;
+    require_mapping(response)
 }
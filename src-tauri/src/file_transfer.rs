@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 use crate::clipboard;
-use crate::sunshine::{create_https_client, get_active_sessions, get_sunshine_url};
+use crate::core_api::{CoreApiError, CoreClient};
+use crate::sunshine::get_active_sessions;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct FileOffer {
@@ -62,6 +63,22 @@ pub async fn send_file_to_client(path: String) -> Result<String, String> {
     send_paths_to_client(vec![path]).await
 }
 
+/// Offer a file to a single streaming client; the Core delivers it to that
+/// session only.
+pub async fn send_file_to_session(
+    session_id: u32,
+    path: &str,
+) -> Result<crate::sunshine::TrayActionResponse, String> {
+    let path = canonicalize_file(path)?;
+    let offer = register_offer(&path).await?;
+    info!(
+        "file transfer: offering {} to session {}",
+        offer.name, session_id
+    );
+    crate::sunshine::post_tray_session_action("session_send_file", session_id, Some(&offer.id))
+        .await
+}
+
 async fn send_paths_to_client(paths: Vec<String>) -> Result<String, String> {
     let first = paths.first().ok_or_else(|| "没有选择文件".to_string())?;
 
@@ -105,30 +122,25 @@ fn canonicalize_file(path: &str) -> Result<PathBuf, String> {
     Ok(canonical)
 }
 
-async fn register_offer(path: &PathBuf) -> Result<FileOffer, String> {
-    let url = get_sunshine_url().await?;
-    let client = create_https_client()?;
-    let endpoint = format!("{}/api/v1/file-transfer/offers", url.trim_end_matches('/'));
-
-    let body = serde_json::json!({
-        "path": path.to_string_lossy(),
-    });
+#[derive(Debug, Serialize)]
+struct RegisterOfferRequest {
+    path: String,
+}
 
-    let resp = client
-        .post(endpoint)
-        .json(&body)
+async fn register_offer(path: &PathBuf) -> Result<FileOffer, String> {
+    let request = RegisterOfferRequest {
+        path: path.to_string_lossy().into_owned(),
+    };
+    let failed = |e: CoreApiError| format!("注册文件 offer 失败: {}", e.detail());
+    CoreClient::target()
+        .await
+        .map_err(failed)?
+        .post("api/v1/file-transfer/offers")
+        .json(&request)
+        .map_err(failed)?
         .send()
         .await
-        .map_err(|e| e.to_string())?;
-
-    let status = resp.status();
-    let text = resp.text().await.map_err(|e| e.to_string())?;
-    if !status.is_success() {
-        return Err(format!("注册文件 offer 失败: HTTP {status} {text}"));
-    }
-
-    serde_json::from_str::<FileOffer>(&text)
-        .map_err(|e| format!("解析文件 offer 响应失败: {e}; body={text}"))
+        .map_err(failed)
 }
 
 fn build_client_offer_payload(offer: &FileOffer) -> Result<Vec<u8>, String> {
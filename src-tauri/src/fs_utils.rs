@@ -250,80 +250,19 @@ pub async fn cleanup_unused_covers() -> Result<serde_json::Value, String> {
     info!("🧹 开始清理无用封面...");
 
     let covers_dir = sunshine::covers_dir();
-    let apps_json_path = sunshine::config_dir().join("apps.json");
+    let apps_json_path = crate::apps::apps_path();
 
     debug!("📂 使用 covers 目录: {:?}", covers_dir);
     debug!("📄 使用 apps.json 路径: {:?}", apps_json_path);
 
-    // 读取 apps.json 获取所有正在使用的图片
-    let used_images: HashSet<String> = if apps_json_path.exists() {
-        match fs::read_to_string(&apps_json_path) {
-            Ok(content) => {
-                // 检查文件内容是否为空或只包含空白字符
-                let trimmed_content = content.trim();
-                if trimmed_content.is_empty() {
-                    warn!("⚠️  apps.json 文件为空，跳过解析");
-                    HashSet::new()
-                } else {
-                    // 尝试解析 JSON
-                    match serde_json::from_str::<serde_json::Value>(trimmed_content) {
-                        Ok(apps) => {
-                            let mut images = HashSet::new();
-
-                            if let Some(apps_array) = apps.get("apps").and_then(|a| a.as_array()) {
-                                for app in apps_array {
-                                    if let Some(image_path) =
-                                        app.get("image-path").and_then(|p| p.as_str())
-                                    {
-                                        // 跳过无效或默认图片
-                                        if image_path.is_empty() || image_path == "desktop" {
-                                            continue;
-                                        }
-
-                                        // 提取文件名（去除路径）
-                                        let filename = image_path
-                                            .split('/')
-                                            .last()
-                                            .or_else(|| image_path.split('\\').last())
-                                            .unwrap_or(image_path);
-
-                                        if !filename.is_empty() && filename != "desktop" {
-                                            // 始终保存文件名
-                                            images.insert(filename.to_string());
-
-                                            // 如果路径包含分隔符，也保存完整路径
-                                            if image_path.contains('/') || image_path.contains('\\')
-                                            {
-                                                images.insert(image_path.to_string());
-                                                debug!(
-                                                    "  📌 使用中: {} (完整路径: {})",
-                                                    filename, image_path
-                                                );
-                                            } else {
-                                                debug!("  📌 使用中: {}", filename);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            images
-                        }
-                        Err(e) => {
-                            warn!("⚠️  解析 apps.json 失败: {}，跳过解析", e);
-                            HashSet::new()
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                warn!("⚠️  读取 apps.json 失败: {}，跳过解析", e);
-                HashSet::new()
-            }
-        }
-    } else {
-        debug!("📄 apps.json 不存在，跳过解析");
-        HashSet::new()
-    };
+    // 读取 apps.json 获取所有正在使用的图片；无法解析时不能判断哪些封面无用
+    let used_images: HashSet<String> = crate::apps::load_apps_file(&apps_json_path)
+        .map_err(|e| format!("{}，已取消清理", e))?
+        .apps
+        .iter()
+        .flat_map(|app| app.cover_references())
+        .inspect(|image| debug!("  📌 使用中: {}", image))
+        .collect();
 
     debug!("  正在使用的封面数: {}", used_images.len());
 
@@ -1619,8 +1558,7 @@ pub async fn upload_steam_cover(
         "data": b64,
     });
 
-    let upload_resp = client
-        .post(&upload_url)
+    let upload_resp = crate::proxy_server::with_proxy_access(client.post(&upload_url), &upload_url)
         .json(&payload)
         .send()
         .await
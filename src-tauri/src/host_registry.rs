@@ -0,0 +1,696 @@
+//! Named remote Sunshine hosts the main window and proxy can switch between.
+//!
+//! The registry lives in `<config>/Sunshine GUI/hosts.json`. Switching hosts
+//! only retargets the main window and the local proxy; tray traffic keeps
+//! talking to the Core installed on this machine. A registered host's WebUI
+//! is framed from an isolated origin that has no Tauri IPC. Passwords live in
+//! the Windows Credential Manager and are never returned to the WebView;
+//! passwords left in the file by older versions are moved there on first read
+//! and stay in the file until the move succeeds.
+
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+mod credentials;
+
+const HOSTS_FILE: &str = "hosts.json";
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(30);
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const HOST_HEALTH_EVENT: &str = "sunshine-hosts-health";
+const HOST_CHANGED_EVENT: &str = "sunshine-host-changed";
+
+/// Serializes read-modify-write cycles on `hosts.json`.
+static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+static HOST_HEALTH: Lazy<RwLock<HashMap<String, HostHealth>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+static HEALTH_MONITOR_STARTED: std::sync::atomic::AtomicBool =
+    std::sync::atomic::AtomicBool::new(false);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SunshineHost {
+    id: String,
+    name: String,
+    /// Normalized to `scheme://host:port`.
+    url: String,
+    username: Option<String>,
+    /// Whether the credential store holds a password for this host.
+    has_password: bool,
+    /// A password not yet moved into the credential store: a new one from the
+    /// WebView, or one left in the file by an older version. Only written back
+    /// while the credential store keeps rejecting it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<String>,
+}
+
+impl SunshineHost {
+    fn credentials(&self) -> Option<(String, String)> {
+        let username = self.username.clone()?;
+        let password = match &self.password {
+            Some(password) => password.clone(),
+            None if self.has_password => match credentials::load(&self.id) {
+                Ok(password) => password?,
+                Err(e) => {
+                    warn!("Cannot read password of host '{}': {}", self.name, e);
+                    return None;
+                }
+            },
+            None => return None,
+        };
+        Some((username, password))
+    }
+
+    /// Move a pending password into the credential store, or drop the stored
+    /// one once the host no longer has a password.
+    fn persist_password(&mut self) -> Result<(), String> {
+        match &self.password {
+            Some(password) => {
+                credentials::store(&self.id, password)?;
+                self.has_password = true;
+                self.password = None;
+            }
+            None if !self.has_password => credentials::delete(&self.id)?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct HostRegistry {
+    hosts: Vec<SunshineHost>,
+    /// `None` means the Core installed on this machine.
+    active_host_id: Option<String>,
+}
+
+/// Host fields accepted from the WebView. A missing `id` creates a host; a
+/// missing `password` keeps the stored one so the UI never has to read it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SunshineHostInput {
+    pub id: Option<String>,
+    pub name: String,
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HostStatus {
+    #[default]
+    Unknown,
+    Online,
+    /// Reachable, but the stored credentials were rejected.
+    Unauthorized,
+    Offline,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostHealth {
+    pub status: HostStatus,
+    pub latency_ms: Option<u64>,
+    pub checked_at: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SunshineHostInfo {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub username: Option<String>,
+    pub has_password: bool,
+    pub active: bool,
+    pub health: HostHealth,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SunshineHostList {
+    /// `None` while the main window targets this machine's Core.
+    pub active_host_id: Option<String>,
+    pub hosts: Vec<SunshineHostInfo>,
+}
+
+fn registry_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(HOSTS_FILE))
+}
+
+fn load_registry(path: &Path) -> Result<HostRegistry, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(HostRegistry::default());
+        }
+        Err(e) => return Err(format!("读取主机列表失败: {}", e)),
+    };
+    let mut registry: HostRegistry = match serde_json::from_str(&text) {
+        Ok(registry) => registry,
+        Err(e) => {
+            warn!("Invalid host registry, starting a new one: {}", e);
+            back_up_unreadable(path)?;
+            return Ok(HostRegistry::default());
+        }
+    };
+    if !migrate_plaintext_passwords(&mut registry) {
+        return Ok(registry);
+    }
+    if let Err(e) = save_registry(path, &registry) {
+        warn!("Cannot rewrite host registry without passwords: {}", e);
+    }
+    Ok(registry)
+}
+
+/// Keep an unreadable `hosts.json` next to the new one instead of letting the
+/// next save replace it.
+fn back_up_unreadable(path: &Path) -> Result<(), String> {
+    let backup = path.with_extension(format!(
+        "json.invalid-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::rename(path, &backup).map_err(|e| format!("备份无法读取的主机列表失败: {}", e))?;
+    warn!("⚠️ 无法读取的主机列表已备份到 {}", backup.display());
+    Ok(())
+}
+
+/// Move passwords saved in `hosts.json` by older versions into the credential
+/// store. Returns whether any moved, so the file is rewritten without them;
+/// passwords the store rejected are written back unchanged.
+fn migrate_plaintext_passwords(registry: &mut HostRegistry) -> bool {
+    let mut migrated = false;
+    for host in registry
+        .hosts
+        .iter_mut()
+        .filter(|host| host.password.is_some())
+    {
+        match host.persist_password() {
+            Ok(()) => {
+                info!("🔐 已将主机 {} 的密码移入凭据管理器", host.name);
+                migrated = true;
+            }
+            // Kept in the file and retried on the next read.
+            Err(e) => warn!("Cannot move password of host '{}': {}", host.name, e),
+        }
+    }
+    migrated
+}
+
+fn save_registry(path: &Path, registry: &HostRegistry) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(registry).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+impl HostRegistry {
+    fn find(&self, id: &str) -> Option<&SunshineHost> {
+        self.hosts.iter().find(|host| host.id == id)
+    }
+
+    fn active_host(&self) -> Option<&SunshineHost> {
+        self.find(self.active_host_id.as_deref()?)
+    }
+
+    /// Insert or update a host and return its id.
+    fn upsert(&mut self, input: SunshineHostInput) -> Result<String, String> {
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            return Err("Host name must not be empty".to_string());
+        }
+        let url = crate::sunshine::parse_url_to_base(input.url.trim())
+            .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+            .ok_or_else(|| format!("Invalid Sunshine URL: {}", input.url))?;
+        let username = non_empty(input.username);
+        let password = input.password.filter(|password| !password.is_empty());
+
+        let id = non_empty(input.id);
+        if self
+            .hosts
+            .iter()
+            .any(|host| host.name.eq_ignore_ascii_case(&name) && Some(&host.id) != id.as_ref())
+        {
+            return Err(format!("A host named '{}' already exists", name));
+        }
+
+        match id {
+            Some(id) => {
+                let host = self
+                    .hosts
+                    .iter_mut()
+                    .find(|host| host.id == id)
+                    .ok_or_else(|| format!("Unknown host: {}", id))?;
+                host.name = name;
+                host.url = url;
+                if username.is_none() {
+                    host.password = None;
+                    host.has_password = false;
+                } else if password.is_some() {
+                    host.password = password;
+                }
+                host.username = username;
+                Ok(id)
+            }
+            None => {
+                let id = uuid::Uuid::new_v4().simple().to_string();
+                self.hosts.push(SunshineHost {
+                    id: id.clone(),
+                    name,
+                    url,
+                    password: password.filter(|_| username.is_some()),
+                    username,
+                    has_password: false,
+                });
+                Ok(id)
+            }
+        }
+    }
+
+    fn remove(&mut self, id: &str) -> Result<SunshineHost, String> {
+        let index = self
+            .hosts
+            .iter()
+            .position(|host| host.id == id)
+            .ok_or_else(|| format!("Unknown host: {}", id))?;
+        if self.active_host_id.as_deref() == Some(id) {
+            self.active_host_id = None;
+        }
+        Ok(self.hosts.remove(index))
+    }
+
+    fn to_list(&self, health: &HashMap<String, HostHealth>) -> SunshineHostList {
+        SunshineHostList {
+            active_host_id: self.active_host_id.clone(),
+            hosts: self
+                .hosts
+                .iter()
+                .map(|host| SunshineHostInfo {
+                    id: host.id.clone(),
+                    name: host.name.clone(),
+                    url: host.url.clone(),
+                    username: host.username.clone(),
+                    has_password: host.has_password || host.password.is_some(),
+                    active: self.active_host_id.as_deref() == Some(host.id.as_str()),
+                    health: health.get(&host.id).cloned().unwrap_or_default(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn read_registry() -> Result<HostRegistry, String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    load_registry(&registry_path()?)
+}
+
+fn update_registry<T>(
+    update: impl FnOnce(&mut HostRegistry) -> Result<T, String>,
+) -> Result<(HostRegistry, T), String> {
+    let _guard = REGISTRY_LOCK.lock().unwrap();
+    let path = registry_path()?;
+    let mut registry = load_registry(&path)?;
+    let value = update(&mut registry)?;
+    save_registry(&path, &registry)?;
+    Ok((registry, value))
+}
+
+fn host_list(registry: &HostRegistry) -> SunshineHostList {
+    let health = HOST_HEALTH.read().map(|h| h.clone()).unwrap_or_default();
+    registry.to_list(&health)
+}
+
+fn retarget(base_url: String, credentials: Option<(String, String)>) -> String {
+    crate::proxy_server::set_sunshine_target(base_url.clone());
+    crate::proxy_server::set_sunshine_target_credentials(
+        credentials
+            .as_ref()
+            .map(|(username, password)| (username.as_str(), password.as_str())),
+    );
+    crate::proxy_server::reset_fast_fail();
+    base_url
+}
+
+fn apply_host_target(host: &SunshineHost) -> Result<String, String> {
+    let base_url = crate::sunshine::set_runtime_sunshine_url(&host.url)?;
+    let base_url = retarget(base_url, host.credentials());
+    // Registered hosts are other machines even when reached through a local
+    // tunnel; their pages must not get the IPC-enabled loopback origin.
+    crate::proxy_server::isolate_sunshine_target();
+    Ok(base_url)
+}
+
+/// Point the runtime Sunshine URL and the proxy at `host`, or at this
+/// machine's Core when `host` is `None`.
+async fn apply_target(host: Option<&SunshineHost>) -> Result<String, String> {
+    match host {
+        Some(host) => apply_host_target(host),
+        None => {
+            let local_url = crate::sunshine::get_local_sunshine_url().await?;
+            let base_url = crate::sunshine::set_runtime_sunshine_url(&local_url)?;
+            Ok(retarget(base_url, None))
+        }
+    }
+}
+
+/// Restore the host selected in a previous session before any window reads
+/// the Sunshine URL, and again after the tray's pairing window borrowed the
+/// proxy for this machine's Core. `--url=` and `WEBUI_DEV_TARGET` still take
+/// precedence for a single launch.
+pub fn restore_active_host() {
+    if std::env::var_os("WEBUI_DEV_TARGET").is_some()
+        || crate::sunshine::get_command_line_url().is_some()
+        || crate::simulated_core::url().is_some()
+    {
+        return;
+    }
+    let registry = match read_registry() {
+        Ok(registry) => registry,
+        Err(e) => {
+            warn!("Cannot read host registry: {}", e);
+            return;
+        }
+    };
+    let Some(host) = registry.active_host() else {
+        return;
+    };
+    match apply_host_target(host) {
+        Ok(url) => info!("🖥️ 已恢复上次选择的主机: {} ({})", host.name, url),
+        Err(e) => warn!("Cannot restore host '{}': {}", host.name, e),
+    }
+}
+
+fn classify_probe(status: reqwest::StatusCode, has_credentials: bool) -> HostStatus {
+    if has_credentials && status == reqwest::StatusCode::UNAUTHORIZED {
+        HostStatus::Unauthorized
+    } else {
+        // Any HTTP answer proves the WebUI is up; without credentials a 401
+        // from the API is expected.
+        HostStatus::Online
+    }
+}
+
+async fn probe_host(client: &reqwest::Client, host: &SunshineHost) -> HostHealth {
+    let started = Instant::now();
+    let mut request = client
+        .get(format!("{}/api/config", host.url))
+        .timeout(HEALTH_PROBE_TIMEOUT);
+    let credentials = host.credentials();
+    if let Some((username, password)) = &credentials {
+        request = request.basic_auth(username, Some(password));
+    }
+    let checked_at = Some(chrono::Local::now().to_rfc3339());
+    match request.send().await {
+        Ok(response) => HostHealth {
+            status: classify_probe(response.status(), credentials.is_some()),
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+            checked_at,
+            error: None,
+        },
+        Err(e) => HostHealth {
+            status: HostStatus::Offline,
+            latency_ms: None,
+            checked_at,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+async fn poll_all_hosts(app: &AppHandle) -> Result<SunshineHostList, String> {
+    let registry = read_registry()?;
+    let client = crate::sunshine::create_https_client()?;
+    let results = futures_util::future::join_all(
+        registry
+            .hosts
+            .iter()
+            .map(|host| async { (host.id.clone(), probe_host(&client, host).await) }),
+    )
+    .await;
+
+    if let Ok(mut health) = HOST_HEALTH.write() {
+        health.retain(|id, _| registry.find(id).is_some());
+        for (id, result) in results {
+            debug!("Host {} health: {:?}", id, result.status);
+            health.insert(id, result);
+        }
+    }
+
+    let list = host_list(&registry);
+    let _ = app.emit(HOST_HEALTH_EVENT, &list);
+    Ok(list)
+}
+
+/// Poll every registered host in the background and emit
+/// `sunshine-hosts-health` with the refreshed list after each round.
+pub fn start_health_monitor(app: AppHandle) {
+    use std::sync::atomic::Ordering;
+
+    if HEALTH_MONITOR_STARTED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(HEALTH_POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = poll_all_hosts(&app).await {
+                debug!("Host health poll skipped: {}", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_sunshine_hosts() -> Result<SunshineHostList, String> {
+    Ok(host_list(&read_registry()?))
+}
+
+#[tauri::command]
+pub fn save_sunshine_host(host: SunshineHostInput) -> Result<SunshineHostList, String> {
+    let (registry, id) = update_registry(|registry| {
+        let id = registry.upsert(host)?;
+        if let Some(host) = registry.hosts.iter_mut().find(|host| host.id == id) {
+            host.persist_password()?;
+        }
+        Ok(id)
+    })?;
+    info!("🖥️ 已保存主机: {}", id);
+    Ok(host_list(&registry))
+}
+
+#[tauri::command]
+pub async fn delete_sunshine_host(app: AppHandle, id: String) -> Result<SunshineHostList, String> {
+    let (registry, removed) = update_registry(|registry| {
+        let was_active = registry.active_host_id.as_deref() == Some(id.as_str());
+        registry.remove(&id).map(|host| (host, was_active))
+    })?;
+    if let Ok(mut health) = HOST_HEALTH.write() {
+        health.remove(&id);
+    }
+    let (host, was_active) = removed;
+    if let Err(e) = credentials::delete(&host.id) {
+        warn!("Cannot delete password of host '{}': {}", host.name, e);
+    }
+    info!("🖥️ 已删除主机: {}", host.name);
+    let list = host_list(&registry);
+    if was_active {
+        apply_target(None).await?;
+        let _ = app.emit(HOST_CHANGED_EVENT, &list);
+    }
+    Ok(list)
+}
+
+/// Switch the main window and proxy to a registered host, or back to this
+/// machine's Core when `id` is `None`. The choice is only saved once the new
+/// target is applied, so a failed switch is not restored on the next launch.
+#[tauri::command]
+pub async fn switch_sunshine_host(
+    app: AppHandle,
+    id: Option<String>,
+) -> Result<SunshineHostList, String> {
+    let host = match id.as_deref() {
+        Some(id) => Some(
+            read_registry()?
+                .find(id)
+                .cloned()
+                .ok_or_else(|| format!("Unknown host: {}", id))?,
+        ),
+        None => None,
+    };
+
+    let base_url = apply_target(host.as_ref()).await?;
+    let (registry, ()) = update_registry(|registry| {
+        registry.active_host_id = host
+            .as_ref()
+            .map(|host| host.id.clone())
+            .filter(|id| registry.find(id).is_some());
+        Ok(())
+    })?;
+    info!(
+        "🖥️ 已切换主机: {} ({})",
+        host.as_ref().map_or("本机", |host| host.name.as_str()),
+        base_url
+    );
+
+    let list = host_list(&registry);
+    let _ = app.emit(HOST_CHANGED_EVENT, &list);
+    Ok(list)
+}
+
+/// Probe every host now instead of waiting for the next background round.
+#[tauri::command]
+pub async fn refresh_sunshine_host_health(app: AppHandle) -> Result<SunshineHostList, String> {
+    poll_all_hosts(&app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(name: &str, url: &str) -> SunshineHostInput {
+        SunshineHostInput {
+            name: name.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn upsert_normalizes_urls_and_rejects_duplicates() {
+        let mut registry = HostRegistry::default();
+        let id = registry
+            .upsert(input("Rack 1", " https://10.0.0.5:47990/config "))
+            .unwrap();
+        assert_eq!(registry.find(&id).unwrap().url, "https://10.0.0.5:47990");
+
+        assert!(
+            registry
+                .upsert(input("rack 1", "https://10.0.0.6"))
+                .is_err()
+        );
+        assert!(registry.upsert(input("", "https://10.0.0.6")).is_err());
+        assert!(registry.upsert(input("Rack 2", "not a url")).is_err());
+        assert!(registry.upsert(input("Rack 2", "ftp://10.0.0.6")).is_err());
+    }
+
+    #[test]
+    fn updates_keep_the_stored_password_unless_replaced() {
+        let mut registry = HostRegistry::default();
+        let id = registry
+            .upsert(SunshineHostInput {
+                username: Some("admin".into()),
+                password: Some("secret".into()),
+                ..input("Rack 1", "https://10.0.0.5:47990")
+            })
+            .unwrap();
+
+        registry
+            .upsert(SunshineHostInput {
+                id: Some(id.clone()),
+                username: Some("admin".into()),
+                ..input("Rack 1b", "https://10.0.0.5:47990")
+            })
+            .unwrap();
+        let host = registry.find(&id).unwrap();
+        assert_eq!(host.name, "Rack 1b");
+        assert_eq!(
+            host.credentials(),
+            Some(("admin".to_string(), "secret".to_string()))
+        );
+
+        registry
+            .upsert(SunshineHostInput {
+                id: Some(id.clone()),
+                ..input("Rack 1b", "https://10.0.0.5:47990")
+            })
+            .unwrap();
+        assert_eq!(registry.find(&id).unwrap().credentials(), None);
+    }
+
+    #[test]
+    fn listing_hides_passwords_and_marks_the_active_host() {
+        let mut registry = HostRegistry::default();
+        let id = registry
+            .upsert(SunshineHostInput {
+                username: Some("admin".into()),
+                password: Some("secret".into()),
+                ..input("Rack 1", "https://10.0.0.5:47990")
+            })
+            .unwrap();
+        registry.active_host_id = Some(id.clone());
+
+        let list = registry.to_list(&HashMap::new());
+        let json = serde_json::to_string(&list).unwrap();
+        assert!(!json.contains("secret"));
+        assert!(list.hosts[0].has_password);
+        assert!(list.hosts[0].active);
+        assert_eq!(list.hosts[0].health.status, HostStatus::Unknown);
+
+        registry.remove(&id).unwrap();
+        assert_eq!(registry.active_host_id, None);
+    }
+
+    #[test]
+    fn old_passwords_stay_in_the_file_until_moved() {
+        let mut registry: HostRegistry = serde_json::from_str(
+            r#"{"hosts":[{"id":"a","name":"Rack 1","url":"https://10.0.0.5:47990","username":"admin","password":"secret"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(registry.hosts[0].password.as_deref(), Some("secret"));
+        assert!(registry.to_list(&HashMap::new()).hosts[0].has_password);
+        assert!(serde_json::to_string(&registry).unwrap().contains("secret"));
+
+        registry.hosts[0].password = None;
+        registry.hosts[0].has_password = true;
+        assert!(!serde_json::to_string(&registry).unwrap().contains("secret"));
+    }
+
+    #[test]
+    fn unreadable_registry_is_backed_up_not_replaced() {
+        let dir = std::env::temp_dir().join(format!("hosts-{}", uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(HOSTS_FILE);
+        fs::write(&path, "{ not json").unwrap();
+
+        assert!(load_registry(&path).unwrap().hosts.is_empty());
+        assert!(!path.exists());
+        let backups: Vec<_> = fs::read_dir(&dir).unwrap().collect();
+        assert_eq!(backups.len(), 1);
+        let backup = backups[0].as_ref().unwrap().path();
+        assert_eq!(fs::read_to_string(backup).unwrap(), "{ not json");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unauthorized_probe_only_counts_with_credentials() {
+        assert_eq!(
+            classify_probe(reqwest::StatusCode::UNAUTHORIZED, true),
+            HostStatus::Unauthorized
+        );
+        assert_eq!(
+            classify_probe(reqwest::StatusCode::UNAUTHORIZED, false),
+            HostStatus::Online
+        );
+        assert_eq!(
+            classify_probe(reqwest::StatusCode::OK, true),
+            HostStatus::Online
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! Host passwords in the Windows Credential Manager.
+//!
+//! Each host gets a generic credential named `Sunshine GUI/host/<id>`, so
+//! `hosts.json` only records whether a password exists.
+
+const TARGET_PREFIX: &str = "Sunshine GUI/host/";
+
+fn target_name(host_id: &str) -> String {
+    format!("{TARGET_PREFIX}{host_id}")
+}
+
+#[cfg(target_os = "windows")]
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0u16)).collect()
+}
+
+#[cfg(target_os = "windows")]
+pub(super) fn store(host_id: &str, password: &str) -> Result<(), String> {
+    use ::windows::Win32::Security::Credentials::{
+        CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC, CREDENTIALW, CredWriteW,
+    };
+    use ::windows::core::PWSTR;
+
+    let mut target = to_wide(&target_name(host_id));
+    let mut blob: Vec<u8> = password
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+    let credential = CREDENTIALW {
+        Type: CRED_TYPE_GENERIC,
+        TargetName: PWSTR(target.as_mut_ptr()),
+        CredentialBlobSize: blob.len() as u32,
+        CredentialBlob: blob.as_mut_ptr(),
+        Persist: CRED_PERSIST_LOCAL_MACHINE,
+        ..Default::default()
+    };
+    unsafe { CredWriteW(&credential, 0) }.map_err(|e| format!("保存主机密码失败: {e}"))
+}
+
+#[cfg(target_os = "windows")]
+pub(super) fn load(host_id: &str) -> Result<Option<String>, String> {
+    use ::windows::Win32::Foundation::ERROR_NOT_FOUND;
+    use ::windows::Win32::Security::Credentials::{
+        CRED_TYPE_GENERIC, CREDENTIALW, CredFree, CredReadW,
+    };
+    use ::windows::core::PCWSTR;
+
+    let target = to_wide(&target_name(host_id));
+    let mut credential: *mut CREDENTIALW = std::ptr::null_mut();
+    if let Err(e) = unsafe {
+        CredReadW(
+            PCWSTR(target.as_ptr()),
+            CRED_TYPE_GENERIC,
+            None,
+            &mut credential,
+        )
+    } {
+        if e.code() == ERROR_NOT_FOUND.to_hresult() {
+            return Ok(None);
+        }
+        return Err(format!("读取主机密码失败: {e}"));
+    }
+    let password = unsafe {
+        let blob = std::slice::from_raw_parts(
+            (*credential).CredentialBlob,
+            (*credential).CredentialBlobSize as usize,
+        );
+        let units: Vec<u16> = blob
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        CredFree(credential as *const _);
+        String::from_utf16_lossy(&units)
+    };
+    Ok(Some(password))
+}
+
+#[cfg(target_os = "windows")]
+pub(super) fn delete(host_id: &str) -> Result<(), String> {
+    use ::windows::Win32::Foundation::ERROR_NOT_FOUND;
+    use ::windows::Win32::Security::Credentials::{CRED_TYPE_GENERIC, CredDeleteW};
+    use ::windows::core::PCWSTR;
+
+    let target = to_wide(&target_name(host_id));
+    match unsafe { CredDeleteW(PCWSTR(target.as_ptr()), CRED_TYPE_GENERIC, None) } {
+        Err(e) if e.code() == ERROR_NOT_FOUND.to_hresult() => Ok(()),
+        result => result.map_err(|e| format!("删除主机密码失败: {e}")),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(super) fn store(host_id: &str, _password: &str) -> Result<(), String> {
+    Err(format!(
+        "此平台没有凭据管理器，无法保存 {} 的密码",
+        target_name(host_id)
+    ))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(super) fn load(_host_id: &str) -> Result<Option<String>, String> {
+    Ok(None)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(super) fn delete(_host_id: &str) -> Result<(), String> {
+    Ok(())
+}
@@ -5,8 +5,13 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 
+use crate::desktop_settings::DesktopSettings;
+
+mod file_sink;
+
 /// In-memory log entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
@@ -39,6 +44,17 @@ impl LogCollector {
     }
 
     pub fn add_log(&self, record: &Record) {
+        if let Some(message) = file_sink::take_failure() {
+            self.show(LogEntry {
+                timestamp: Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+                level: "error".to_string(),
+                message,
+                target: Some(module_path!().to_string()),
+                file: None,
+                line: None,
+            });
+        }
+
         // Keep only the file name, not the full path.
         let file = record.file().map(|f| {
             f.split('/')
@@ -63,6 +79,20 @@ impl LogCollector {
             line: record.line(),
         };
 
+        // The file level is independent of the console level; the writer
+        // thread does the disk work.
+        let level = record_level_to_u8(record.level());
+        if level <= FILE_LOG_LEVEL.load(Ordering::Relaxed) {
+            file_sink::append(format_entry(&entry));
+        }
+        if level > LOG_LEVEL_FILTER.load(Ordering::Relaxed) {
+            return;
+        }
+        self.show(entry);
+    }
+
+    /// Keep `entry` in memory and stream it to the log console.
+    fn show(&self, entry: LogEntry) {
         // Append and cap the in-memory log list.
         {
             let mut logs = self.logs.lock().unwrap();
@@ -118,8 +148,10 @@ impl TauriLogger {
 
 impl Log for TauriLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
+        let level = record_level_to_u8(metadata.level());
         self.inner.enabled(metadata)
-            && record_level_to_u8(metadata.level()) <= LOG_LEVEL_FILTER.load(Ordering::Relaxed)
+            && (level <= LOG_LEVEL_FILTER.load(Ordering::Relaxed)
+                || level <= FILE_LOG_LEVEL.load(Ordering::Relaxed))
     }
 
     fn log(&self, record: &Record) {
@@ -131,13 +163,15 @@ impl Log for TauriLogger {
     }
 
     fn flush(&self) {
-        // Nothing to flush.
+        file_sink::flush();
     }
 }
 
 static LOG_COLLECTOR: once_cell::sync::OnceCell<Arc<LogCollector>> =
     once_cell::sync::OnceCell::new();
 static LOG_LEVEL_FILTER: AtomicU8 = AtomicU8::new(3);
+/// Level written to the log files; off until `configure_file_log`.
+static FILE_LOG_LEVEL: AtomicU8 = AtomicU8::new(0);
 
 fn level_to_u8(level: LevelFilter) -> u8 {
     match level {
@@ -152,6 +186,7 @@ fn level_to_u8(level: LevelFilter) -> u8 {
 
 fn level_from_str(level: &str) -> LevelFilter {
     match level.trim().to_ascii_lowercase().as_str() {
+        "off" => LevelFilter::Off,
         "error" => LevelFilter::Error,
         "warn" => LevelFilter::Warn,
         "debug" => LevelFilter::Debug,
@@ -174,12 +209,48 @@ pub fn set_log_level(level: &str) {
     LOG_LEVEL_FILTER.store(level_to_u8(level_from_str(level)), Ordering::Relaxed);
 }
 
+/// Apply the log file level, rotation limits and retention.
+pub fn configure_file_log(settings: &DesktopSettings) {
+    let Some(dir) = file_sink::log_dir() else {
+        FILE_LOG_LEVEL.store(0, Ordering::Relaxed);
+        return;
+    };
+    file_sink::configure(file_sink::FileLogConfig {
+        dir,
+        max_bytes: u64::from(settings.log_file_max_size_mb) * 1024 * 1024,
+        max_age: Duration::from_secs(u64::from(settings.log_file_max_age_days) * 24 * 60 * 60),
+        retention: settings.log_file_retention as usize,
+    });
+    FILE_LOG_LEVEL.store(
+        level_to_u8(level_from_str(&settings.file_log_level)),
+        Ordering::Relaxed,
+    );
+}
+
+fn format_entry(log: &LogEntry) -> String {
+    let file_info = if let (Some(file), Some(line)) = (log.file.as_ref(), log.line) {
+        format!("{}:{}", file, line)
+    } else if let Some(file) = log.file.as_ref() {
+        file.clone()
+    } else {
+        "unknown".to_string()
+    };
+    format!(
+        "[{}] [{}] [{}] {}\n",
+        log.timestamp,
+        log.level.to_uppercase(),
+        file_info,
+        log.message
+    )
+}
+
 /// Initialize logging.
 pub fn init_logger(app: AppHandle) {
     let collector = Arc::new(LogCollector::new(10000));
     collector.set_app_handle(app);
     let settings = crate::desktop_settings::load_desktop_settings_from_disk();
     set_log_level(&settings.log_level);
+    configure_file_log(&settings);
 
     LOG_COLLECTOR.set(collector.clone()).ok();
 
@@ -188,6 +259,14 @@ pub fn init_logger(app: AppHandle) {
     log::set_logger(Box::leak(logger))
         .map(|()| log::set_max_level(log::LevelFilter::Trace))
         .expect("Failed to initialize logger");
+
+    // Get the panic message and everything before it into the log file.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!("💥 GUI panic: {}", info);
+        file_sink::flush();
+        default_hook(info);
+    }));
 }
 
 /// Return all collected logs.
@@ -208,11 +287,12 @@ pub fn clear_logs() {
     }
 }
 
-/// Export collected logs to a file.
+/// Export collected logs to a file. `zip` bundles the text export with the
+/// current and rotated log files.
 #[tauri::command]
 pub async fn export_logs(
     app: AppHandle,
-    format: String, // "txt" or "json"
+    format: String, // "txt", "json" or "zip"
 ) -> Result<String, String> {
     use tauri_plugin_dialog::DialogExt;
     use tokio::sync::oneshot;
@@ -240,6 +320,7 @@ pub async fn export_logs(
         .set_file_name(&default_filename)
         .add_filter("Text files", &["txt"])
         .add_filter("JSON files", &["json"])
+        .add_filter("ZIP archives", &["zip"])
         .add_filter("All files", &["*"])
         .save_file(move |file_path_opt| {
             let _ = tx.send(file_path_opt);
@@ -254,43 +335,22 @@ pub async fn export_logs(
     // Convert FilePath to PathBuf.
     let file_path: PathBuf = PathBuf::from(file_path.to_string());
 
-    // Generate export content.
-    let content = match format.as_str() {
-        "json" => serde_json::to_string_pretty(&logs)
-            .map_err(|e| format!("Failed to serialize JSON: {}", e))?,
-        "txt" | _ => {
-            let mut text = String::new();
-            text.push_str("Sunshine Control Panel Log Export\n");
-            text.push_str(&format!(
-                "Export time: {}\n",
-                Local::now().format("%Y-%m-%d %H:%M:%S")
-            ));
-            text.push_str(&format!("Total logs: {}\n", logs.len()));
-            text.push_str(&format!("{}\n\n", "=".repeat(80)));
-
-            for log in &logs {
-                let file_info = if let (Some(file), Some(line)) = (log.file.as_ref(), log.line) {
-                    format!("{}:{}", file, line)
-                } else if let Some(file) = log.file.as_ref() {
-                    file.clone()
-                } else {
-                    "unknown".to_string()
-                };
-
-                text.push_str(&format!(
-                    "[{}] [{}] [{}] {}\n",
-                    log.timestamp,
-                    log.level.to_uppercase(),
-                    file_info,
-                    log.message
-                ));
-            }
-            text
+    // Write the export; archives are streamed straight to disk.
+    match format.as_str() {
+        "zip" => {
+            let path = file_path.clone();
+            tokio::task::spawn_blocking(move || zip_export(&path, &logs))
+                .await
+                .map_err(|e| format!("Failed to build log archive: {}", e))??;
         }
-    };
-
-    // Write export file.
-    fs::write(&file_path, content).map_err(|e| format!("Failed to write file: {}", e))?;
+        "json" => {
+            let content = serde_json::to_string_pretty(&logs)
+                .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
+            fs::write(&file_path, content).map_err(|e| format!("Failed to write file: {}", e))?;
+        }
+        _ => fs::write(&file_path, text_export(&logs))
+            .map_err(|e| format!("Failed to write file: {}", e))?,
+    }
 
     let file_name = file_path
         .file_name()
@@ -300,3 +360,56 @@ pub async fn export_logs(
 
     Ok(format!("Logs exported to: {}", file_name))
 }
+
+fn text_export(logs: &[LogEntry]) -> String {
+    let mut text = String::new();
+    text.push_str("Sunshine Control Panel Log Export\n");
+    text.push_str(&format!(
+        "Export time: {}\n",
+        Local::now().format("%Y-%m-%d %H:%M:%S")
+    ));
+    text.push_str(&format!("Total logs: {}\n", logs.len()));
+    text.push_str(&format!("{}\n\n", "=".repeat(80)));
+
+    for log in logs {
+        text.push_str(&format_entry(log));
+    }
+    text
+}
+
+/// Write the text export plus every log file, under `files/`, to `path`.
+fn zip_export(path: &std::path::Path, logs: &[LogEntry]) -> Result<(), String> {
+    use std::io::Write;
+
+    file_sink::flush();
+    let file = fs::File::create(path).map_err(|e| format!("Failed to write file: {}", e))?;
+    let mut archive = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    archive
+        .start_file("sunshine_gui_logs.txt", options)
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    archive
+        .write_all(text_export(logs).as_bytes())
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+
+    let files = file_sink::log_dir()
+        .map(|dir| file_sink::log_files(&dir))
+        .unwrap_or_default();
+    for path in files {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let mut log_file =
+            fs::File::open(&path).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        archive
+            .start_file(format!("files/{}", name), options)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        std::io::copy(&mut log_file, &mut archive)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+    }
+    archive
+        .finish()
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(())
+}
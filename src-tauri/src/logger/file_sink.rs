@@ -0,0 +1,311 @@
+//! Rotating log files written from a background thread.
+//!
+//! Lines are formatted on the logging thread and handed to the writer over an
+//! unbounded channel, so logging never waits for the disk. The writer appends
+//! to `sunshine-gui.log` and, once that file would grow past the size limit or
+//! is older than the age limit, renames it to `sunshine-gui-<time>.log` and
+//! starts a new one. Only the newest `retention` rotated files are kept.
+//! Failures cannot be logged to the file they concern, so the first one of a
+//! run of failures is kept for the log console to show.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+const CURRENT_FILE: &str = "sunshine-gui.log";
+const ROTATED_PREFIX: &str = "sunshine-gui-";
+const LOG_SUFFIX: &str = ".log";
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FileLogConfig {
+    pub dir: PathBuf,
+    pub max_bytes: u64,
+    pub max_age: Duration,
+    /// Rotated files kept next to the current one.
+    pub retention: usize,
+}
+
+enum Command {
+    Line(String),
+    Configure(FileLogConfig),
+    /// Answered once every line sent before it is on disk.
+    Flush(Sender<()>),
+}
+
+static SENDER: OnceLock<Sender<Command>> = OnceLock::new();
+/// A failure not yet shown in the log console.
+static FAILURE: Mutex<Option<String>> = Mutex::new(None);
+static FAILURE_PENDING: AtomicBool = AtomicBool::new(false);
+
+fn report_failure(message: String) {
+    *FAILURE.lock().unwrap_or_else(|e| e.into_inner()) = Some(message);
+    FAILURE_PENDING.store(true, Ordering::Release);
+}
+
+/// Take the failure to show, once per report.
+pub(super) fn take_failure() -> Option<String> {
+    if !FAILURE_PENDING.swap(false, Ordering::AcqRel) {
+        return None;
+    }
+    FAILURE.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+pub(super) fn log_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("Sunshine GUI").join("logs"))
+}
+
+/// Start the writer thread with `config`, or reconfigure the running one.
+pub(super) fn configure(config: FileLogConfig) {
+    if let Some(sender) = SENDER.get() {
+        let _ = sender.send(Command::Configure(config));
+        return;
+    }
+    let (sender, receiver) = mpsc::channel();
+    let _ = sender.send(Command::Configure(config));
+    if SENDER.set(sender).is_err() {
+        return;
+    }
+    if let Err(e) = std::thread::Builder::new()
+        .name("sunshine-log-writer".to_string())
+        .spawn(move || run(receiver))
+    {
+        report_failure(format!("Failed to start log file writer: {e}"));
+    }
+}
+
+/// Queue one formatted line, including its trailing newline.
+pub(super) fn append(line: String) {
+    if let Some(sender) = SENDER.get() {
+        let _ = sender.send(Command::Line(line));
+    }
+}
+
+/// Wait, for a bounded time, until every queued line is written.
+pub(super) fn flush() {
+    let Some(sender) = SENDER.get() else {
+        return;
+    };
+    let (done, wait) = mpsc::channel();
+    if sender.send(Command::Flush(done)).is_ok() {
+        let _ = wait.recv_timeout(FLUSH_TIMEOUT);
+    }
+}
+
+/// Log files in `dir`, oldest first: the rotated files, then the current one.
+pub(super) fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = rotated_names(dir)
+        .into_iter()
+        .map(|name| dir.join(name))
+        .collect();
+    let current = dir.join(CURRENT_FILE);
+    if current.is_file() {
+        files.push(current);
+    }
+    files
+}
+
+/// Rotated file names in `dir`, oldest first; the timestamps sort by name.
+fn rotated_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| name.starts_with(ROTATED_PREFIX) && name.ends_with(LOG_SUFFIX))
+        .collect();
+    names.sort();
+    names
+}
+
+fn needs_rotation(size: u64, incoming: u64, age: Duration, config: &FileLogConfig) -> bool {
+    size > 0 && (size + incoming > config.max_bytes || age >= config.max_age)
+}
+
+fn run(receiver: Receiver<Command>) {
+    let mut writer = Writer::new();
+    while let Ok(command) = receiver.recv() {
+        let mut waiting = Vec::new();
+        let mut next = Some(command);
+        // Drain whatever is queued before flushing once.
+        while let Some(command) = next {
+            match command {
+                Command::Line(line) => writer.write(&line, SystemTime::now()),
+                Command::Configure(config) => writer.configure(config),
+                Command::Flush(done) => waiting.push(done),
+            }
+            next = receiver.try_recv().ok();
+        }
+        writer.flush();
+        for done in waiting {
+            let _ = done.send(());
+        }
+    }
+}
+
+struct Writer {
+    config: Option<FileLogConfig>,
+    file: Option<BufWriter<File>>,
+    size: u64,
+    opened_at: SystemTime,
+    /// Whether the last write failed, so a broken disk is reported once.
+    failing: bool,
+}
+
+impl Writer {
+    fn new() -> Self {
+        Self {
+            config: None,
+            file: None,
+            size: 0,
+            opened_at: SystemTime::UNIX_EPOCH,
+            failing: false,
+        }
+    }
+
+    fn configure(&mut self, config: FileLogConfig) {
+        if self.config.as_ref().map(|current| &current.dir) != Some(&config.dir) {
+            self.flush();
+            self.file = None;
+        }
+        self.config = Some(config);
+    }
+
+    fn write(&mut self, line: &str, now: SystemTime) {
+        let Some(config) = self.config.clone() else {
+            return;
+        };
+        match self.try_write(&config, line, now) {
+            Ok(()) => self.failing = false,
+            Err(e) => {
+                if !self.failing {
+                    report_failure(format!(
+                        "Failed to write log file in {}: {e}",
+                        config.dir.display()
+                    ));
+                }
+                self.failing = true;
+                // Reopen on the next line.
+                self.file = None;
+            }
+        }
+    }
+
+    fn try_write(&mut self, config: &FileLogConfig, line: &str, now: SystemTime) -> io::Result<()> {
+        if self.file.is_none() {
+            self.open(config, now)?;
+        }
+        let incoming = line.len() as u64;
+        let age = now.duration_since(self.opened_at).unwrap_or_default();
+        if needs_rotation(self.size, incoming, age, config) {
+            self.rotate(config, now)?;
+        }
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        file.write_all(line.as_bytes())?;
+        self.size += incoming;
+        Ok(())
+    }
+
+    fn open(&mut self, config: &FileLogConfig, now: SystemTime) -> io::Result<()> {
+        fs::create_dir_all(&config.dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(config.dir.join(CURRENT_FILE))?;
+        let metadata = file.metadata()?;
+        self.size = metadata.len();
+        // A file left by an earlier run keeps aging from when it was created.
+        self.opened_at = if self.size == 0 {
+            now
+        } else {
+            metadata
+                .created()
+                .or_else(|_| metadata.modified())
+                .unwrap_or(now)
+        };
+        self.file = Some(BufWriter::new(file));
+        Ok(())
+    }
+
+    fn rotate(&mut self, config: &FileLogConfig, now: SystemTime) -> io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+        let stamp = chrono::DateTime::<chrono::Local>::from(now).format("%Y%m%d-%H%M%S-%3f");
+        fs::rename(
+            config.dir.join(CURRENT_FILE),
+            config
+                .dir
+                .join(format!("{ROTATED_PREFIX}{stamp}{LOG_SUFFIX}")),
+        )?;
+        let names = rotated_names(&config.dir);
+        let expired = names.len().saturating_sub(config.retention);
+        for name in &names[..expired] {
+            let _ = fs::remove_file(config.dir.join(name));
+        }
+        self.open(config, now)
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(dir: &Path) -> FileLogConfig {
+        FileLogConfig {
+            dir: dir.to_path_buf(),
+            max_bytes: 100,
+            max_age: Duration::from_secs(3600),
+            retention: 2,
+        }
+    }
+
+    #[test]
+    fn rotation_needs_a_non_empty_file() {
+        let config = config(Path::new("logs"));
+        assert!(!needs_rotation(0, 500, Duration::from_secs(7200), &config));
+        assert!(!needs_rotation(60, 40, Duration::ZERO, &config));
+        assert!(needs_rotation(60, 41, Duration::ZERO, &config));
+        assert!(needs_rotation(1, 1, Duration::from_secs(3600), &config));
+    }
+
+    #[test]
+    fn writer_rotates_by_size_and_age_and_keeps_the_newest_files() {
+        let dir = std::env::temp_dir().join(format!("sunshine-logs-{}", uuid::Uuid::new_v4()));
+        let mut writer = Writer::new();
+        writer.configure(config(&dir));
+        let start = SystemTime::now();
+        let line = format!("{}\n", "x".repeat(39));
+
+        // Two 40-byte lines fit in 100 bytes; every third line rotates.
+        for index in 0..9 {
+            writer.write(&line, start + Duration::from_millis(index * 10));
+        }
+        writer.flush();
+        let files = log_files(&dir);
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[2].file_name().unwrap(), CURRENT_FILE);
+        assert_eq!(fs::read_to_string(&files[2]).unwrap(), line);
+
+        writer.write(&line, start + Duration::from_secs(7200));
+        writer.flush();
+        let rotated = rotated_names(&dir);
+        assert_eq!(rotated.len(), 2);
+        assert_eq!(fs::read_to_string(dir.join(&rotated[1])).unwrap(), line);
+        assert_eq!(fs::read_to_string(dir.join(CURRENT_FILE)).unwrap(), line);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -2,23 +2,35 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod app;
+mod apps;
 mod bat_runner;
+mod bitrate_controller;
+mod client_blocklist;
 mod client_fingerprint_rules;
+mod client_profiles;
 mod clipboard;
 mod commands;
+mod config_snapshots;
 mod controllermeta;
+mod core_api;
+mod core_watchdog;
 mod desktop_settings;
 mod dualsense;
+mod event_hooks;
 mod file_mapping;
 mod file_transfer;
 mod fs_utils;
+mod host_registry;
 mod hwinfo;
 mod logger;
 mod moonlight_web;
+mod paired_clients;
 mod proxy_server;
 mod rtss;
+mod session_history;
 #[cfg(target_os = "windows")]
 mod shell_context_menu;
+mod simulated_core;
 mod sunshine;
 mod system;
 mod toolbar;
@@ -32,6 +44,7 @@ mod vdd_calibration;
 mod vdd_ioctl;
 mod vigem;
 mod vmouse;
+mod webui_plugins;
 mod windows;
 
 use log::info;
@@ -125,6 +138,7 @@ fn main() {
     // allocating one worker per logical CPU on high-core-count hosts. Proxy
     // environment variables must be finalized before worker threads start.
     configure_async_runtime();
+    simulated_core::start_if_requested();
 
     let builder = tauri::Builder::default()
         .manage(app::AppState {
@@ -209,9 +223,50 @@ fn main() {
             sunshine::restart_sunshine_in_user_mode,
             sunshine::restart_sunshine_service,
             proxy_server::get_proxy_url_command,
+            proxy_server::get_frame_url_command,
             proxy_server::get_proxy_health_check,
             proxy_server::refresh_sunshine_target,
             proxy_server::wait_for_proxy_ready,
+            proxy_server::get_external_proxy_policy,
+            proxy_server::save_external_proxy_policy,
+            host_registry::list_sunshine_hosts,
+            host_registry::save_sunshine_host,
+            host_registry::delete_sunshine_host,
+            host_registry::switch_sunshine_host,
+            host_registry::refresh_sunshine_host_health,
+            tray_config::preview_config_import,
+            tray_config::apply_config_import,
+            config_snapshots::list_config_snapshots,
+            config_snapshots::diff_config_snapshot,
+            config_snapshots::restore_config_snapshot,
+            apps::list_apps,
+            apps::create_app,
+            apps::update_app,
+            apps::delete_app,
+            apps::reorder_apps,
+            session_history::list_recent_sessions,
+            session_history::get_session_totals_by_client,
+            session_history::get_session_totals_by_app,
+            event_hooks::get_event_hooks,
+            event_hooks::save_event_hooks,
+            event_hooks::list_event_hook_runs,
+            event_hooks::test_event_hook,
+            bitrate_controller::get_bitrate_controller,
+            bitrate_controller::save_bitrate_controller_settings,
+            client_profiles::list_client_profiles,
+            client_profiles::save_client_profile,
+            client_profiles::delete_client_profile,
+            paired_clients::list_paired_clients,
+            paired_clients::rename_paired_client,
+            paired_clients::unpair_client,
+            paired_clients::unpair_stale_clients,
+            client_blocklist::list_blocked_clients,
+            client_blocklist::unblock_client,
+            core_watchdog::get_core_watchdog,
+            core_watchdog::save_core_watchdog_settings,
+            core_watchdog::reset_core_watchdog,
+            webui_plugins::list_webui_plugins,
+            webui_plugins::set_webui_plugin_enabled,
             utils::open_external_url,
             utils::open_local_path,
             utils::restart_graphics_driver,
@@ -297,6 +352,13 @@ fn main() {
             hwinfo::hwinfo_check_available,
             tray::set_locale_preferences,
             tray::get_tray_locale,
+            tray::get_tray_apps_settings,
+            tray::save_tray_apps_settings,
+            tray::get_notification_rules,
+            tray::save_notification_rules,
+            tray::get_notification_history,
+            tray::acknowledge_notifications,
+            tray::clear_notification_history,
             tray::main_panel_loading,
             tray::main_panel_ready,
         ])
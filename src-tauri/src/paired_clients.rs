@@ -0,0 +1,352 @@
+//! Paired client management on top of the Core's `/api/clients/*` endpoints.
+//!
+//! The Core only knows names and certificate UUIDs, so the list is joined
+//! with the local session history for last-seen times and with the current
+//! tray sessions for connection state and the unknown-client warning.
+
+use chrono::{DateTime, Duration, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::core_api::{CoreApiError, CoreClient, Envelope};
+use crate::session_history::SessionRecord;
+use crate::sunshine::TrayClientSession;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct NamedCert {
+    name: String,
+    uuid: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ClientList {
+    named_certs: Vec<NamedCert>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairedClient {
+    pub uuid: String,
+    pub name: String,
+    /// End of the most recent session recorded under the current name, if any.
+    pub last_seen: Option<DateTime<Utc>>,
+    pub sessions: usize,
+    pub connected: bool,
+    /// A current session of this client is flagged by the fingerprint rules.
+    pub highly_suspected_unknown_client: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnpairFailure {
+    pub uuid: String,
+    pub name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleUnpairResult {
+    pub unpaired: Vec<PairedClient>,
+    pub failed: Vec<UnpairFailure>,
+}
+
+fn same_name(a: &str, b: &str) -> bool {
+    let (a, b) = (a.trim(), b.trim());
+    !a.is_empty() && a.eq_ignore_ascii_case(b)
+}
+
+/// Whether the session fingerprint is the certificate's UUID, in any case.
+fn same_fingerprint(cert: &NamedCert, session: &TrayClientSession) -> bool {
+    !cert.uuid.is_empty()
+        && cert
+            .uuid
+            .eq_ignore_ascii_case(session.client_fingerprint.trim())
+}
+
+fn matches_session(cert: &NamedCert, session: &TrayClientSession) -> bool {
+    same_fingerprint(cert, session) || same_name(&cert.name, &session.client_name)
+}
+
+/// The certificate a tray session was paired with: the one whose UUID is the
+/// session fingerprint, otherwise the only certificate with the session's name.
+fn cert_for_session<'a>(
+    certs: &'a [NamedCert],
+    session: &TrayClientSession,
+) -> Result<&'a NamedCert, String> {
+    if let Some(cert) = certs.iter().find(|cert| same_fingerprint(cert, session)) {
+        return Ok(cert);
+    }
+    let mut named = certs
+        .iter()
+        .filter(|cert| same_name(&cert.name, &session.client_name));
+    match (named.next(), named.next()) {
+        (Some(cert), None) => Ok(cert),
+        (None, _) => Err(format!(
+            "No paired client matches '{}'",
+            session.client_name
+        )),
+        (Some(_), Some(_)) => Err(format!(
+            "Several paired clients are named '{}'",
+            session.client_name
+        )),
+    }
+}
+
+fn build_clients(
+    certs: Vec<NamedCert>,
+    history: &[SessionRecord],
+    sessions: &[TrayClientSession],
+) -> Vec<PairedClient> {
+    certs
+        .into_iter()
+        .map(|cert| {
+            let records = history
+                .iter()
+                .filter(|record| same_name(&cert.name, &record.client_name));
+            let current: Vec<&TrayClientSession> = sessions
+                .iter()
+                .filter(|session| matches_session(&cert, session))
+                .collect();
+            PairedClient {
+                last_seen: records.clone().map(|record| record.ended_at).max(),
+                sessions: records.count(),
+                connected: !current.is_empty(),
+                highly_suspected_unknown_client: current
+                    .iter()
+                    .any(|session| session.highly_suspected_unknown_client),
+                uuid: cert.uuid,
+                name: cert.name,
+            }
+        })
+        .collect()
+}
+
+/// Clients not seen for `stale_days`. Connected clients are never stale, and
+/// neither are clients without a session recorded under their current name:
+/// the history only knows names, so a renamed client or one that last
+/// connected before the history existed would look unused.
+fn stale_clients(
+    clients: Vec<PairedClient>,
+    now: DateTime<Utc>,
+    stale_days: u32,
+) -> Vec<PairedClient> {
+    let cutoff = now - Duration::days(i64::from(stale_days));
+    clients
+        .into_iter()
+        .filter(|client| !client.connected)
+        .filter(|client| client.last_seen.is_some_and(|last_seen| last_seen < cutoff))
+        .collect()
+}
+
+async fn fetch_certs(client: &CoreClient) -> Result<Vec<NamedCert>, CoreApiError> {
+    let list: ClientList = client
+        .get("api/clients/list")
+        .envelope(Envelope::Status)
+        .send()
+        .await?;
+    Ok(list.named_certs)
+}
+
+async fn unpair(client: &CoreClient, uuid: &str) -> Result<(), CoreApiError> {
+    client
+        .post("api/clients/unpair")
+        .json(&serde_json::json!({ "uuid": uuid }))?
+        .envelope(Envelope::Status)
+        .send_value()
+        .await?;
+    Ok(())
+}
+
+async fn paired_clients(client: &CoreClient) -> Result<Vec<PairedClient>, CoreApiError> {
+    let certs = fetch_certs(client).await?;
+    let history = tokio::task::spawn_blocking(crate::session_history::load_history)
+        .await
+        .unwrap_or_default();
+    Ok(build_clients(
+        certs,
+        &history,
+        &crate::tray::current_client_sessions(),
+    ))
+}
+
+#[tauri::command]
+pub async fn list_paired_clients() -> Result<Vec<PairedClient>, String> {
+    Ok(paired_clients(&CoreClient::local().await?).await?)
+}
+
+#[tauri::command]
+pub async fn rename_paired_client(uuid: String, name: String) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("客户端名称不能为空".to_string());
+    }
+    CoreClient::local()
+        .await?
+        .post("api/clients/rename")
+        .json(&serde_json::json!({ "uuid": uuid, "name": name }))?
+        .envelope(Envelope::Status)
+        .send_value()
+        .await?;
+    info!("✏️ 已重命名配对客户端 {} -> {}", uuid, name);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unpair_client(uuid: String) -> Result<(), String> {
+    unpair(&CoreClient::local().await?, &uuid).await?;
+    info!("🔓 已取消配对客户端 {}", uuid);
+    Ok(())
+}
+
+/// Unpair the client behind a tray session and return its paired name. The
+/// session only carries a fingerprint and a name, so the certificate is looked
+/// up in the Core's client list first, by UUID and then by name.
+pub async fn unpair_session_client(session: &TrayClientSession) -> Result<String, String> {
+    let client = CoreClient::local().await?;
+    let certs = fetch_certs(&client).await?;
+    let cert = cert_for_session(&certs, session)?;
+    unpair(&client, &cert.uuid).await?;
+    info!("🔓 已取消配对客户端 {} ({})", cert.name, cert.uuid);
+    Ok(cert.name.clone())
+}
+
+/// Unpair every client not seen for `stale_days`. With `dry_run` nothing is
+/// unpaired and `unpaired` lists the clients that would be.
+#[tauri::command]
+pub async fn unpair_stale_clients(
+    stale_days: u32,
+    dry_run: bool,
+) -> Result<StaleUnpairResult, String> {
+    if stale_days == 0 {
+        return Err("闲置天数至少为 1 天".to_string());
+    }
+    let client = CoreClient::local().await?;
+    let stale = stale_clients(paired_clients(&client).await?, Utc::now(), stale_days);
+    if dry_run {
+        return Ok(StaleUnpairResult {
+            unpaired: stale,
+            failed: Vec::new(),
+        });
+    }
+
+    let mut result = StaleUnpairResult::default();
+    for paired in stale {
+        match unpair(&client, &paired.uuid).await {
+            Ok(()) => result.unpaired.push(paired),
+            Err(e) => {
+                warn!("⚠️ 取消配对 {} 失败: {}", paired.name, e);
+                result.failed.push(UnpairFailure {
+                    uuid: paired.uuid,
+                    name: paired.name,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    info!(
+        "🧹 已取消配对 {} 个超过 {} 天未连接的客户端",
+        result.unpaired.len(),
+        stale_days
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cert(name: &str, uuid: &str) -> NamedCert {
+        NamedCert {
+            name: name.to_string(),
+            uuid: uuid.to_string(),
+        }
+    }
+
+    fn record(client: &str, ended_at: DateTime<Utc>) -> SessionRecord {
+        SessionRecord {
+            client_name: client.to_string(),
+            ended_at,
+            ..SessionRecord::default()
+        }
+    }
+
+    fn session(name: &str, suspicious: bool) -> TrayClientSession {
+        TrayClientSession {
+            id: 1,
+            client_name: name.to_string(),
+            highly_suspected_unknown_client: suspicious,
+            ..TrayClientSession::default()
+        }
+    }
+
+    #[test]
+    fn clients_are_joined_with_history_and_tray_sessions() {
+        let now = Utc::now();
+        let history = vec![
+            record("Living Room TV", now - Duration::days(3)),
+            record("living room tv", now - Duration::days(1)),
+            record("Deck", now - Duration::days(2)),
+        ];
+        let clients = build_clients(
+            vec![cert("Living Room TV", "tv"), cert("Laptop", "laptop")],
+            &history,
+            &[session("Laptop", true)],
+        );
+
+        assert_eq!(clients[0].sessions, 2);
+        assert_eq!(clients[0].last_seen, Some(now - Duration::days(1)));
+        assert!(!clients[0].connected);
+        assert_eq!(clients[1].last_seen, None);
+        assert!(clients[1].connected);
+        assert!(clients[1].highly_suspected_unknown_client);
+    }
+
+    #[test]
+    fn sessions_resolve_to_their_certificate() {
+        let certs = vec![
+            cert("Deck", "uuid-deck"),
+            cert("TV", "uuid-tv-1"),
+            cert("tv", "uuid-tv-2"),
+        ];
+        let mut deck = session("Deck", true);
+        deck.client_fingerprint = "fp-deck".to_string();
+        assert_eq!(cert_for_session(&certs, &deck).unwrap().uuid, "uuid-deck");
+
+        let mut tv = session("TV", true);
+        assert!(cert_for_session(&certs, &tv).is_err());
+        tv.client_fingerprint = "UUID-TV-2".to_string();
+        assert_eq!(cert_for_session(&certs, &tv).unwrap().uuid, "uuid-tv-2");
+        assert!(matches_session(&certs[2], &tv));
+        assert!(!matches_session(&certs[0], &tv));
+
+        assert!(cert_for_session(&certs, &session("Laptop", true)).is_err());
+    }
+
+    #[test]
+    fn stale_clients_skip_connected_and_unmatched_clients() {
+        let now = Utc::now();
+        let client = |uuid: &str, days: Option<i64>, connected: bool| PairedClient {
+            uuid: uuid.to_string(),
+            last_seen: days.map(|days| now - Duration::days(days)),
+            connected,
+            ..PairedClient::default()
+        };
+        let clients = vec![
+            client("old", Some(40), false),
+            client("recent", Some(5), false),
+            client("never", None, false),
+            client("old-but-connected", Some(40), true),
+        ];
+
+        let uuids = |stale: Vec<PairedClient>| {
+            stale
+                .into_iter()
+                .map(|client| client.uuid)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(uuids(stale_clients(clients, now, 30)), ["old"]);
+    }
+}
@@ -14,16 +14,38 @@ use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tower_http::compression::{CompressionLayer, Predicate, predicate::DefaultPredicate};
 use tower_http::cors::CorsLayer;
 
+mod external_policy;
+
+use external_policy::{
+    ExternalProxyPolicy, ExternalProxyRejection, PolicyDecision, RateLimiter, RejectionLog,
+};
+
 /// 全局 Sunshine 目标 URL（动态配置）
 static SUNSHINE_TARGET: Lazy<Arc<RwLock<String>>> =
     Lazy::new(|| Arc::new(RwLock::new(String::from("https://localhost:47990"))));
 
+/// 当前目标主机的 Basic 认证头（来自主机注册表，本机目标为空）
+static SUNSHINE_TARGET_AUTHORIZATION: Lazy<RwLock<Option<String>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// 当前目标是否为其他机器上的 Core，其页面只能在隔离源中运行
+static SUNSHINE_TARGET_ISOLATED: AtomicBool = AtomicBool::new(false);
+
 #[cfg(test)]
 static TEST_REFRESH_TARGET: Lazy<std::sync::Mutex<Option<String>>> =
     Lazy::new(|| std::sync::Mutex::new(None));
 
+/// `/_proxy/` 外部代理的域名白名单与限流状态
+static EXTERNAL_PROXY_POLICY: Lazy<RwLock<ExternalProxyPolicy>> =
+    Lazy::new(|| RwLock::new(external_policy::load_policy()));
+static EXTERNAL_PROXY_LIMITER: Lazy<std::sync::Mutex<RateLimiter>> =
+    Lazy::new(|| std::sync::Mutex::new(RateLimiter::default()));
+static EXTERNAL_PROXY_REJECTIONS: Lazy<std::sync::Mutex<RejectionLog>> =
+    Lazy::new(|| std::sync::Mutex::new(RejectionLog::default()));
+
 /// 快速失败机制：记录 Sunshine 是否可用
 static SUNSHINE_AVAILABLE: AtomicBool = AtomicBool::new(true);
 static LAST_CHECK_TIME: AtomicU64 = AtomicU64::new(0);
@@ -41,6 +63,9 @@ static PROXY_REQUEST_PERMITS: Lazy<Arc<Semaphore>> =
 static PROXY_PENDING_PERMITS: Lazy<Arc<Semaphore>> =
     Lazy::new(|| Arc::new(Semaphore::new(MAX_PENDING_PROXY_REQUESTS)));
 static PROXY_HEALTH_TOKEN: Lazy<String> = Lazy::new(|| uuid::Uuid::new_v4().to_string());
+/// Per-process credential presented by GUI webviews. Other local processes
+/// cannot read it, so they cannot drive the Core admin API through the proxy.
+static PROXY_ACCESS_TOKEN: Lazy<String> = Lazy::new(|| uuid::Uuid::new_v4().simple().to_string());
 
 /// 快速失败冷却时间（秒）- 在此时间内不重试，超过后会重新尝试连接
 const FAST_FAIL_COOLDOWN_SECS: u64 = 3;
@@ -54,7 +79,7 @@ const AI_PROXY_TIMEOUT_SECS: u64 = 120;
 /// 本机 Sunshine 连接应快速建立；连接超时不跟随 AI 响应超时放大。
 const PROXY_CONNECT_TIMEOUT_MS: u64 = 500;
 
-/// Bound memory and active work exposed by the unauthenticated loopback proxy.
+/// Bound memory and active work exposed by the loopback proxy.
 const MAX_PROXY_REQUEST_BODY_BYTES: usize = 16 * 1024 * 1024;
 const MAX_PROXY_RESPONSE_BODY_BYTES: usize = 64 * 1024 * 1024;
 const MAX_INJECTABLE_HTML_BYTES: usize = 8 * 1024 * 1024;
@@ -65,6 +90,30 @@ const PROXY_REQUEST_BODY_TIMEOUT_SECS: u64 = 15;
 const PROXY_STREAM_IDLE_TIMEOUT_SECS: u64 = 60;
 const PROXY_STREAM_CHUNK_BYTES: usize = 64 * 1024;
 const PROXY_HEALTH_PATH: &str = "/__foundation_proxy_health";
+const PROXY_ACCESS_HEADER: &str = "x-sunshine-gui-token";
+const PROXY_ACCESS_COOKIE: &str = "sunshine_gui_token";
+/// Only used for the first iframe navigation, which cannot carry a header.
+const PROXY_ACCESS_QUERY: &str = "__gui_token";
+/// Remote hosts are framed from this name instead of `127.0.0.1`. WebView2
+/// resolves `*.localhost` to loopback, but the IPC capability only lists
+/// `localhost` and `127.0.0.1`, so pages of another machine get no Tauri IPC.
+const ISOLATED_FRAME_HOST: &str = "remote.localhost";
+/// Fetch destinations whose response runs as a page or worker at the
+/// requested origin.
+const EXECUTING_FETCH_DESTS: &[&str] = &[
+    "document",
+    "iframe",
+    "frame",
+    "embed",
+    "object",
+    "worker",
+    "sharedworker",
+    "serviceworker",
+];
+
+/// Paths that must answer without the access token. The health endpoint has
+/// its own challenge and is used before the WebView has a session cookie.
+const PROXY_ACCESS_EXEMPT_PATHS: &[&str] = &[PROXY_HEALTH_PATH];
 
 #[derive(serde::Serialize)]
 pub struct ProxyHealthCheck {
@@ -88,6 +137,11 @@ const UNBOUNDED_STREAM_RULES: &[UnboundedStreamRule] = &[UnboundedStreamRule {
     bounded_when_header_present: Some("x-log-offset"),
 }];
 
+/// Marks proxied responses that must reach the WebView chunk by chunk.
+/// Encoders buffer output, which would stall live log tails.
+#[derive(Clone, Copy)]
+struct UncompressedStream;
+
 /// 代理服务器端口范围
 const PROXY_PORT_START: u16 = 48081;
 const PROXY_PORT_END: u16 = 48090;
@@ -108,6 +162,21 @@ pub fn get_proxy_url_command() -> String {
     get_proxy_url()
 }
 
+/// 主窗口加载 WebUI 所用的源：本机 Core 走代理地址，其他主机走隔离源
+pub fn get_frame_url() -> String {
+    if SUNSHINE_TARGET_ISOLATED.load(Ordering::Acquire) {
+        format!("http://{}:{}", ISOLATED_FRAME_HOST, get_proxy_port())
+    } else {
+        get_proxy_url()
+    }
+}
+
+/// Tauri command: 获取主窗口加载 WebUI 的 URL
+#[tauri::command]
+pub fn get_frame_url_command() -> String {
+    get_frame_url()
+}
+
 /// Return a per-process challenge used by the WebView to verify loopback routing.
 #[tauri::command]
 pub fn get_proxy_health_check() -> ProxyHealthCheck {
@@ -146,6 +215,219 @@ async fn proxy_health_handler(request: Request) -> Response {
         .into_response()
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum ProxyAccess {
+    Granted,
+    /// A navigation carried the token in its query. Answer with a redirect
+    /// that stores the cookie and drops the token from the visible URL.
+    GrantedByQuery {
+        location: String,
+    },
+    Denied,
+}
+
+fn tokens_match(candidate: &str, expected: &str) -> bool {
+    candidate.len() == expected.len()
+        && candidate
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn cookie_access_token(headers: &axum::http::HeaderMap) -> Option<&str> {
+    headers
+        .get_all(axum::http::header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .find_map(|pair| {
+            pair.trim()
+                .strip_prefix(PROXY_ACCESS_COOKIE)
+                .and_then(|rest| rest.strip_prefix('='))
+        })
+}
+
+/// The client's cookies without the GUI access token, which must never reach
+/// the Core. `None` when nothing is left to forward.
+fn forwarded_cookie(headers: &axum::http::HeaderMap) -> Option<String> {
+    let cookies: Vec<&str> = headers
+        .get_all(axum::http::header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .map(str::trim)
+        .filter(|pair| {
+            !pair.is_empty()
+                && pair
+                    .strip_prefix(PROXY_ACCESS_COOKIE)
+                    .is_none_or(|rest| !rest.starts_with('='))
+        })
+        .collect();
+    (!cookies.is_empty()).then(|| cookies.join("; "))
+}
+
+fn check_proxy_access(
+    method: &axum::http::Method,
+    uri: &axum::http::Uri,
+    headers: &axum::http::HeaderMap,
+) -> ProxyAccess {
+    let expected = PROXY_ACCESS_TOKEN.as_str();
+    let header_token = headers
+        .get(PROXY_ACCESS_HEADER)
+        .and_then(|value| value.to_str().ok());
+    if header_token.is_some_and(|token| tokens_match(token, expected))
+        || cookie_access_token(headers).is_some_and(|token| tokens_match(token, expected))
+    {
+        return ProxyAccess::Granted;
+    }
+
+    if !matches!(*method, axum::http::Method::GET | axum::http::Method::HEAD) {
+        return ProxyAccess::Denied;
+    }
+
+    let Some(query) = uri.query() else {
+        return ProxyAccess::Denied;
+    };
+    let mut valid_token = false;
+    let remaining: Vec<(String, String)> = url::form_urlencoded::parse(query.as_bytes())
+        .filter(|(key, value)| {
+            if key == PROXY_ACCESS_QUERY {
+                valid_token |= tokens_match(&value, expected);
+                false
+            } else {
+                true
+            }
+        })
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if !valid_token {
+        return ProxyAccess::Denied;
+    }
+
+    let mut location = uri.path().to_string();
+    if !remaining.is_empty() {
+        location.push('?');
+        location.push_str(
+            &url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(remaining)
+                .finish(),
+        );
+    }
+    ProxyAccess::GrantedByQuery { location }
+}
+
+fn proxy_access_cookie() -> String {
+    // The panel embeds the proxy in a cross-site iframe, so the cookie needs
+    // SameSite=None. Chromium accepts Secure cookies from loopback over HTTP.
+    format!(
+        "{}={}; Path=/; HttpOnly; Secure; SameSite=None",
+        PROXY_ACCESS_COOKIE,
+        PROXY_ACCESS_TOKEN.as_str()
+    )
+}
+
+async fn proxy_access_middleware(req: Request, next: Next) -> Response {
+    let path = req.uri().path();
+    if req.method() == axum::http::Method::OPTIONS || PROXY_ACCESS_EXEMPT_PATHS.contains(&path) {
+        return next.run(req).await;
+    }
+
+    match check_proxy_access(req.method(), req.uri(), req.headers()) {
+        ProxyAccess::Granted => next.run(req).await,
+        ProxyAccess::GrantedByQuery { location } => Response::builder()
+            .status(axum::http::StatusCode::SEE_OTHER)
+            .header(axum::http::header::LOCATION, location)
+            .header(axum::http::header::SET_COOKIE, proxy_access_cookie())
+            .header(axum::http::header::CACHE_CONTROL, "no-store")
+            .body(Body::empty())
+            .unwrap_or_else(|_| axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+        ProxyAccess::Denied => {
+            warn!(
+                "Rejected loopback proxy request without GUI token: {} {}",
+                req.method(),
+                path
+            );
+            proxy_limit_response(
+                axum::http::StatusCode::FORBIDDEN,
+                "Sunshine GUI proxy access token required",
+            )
+        }
+    }
+}
+
+/// Attach the access token to an in-process request aimed at the local proxy.
+/// Requests to any other origin are returned unchanged so the token never leaks.
+pub fn with_proxy_access(builder: reqwest::RequestBuilder, url: &str) -> reqwest::RequestBuilder {
+    let is_local_proxy = url::Url::parse(url).ok().is_some_and(|url| {
+        url.scheme() == "http"
+            && matches!(url.host_str(), Some("127.0.0.1" | "localhost"))
+            && url.port() == Some(get_proxy_port())
+    });
+    if is_local_proxy {
+        builder.header(PROXY_ACCESS_HEADER, PROXY_ACCESS_TOKEN.as_str())
+    } else {
+        builder
+    }
+}
+
+/// Initialization script for GUI webviews. It tags fetch and XHR calls to the
+/// loopback proxy with the access header and exposes the token for the first
+/// iframe navigation.
+pub fn webview_access_script() -> String {
+    format!(
+        r#"
+(function() {{
+    var access = Object.freeze({{ header: '{header}', query: '{query}', token: '{token}' }});
+    Object.defineProperty(window, '__SUNSHINE_PROXY_ACCESS__', {{ value: access }});
+
+    function isProxyUrl(input) {{
+        try {{
+            var url = new URL(input, window.location.href);
+            var port = Number(url.port);
+            return url.protocol === 'http:'
+                && (url.hostname === '127.0.0.1' || url.hostname === 'localhost'
+                    || url.hostname === '{isolated_host}')
+                && port >= {port_start} && port <= {port_end};
+        }} catch (e) {{
+            return false;
+        }}
+    }}
+
+    var originalFetch = window.fetch;
+    window.fetch = function(input, init) {{
+        var url = input instanceof Request ? input.url : String(input);
+        if (!isProxyUrl(url)) {{
+            return originalFetch.call(this, input, init);
+        }}
+        var headers = new Headers((init && init.headers) || (input instanceof Request ? input.headers : undefined));
+        headers.set(access.header, access.token);
+        return originalFetch.call(this, input, Object.assign({{}}, init, {{ headers: headers }}));
+    }};
+
+    var originalOpen = XMLHttpRequest.prototype.open;
+    var originalSend = XMLHttpRequest.prototype.send;
+    XMLHttpRequest.prototype.open = function(method, url) {{
+        this.__sunshineProxyRequest = isProxyUrl(url);
+        return originalOpen.apply(this, arguments);
+    }};
+    XMLHttpRequest.prototype.send = function() {{
+        if (this.__sunshineProxyRequest) {{
+            this.setRequestHeader(access.header, access.token);
+        }}
+        return originalSend.apply(this, arguments);
+    }};
+}})();
+"#,
+        header = PROXY_ACCESS_HEADER,
+        query = PROXY_ACCESS_QUERY,
+        token = PROXY_ACCESS_TOKEN.as_str(),
+        isolated_host = ISOLATED_FRAME_HOST,
+        port_start = PROXY_PORT_START,
+        port_end = PROXY_PORT_END,
+    )
+}
+
 /// Wait until the local proxy listener is ready before loading the main frame.
 #[tauri::command]
 pub async fn wait_for_proxy_ready() -> Result<String, String> {
@@ -199,14 +481,98 @@ pub fn ensure_started() {
     });
 }
 
-/// 设置 Sunshine 目标 URL
+/// 设置 Sunshine 目标 URL；非回环地址的目标自动进入隔离源
 pub fn set_sunshine_target(url: String) {
+    SUNSHINE_TARGET_ISOLATED.store(!is_loopback_url(&url), Ordering::Release);
     if let Ok(mut target) = SUNSHINE_TARGET.write() {
         info!("🎯 代理目标已更新: {}", url);
         *target = url;
     }
 }
 
+/// 将当前目标视为其他机器上的 Core，例如经本机端口转发访问的注册主机
+pub fn isolate_sunshine_target() {
+    SUNSHINE_TARGET_ISOLATED.store(true, Ordering::Release);
+}
+
+fn is_loopback_url(url: &str) -> bool {
+    match url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host().map(|host| host.to_owned()))
+    {
+        Some(url::Host::Domain(name)) => name.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+/// Whether a request would run a page or worker on the IPC-enabled loopback
+/// origin rather than on [`ISOLATED_FRAME_HOST`].
+fn executes_on_ipc_origin(headers: &axum::http::HeaderMap) -> bool {
+    let executes = headers
+        .get("sec-fetch-dest")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|dest| EXECUTING_FETCH_DESTS.contains(&dest));
+    let host = headers
+        .get(axum::http::header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .map(|host| host.rsplit_once(':').map_or(host, |(name, _)| name))
+        .unwrap_or_default();
+    executes && !host.eq_ignore_ascii_case(ISOLATED_FRAME_HOST)
+}
+
+/// Pages of a remote host only load on the isolated origin, even when a stale
+/// frame URL or the page itself navigates to the loopback address.
+async fn frame_isolation_middleware(req: Request, next: Next) -> Response {
+    if SUNSHINE_TARGET_ISOLATED.load(Ordering::Acquire) && executes_on_ipc_origin(req.headers()) {
+        warn!(
+            "Refused to load a remote host page on the loopback origin: {}",
+            req.uri().path()
+        );
+        return proxy_limit_response(
+            axum::http::StatusCode::FORBIDDEN,
+            "Remote host pages are only served from the isolated origin",
+        );
+    }
+    next.run(req).await
+}
+
+/// 设置代理转发到 Sunshine 时使用的凭据；`None` 表示交给 WebUI 自行登录
+pub fn set_sunshine_target_credentials(credentials: Option<(&str, &str)>) {
+    use base64::Engine as _;
+
+    let authorization = credentials.map(|(username, password)| {
+        let encoded =
+            base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"));
+        format!("Basic {encoded}")
+    });
+    if let Ok(mut current) = SUNSHINE_TARGET_AUTHORIZATION.write() {
+        *current = authorization;
+    }
+}
+
+/// 当前目标主机的 Basic 认证头，供直接访问目标 Core 的客户端复用
+pub fn sunshine_target_authorization() -> Option<String> {
+    SUNSHINE_TARGET_AUTHORIZATION
+        .read()
+        .ok()
+        .and_then(|value| value.clone())
+}
+
+/// 页面未携带认证头时补上目标主机的凭据
+fn with_target_authorization(mut headers: axum::http::HeaderMap) -> axum::http::HeaderMap {
+    if headers.contains_key(axum::http::header::AUTHORIZATION) {
+        return headers;
+    }
+    if let Some(value) = sunshine_target_authorization()
+        .and_then(|value| axum::http::HeaderValue::from_str(&value).ok())
+    {
+        headers.insert(axum::http::header::AUTHORIZATION, value);
+    }
+    headers
+}
+
 /// Dynamic Sunshine target helpers.
 fn get_sunshine_target() -> String {
     SUNSHINE_TARGET
@@ -383,6 +749,22 @@ async fn acquire_proxy_permit_with_wait(
         .ok()
 }
 
+fn is_compressible_proxy_response(
+    _status: axum::http::StatusCode,
+    _version: axum::http::Version,
+    _headers: &axum::http::HeaderMap,
+    extensions: &axum::http::Extensions,
+) -> bool {
+    extensions.get::<UncompressedStream>().is_none()
+}
+
+/// Negotiate gzip/brotli with the WebView from its Accept-Encoding header.
+/// The default predicate already skips SSE, images and tiny bodies.
+fn proxy_compression_layer() -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new()
+        .compress_when(DefaultPredicate::new().and(is_compressible_proxy_response))
+}
+
 /// 启动本地代理服务器
 pub async fn start_proxy_server() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     PROXY_READY.store(false, Ordering::Release);
@@ -390,9 +772,12 @@ pub async fn start_proxy_server() -> Result<(), Box<dyn std::error::Error + Send
     let app = Router::new()
         .route(PROXY_HEALTH_PATH, get(proxy_health_handler))
         .fallback(proxy_handler)
+        .layer(proxy_compression_layer())
         .layer(CorsLayer::permissive())
         .layer(axum::middleware::from_fn(pna_middleware))
-        .layer(axum::middleware::from_fn(proxy_concurrency_middleware));
+        .layer(axum::middleware::from_fn(proxy_concurrency_middleware))
+        .layer(axum::middleware::from_fn(proxy_access_middleware))
+        .layer(axum::middleware::from_fn(frame_isolation_middleware));
 
     // 尝试在端口范围内找到可用端口
     let mut listener = None;
@@ -858,6 +1243,8 @@ async fn proxy_handler(req: Request) -> Response {
         return handle_steam_api(&path, &query, &method, &headers, req).await;
     }
 
+    let headers = with_target_authorization(headers);
+
     // 判断是否是 API 请求
     let is_api = is_api_request(&path);
     let is_ai_api = is_ai_api_request(&path);
@@ -1098,39 +1485,46 @@ async fn handle_external_proxy(
         }
     };
 
-    // 安全检查：只允许 HTTPS 请求到白名单域名
-    let allowed_domains = [
-        "github.io",
-        "raw.githubusercontent.com",
-        "github.com",
-        "api.github.com",
-    ];
-
-    let is_allowed = url::Url::parse(&target_url)
+    // 安全检查：只允许 HTTPS 请求到白名单域名，并按域名限流
+    let host = url::Url::parse(&target_url)
         .ok()
-        .map(|u| {
-            u.scheme() == "https"
-                && u.host_str()
-                    .map(|host| {
-                        allowed_domains
-                            .iter()
-                            .any(|d| host == *d || host.ends_with(&format!(".{}", d)))
-                    })
-                    .unwrap_or(false)
-        })
-        .unwrap_or(false);
+        .filter(|u| u.scheme() == "https")
+        .and_then(|u| u.host_str().map(str::to_string));
+    let decision = match host.as_deref() {
+        Some(host) => check_external_proxy_policy(host),
+        None => PolicyDecision::HostNotAllowed,
+    };
 
-    if !is_allowed {
-        warn!("⚠️ 外部代理请求被拒绝（域名不在白名单）: {}", target_url);
-        return (
-            axum::http::StatusCode::FORBIDDEN,
-            [(
-                axum::http::header::CONTENT_TYPE,
-                "application/json; charset=utf-8",
-            )],
-            r#"{"success":false,"error":"Domain not allowed"}"#,
-        )
-            .into_response();
+    match decision {
+        PolicyDecision::Allowed => {}
+        PolicyDecision::HostNotAllowed => {
+            record_external_proxy_rejection(&target_url, "host not allowed", headers);
+            return (
+                axum::http::StatusCode::FORBIDDEN,
+                [(
+                    axum::http::header::CONTENT_TYPE,
+                    "application/json; charset=utf-8",
+                )],
+                r#"{"success":false,"error":"Domain not allowed"}"#,
+            )
+                .into_response();
+        }
+        PolicyDecision::RateLimited { retry_after } => {
+            record_external_proxy_rejection(&target_url, "rate limited", headers);
+            let retry_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            return (
+                axum::http::StatusCode::TOO_MANY_REQUESTS,
+                [
+                    (
+                        axum::http::header::CONTENT_TYPE,
+                        "application/json; charset=utf-8".to_string(),
+                    ),
+                    (axum::http::header::RETRY_AFTER, retry_secs.to_string()),
+                ],
+                r#"{"success":false,"error":"Rate limit exceeded"}"#,
+            )
+                .into_response();
+        }
     }
 
     debug!("🌐 外部代理请求: {}", target_url);
@@ -1163,6 +1557,87 @@ async fn handle_external_proxy(
     }
 }
 
+fn check_external_proxy_policy(host: &str) -> PolicyDecision {
+    let policy = EXTERNAL_PROXY_POLICY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    let mut limiter = EXTERNAL_PROXY_LIMITER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    external_policy::evaluate(&policy, &mut limiter, host, std::time::Instant::now())
+}
+
+fn record_external_proxy_rejection(
+    target_url: &str,
+    reason: &str,
+    headers: &axum::http::HeaderMap,
+) {
+    let page = [axum::http::header::REFERER, axum::http::header::ORIGIN]
+        .iter()
+        .find_map(|name| headers.get(name).and_then(|value| value.to_str().ok()))
+        .map(str::to_string);
+    warn!(
+        "⚠️ 外部代理请求被拒绝（{}）: {} 来自页面 {}",
+        reason,
+        target_url,
+        page.as_deref().unwrap_or("未知")
+    );
+    EXTERNAL_PROXY_REJECTIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(ExternalProxyRejection {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            url: target_url.to_string(),
+            reason: reason.to_string(),
+            page,
+        });
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalProxyPolicyStatus {
+    policy: ExternalProxyPolicy,
+    recent_rejections: Vec<ExternalProxyRejection>,
+}
+
+fn external_proxy_policy_status() -> ExternalProxyPolicyStatus {
+    ExternalProxyPolicyStatus {
+        policy: EXTERNAL_PROXY_POLICY
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone(),
+        recent_rejections: EXTERNAL_PROXY_REJECTIONS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .recent(),
+    }
+}
+
+/// Current `/_proxy/` allowlist, rate limits and recent rejections.
+#[tauri::command]
+pub fn get_external_proxy_policy() -> ExternalProxyPolicyStatus {
+    external_proxy_policy_status()
+}
+
+/// Validate, persist and apply a new `/_proxy/` policy.
+#[tauri::command]
+pub fn save_external_proxy_policy(
+    policy: ExternalProxyPolicy,
+) -> Result<ExternalProxyPolicyStatus, String> {
+    let policy = policy.validated()?;
+    external_policy::save_policy(&policy)?;
+    EXTERNAL_PROXY_LIMITER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain_hosts(&policy);
+    *EXTERNAL_PROXY_POLICY
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = policy;
+    info!("外部代理白名单已更新");
+    Ok(external_proxy_policy_status())
+}
+
 /// 创建共享的 HTTP 客户端（连接复用，性能优化）
 fn get_http_client() -> &'static reqwest::Client {
     use std::sync::OnceLock;
@@ -1188,6 +1663,9 @@ fn get_streaming_http_client() -> &'static reqwest::Client {
             .read_timeout(std::time::Duration::from_secs(
                 PROXY_STREAM_IDLE_TIMEOUT_SECS,
             ))
+            // 流式响应不经代理重新压缩，上游编码原样交给 WebView 解码
+            .no_gzip()
+            .no_deflate()
             .build()
             .expect("Failed to create streaming HTTP client")
     })
@@ -1210,6 +1688,7 @@ async fn send_request(
     method: &axum::http::Method,
     headers: &axum::http::HeaderMap,
     body: &Bytes,
+    forward_accept_encoding: bool,
 ) -> Result<reqwest::Response, reqwest::Error> {
     let mut req_builder = match method.as_str() {
         "GET" => client.get(url),
@@ -1221,18 +1700,32 @@ async fn send_request(
         _ => client.get(url),
     };
 
-    // 复制请求头（排除特殊头部）
+    // 复制请求头（排除特殊头部）。代理会重新压缩的响应由 reqwest 自行协商
+    // Accept-Encoding，保证上游响应总能被解码；不重新压缩的流式响应则转发
+    // 客户端自己的 Accept-Encoding，编码原样透传。
+    // GUI 访问令牌只用于本地代理，不转发给 Core。
     for (key, value) in headers.iter() {
         let key_str = key.as_str();
+        if key_str == "accept-encoding" && !forward_accept_encoding {
+            continue;
+        }
         if !matches!(
             key_str,
-            "host" | "connection" | "content-length" | "transfer-encoding"
+            "host"
+                | "connection"
+                | "content-length"
+                | "transfer-encoding"
+                | "cookie"
+                | PROXY_ACCESS_HEADER
         ) {
             if let Ok(value_str) = value.to_str() {
                 req_builder = req_builder.header(key_str, value_str);
             }
         }
     }
+    if let Some(cookie) = forwarded_cookie(headers) {
+        req_builder = req_builder.header(axum::http::header::COOKIE, cookie);
+    }
 
     if !body.is_empty() {
         req_builder = req_builder.body(body.clone());
@@ -1250,7 +1743,9 @@ async fn fetch_and_proxy(
     is_ai_api: bool,
 ) -> Result<Response, Box<dyn std::error::Error + Send + Sync>> {
     let response_body_limit = proxy_response_body_limit(url, method, headers);
-    let client = if response_body_limit.is_none() {
+    // 不限长度的流式响应不经代理压缩层，编码在客户端与上游之间直接协商
+    let passthrough_encoding = response_body_limit.is_none();
+    let client = if passthrough_encoding {
         get_streaming_http_client()
     } else if is_ai_api {
         get_ai_http_client()
@@ -1259,15 +1754,24 @@ async fn fetch_and_proxy(
     };
 
     // 尝试请求，HTTPS 失败时降级到 HTTP（仅限非连接错误）
-    let response = match send_request(client, url, method, headers, body).await {
-        Ok(resp) => resp,
-        Err(e) if url.starts_with("https://") && !is_connection_error(&e.to_string()) => {
-            let http_url = url.replace("https://", "http://");
-            warn!("⚠️  HTTPS 连接失败，尝试 HTTP: {}", http_url);
-            send_request(client, &http_url, method, headers, body).await?
-        }
-        Err(e) => return Err(e.into()),
-    };
+    let response =
+        match send_request(client, url, method, headers, body, passthrough_encoding).await {
+            Ok(resp) => resp,
+            Err(e) if url.starts_with("https://") && !is_connection_error(&e.to_string()) => {
+                let http_url = url.replace("https://", "http://");
+                warn!("⚠️  HTTPS 连接失败，尝试 HTTP: {}", http_url);
+                send_request(
+                    client,
+                    &http_url,
+                    method,
+                    headers,
+                    body,
+                    passthrough_encoding,
+                )
+                .await?
+            }
+            Err(e) => return Err(e.into()),
+        };
 
     let status = response.status();
     let resp_headers = response.headers().clone();
@@ -1282,7 +1786,7 @@ async fn fetch_and_proxy(
         let body_bytes = read_limited_response_bytes(response, MAX_INJECTABLE_HTML_BYTES)
             .await
             .map_err(std::io::Error::other)?;
-        Body::from(inject_if_needed(body_bytes))
+        Body::from(inject_if_needed(body_bytes, &request_path(url)))
     } else {
         streamed_response_body(response, response_body_limit).map_err(std::io::Error::other)?
     };
@@ -1292,11 +1796,11 @@ async fn fetch_and_proxy(
 
     for (key, value) in resp_headers.iter() {
         let key_str = key.as_str().to_lowercase();
-        // 排除内容长度、传输编码、内容编码，以及需要注入时排除缓存相关头部
-        if matches!(
-            key_str.as_str(),
-            "content-length" | "transfer-encoding" | "content-encoding"
-        ) {
+        // 排除内容长度、传输编码、已解码响应的内容编码，以及需要注入时排除缓存相关头部
+        if matches!(key_str.as_str(), "content-length" | "transfer-encoding") {
+            continue;
+        }
+        if key_str == "content-encoding" && !passthrough_encoding {
             continue;
         }
         if needs_injection
@@ -1317,6 +1821,10 @@ async fn fetch_and_proxy(
         res = res.header("Expires", "0");
     }
 
+    if passthrough_encoding {
+        res = res.extension(UncompressedStream);
+    }
+
     Ok(res.body(final_body)?)
 }
 
@@ -1334,22 +1842,28 @@ fn should_inject_script(url: &str, content_type: &str) -> bool {
         || url.ends_with(".htm")
 }
 
+fn request_path(url: &str) -> String {
+    url::Url::parse(url)
+        .map(|url| url.path().to_string())
+        .unwrap_or_else(|_| "/".to_string())
+}
+
 /// 如果需要则注入脚本
-fn inject_if_needed(body: Vec<u8>) -> Vec<u8> {
+fn inject_if_needed(body: Vec<u8>, path: &str) -> Vec<u8> {
     match String::from_utf8(body) {
         Ok(html)
             if !html.contains("主题同步脚本已加载")
                 && (html.contains("<html") || html.contains("<!DOCTYPE")) =>
         {
-            inject_theme_script(html).into_bytes()
+            inject_theme_script(html, &crate::webui_plugins::injection_markup(path)).into_bytes()
         }
         Ok(html) => html.into_bytes(),
         Err(e) => e.into_bytes(),
     }
 }
 
-/// 注入主题同步脚本到 HTML
-fn inject_theme_script(html: String) -> String {
+/// 注入主题同步脚本到 HTML，用户插件紧随内置样式和脚本之后
+fn inject_theme_script(html: String, plugin_markup: &str) -> String {
     let Some(pos) = html.find("</head>") else {
         return html;
     };
@@ -1362,7 +1876,11 @@ fn inject_theme_script(html: String) -> String {
         "window.TAURI_PRODUCTION = false;"
     };
 
-    let inject_size = INJECT_STYLES.len() + INJECT_SCRIPT.len() + production_flag.len() + 150;
+    let inject_size = INJECT_STYLES.len()
+        + INJECT_SCRIPT.len()
+        + production_flag.len()
+        + plugin_markup.len()
+        + 150;
     let mut result = String::with_capacity(html.len() + inject_size);
 
     result.push_str(&html[..pos]);
@@ -1373,6 +1891,10 @@ fn inject_theme_script(html: String) -> String {
     result.push_str("\n");
     result.push_str(INJECT_SCRIPT);
     result.push_str("\n</script>\n");
+    if !plugin_markup.is_empty() {
+        result.push_str("<!-- 用户 WebUI 插件 -->\n");
+        result.push_str(plugin_markup);
+    }
     result.push_str(&html[pos..]);
 
     result
@@ -1458,6 +1980,313 @@ mod tests {
         }
     }
 
+    #[test]
+    fn proxy_access_accepts_header_or_cookie_token() {
+        let uri: axum::http::Uri = "/api/apps".parse().unwrap();
+        let mut headers = axum::http::HeaderMap::new();
+        assert_eq!(
+            check_proxy_access(&axum::http::Method::GET, &uri, &headers),
+            ProxyAccess::Denied
+        );
+
+        headers.insert(
+            PROXY_ACCESS_HEADER,
+            axum::http::HeaderValue::from_str(PROXY_ACCESS_TOKEN.as_str()).unwrap(),
+        );
+        assert_eq!(
+            check_proxy_access(&axum::http::Method::POST, &uri, &headers),
+            ProxyAccess::Granted
+        );
+
+        let mut cookie_headers = axum::http::HeaderMap::new();
+        cookie_headers.insert(
+            axum::http::header::COOKIE,
+            axum::http::HeaderValue::from_str(&format!(
+                "theme=dark; {}={}",
+                PROXY_ACCESS_COOKIE,
+                PROXY_ACCESS_TOKEN.as_str()
+            ))
+            .unwrap(),
+        );
+        assert_eq!(
+            check_proxy_access(&axum::http::Method::DELETE, &uri, &cookie_headers),
+            ProxyAccess::Granted
+        );
+
+        let mut wrong_headers = axum::http::HeaderMap::new();
+        wrong_headers.insert(
+            PROXY_ACCESS_HEADER,
+            axum::http::HeaderValue::from_static("not-the-token"),
+        );
+        assert_eq!(
+            check_proxy_access(&axum::http::Method::GET, &uri, &wrong_headers),
+            ProxyAccess::Denied
+        );
+    }
+
+    #[test]
+    fn remote_pages_only_run_on_the_isolated_origin() {
+        assert!(is_loopback_url("https://localhost:47990"));
+        assert!(is_loopback_url("https://127.0.0.1:47990"));
+        assert!(is_loopback_url("https://[::1]:47990"));
+        assert!(!is_loopback_url("https://10.0.0.5:47990"));
+        assert!(!is_loopback_url("https://rack.local:47990"));
+
+        let request = |host: &'static str, dest: &'static str| {
+            let mut headers = axum::http::HeaderMap::new();
+            headers.insert(
+                axum::http::header::HOST,
+                axum::http::HeaderValue::from_static(host),
+            );
+            headers.insert("sec-fetch-dest", axum::http::HeaderValue::from_static(dest));
+            headers
+        };
+        assert!(executes_on_ipc_origin(&request(
+            "127.0.0.1:48081",
+            "iframe"
+        )));
+        assert!(executes_on_ipc_origin(&request(
+            "localhost:48081",
+            "document"
+        )));
+        assert!(executes_on_ipc_origin(&request(
+            "127.0.0.1:48081",
+            "worker"
+        )));
+        assert!(!executes_on_ipc_origin(&request(
+            "127.0.0.1:48081",
+            "empty"
+        )));
+        assert!(!executes_on_ipc_origin(&request(
+            "remote.localhost:48081",
+            "iframe"
+        )));
+    }
+
+    #[test]
+    fn access_token_cookie_is_not_forwarded() {
+        let mut headers = axum::http::HeaderMap::new();
+        assert_eq!(forwarded_cookie(&headers), None);
+
+        headers.insert(
+            axum::http::header::COOKIE,
+            axum::http::HeaderValue::from_str(&format!(
+                "theme=dark; {}=secret; {}_other=1",
+                PROXY_ACCESS_COOKIE, PROXY_ACCESS_COOKIE
+            ))
+            .unwrap(),
+        );
+        assert_eq!(
+            forwarded_cookie(&headers).as_deref(),
+            Some(format!("theme=dark; {}_other=1", PROXY_ACCESS_COOKIE).as_str())
+        );
+
+        headers.insert(
+            axum::http::header::COOKIE,
+            axum::http::HeaderValue::from_str(&format!("{}=secret", PROXY_ACCESS_COOKIE)).unwrap(),
+        );
+        assert_eq!(forwarded_cookie(&headers), None);
+    }
+
+    #[tokio::test]
+    async fn target_credentials_fill_missing_authorization_only() {
+        let _guard = TEST_LOCK.lock().await;
+        set_sunshine_target_credentials(Some(("admin", "secret")));
+        let headers = with_target_authorization(axum::http::HeaderMap::new());
+        let mut explicit = axum::http::HeaderMap::new();
+        explicit.insert(
+            axum::http::header::AUTHORIZATION,
+            axum::http::HeaderValue::from_static("Basic dXNlcjpwYXNz"),
+        );
+        let explicit = with_target_authorization(explicit);
+        set_sunshine_target_credentials(None);
+
+        assert_eq!(
+            headers.get(axum::http::header::AUTHORIZATION).unwrap(),
+            "Basic YWRtaW46c2VjcmV0"
+        );
+        assert_eq!(
+            explicit.get(axum::http::header::AUTHORIZATION).unwrap(),
+            "Basic dXNlcjpwYXNz"
+        );
+        assert!(
+            with_target_authorization(axum::http::HeaderMap::new())
+                .get(axum::http::header::AUTHORIZATION)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn proxy_access_query_token_redirects_without_the_token() {
+        let headers = axum::http::HeaderMap::new();
+        let uri: axum::http::Uri = format!(
+            "/config?{}={}&tab=av",
+            PROXY_ACCESS_QUERY,
+            PROXY_ACCESS_TOKEN.as_str()
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(
+            check_proxy_access(&axum::http::Method::GET, &uri, &headers),
+            ProxyAccess::GrantedByQuery {
+                location: "/config?tab=av".to_string()
+            }
+        );
+        assert_eq!(
+            check_proxy_access(&axum::http::Method::POST, &uri, &headers),
+            ProxyAccess::Denied
+        );
+
+        let wrong: axum::http::Uri = format!("/?{}=wrong", PROXY_ACCESS_QUERY).parse().unwrap();
+        assert_eq!(
+            check_proxy_access(&axum::http::Method::GET, &wrong, &headers),
+            ProxyAccess::Denied
+        );
+    }
+
+    #[tokio::test]
+    async fn proxy_access_middleware_rejects_other_local_callers() {
+        let app = Router::new()
+            .route(PROXY_HEALTH_PATH, get(proxy_health_handler))
+            .fallback(|| async { "proxied" })
+            .layer(axum::middleware::from_fn(proxy_access_middleware));
+
+        let denied = tower::ServiceExt::oneshot(
+            app.clone(),
+            axum::http::Request::builder()
+                .uri("/api/config")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(denied.status(), axum::http::StatusCode::FORBIDDEN);
+
+        let health = tower::ServiceExt::oneshot(
+            app.clone(),
+            axum::http::Request::builder()
+                .uri(get_proxy_health_check().url)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(health.status(), axum::http::StatusCode::OK);
+
+        let navigation = tower::ServiceExt::oneshot(
+            app,
+            axum::http::Request::builder()
+                .uri(format!(
+                    "/?{}={}",
+                    PROXY_ACCESS_QUERY,
+                    PROXY_ACCESS_TOKEN.as_str()
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(navigation.status(), axum::http::StatusCode::SEE_OTHER);
+        assert_eq!(navigation.headers()[axum::http::header::LOCATION], "/");
+        assert!(
+            navigation.headers()[axum::http::header::SET_COOKIE]
+                .to_str()
+                .unwrap()
+                .starts_with(&format!(
+                    "{}={}",
+                    PROXY_ACCESS_COOKIE,
+                    PROXY_ACCESS_TOKEN.as_str()
+                ))
+        );
+    }
+
+    fn compression_test_router(stream: bool) -> Router {
+        Router::new()
+            .fallback(move || async move {
+                let mut response = (
+                    [(axum::http::header::CONTENT_TYPE, "application/json")],
+                    format!("[{}]", vec![r#"{"name":"Desktop"}"#; 64].join(",")),
+                )
+                    .into_response();
+                if stream {
+                    response.extensions_mut().insert(UncompressedStream);
+                }
+                response
+            })
+            .layer(proxy_compression_layer())
+    }
+
+    async fn request_with_encoding(app: Router, encoding: &str) -> Response {
+        tower::ServiceExt::oneshot(
+            app,
+            axum::http::Request::builder()
+                .uri("/api/apps")
+                .header(axum::http::header::ACCEPT_ENCODING, encoding)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn proxy_compresses_according_to_accept_encoding() {
+        let gzip = request_with_encoding(compression_test_router(false), "gzip").await;
+        assert_eq!(gzip.headers()[axum::http::header::CONTENT_ENCODING], "gzip");
+
+        let brotli = request_with_encoding(compression_test_router(false), "br").await;
+        assert_eq!(brotli.headers()[axum::http::header::CONTENT_ENCODING], "br");
+
+        let identity = request_with_encoding(compression_test_router(false), "identity").await;
+        assert!(
+            !identity
+                .headers()
+                .contains_key(axum::http::header::CONTENT_ENCODING)
+        );
+    }
+
+    #[tokio::test]
+    async fn proxy_does_not_compress_unbounded_streams() {
+        let response = request_with_encoding(compression_test_router(true), "gzip, br").await;
+        assert!(
+            !response
+                .headers()
+                .contains_key(axum::http::header::CONTENT_ENCODING)
+        );
+    }
+
+    #[tokio::test]
+    async fn injected_html_is_compressed_without_a_stale_length() {
+        let _guard = TEST_LOCK.lock().await;
+        let target =
+            spawn_one_shot_http_server("<!DOCTYPE html><html><head></head><body></body></html>")
+                .await;
+        set_sunshine_target(target);
+        reset_fast_fail();
+
+        let app = Router::new()
+            .fallback(proxy_handler)
+            .layer(proxy_compression_layer());
+        let response = request_with_encoding(app, "gzip").await;
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_ENCODING],
+            "gzip"
+        );
+        assert!(
+            !response
+                .headers()
+                .contains_key(axum::http::header::CONTENT_LENGTH)
+        );
+        assert!(
+            !axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     async fn proxy_rejects_oversized_request_body() {
         let request = axum::http::Request::builder()
@@ -1687,6 +2516,52 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn client_accept_encoding_is_forwarded_only_for_passthrough_streams() {
+        async fn forwarded_request(path: &str) -> (String, Response) {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let server = tokio::spawn(async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request_buf = [0_u8; 4096];
+                let read = stream.read(&mut request_buf).await.unwrap();
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Encoding: br\r\nContent-Length: 4\r\nConnection: close\r\n\r\nbrot",
+                    )
+                    .await
+                    .unwrap();
+                String::from_utf8_lossy(&request_buf[..read]).to_lowercase()
+            });
+
+            let mut headers = axum::http::HeaderMap::new();
+            headers.insert(
+                axum::http::header::ACCEPT_ENCODING,
+                axum::http::HeaderValue::from_static("br"),
+            );
+            let response = fetch_and_proxy(
+                &format!("http://{addr}{path}"),
+                &axum::http::Method::GET,
+                &headers,
+                &Bytes::new(),
+                false,
+            )
+            .await
+            .unwrap();
+            (server.await.unwrap(), response)
+        }
+
+        let (request, response) = forwarded_request("/api/logs").await;
+        assert!(request.contains("accept-encoding: br\r\n"));
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_ENCODING],
+            "br"
+        );
+
+        let (request, _) = forwarded_request("/api/config").await;
+        assert!(!request.contains("accept-encoding: br\r\n"));
+    }
+
     #[tokio::test]
     async fn proxy_retries_with_refreshed_target_when_port_changes() {
         let _guard = TEST_LOCK.lock().await;
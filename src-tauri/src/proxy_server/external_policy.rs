@@ -0,0 +1,349 @@
+//! Host allowlist and per-host rate limits for the `/_proxy/` CORS bypass.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const POLICY_FILE: &str = "external-proxy.json";
+const MAX_RECENT_REJECTIONS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HostRateLimit {
+    pub requests_per_minute: u32,
+    pub burst: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub(crate) struct ExternalProxyPolicy {
+    /// A host entry also allows its subdomains, so `github.io` covers
+    /// `user.github.io`.
+    pub allowed_hosts: Vec<String>,
+    /// Default limit applied to each allowed host separately.
+    pub rate_limit: HostRateLimit,
+    /// Per-host overrides keyed by an entry from `allowed_hosts`.
+    pub host_limits: BTreeMap<String, HostRateLimit>,
+}
+
+impl Default for ExternalProxyPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_hosts: vec![
+                "github.io".to_string(),
+                "raw.githubusercontent.com".to_string(),
+                "github.com".to_string(),
+                "api.github.com".to_string(),
+            ],
+            rate_limit: HostRateLimit {
+                requests_per_minute: 60,
+                burst: 20,
+            },
+            host_limits: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExternalProxyRejection {
+    pub timestamp: String,
+    pub url: String,
+    pub reason: String,
+    /// Page that issued the request, from Referer or Origin.
+    pub page: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PolicyDecision {
+    Allowed,
+    HostNotAllowed,
+    RateLimited { retry_after: Duration },
+}
+
+fn normalize_host(entry: &str) -> Option<String> {
+    let host = entry
+        .trim()
+        .trim_start_matches("*.")
+        .trim_start_matches('.')
+        .trim_end_matches('.')
+        .to_ascii_lowercase();
+    let valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'));
+    valid.then_some(host)
+}
+
+impl ExternalProxyPolicy {
+    /// Normalize host entries and reject values that cannot match a host.
+    pub(crate) fn validated(mut self) -> Result<Self, String> {
+        let mut hosts = Vec::with_capacity(self.allowed_hosts.len());
+        for entry in &self.allowed_hosts {
+            let host = normalize_host(entry).ok_or_else(|| format!("Invalid host: {entry}"))?;
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+        self.allowed_hosts = hosts;
+
+        let mut limits = BTreeMap::new();
+        for (entry, limit) in std::mem::take(&mut self.host_limits) {
+            let host = normalize_host(&entry).ok_or_else(|| format!("Invalid host: {entry}"))?;
+            limits.insert(host, limit);
+        }
+        self.host_limits = limits;
+
+        for limit in std::iter::once(&self.rate_limit).chain(self.host_limits.values()) {
+            if limit.requests_per_minute == 0 || limit.burst == 0 {
+                return Err("Rate limits must allow at least one request".to_string());
+            }
+        }
+        Ok(self)
+    }
+
+    /// Return the most specific allowlist entry that covers `host`, if any.
+    pub(crate) fn matching_entry(&self, host: &str) -> Option<&str> {
+        let host = host.to_ascii_lowercase();
+        self.allowed_hosts
+            .iter()
+            .filter(|entry| host == **entry || host.ends_with(&format!(".{entry}")))
+            .max_by_key(|entry| entry.len())
+            .map(String::as_str)
+    }
+
+    fn limit_for(&self, entry: &str) -> HostRateLimit {
+        self.host_limits
+            .get(entry)
+            .copied()
+            .unwrap_or(self.rate_limit)
+    }
+}
+
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// Token buckets keyed by allowlist entry, so subdomains share one budget.
+#[derive(Default)]
+pub(crate) struct RateLimiter {
+    buckets: HashMap<String, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Take one token, or report how long until the next one is available.
+    fn try_acquire(
+        &mut self,
+        key: &str,
+        limit: HostRateLimit,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let capacity = f64::from(limit.burst);
+        let per_second = f64::from(limit.requests_per_minute) / 60.0;
+        let bucket = self
+            .buckets
+            .entry(key.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: capacity,
+                updated_at: now,
+            });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * per_second).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second))
+        }
+    }
+
+    /// Forget buckets for hosts that are no longer allowed.
+    pub(crate) fn retain_hosts(&mut self, policy: &ExternalProxyPolicy) {
+        self.buckets
+            .retain(|key, _| policy.allowed_hosts.iter().any(|host| host == key));
+    }
+}
+
+/// Check the host against the allowlist, then charge its rate limit.
+pub(crate) fn evaluate(
+    policy: &ExternalProxyPolicy,
+    limiter: &mut RateLimiter,
+    host: &str,
+    now: Instant,
+) -> PolicyDecision {
+    let Some(entry) = policy.matching_entry(host) else {
+        return PolicyDecision::HostNotAllowed;
+    };
+    match limiter.try_acquire(entry, policy.limit_for(entry), now) {
+        Ok(()) => PolicyDecision::Allowed,
+        Err(retry_after) => PolicyDecision::RateLimited { retry_after },
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct RejectionLog {
+    entries: VecDeque<ExternalProxyRejection>,
+}
+
+impl RejectionLog {
+    pub(crate) fn push(&mut self, rejection: ExternalProxyRejection) {
+        if self.entries.len() == MAX_RECENT_REJECTIONS {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(rejection);
+    }
+
+    /// Most recent first.
+    pub(crate) fn recent(&self) -> Vec<ExternalProxyRejection> {
+        self.entries.iter().rev().cloned().collect()
+    }
+}
+
+fn policy_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(POLICY_FILE))
+}
+
+pub(crate) fn load_policy() -> ExternalProxyPolicy {
+    let Ok(path) = policy_path() else {
+        return ExternalProxyPolicy::default();
+    };
+    let Ok(text) = fs::read_to_string(path) else {
+        return ExternalProxyPolicy::default();
+    };
+    serde_json::from_str::<ExternalProxyPolicy>(&text)
+        .map_err(|e| e.to_string())
+        .and_then(ExternalProxyPolicy::validated)
+        .unwrap_or_else(|e| {
+            log::warn!("Invalid external proxy policy, using defaults: {}", e);
+            ExternalProxyPolicy::default()
+        })
+}
+
+pub(crate) fn save_policy(policy: &ExternalProxyPolicy) -> Result<(), String> {
+    let path = policy_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(policy).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowlist_entries_cover_subdomains_only() {
+        let policy = ExternalProxyPolicy::default();
+        assert_eq!(policy.matching_entry("user.github.io"), Some("github.io"));
+        assert_eq!(
+            policy.matching_entry("API.GitHub.com"),
+            Some("api.github.com")
+        );
+        assert_eq!(policy.matching_entry("gist.github.com"), Some("github.com"));
+        assert_eq!(policy.matching_entry("evilgithub.com"), None);
+        assert_eq!(policy.matching_entry("example.com"), None);
+    }
+
+    #[test]
+    fn validation_normalizes_hosts_and_rejects_bad_entries() {
+        let policy = ExternalProxyPolicy {
+            allowed_hosts: vec!["*.Example.com".into(), "example.com".into()],
+            ..ExternalProxyPolicy::default()
+        }
+        .validated()
+        .unwrap();
+        assert_eq!(policy.allowed_hosts, ["example.com"]);
+
+        let invalid = ExternalProxyPolicy {
+            allowed_hosts: vec!["https://example.com/path".into()],
+            ..ExternalProxyPolicy::default()
+        };
+        assert!(invalid.validated().is_err());
+
+        let zero = ExternalProxyPolicy {
+            rate_limit: HostRateLimit {
+                requests_per_minute: 0,
+                burst: 1,
+            },
+            ..ExternalProxyPolicy::default()
+        };
+        assert!(zero.validated().is_err());
+    }
+
+    #[test]
+    fn token_bucket_allows_burst_then_refills() {
+        let limit = HostRateLimit {
+            requests_per_minute: 60,
+            burst: 2,
+        };
+        let mut limiter = RateLimiter::default();
+        let start = Instant::now();
+
+        assert!(limiter.try_acquire("github.com", limit, start).is_ok());
+        assert!(limiter.try_acquire("github.com", limit, start).is_ok());
+        assert!(limiter.try_acquire("github.com", limit, start).is_err());
+        assert!(limiter.try_acquire("github.io", limit, start).is_ok());
+        assert!(
+            limiter
+                .try_acquire("github.com", limit, start + Duration::from_secs(1))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn evaluation_uses_host_overrides_and_reports_retry_delay() {
+        let mut policy = ExternalProxyPolicy::default();
+        policy.host_limits.insert(
+            "github.com".into(),
+            HostRateLimit {
+                requests_per_minute: 6,
+                burst: 1,
+            },
+        );
+        let mut limiter = RateLimiter::default();
+        let now = Instant::now();
+
+        assert_eq!(
+            evaluate(&policy, &mut limiter, "example.com", now),
+            PolicyDecision::HostNotAllowed
+        );
+        assert_eq!(
+            evaluate(&policy, &mut limiter, "api.github.com", now),
+            PolicyDecision::Allowed
+        );
+        // `api.github.com` is its own allowlist entry with the default budget.
+        assert_eq!(
+            evaluate(&policy, &mut limiter, "github.com", now),
+            PolicyDecision::Allowed
+        );
+        match evaluate(&policy, &mut limiter, "gist.github.com", now) {
+            PolicyDecision::RateLimited { retry_after } => {
+                assert_eq!(retry_after.as_secs_f64().round(), 10.0)
+            }
+            decision => panic!("unexpected decision: {decision:?}"),
+        }
+    }
+
+    #[test]
+    fn rejection_log_is_bounded() {
+        let mut log = RejectionLog::default();
+        for index in 0..MAX_RECENT_REJECTIONS + 5 {
+            log.push(ExternalProxyRejection {
+                timestamp: String::new(),
+                url: index.to_string(),
+                reason: String::new(),
+                page: None,
+            });
+        }
+        let recent = log.recent();
+        assert_eq!(recent.len(), MAX_RECENT_REJECTIONS);
+        assert_eq!(recent[0].url, (MAX_RECENT_REJECTIONS + 4).to_string());
+    }
+}
@@ -1711,28 +1711,16 @@ fn get_process_memory_wmi(pid: u32) -> f64 {
 /// 数据源: sunshine.conf 中的 `capture` 字段
 fn get_capture_method() -> String {
     let config_path = crate::sunshine::config_dir().join("sunshine.conf");
-    if let Ok(content) = std::fs::read_to_string(&config_path) {
-        for line in content.lines() {
-            let line = line.trim();
-            if line.starts_with('#') || line.is_empty() {
-                continue;
-            }
-            if let Some(rest) = line.strip_prefix("capture") {
-                let rest = rest.trim_start();
-                if let Some(value) = rest.strip_prefix('=') {
-                    let value = value.trim().trim_matches('"').to_lowercase();
-                    return match value.as_str() {
-                        "wgc" => "WGC".into(),
-                        "ddx" => "DDX".into(),
-                        "amd" => "AMD".into(),
-                        "" => "Auto".into(),
-                        other => other.to_uppercase(),
-                    };
-                }
-            }
-        }
+    let Ok(conf) = crate::sunshine::conf::SunshineConf::load(&config_path) else {
+        return "Auto".into();
+    };
+    match conf.capture().map(str::to_lowercase).as_deref() {
+        Some("wgc") => "WGC".into(),
+        Some("ddx") => "DDX".into(),
+        Some("amd") => "AMD".into(),
+        Some(other) => other.to_uppercase(),
+        None => "Auto".into(),
     }
-    "Auto".into()
 }
 
 /// 从 Sunshine API 获取串流会话信息
@@ -1740,69 +1728,60 @@ async fn fetch_session_info() -> std::collections::HashMap<String, String> {
     use std::collections::HashMap;
     let mut map = HashMap::new();
 
-    let proxy_url = crate::proxy_server::get_proxy_url();
-    let url = format!("{}/api/runtime/sessions", proxy_url);
-
-    let client = match reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .timeout(std::time::Duration::from_secs(2))
-        .build()
-    {
-        Ok(c) => c,
-        Err(_) => return map,
+    let result = match core_poll_client() {
+        Ok(client) => client.get("api/runtime/sessions").send_value().await,
+        Err(e) => Err(e),
     };
 
-    match client.get(&url).send().await {
-        Ok(resp) => {
-            if let Ok(body) = resp.json::<serde_json::Value>().await {
-                // API 返回结构: {success, total_sessions, sessions: [...]}
-                let sessions = body.get("sessions").and_then(|v| v.as_array());
-                if let Some(session) = sessions.and_then(|arr| arr.first()) {
-                    if let Some(state) = session.get("state").and_then(|v| v.as_str()) {
-                        map.insert("session_state".into(), state.to_string());
-                    }
-                    if let Some(client) = session.get("client_name").and_then(|v| v.as_str()) {
-                        map.insert("stream_client".into(), client.to_string());
-                    }
-                    if let Some(w) = session.get("width").and_then(|v| v.as_i64()) {
-                        if let Some(h) = session.get("height").and_then(|v| v.as_i64()) {
-                            map.insert("stream_resolution".into(), format!("{}x{}", w, h));
-                        }
-                    }
-                    if let Some(fps) = session.get("fps").and_then(|v| v.as_i64()) {
-                        map.insert("stream_fps".into(), format!("{}", fps));
-                    }
-                    if let Some(bitrate) = session.get("bitrate").and_then(|v| v.as_i64()) {
-                        map.insert("stream_bitrate".into(), format!("{} Kbps", bitrate));
-                    }
-                    if let Some(app) = session.get("app_name").and_then(|v| v.as_str()) {
-                        map.insert("app_name".into(), app.to_string());
-                    }
-                    if let Some(hdr) = session.get("enable_hdr").and_then(|v| v.as_bool()) {
-                        map.insert("stream_hdr".into(), if hdr { "ON" } else { "OFF" }.into());
-                    }
-                    if let Some(codec) = session.get("codec").and_then(|v| v.as_str()) {
-                        map.insert("stream_codec".into(), codec.to_string());
+    match result {
+        Ok(body) => {
+            // API 返回结构: {success, total_sessions, sessions: [...]}
+            let sessions = body.get("sessions").and_then(|v| v.as_array());
+            if let Some(session) = sessions.and_then(|arr| arr.first()) {
+                if let Some(state) = session.get("state").and_then(|v| v.as_str()) {
+                    map.insert("session_state".into(), state.to_string());
+                }
+                if let Some(client) = session.get("client_name").and_then(|v| v.as_str()) {
+                    map.insert("stream_client".into(), client.to_string());
+                }
+                if let Some(w) = session.get("width").and_then(|v| v.as_i64()) {
+                    if let Some(h) = session.get("height").and_then(|v| v.as_i64()) {
+                        map.insert("stream_resolution".into(), format!("{}x{}", w, h));
                     }
                 }
-
-                if !map.contains_key("session_state") {
-                    map.insert("session_state".into(), "IDLE".into());
+                if let Some(fps) = session.get("fps").and_then(|v| v.as_i64()) {
+                    map.insert("stream_fps".into(), format!("{}", fps));
                 }
-                // 无活跃会话时，为所有 session 指标填充默认值
-                if map.get("session_state").map_or(false, |s| s == "IDLE") {
-                    map.entry("stream_client".into())
-                        .or_insert_with(|| "-".into());
-                    map.entry("stream_resolution".into())
-                        .or_insert_with(|| "-".into());
-                    map.entry("stream_fps".into()).or_insert_with(|| "-".into());
-                    map.entry("stream_bitrate".into())
-                        .or_insert_with(|| "-".into());
-                    map.entry("stream_codec".into())
-                        .or_insert_with(|| "-".into());
-                    map.entry("stream_hdr".into()).or_insert_with(|| "-".into());
-                    map.entry("app_name".into()).or_insert_with(|| "-".into());
+                if let Some(bitrate) = session.get("bitrate").and_then(|v| v.as_i64()) {
+                    map.insert("stream_bitrate".into(), format!("{} Kbps", bitrate));
                 }
+                if let Some(app) = session.get("app_name").and_then(|v| v.as_str()) {
+                    map.insert("app_name".into(), app.to_string());
+                }
+                if let Some(hdr) = session.get("enable_hdr").and_then(|v| v.as_bool()) {
+                    map.insert("stream_hdr".into(), if hdr { "ON" } else { "OFF" }.into());
+                }
+                if let Some(codec) = session.get("codec").and_then(|v| v.as_str()) {
+                    map.insert("stream_codec".into(), codec.to_string());
+                }
+            }
+
+            if !map.contains_key("session_state") {
+                map.insert("session_state".into(), "IDLE".into());
+            }
+            // 无活跃会话时，为所有 session 指标填充默认值
+            if map.get("session_state").map_or(false, |s| s == "IDLE") {
+                map.entry("stream_client".into())
+                    .or_insert_with(|| "-".into());
+                map.entry("stream_resolution".into())
+                    .or_insert_with(|| "-".into());
+                map.entry("stream_fps".into()).or_insert_with(|| "-".into());
+                map.entry("stream_bitrate".into())
+                    .or_insert_with(|| "-".into());
+                map.entry("stream_codec".into())
+                    .or_insert_with(|| "-".into());
+                map.entry("stream_hdr".into()).or_insert_with(|| "-".into());
+                map.entry("app_name".into()).or_insert_with(|| "-".into());
             }
         }
         Err(e) => {
@@ -1854,35 +1833,23 @@ fn insert_host_perf_defaults(map: &mut std::collections::HashMap<String, String>
     }
 }
 
-static HOST_PERF_CLIENT: Lazy<Option<reqwest::Client>> = Lazy::new(|| {
-    reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .timeout(std::time::Duration::from_secs(2))
-        .build()
-        .ok()
-});
+/// 性能浮窗轮询走本地代理：短超时、不重试，失败时直接显示 N/A
+fn core_poll_client() -> Result<crate::core_api::CoreClient, crate::core_api::CoreApiError> {
+    Ok(crate::core_api::CoreClient::via_proxy()?
+        .with_timeout(std::time::Duration::from_secs(2))
+        .with_retry(crate::core_api::RetryPolicy::NONE))
+}
 
 async fn fetch_host_perf_info() -> std::collections::HashMap<String, String> {
     use std::collections::HashMap;
     let mut map = HashMap::new();
 
-    let proxy_url = crate::proxy_server::get_proxy_url();
-    let url = format!("{}/api/perf/current", proxy_url);
-
-    let Some(client) = HOST_PERF_CLIENT.as_ref() else {
-        insert_host_perf_defaults(&mut map, "N/A");
-        return map;
+    let result = match core_poll_client() {
+        Ok(client) => client.get("api/perf/current").send_value().await,
+        Err(e) => Err(e),
     };
-
-    let body = match client.get(&url).send().await {
-        Ok(resp) => match resp.json::<serde_json::Value>().await {
-            Ok(body) => body,
-            Err(e) => {
-                debug!("failed to parse Sunshine perf snapshot: {}", e);
-                insert_host_perf_defaults(&mut map, "N/A");
-                return map;
-            }
-        },
+    let body = match result {
+        Ok(body) => body,
         Err(e) => {
             debug!("failed to fetch Sunshine perf snapshot: {}", e);
             insert_host_perf_defaults(&mut map, "N/A");
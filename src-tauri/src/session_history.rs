@@ -0,0 +1,555 @@
+//! Streaming session history recorded from the local Core's tray state.
+//!
+//! The tray state says when sessions start and end; `/api/runtime/sessions` is
+//! polled while any are open for the stream details and bitrate changes. Each
+//! finished session is appended as one line to
+//! `<config>/Sunshine GUI/session-history.jsonl`.
+
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use crate::sunshine::{SessionInfo, TrayState};
+
+const HISTORY_FILE: &str = "session-history.jsonl";
+const DETAIL_POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Compact the history once it grows past this size.
+const MAX_HISTORY_BYTES: u64 = 4 * 1024 * 1024;
+/// Records kept when the history is compacted.
+const KEPT_RECORDS: usize = 5_000;
+const DEFAULT_RECENT_LIMIT: usize = 50;
+/// Key for the single session reported by Cores without session snapshots.
+const LEGACY_SESSION_ID: u32 = 0;
+
+static RECORDER: Lazy<Mutex<SessionRecorder>> =
+    Lazy::new(|| Mutex::new(SessionRecorder::default()));
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+static DETAILS_WANTED: Lazy<Notify> = Lazy::new(Notify::new);
+static POLLER_STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BitrateChange {
+    pub at: DateTime<Utc>,
+    pub kbps: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SessionRecord {
+    pub id: String,
+    pub session_id: u32,
+    pub client_name: String,
+    pub client_address: String,
+    pub app_name: String,
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    pub hdr: bool,
+    /// The first entry is the bitrate the stream started with.
+    pub bitrate_changes: Vec<BitrateChange>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub duration_secs: u64,
+    /// The session was still open when the Core went away.
+    pub interrupted: bool,
+}
+
+impl Default for SessionRecord {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            session_id: 0,
+            client_name: String::new(),
+            client_address: String::new(),
+            app_name: String::new(),
+            width: 0,
+            height: 0,
+            fps: 0,
+            hdr: false,
+            bitrate_changes: Vec::new(),
+            started_at: DateTime::<Utc>::UNIX_EPOCH,
+            ended_at: DateTime::<Utc>::UNIX_EPOCH,
+            duration_secs: 0,
+            interrupted: false,
+        }
+    }
+}
+
+impl SessionRecord {
+    fn open(session_id: u32, client_name: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            session_id,
+            client_name: client_name.trim().to_string(),
+            started_at: now,
+            ..Self::default()
+        }
+    }
+
+    fn apply_details(&mut self, info: &SessionInfo, now: DateTime<Utc>) {
+        if self.client_name.is_empty() && info.client_name != "Unknown" {
+            self.client_name = info.client_name.clone();
+        }
+        if !info.client_address.is_empty() {
+            self.client_address = info.client_address.clone();
+        }
+        if !info.app_name.is_empty() {
+            self.app_name = info.app_name.clone();
+        }
+        if info.width > 0 && info.height > 0 {
+            self.width = info.width;
+            self.height = info.height;
+        }
+        if info.fps > 0 {
+            self.fps = info.fps;
+        }
+        self.hdr |= info.enable_hdr;
+        let bitrate_changed = self
+            .bitrate_changes
+            .last()
+            .is_none_or(|last| last.kbps != info.bitrate);
+        if info.bitrate > 0 && bitrate_changed {
+            self.bitrate_changes.push(BitrateChange {
+                at: now,
+                kbps: info.bitrate,
+            });
+        }
+    }
+
+    fn close(mut self, now: DateTime<Utc>, interrupted: bool) -> Self {
+        self.ended_at = now;
+        self.duration_secs = (now - self.started_at).num_seconds().max(0) as u64;
+        self.interrupted = interrupted;
+        self
+    }
+}
+
+/// Tracks open sessions between tray state updates.
+#[derive(Debug, Default)]
+struct SessionRecorder {
+    instance_id: String,
+    open: HashMap<u32, SessionRecord>,
+}
+
+impl SessionRecorder {
+    /// Open and close records for `state` and return the finished ones.
+    fn observe(&mut self, state: &TrayState, now: DateTime<Utc>) -> Vec<SessionRecord> {
+        let mut finished = Vec::new();
+        if self.instance_id != state.instance_id {
+            finished = self.close_all(now);
+            self.instance_id = state.instance_id.clone();
+        }
+
+        let current: Vec<(u32, &str)> = if supports_session_snapshots(state) {
+            state
+                .sessions
+                .iter()
+                .map(|session| (session.id, session.client_name.as_str()))
+                .collect()
+        } else if state.status == "streaming" {
+            vec![(LEGACY_SESSION_ID, "")]
+        } else {
+            Vec::new()
+        };
+
+        let ended: Vec<u32> = self
+            .open
+            .keys()
+            .filter(|id| !current.iter().any(|(current_id, _)| current_id == *id))
+            .copied()
+            .collect();
+        for id in ended {
+            if let Some(record) = self.open.remove(&id) {
+                finished.push(record.close(now, false));
+            }
+        }
+        for (id, client_name) in current {
+            self.open
+                .entry(id)
+                .or_insert_with(|| SessionRecord::open(id, client_name, now));
+        }
+        finished
+    }
+
+    /// Attach stream details to open records.
+    fn update_details(&mut self, sessions: &[SessionInfo], now: DateTime<Utc>) {
+        let legacy = self.open.len() == 1 && self.open.contains_key(&LEGACY_SESSION_ID);
+        for (id, record) in self.open.iter_mut() {
+            let by_id = sessions
+                .iter()
+                .find(|info| u32::try_from(info.session_id).ok() == Some(*id));
+            let by_name = || {
+                let mut named = sessions
+                    .iter()
+                    .filter(|info| info.client_name == record.client_name);
+                match (named.next(), named.next()) {
+                    (Some(info), None) => Some(info),
+                    _ => None,
+                }
+            };
+            let info = by_id
+                .or_else(|| (!record.client_name.is_empty()).then(by_name).flatten())
+                .or_else(|| legacy.then(|| sessions.first()).flatten());
+            if let Some(info) = info {
+                record.apply_details(info, now);
+            }
+        }
+    }
+
+    fn close_all(&mut self, now: DateTime<Utc>) -> Vec<SessionRecord> {
+        self.open
+            .drain()
+            .map(|(_, record)| record.close(now, true))
+            .collect()
+    }
+}
+
+fn supports_session_snapshots(state: &TrayState) -> bool {
+    state
+        .capabilities
+        .iter()
+        .any(|capability| capability == "sessions-v1")
+}
+
+fn history_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(HISTORY_FILE))
+}
+
+fn append_records(path: &Path, records: &[SessionRecord]) -> Result<(), String> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| format!("写入会话历史失败: {}", e))?;
+
+    let too_large = fs::metadata(path).is_ok_and(|meta| meta.len() > MAX_HISTORY_BYTES);
+    if too_large {
+        compact_history(path)?;
+    }
+    Ok(())
+}
+
+fn compact_history(path: &Path) -> Result<(), String> {
+    let records = read_records(path);
+    let kept = &records[records.len().saturating_sub(KEPT_RECORDS)..];
+    let mut content = String::new();
+    for record in kept {
+        content.push_str(&serde_json::to_string(record).map_err(|e| e.to_string())?);
+        content.push('\n');
+    }
+    let temp = path.with_extension("jsonl.tmp");
+    fs::write(&temp, content).map_err(|e| e.to_string())?;
+    fs::rename(&temp, path).map_err(|e| e.to_string())?;
+    info!("🗜️ 会话历史已压缩，保留 {} 条", kept.len());
+    Ok(())
+}
+
+/// Records oldest first. Unreadable lines are skipped.
+fn read_records(path: &Path) -> Vec<SessionRecord> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                debug!("Skipping unreadable session history line: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn persist(records: Vec<SessionRecord>) {
+    if records.is_empty() {
+        return;
+    }
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let result = history_path().and_then(|path| append_records(&path, &records));
+    match result {
+        Ok(()) => debug!("Recorded {} finished session(s)", records.len()),
+        Err(e) => warn!("⚠️ 保存会话历史失败: {}", e),
+    }
+}
+
+/// Feed a tray state the monitor accepted.
+pub fn observe_tray_state(state: &TrayState) {
+    let (finished, has_open) = {
+        let mut recorder = RECORDER.lock().unwrap_or_else(|e| e.into_inner());
+        let finished = recorder.observe(state, Utc::now());
+        (finished, !recorder.open.is_empty())
+    };
+    persist(finished);
+    if has_open {
+        DETAILS_WANTED.notify_one();
+    }
+}
+
+/// Close every open session when the Core stops answering.
+pub fn observe_core_disconnected() {
+    let finished = RECORDER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .close_all(Utc::now());
+    persist(finished);
+}
+
+/// Poll session details while sessions are open.
+pub fn start_detail_poller() {
+    use std::sync::atomic::Ordering;
+
+    if POLLER_STARTED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let has_open = !RECORDER
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .open
+                .is_empty();
+            if !has_open {
+                DETAILS_WANTED.notified().await;
+                continue;
+            }
+
+            match crate::sunshine::get_local_sessions().await {
+                Ok(sessions) => RECORDER
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .update_details(&sessions, Utc::now()),
+                Err(e) => debug!("Session details unavailable: {}", e),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(DETAIL_POLL_INTERVAL) => {}
+                _ = DETAILS_WANTED.notified() => {}
+            }
+        }
+    });
+}
+
+/// Every recorded session, oldest first.
+pub fn load_history() -> Vec<SessionRecord> {
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    history_path()
+        .map(|path| read_records(&path))
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTotals {
+    pub name: String,
+    pub sessions: usize,
+    pub total_secs: u64,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+fn totals_by(
+    records: &[SessionRecord],
+    key: impl Fn(&SessionRecord) -> &str,
+) -> Vec<SessionTotals> {
+    let mut totals: BTreeMap<String, SessionTotals> = BTreeMap::new();
+    for record in records {
+        let name = match key(record).trim() {
+            "" => "Unknown",
+            name => name,
+        };
+        let entry = totals
+            .entry(name.to_string())
+            .or_insert_with(|| SessionTotals {
+                name: name.to_string(),
+                ..SessionTotals::default()
+            });
+        entry.sessions += 1;
+        entry.total_secs += record.duration_secs;
+        entry.last_seen = entry.last_seen.max(Some(record.ended_at));
+    }
+
+    let mut totals: Vec<SessionTotals> = totals.into_values().collect();
+    totals.sort_by(|a, b| b.total_secs.cmp(&a.total_secs).then(a.name.cmp(&b.name)));
+    totals
+}
+
+/// Newest first.
+#[tauri::command]
+pub fn list_recent_sessions(limit: Option<usize>) -> Vec<SessionRecord> {
+    let mut records = load_history();
+    records.reverse();
+    records.truncate(limit.unwrap_or(DEFAULT_RECENT_LIMIT));
+    records
+}
+
+#[tauri::command]
+pub fn get_session_totals_by_client() -> Vec<SessionTotals> {
+    totals_by(&load_history(), |record| &record.client_name)
+}
+
+#[tauri::command]
+pub fn get_session_totals_by_app() -> Vec<SessionTotals> {
+    totals_by(&load_history(), |record| &record.app_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sunshine::TrayClientSession;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    fn state(instance_id: &str, sessions: &[(u32, &str)]) -> TrayState {
+        TrayState {
+            instance_id: instance_id.to_string(),
+            capabilities: vec!["sessions-v1".to_string()],
+            status: if sessions.is_empty() {
+                "idle"
+            } else {
+                "streaming"
+            }
+            .to_string(),
+            sessions: sessions
+                .iter()
+                .map(|(id, name)| TrayClientSession {
+                    id: *id,
+                    client_name: name.to_string(),
+                    ..TrayClientSession::default()
+                })
+                .collect(),
+            ..TrayState::default()
+        }
+    }
+
+    fn info(session_id: i32, bitrate: u32) -> SessionInfo {
+        SessionInfo {
+            client_name: "Deck".to_string(),
+            client_address: "192.168.1.20".to_string(),
+            session_id,
+            width: 1280,
+            height: 800,
+            fps: 60,
+            bitrate,
+            enable_hdr: true,
+            app_name: "Desktop".to_string(),
+            ..SessionInfo::default()
+        }
+    }
+
+    #[test]
+    fn recorder_tracks_details_and_bitrate_changes() {
+        let mut recorder = SessionRecorder::default();
+        assert!(
+            recorder
+                .observe(&state("a", &[(7, "Deck")]), at(0))
+                .is_empty()
+        );
+        recorder.update_details(&[info(7, 20_000)], at(5));
+        recorder.update_details(&[info(7, 20_000)], at(15));
+        recorder.update_details(&[info(7, 12_000)], at(25));
+
+        let finished = recorder.observe(&state("a", &[]), at(90));
+        assert_eq!(finished.len(), 1);
+        let record = &finished[0];
+        assert_eq!(record.client_address, "192.168.1.20");
+        assert_eq!((record.width, record.height, record.fps), (1280, 800, 60));
+        assert!(record.hdr && !record.interrupted);
+        assert_eq!(record.duration_secs, 90);
+        assert_eq!(
+            record
+                .bitrate_changes
+                .iter()
+                .map(|change| change.kbps)
+                .collect::<Vec<_>>(),
+            vec![20_000, 12_000]
+        );
+    }
+
+    #[test]
+    fn recorder_closes_sessions_on_core_restart_and_legacy_status() {
+        let mut recorder = SessionRecorder::default();
+        recorder.observe(&state("a", &[(1, "Deck"), (2, "TV")]), at(0));
+        let finished = recorder.observe(&state("b", &[]), at(30));
+        assert_eq!(finished.len(), 2);
+        assert!(finished.iter().all(|record| record.interrupted));
+
+        let mut legacy = TrayState {
+            instance_id: "b".to_string(),
+            status: "streaming".to_string(),
+            ..TrayState::default()
+        };
+        recorder.observe(&legacy, at(40));
+        recorder.update_details(&[info(99, 5_000)], at(41));
+        legacy.status = "idle".to_string();
+        let finished = recorder.observe(&legacy, at(100));
+        assert_eq!(finished[0].client_name, "Deck");
+        assert_eq!(finished[0].duration_secs, 60);
+    }
+
+    #[test]
+    fn history_round_trips_and_totals_group_records() {
+        let dir = std::env::temp_dir().join(format!("sunshine-history-{}", uuid::Uuid::new_v4()));
+        let path = dir.join(HISTORY_FILE);
+        let record = |client: &str, app: &str, secs: u64, end: i64| SessionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            client_name: client.to_string(),
+            app_name: app.to_string(),
+            duration_secs: secs,
+            ended_at: at(end),
+            ..SessionRecord::default()
+        };
+        append_records(
+            &path,
+            &[
+                record("Deck", "Desktop", 60, 100),
+                record("TV", "Steam", 600, 200),
+                record("Deck", "Steam", 30, 300),
+            ],
+        )
+        .unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(b"not json\n"))
+            .unwrap();
+
+        let records = read_records(&path);
+        assert_eq!(records.len(), 3);
+
+        let clients = totals_by(&records, |record| &record.client_name);
+        assert_eq!(clients[0].name, "TV");
+        assert_eq!(clients[1].sessions, 2);
+        assert_eq!(clients[1].total_secs, 90);
+        assert_eq!(clients[1].last_seen, Some(at(300)));
+
+        let apps = totals_by(&records, |record| &record.app_name);
+        assert_eq!(apps[0].name, "Steam");
+        assert_eq!(apps[0].total_secs, 630);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
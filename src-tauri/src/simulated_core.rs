@@ -0,0 +1,500 @@
+//! Built-in simulated Core for demos and frontend work without a Sunshine host.
+//!
+//! Started with `--simulated-core[=<scenario>]` or `SUNSHINE_SIMULATED_CORE=<scenario>`,
+//! where the scenario is a built-in name (`idle`, `demo`, `crash-loop`) or the
+//! path of a scenario file. It serves the tray, runtime session, config, apps,
+//! clipboard, file-transfer and file-mapping endpoints over plain HTTP on a
+//! loopback port, and both the local and the target Sunshine URL resolve to it
+//! for the whole run, so every tray and notification path can be exercised on
+//! any platform.
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{Path, Query, Request, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{get, post},
+};
+use futures_util::stream::{self, Stream};
+use log::{error, info, warn};
+use once_cell::sync::OnceCell;
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{broadcast, watch};
+
+mod scenario;
+mod state;
+
+use scenario::{Scenario, ScenarioEvent};
+use state::SimState;
+
+const ENV_VAR: &str = "SUNSHINE_SIMULATED_CORE";
+const FLAG: &str = "--simulated-core";
+const DEFAULT_SCENARIO: &str = "demo";
+/// Downtime of a restart requested through the tray.
+const RESTART_DOWNTIME: Duration = Duration::from_secs(3);
+
+static SIMULATED_URL: OnceCell<String> = OnceCell::new();
+
+struct SimCore {
+    state: Mutex<SimState>,
+    /// Bumped on every state change; `None` while the Core is down.
+    revision: watch::Sender<Option<u64>>,
+    clipboard: broadcast::Sender<Vec<u8>>,
+}
+
+type Shared = Arc<SimCore>;
+
+impl SimCore {
+    fn is_down(&self) -> bool {
+        self.revision.borrow().is_none()
+    }
+
+    fn update<T>(&self, change: impl FnOnce(&mut SimState) -> T) -> T {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let output = change(&mut state);
+        let revision = state.tray_state().revision;
+        drop(state);
+        if !self.is_down() {
+            self.revision.send_replace(Some(revision));
+        }
+        output
+    }
+
+    async fn crash(&self, downtime: Duration) {
+        warn!("💥 模拟 Core 崩溃，{:?} 后恢复", downtime);
+        self.revision.send_replace(None);
+        self.update(SimState::restart);
+        tokio::time::sleep(downtime).await;
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        info!("模拟 Core 已恢复: {}", state.instance_id);
+        self.revision
+            .send_replace(Some(state.tray_state().revision));
+    }
+}
+
+/// Scenario requested on the command line or through the environment.
+fn requested_scenario() -> Option<String> {
+    let from_args = std::env::args().find_map(|arg| {
+        if arg == FLAG {
+            Some(DEFAULT_SCENARIO.to_string())
+        } else {
+            arg.strip_prefix(FLAG)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::to_string)
+        }
+    });
+    from_args.or_else(|| {
+        std::env::var(ENV_VAR)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    })
+}
+
+/// Base URL of the simulated Core when one is running.
+pub fn url() -> Option<String> {
+    SIMULATED_URL.get().cloned()
+}
+
+/// Bind and start the simulated Core if requested. Must run after the async
+/// runtime is configured and before anything resolves the Sunshine URL. When
+/// the scenario cannot be loaded or served the GUI keeps using the real Core.
+pub fn start_if_requested() {
+    let Some(name) = requested_scenario() else {
+        return;
+    };
+    let scenario = match scenario::load(&name) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            error!("Simulated Core not started: {}", e);
+            return;
+        }
+    };
+    let listener = match std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+    {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Cannot bind the simulated Core: {}", e);
+            return;
+        }
+    };
+    let Ok(address) = listener.local_addr() else {
+        return;
+    };
+    let _ = SIMULATED_URL.set(format!("http://{}", address));
+
+    let (revision, _) = watch::channel(Some(1));
+    let (clipboard, _) = broadcast::channel(16);
+    let core = Arc::new(SimCore {
+        state: Mutex::new(SimState::default()),
+        revision,
+        clipboard,
+    });
+
+    tauri::async_runtime::spawn(run_scenario(core.clone(), scenario));
+    tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::from_std(listener) {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!("⚠️ 模拟 Core 启动失败: {}", e);
+                return;
+            }
+        };
+        info!("🧪 模拟 Core 已启动: http://{}", address);
+        if let Err(e) = axum::serve(listener, router(core)).await {
+            warn!("⚠️ 模拟 Core 运行失败: {}", e);
+        }
+    });
+}
+
+async fn run_scenario(core: Shared, scenario: Scenario) {
+    info!("🧪 模拟 Core 场景: {}", scenario.name);
+    loop {
+        for step in &scenario.steps {
+            tokio::time::sleep(Duration::from_secs_f64(step.after_secs.max(0.0))).await;
+            info!("🧪 场景事件: {:?}", step.event);
+            match &step.event {
+                ScenarioEvent::Crash { downtime_secs } => {
+                    core.crash(Duration::from_secs_f64(downtime_secs.max(0.0)))
+                        .await;
+                }
+                ScenarioEvent::ClipboardText { text } => {
+                    let _ = core.clipboard.send(crate::clipboard::text_frame(text));
+                }
+                event => core.update(|state| state.apply(event)),
+            }
+        }
+        if !scenario.repeat || scenario.steps.is_empty() {
+            return;
+        }
+    }
+}
+
+fn router(core: Shared) -> Router {
+    Router::new()
+        .route("/api/tray/state", get(tray_state))
+        .route("/api/tray/events", get(tray_events))
+        .route("/api/tray/action", post(tray_action))
+        .route("/api/runtime/sessions", get(runtime_sessions))
+        .route("/api/runtime/bitrate", get(runtime_bitrate))
+        .route("/api/config", get(get_config).post(save_config))
+        .route("/api/configLocale", get(config_locale))
+        .route("/api/apps", get(get_apps).post(save_apps))
+        .route("/api/v1/clipboard/capability", post(clipboard_capability))
+        .route("/api/v1/clipboard/item", post(clipboard_item))
+        .route("/api/v1/clipboard/events", get(clipboard_events))
+        .route("/api/v1/clipboard/blob", post(upload_blob))
+        .route("/api/v1/clipboard/blob/{id}", get(fetch_blob))
+        .route("/api/v1/file-transfer/offers", post(create_file_offer))
+        .route(
+            "/api/v1/file-mapping/mappings",
+            get(list_mappings).post(create_mapping),
+        )
+        .route(
+            "/api/v1/file-mapping/mappings/{id}",
+            axum::routing::patch(update_mapping).delete(delete_mapping),
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            core.clone(),
+            refuse_while_down,
+        ))
+        .with_state(core)
+}
+
+/// A crashed Core does not answer at all.
+async fn refuse_while_down(State(core): State<Shared>, request: Request, next: Next) -> Response {
+    if core.is_down() {
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+    next.run(request).await
+}
+
+fn current_tray_state(core: &SimCore) -> Value {
+    let state = core.state.lock().unwrap_or_else(|e| e.into_inner());
+    serde_json::to_value(state.tray_state()).unwrap_or(Value::Null)
+}
+
+async fn tray_state(State(core): State<Shared>) -> Json<Value> {
+    Json(current_tray_state(&core))
+}
+
+async fn tray_events(
+    State(core): State<Shared>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = core.revision.subscribe();
+    let events = stream::unfold(
+        (core, receiver, true),
+        |(core, mut receiver, first)| async move {
+            if !first && receiver.changed().await.is_err() {
+                return None;
+            }
+            // The stream ends with the Core, like a real crash.
+            let revision = *receiver.borrow_and_update();
+            revision?;
+            let event = Event::default()
+                .event("tray-state")
+                .data(current_tray_state(&core).to_string());
+            Some((Ok(event), (core, receiver, false)))
+        },
+    );
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn tray_action(
+    State(core): State<Shared>,
+    Json(request): Json<Map<String, Value>>,
+) -> Json<Value> {
+    let response = core.update(|state| state.tray_action(&request));
+    if response.status && matches!(response.action.as_str(), "restart" | "shutdown") {
+        let core = core.clone();
+        tokio::spawn(async move { core.crash(RESTART_DOWNTIME).await });
+    }
+    Json(serde_json::to_value(response).unwrap_or(Value::Null))
+}
+
+fn success(mut body: Map<String, Value>) -> Json<Value> {
+    body.insert("success".to_string(), json!(true));
+    body.insert("status_code".to_string(), json!(200));
+    Json(Value::Object(body))
+}
+
+async fn runtime_sessions(State(core): State<Shared>) -> Json<Value> {
+    let sessions = core
+        .state
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .runtime_sessions();
+    let mut body = Map::new();
+    body.insert("sessions".to_string(), json!(sessions));
+    success(body)
+}
+
+async fn runtime_bitrate(
+    State(core): State<Shared>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Json<Value> {
+    let bitrate = query.get("bitrate").and_then(|value| value.parse().ok());
+    let client_name = query.get("clientname").cloned().unwrap_or_default();
+    let found = bitrate
+        .is_some_and(|bitrate| core.update(|state| state.set_bitrate(&client_name, bitrate)));
+    if found {
+        return success(Map::new());
+    }
+    Json(json!({
+        "success": false,
+        "status_code": 404,
+        "status_message": format!("no session for client '{}'", client_name),
+    }))
+}
+
+async fn get_config(State(core): State<Shared>) -> Json<Value> {
+    let state = core.state.lock().unwrap_or_else(|e| e.into_inner());
+    let mut body: Map<String, Value> = state
+        .config
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    body.insert("status".to_string(), json!(true));
+    body.insert("platform".to_string(), json!("simulated"));
+    Json(Value::Object(body))
+}
+
+async fn save_config(
+    State(core): State<Shared>,
+    Json(changes): Json<Map<String, Value>>,
+) -> Json<Value> {
+    core.update(|state| state.config.extend(changes));
+    Json(json!({ "status": true }))
+}
+
+async fn config_locale(State(core): State<Shared>) -> Json<Value> {
+    let state = core.state.lock().unwrap_or_else(|e| e.into_inner());
+    Json(json!({ "status": true, "locale": state.config.get("locale") }))
+}
+
+async fn get_apps(State(core): State<Shared>) -> Json<Value> {
+    let state = core.state.lock().unwrap_or_else(|e| e.into_inner());
+    Json(json!({ "env": {}, "apps": state.apps }))
+}
+
+/// Same contract as the Core: `editApp` replaces the app at its index or, at
+/// -1, is appended to `apps`; without it `apps` becomes the list.
+async fn save_apps(
+    State(core): State<Shared>,
+    Json(request): Json<Map<String, Value>>,
+) -> Json<Value> {
+    let Some(mut apps) = request.get("apps").and_then(Value::as_array).cloned() else {
+        return Json(json!({ "status": false, "error": "missing apps" }));
+    };
+    if let Some(Value::Object(edited)) = request.get("editApp") {
+        let mut edited = edited.clone();
+        let index = edited.remove("index").and_then(|index| index.as_i64());
+        match index.and_then(|index| usize::try_from(index).ok()) {
+            Some(index) if index < apps.len() => apps[index] = Value::Object(edited),
+            Some(_) => return Json(json!({ "status": false, "error": "bad index" })),
+            None => apps.push(Value::Object(edited)),
+        }
+    }
+    core.update(|state| state.apps = apps);
+    Json(json!({ "status": true }))
+}
+
+async fn clipboard_capability() -> Json<Value> {
+    Json(json!({ "clipboard_sync": true }))
+}
+
+/// Items copied on this machine are accepted and dropped; the simulated
+/// clients only send.
+async fn clipboard_item(_body: Bytes) -> StatusCode {
+    StatusCode::NO_CONTENT
+}
+
+async fn clipboard_events(
+    State(core): State<Shared>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    use base64::Engine as _;
+
+    let receiver = core.clipboard.subscribe();
+    let events = stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(frame) => {
+                    let data = base64::engine::general_purpose::STANDARD.encode(frame);
+                    return Some((Ok(Event::default().data(data)), receiver));
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn upload_blob(State(core): State<Shared>, headers: HeaderMap, body: Bytes) -> Json<Value> {
+    let mime = headers
+        .get("X-Clipboard-Mime")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let id = uuid::Uuid::new_v4().to_string();
+    core.state
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clipboard_blobs
+        .insert(id.clone(), (mime, body.to_vec()));
+    Json(json!({ "id": id }))
+}
+
+async fn fetch_blob(State(core): State<Shared>, Path(id): Path<String>) -> Response {
+    let state = core.state.lock().unwrap_or_else(|e| e.into_inner());
+    match state.clipboard_blobs.get(&id) {
+        Some((mime, bytes)) => {
+            ([(header::CONTENT_TYPE, mime.clone())], bytes.clone()).into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Offers are only described, never served; the simulated clients do not
+/// download anything.
+async fn create_file_offer(Json(request): Json<Map<String, Value>>) -> Json<Value> {
+    let path = request
+        .get("path")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let name = std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    let size = std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    let id = uuid::Uuid::new_v4().to_string();
+    Json(json!({
+        "id": id,
+        "name": name,
+        "size": size,
+        "mime": "application/octet-stream",
+        "download_url": format!("/api/v1/file-transfer/offers/{id}"),
+        "expires_in": 300,
+        "type": "file_offer",
+    }))
+}
+
+fn mapping_ok(key: &str, value: Value) -> Json<Value> {
+    Json(json!({ "ok": true, key: value }))
+}
+
+async fn list_mappings(State(core): State<Shared>) -> Json<Value> {
+    let state = core.state.lock().unwrap_or_else(|e| e.into_inner());
+    mapping_ok("mappings", Value::Array(state.file_mappings.clone()))
+}
+
+async fn create_mapping(
+    State(core): State<Shared>,
+    Json(request): Json<Map<String, Value>>,
+) -> Json<Value> {
+    let path = request
+        .get("path")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let name = std::path::Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.clone());
+    let mapping = json!({
+        "id": uuid::Uuid::new_v4().to_string(),
+        "name": name,
+        "path": path,
+        "mode": "read_only",
+        "allow_delete": false,
+        "allow_execute": false,
+        "follow_reparse_points": false,
+        "max_file_size": 0,
+        "clients": [],
+    });
+    core.update(|state| state.file_mappings.push(mapping.clone()));
+    mapping_ok("mapping", mapping)
+}
+
+fn mapping_id(mapping: &Value) -> Option<&str> {
+    mapping.get("id").and_then(Value::as_str)
+}
+
+async fn update_mapping(
+    State(core): State<Shared>,
+    Path(id): Path<String>,
+    Json(patch): Json<Map<String, Value>>,
+) -> Json<Value> {
+    let updated = core.update(|state| {
+        let mapping = state
+            .file_mappings
+            .iter_mut()
+            .find(|mapping| mapping_id(mapping) == Some(id.as_str()))?;
+        if let Some(fields) = mapping.as_object_mut() {
+            fields.extend(patch.into_iter().filter(|(key, _)| key != "id"));
+        }
+        Some(mapping.clone())
+    });
+    match updated {
+        Some(mapping) => mapping_ok("mapping", mapping),
+        None => Json(json!({ "ok": false, "error": "mapping not found" })),
+    }
+}
+
+async fn delete_mapping(State(core): State<Shared>, Path(id): Path<String>) -> Json<Value> {
+    core.update(|state| {
+        state
+            .file_mappings
+            .retain(|mapping| mapping_id(mapping) != Some(id.as_str()))
+    });
+    Json(json!({ "ok": true }))
+}
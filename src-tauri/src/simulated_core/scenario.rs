@@ -0,0 +1,197 @@
+//! Scripted scenarios for the simulated Core.
+//!
+//! A scenario is a list of events, each fired `after_secs` seconds after the
+//! previous one. Besides the built-in scenarios, a JSON file with the same
+//! shape can be passed instead of a name:
+//!
+//! ```json
+//! { "name": "tv", "repeat": false, "steps": [
+//!   { "afterSecs": 3, "event": "connect", "clientName": "Living Room TV" },
+//!   { "afterSecs": 20, "event": "crash", "downtimeSecs": 10 }
+//! ] }
+//! ```
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub(crate) enum ScenarioEvent {
+    #[serde(rename_all = "camelCase")]
+    Connect {
+        client_name: String,
+        #[serde(default)]
+        fingerprint: String,
+        #[serde(default)]
+        suspicious: bool,
+        #[serde(default)]
+        app_name: String,
+    },
+    /// Ends the named client's session, or every session without a name.
+    #[serde(rename_all = "camelCase")]
+    Disconnect {
+        #[serde(default)]
+        client_name: Option<String>,
+    },
+    Pause,
+    Resume,
+    #[serde(rename_all = "camelCase")]
+    PairingRequest {
+        client_name: String,
+    },
+    VddConfirmation,
+    Notification {
+        title: String,
+        message: String,
+    },
+    /// Text copied on a streaming client, pushed over the clipboard stream.
+    ClipboardText {
+        text: String,
+    },
+    /// The Core stops answering and comes back as a new instance.
+    #[serde(rename_all = "camelCase")]
+    Crash {
+        downtime_secs: f64,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScenarioStep {
+    pub after_secs: f64,
+    #[serde(flatten)]
+    pub event: ScenarioEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Scenario {
+    pub name: String,
+    /// Start over after the last step.
+    #[serde(default)]
+    pub repeat: bool,
+    #[serde(default)]
+    pub steps: Vec<ScenarioStep>,
+}
+
+const DEMO: &str = r#"{
+  "name": "demo",
+  "repeat": true,
+  "steps": [
+    { "afterSecs": 5, "event": "connect", "clientName": "Living Room TV", "fingerprint": "tv-01", "appName": "Desktop" },
+    { "afterSecs": 10, "event": "connect", "clientName": "Steam Deck", "fingerprint": "deck-01", "appName": "Steam Big Picture" },
+    { "afterSecs": 10, "event": "clipboard_text", "text": "Copied on the Steam Deck" },
+    { "afterSecs": 5, "event": "pause" },
+    { "afterSecs": 5, "event": "resume" },
+    { "afterSecs": 10, "event": "disconnect", "clientName": "Steam Deck" },
+    { "afterSecs": 5, "event": "pairing_request", "clientName": "Office Laptop" },
+    { "afterSecs": 15, "event": "vdd_confirmation" },
+    { "afterSecs": 10, "event": "notification", "title": "Sunshine", "message": "Simulated Core notification" },
+    { "afterSecs": 10, "event": "connect", "clientName": "Unknown Client", "suspicious": true },
+    { "afterSecs": 10, "event": "disconnect" },
+    { "afterSecs": 10, "event": "crash", "downtimeSecs": 15 }
+  ]
+}"#;
+
+const CRASH_LOOP: &str = r#"{
+  "name": "crash-loop",
+  "repeat": true,
+  "steps": [
+    { "afterSecs": 10, "event": "crash", "downtimeSecs": 40 }
+  ]
+}"#;
+
+pub(crate) const BUILTIN_NAMES: &[&str] = &["idle", "demo", "crash-loop"];
+
+fn builtin(name: &str) -> Option<Scenario> {
+    let json = match name {
+        "idle" => {
+            return Some(Scenario {
+                name: "idle".to_string(),
+                repeat: false,
+                steps: Vec::new(),
+            });
+        }
+        "demo" => DEMO,
+        "crash-loop" => CRASH_LOOP,
+        _ => return None,
+    };
+    serde_json::from_str(json).ok()
+}
+
+/// Resolve a built-in scenario name or the path of a scenario file.
+pub(crate) fn load(name_or_path: &str) -> Result<Scenario, String> {
+    let name_or_path = name_or_path.trim();
+    if let Some(scenario) = builtin(name_or_path) {
+        return Ok(scenario);
+    }
+    let text = std::fs::read_to_string(name_or_path).map_err(|e| {
+        format!(
+            "Unknown simulated Core scenario '{}' (built-in: {}): {}",
+            name_or_path,
+            BUILTIN_NAMES.join(", "),
+            e
+        )
+    })?;
+    let scenario: Scenario = serde_json::from_str(&text)
+        .map_err(|e| format!("Invalid scenario file {}: {}", name_or_path, e))?;
+    if scenario.repeat && scenario.steps.iter().all(|step| step.after_secs <= 0.0) {
+        return Err("A repeating scenario needs at least one delayed step".to_string());
+    }
+    Ok(scenario)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_scenarios_parse() {
+        for name in BUILTIN_NAMES {
+            let scenario = load(name).expect("built-in scenario should parse");
+            assert_eq!(scenario.name, *name);
+        }
+        let demo = load("demo").unwrap();
+        assert!(demo.steps.iter().any(|step| matches!(
+            step.event,
+            ScenarioEvent::Connect {
+                suspicious: true,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn scenario_files_are_loaded_and_validated() {
+        let dir =
+            std::env::temp_dir().join(format!("sunshine-scenario-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("tv.json");
+        std::fs::write(
+            &path,
+            r#"{ "name": "tv", "steps": [
+                { "afterSecs": 1, "event": "connect", "clientName": "TV" },
+                { "afterSecs": 2, "event": "disconnect" }
+            ] }"#,
+        )
+        .unwrap();
+        let scenario = load(path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            scenario.steps[1].event,
+            ScenarioEvent::Disconnect { client_name: None }
+        );
+
+        let busy_loop = dir.join("loop.json");
+        std::fs::write(
+            &busy_loop,
+            r#"{ "name": "loop", "repeat": true, "steps": [
+                { "afterSecs": 0, "event": "pause" }
+            ] }"#,
+        )
+        .unwrap();
+        assert!(load(busy_loop.to_str().unwrap()).is_err());
+        assert!(load("no-such-scenario").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
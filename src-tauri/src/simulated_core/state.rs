@@ -0,0 +1,472 @@
+//! In-memory Core state the simulated endpoints read and scenarios mutate.
+
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use super::scenario::ScenarioEvent;
+use crate::sunshine::{
+    SessionInfo, TRAY_PROTOCOL_VERSION, TrayActionResponse, TrayClientSession, TrayState,
+};
+
+#[derive(Debug, Clone)]
+struct SimSession {
+    id: u32,
+    client_name: String,
+    fingerprint: String,
+    suspicious: bool,
+    app_name: String,
+    bitrate: u32,
+    paused: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct SimState {
+    pub instance_id: String,
+    revision: u64,
+    boots: u32,
+    sessions: Vec<SimSession>,
+    next_session_id: u32,
+    pairing_client: Option<String>,
+    notification: Option<(u64, String, String, String)>,
+    next_notification_id: u64,
+    vdd_active: bool,
+    vdd_keep_enabled: bool,
+    vdd_headless_create: bool,
+    vdd_confirmation: u64,
+    next_operation_id: u64,
+    pub config: BTreeMap<String, Value>,
+    pub clipboard_blobs: HashMap<String, (String, Vec<u8>)>,
+    pub file_mappings: Vec<Value>,
+    pub apps: Vec<Value>,
+}
+
+impl Default for SimState {
+    fn default() -> Self {
+        let config = [
+            ("sunshine_name", "Simulated Sunshine"),
+            ("locale", "en"),
+            ("port", "47989"),
+            ("min_log_level", "2"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), Value::String(value.to_string())))
+        .collect();
+        Self {
+            instance_id: "simulated-core-1".to_string(),
+            revision: 1,
+            boots: 1,
+            sessions: Vec::new(),
+            next_session_id: 1,
+            pairing_client: None,
+            notification: None,
+            next_notification_id: 1,
+            vdd_active: false,
+            vdd_keep_enabled: false,
+            vdd_headless_create: false,
+            vdd_confirmation: 0,
+            next_operation_id: 1,
+            config,
+            clipboard_blobs: HashMap::new(),
+            file_mappings: Vec::new(),
+            apps: vec![serde_json::json!({ "name": "Desktop", "image-path": "desktop.png" })],
+        }
+    }
+}
+
+impl SimState {
+    fn touch(&mut self) {
+        self.revision += 1;
+    }
+
+    /// Apply a scenario event. Crashes are handled by the server; here they
+    /// only reset what a restarted Core would lose.
+    pub fn apply(&mut self, event: &ScenarioEvent) {
+        match event {
+            ScenarioEvent::Connect {
+                client_name,
+                fingerprint,
+                suspicious,
+                app_name,
+            } => {
+                self.sessions.push(SimSession {
+                    id: self.next_session_id,
+                    client_name: client_name.clone(),
+                    fingerprint: fingerprint.clone(),
+                    suspicious: *suspicious,
+                    app_name: if app_name.is_empty() {
+                        "Desktop".to_string()
+                    } else {
+                        app_name.clone()
+                    },
+                    bitrate: 20_000,
+                    paused: false,
+                });
+                self.next_session_id += 1;
+            }
+            ScenarioEvent::Disconnect { client_name } => match client_name {
+                Some(name) => self.sessions.retain(|session| &session.client_name != name),
+                None => self.sessions.clear(),
+            },
+            ScenarioEvent::Pause => self.set_paused(None, true),
+            ScenarioEvent::Resume => self.set_paused(None, false),
+            ScenarioEvent::PairingRequest { client_name } => {
+                self.pairing_client = Some(client_name.clone());
+                self.show_notification(
+                    "Incoming pairing request",
+                    &format!("{} wants to pair", client_name),
+                    "open_pin",
+                );
+            }
+            ScenarioEvent::VddConfirmation => {
+                self.vdd_active = true;
+                self.vdd_confirmation = self.next_operation_id;
+                self.next_operation_id += 1;
+            }
+            ScenarioEvent::Notification { title, message } => {
+                self.show_notification(title, message, "");
+            }
+            ScenarioEvent::ClipboardText { .. } => return,
+            ScenarioEvent::Crash { .. } => {
+                self.restart();
+                return;
+            }
+        }
+        self.touch();
+    }
+
+    /// Pause or resume one session, or all of them without an id.
+    fn set_paused(&mut self, session_id: Option<u32>, paused: bool) {
+        for session in &mut self.sessions {
+            if session_id.is_none_or(|id| id == session.id) {
+                session.paused = paused;
+            }
+        }
+    }
+
+    fn show_notification(&mut self, title: &str, message: &str, action: &str) {
+        self.notification = Some((
+            self.next_notification_id,
+            title.to_string(),
+            message.to_string(),
+            action.to_string(),
+        ));
+        self.next_notification_id += 1;
+    }
+
+    /// Come back as a new instance without sessions or pending prompts.
+    pub fn restart(&mut self) {
+        self.boots += 1;
+        self.instance_id = format!("simulated-core-{}", self.boots);
+        self.revision = 1;
+        self.sessions.clear();
+        self.pairing_client = None;
+        self.notification = None;
+        self.vdd_confirmation = 0;
+    }
+
+    fn status(&self) -> &'static str {
+        if self.pairing_client.is_some() {
+            "pairing"
+        } else if !self.sessions.is_empty() && self.sessions.iter().all(|session| session.paused) {
+            "paused"
+        } else if !self.sessions.is_empty() {
+            "streaming"
+        } else if self.notification.is_some() {
+            "notification"
+        } else {
+            "idle"
+        }
+    }
+
+    pub fn tray_state(&self) -> TrayState {
+        let status = self.status();
+        let mut state = TrayState {
+            protocol_version: TRAY_PROTOCOL_VERSION,
+            instance_id: self.instance_id.clone(),
+            owner: "gui".to_string(),
+            capabilities: [
+                "state-v1",
+                "actions-v1",
+                "events-v1",
+                "sessions-v1",
+                "session-actions-v1",
+            ]
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+            status: status.to_string(),
+            icon: match status {
+                "streaming" => "playing",
+                "paused" => "pausing",
+                _ => "",
+            }
+            .to_string(),
+            app_name: self
+                .sessions
+                .last()
+                .map(|session| session.app_name.clone())
+                .unwrap_or_default(),
+            pairing_client_name: self.pairing_client.clone().unwrap_or_default(),
+            sessions: self
+                .sessions
+                .iter()
+                .map(|session| TrayClientSession {
+                    id: session.id,
+                    client_name: session.client_name.clone(),
+                    highly_suspected_unknown_client: session.suspicious,
+                    client_fingerprint: session.fingerprint.clone(),
+                    app_name: session.app_name.clone(),
+                    paused: session.paused,
+                })
+                .collect(),
+            revision: self.revision,
+            updated_at_ms: chrono::Utc::now().timestamp_millis(),
+            ..TrayState::default()
+        };
+        state.vdd.active = self.vdd_active;
+        state.vdd.keep_enabled = self.vdd_keep_enabled;
+        state.vdd.headless_create_enabled = self.vdd_headless_create;
+        state.vdd.awaiting_confirmation = self.vdd_confirmation != 0;
+        state.vdd.confirmation_operation_id = self.vdd_confirmation;
+        if let Some((id, title, message, action)) = &self.notification {
+            state.notification.id = *id;
+            state.notification.active = true;
+            state.notification.title = title.clone();
+            state.notification.message = message.clone();
+            state.notification.action = action.clone();
+        }
+        state
+    }
+
+    pub fn runtime_sessions(&self) -> Vec<SessionInfo> {
+        self.sessions
+            .iter()
+            .map(|session| SessionInfo {
+                client_name: session.client_name.clone(),
+                client_address: format!("192.168.1.{}", 100 + session.id),
+                state: if session.paused { "PAUSED" } else { "RUNNING" }.to_string(),
+                session_id: i32::try_from(session.id).unwrap_or(i32::MAX),
+                width: 1920,
+                height: 1080,
+                fps: 60,
+                bitrate: session.bitrate,
+                app_name: session.app_name.clone(),
+                app_id: 1,
+                ..SessionInfo::default()
+            })
+            .collect()
+    }
+
+    pub fn set_bitrate(&mut self, client_name: &str, bitrate: u32) -> bool {
+        let mut found = false;
+        for session in &mut self.sessions {
+            if session.client_name == client_name {
+                session.bitrate = bitrate;
+                found = true;
+            }
+        }
+        found
+    }
+
+    /// Handle `POST /api/tray/action` the way the real Core answers it.
+    pub fn tray_action(&mut self, request: &Map<String, Value>) -> TrayActionResponse {
+        let action = request
+            .get("action")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let enabled = request.get("enabled").and_then(Value::as_bool);
+        let session_id = request
+            .get("session_id")
+            .and_then(Value::as_u64)
+            .and_then(|id| u32::try_from(id).ok());
+        let result = match action {
+            "notification_ack" => {
+                let id = request.get("notification_id").and_then(Value::as_u64);
+                if self
+                    .notification
+                    .as_ref()
+                    .is_some_and(|notification| Some(notification.0) == id)
+                {
+                    self.notification = None;
+                    self.pairing_client = None;
+                }
+                Ok(())
+            }
+            "vdd_confirm_keep" => {
+                let id = request.get("operation_id").and_then(Value::as_u64);
+                if id == Some(self.vdd_confirmation) && self.vdd_confirmation != 0 {
+                    self.vdd_confirmation = 0;
+                    self.vdd_active = enabled.unwrap_or(false);
+                    Ok(())
+                } else {
+                    Err("no pending VDD confirmation with that id".to_string())
+                }
+            }
+            "vdd_create" => {
+                self.vdd_active = true;
+                Ok(())
+            }
+            "vdd_destroy" => {
+                self.vdd_active = false;
+                Ok(())
+            }
+            "vdd_toggle_keep_enabled" => {
+                self.vdd_keep_enabled = enabled.unwrap_or(!self.vdd_keep_enabled);
+                Ok(())
+            }
+            "vdd_toggle_headless_create" => {
+                self.vdd_headless_create = enabled.unwrap_or(!self.vdd_headless_create);
+                Ok(())
+            }
+            "session_disconnect" | "session_pause" | "session_resume" | "session_send_file" => {
+                let id =
+                    session_id.filter(|id| self.sessions.iter().any(|session| session.id == *id));
+                match (action, id) {
+                    (_, None) => Err("no session with that id".to_string()),
+                    ("session_disconnect", Some(id)) => {
+                        self.sessions.retain(|session| session.id != id);
+                        Ok(())
+                    }
+                    ("session_send_file", Some(_)) if !request.contains_key("offer_id") => {
+                        Err("offer_id is required".to_string())
+                    }
+                    ("session_send_file", Some(_)) => Ok(()),
+                    (action, Some(id)) => {
+                        self.set_paused(Some(id), action == "session_pause");
+                        Ok(())
+                    }
+                }
+            }
+            // The server turns these into a simulated crash.
+            "restart" | "shutdown" => Ok(()),
+            other => Err(format!("unsupported action '{}'", other)),
+        };
+        if result.is_ok() {
+            self.touch();
+        }
+        TrayActionResponse {
+            status: result.is_ok(),
+            action: action.to_string(),
+            error: result.err().unwrap_or_default(),
+            tray_state: Some(self.tray_state()),
+            ..TrayActionResponse::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connect(name: &str, suspicious: bool) -> ScenarioEvent {
+        ScenarioEvent::Connect {
+            client_name: name.to_string(),
+            fingerprint: String::new(),
+            suspicious,
+            app_name: String::new(),
+        }
+    }
+
+    fn action(json: Value) -> Map<String, Value> {
+        json.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn sessions_drive_status_and_revisions() {
+        let mut state = SimState::default();
+        assert_eq!(state.tray_state().status, "idle");
+
+        state.apply(&connect("TV", false));
+        state.apply(&connect("Deck", true));
+        let tray = state.tray_state();
+        assert_eq!(tray.status, "streaming");
+        assert_eq!(tray.revision, 3);
+        assert!(tray.sessions[1].highly_suspected_unknown_client);
+        assert_eq!(state.runtime_sessions()[0].state, "RUNNING");
+
+        state.apply(&ScenarioEvent::Pause);
+        assert_eq!(state.tray_state().status, "paused");
+        state.apply(&ScenarioEvent::Disconnect {
+            client_name: Some("Deck".to_string()),
+        });
+        assert_eq!(state.tray_state().sessions.len(), 1);
+        state.apply(&ScenarioEvent::Disconnect { client_name: None });
+        assert_eq!(state.tray_state().status, "idle");
+    }
+
+    #[test]
+    fn pairing_and_vdd_prompts_are_cleared_by_tray_actions() {
+        let mut state = SimState::default();
+        state.apply(&ScenarioEvent::PairingRequest {
+            client_name: "Laptop".to_string(),
+        });
+        let tray = state.tray_state();
+        assert_eq!(tray.status, "pairing");
+        assert_eq!(tray.notification.action, "open_pin");
+
+        let response = state.tray_action(&action(serde_json::json!({
+            "action": "notification_ack",
+            "notification_id": tray.notification.id,
+        })));
+        assert!(response.status);
+        assert_eq!(response.tray_state.unwrap().status, "idle");
+
+        state.apply(&ScenarioEvent::VddConfirmation);
+        let operation_id = state.tray_state().vdd.confirmation_operation_id;
+        assert!(state.tray_state().vdd.awaiting_confirmation);
+        let response = state.tray_action(&action(serde_json::json!({
+            "action": "vdd_confirm_keep",
+            "enabled": true,
+            "operation_id": operation_id,
+        })));
+        assert!(response.status);
+        let vdd = response.tray_state.unwrap().vdd;
+        assert!(vdd.active && !vdd.awaiting_confirmation);
+
+        let response = state.tray_action(&action(serde_json::json!({ "action": "bogus" })));
+        assert!(!response.status);
+    }
+
+    #[test]
+    fn session_actions_target_one_session() {
+        let mut state = SimState::default();
+        state.apply(&connect("TV", false));
+        state.apply(&connect("Deck", false));
+
+        let response = state.tray_action(&action(serde_json::json!({
+            "action": "session_pause",
+            "session_id": 1,
+        })));
+        let tray = response.tray_state.unwrap();
+        assert_eq!(tray.status, "streaming");
+        assert!(tray.sessions[0].paused && !tray.sessions[1].paused);
+
+        state.tray_action(&action(serde_json::json!({
+            "action": "session_disconnect",
+            "session_id": 2,
+        })));
+        let tray = state.tray_state();
+        assert_eq!(tray.status, "paused");
+        assert_eq!(tray.sessions.len(), 1);
+
+        let response = state.tray_action(&action(serde_json::json!({
+            "action": "session_resume",
+            "session_id": 2,
+        })));
+        assert!(!response.status);
+    }
+
+    #[test]
+    fn crashes_come_back_as_a_new_instance() {
+        let mut state = SimState::default();
+        state.apply(&connect("TV", false));
+        state.apply(&ScenarioEvent::Crash { downtime_secs: 1.0 });
+        let tray = state.tray_state();
+        assert_eq!(tray.instance_id, "simulated-core-2");
+        assert_eq!(tray.revision, 1);
+        assert!(tray.sessions.is_empty());
+        assert!(
+            crate::sunshine::parse_tray_state_json(&serde_json::to_string(&tray).unwrap()).is_ok()
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use crate::core_api::{CoreApiError, CoreClient, Envelope};
+use conf::SunshineConf;
 use log::{debug, error, info, warn};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -5,12 +7,14 @@ use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::{Mutex, RwLock};
-use url::Url;
+
+pub(crate) mod conf;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct SunshineConfig {
     pub port: Option<String>,
     pub adapter_name: Option<String>,
+    pub output_name: Option<String>,
     pub resolutions: Option<String>,
     pub fps: Option<String>,
     pub locale: Option<String>,
@@ -263,39 +267,23 @@ pub async fn get_sunshine_version() -> Result<String, String> {
 }
 
 fn parse_sunshine_config_content(content: &str) -> SunshineConfig {
-    let mut config = SunshineConfig::default();
-
-    for line in content.lines() {
-        let line = line.trim();
-        if line.starts_with('#') || line.is_empty() {
-            continue;
-        }
-
-        if let Some((key, value)) = line.split_once('=') {
-            let key = key.trim();
-            let value = value.trim();
-
-            match key {
-                "port" => config.port = Some(value.to_string()),
-                "adapter_name" => config.adapter_name = Some(value.to_string()),
-                "resolutions" => config.resolutions = Some(value.to_string()),
-                "fps" => config.fps = Some(value.to_string()),
-                "locale" => config.locale = Some(value.to_string()),
-                "client_fingerprint_remote_rules" => {
-                    config.client_fingerprint_remote_rules = Some(value.to_string())
-                }
-                "client_fingerprint_rules_url" => {
-                    config.client_fingerprint_rules_url = Some(value.to_string())
-                }
-                "client_fingerprint_rules_refresh_hours" => {
-                    config.client_fingerprint_rules_refresh_hours = Some(value.to_string())
-                }
-                _ => {}
-            }
-        }
+    let conf = SunshineConf::parse(content);
+    let value = |key: &str| conf.get(key).map(str::to_string);
+    let list = |items: Vec<String>| format!("[{}]", items.join(","));
+
+    SunshineConfig {
+        port: conf.port().map(|port| port.to_string()),
+        adapter_name: conf.adapter_name().map(str::to_string),
+        output_name: conf.output_name().map(str::to_string),
+        resolutions: conf.resolutions().map(list),
+        fps: conf
+            .fps()
+            .map(|fps| list(fps.iter().map(u32::to_string).collect())),
+        locale: conf.locale().map(str::to_string),
+        client_fingerprint_remote_rules: value("client_fingerprint_remote_rules"),
+        client_fingerprint_rules_url: value("client_fingerprint_rules_url"),
+        client_fingerprint_rules_refresh_hours: value("client_fingerprint_rules_refresh_hours"),
     }
-
-    config
 }
 
 pub(crate) fn parse_sunshine_config_sync() -> Result<SunshineConfig, String> {
@@ -322,6 +310,9 @@ const DEFAULT_WEB_UI_PORT: u16 = 47990;
 
 #[tauri::command]
 pub async fn get_sunshine_url() -> Result<String, String> {
+    if let Some(url) = crate::simulated_core::url() {
+        return Ok(url);
+    }
     // 开发模式下优先使用环境变量，和本地代理保持一致
     if let Ok(url) = std::env::var("WEBUI_DEV_TARGET") {
         if let Some(base) = parse_url_to_base(&url) {
@@ -348,6 +339,9 @@ pub async fn get_sunshine_url() -> Result<String, String> {
 /// Resolve the Core instance installed on this machine. Tray traffic must not
 /// follow the remote target selected for the main window.
 pub async fn get_local_sunshine_url() -> Result<String, String> {
+    if let Some(url) = crate::simulated_core::url() {
+        return Ok(url);
+    }
     let config = parse_sunshine_config().await?;
     Ok(local_sunshine_url_from_config(&config))
 }
@@ -374,7 +368,7 @@ pub async fn get_tray_events_url() -> Result<String, String> {
     local_tray_endpoint("events").await
 }
 
-fn parse_url_to_base(url: &str) -> Option<String> {
+pub(crate) fn parse_url_to_base(url: &str) -> Option<String> {
     url::Url::parse(url).ok().map(|parsed| {
         let host = parsed.host_str().unwrap_or("127.0.0.1");
         let port = parsed.port().unwrap_or(DEFAULT_WEB_UI_PORT);
@@ -403,6 +397,7 @@ pub fn get_command_line_url() -> Option<String> {
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct SessionInfo {
     pub client_name: String,
     pub client_address: String,
@@ -419,6 +414,38 @@ pub struct SessionInfo {
     pub app_id: i32,
 }
 
+impl Default for SessionInfo {
+    fn default() -> Self {
+        Self {
+            client_name: "Unknown".to_string(),
+            client_address: String::new(),
+            state: "UNKNOWN".to_string(),
+            session_id: 0,
+            width: 0,
+            height: 0,
+            fps: 0,
+            bitrate: 0,
+            host_audio: false,
+            enable_hdr: false,
+            enable_mic: false,
+            app_name: String::new(),
+            app_id: 0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct SessionsResponse {
+    sessions: Vec<SessionInfo>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct LocaleResponse {
+    locale: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 pub struct TrayClientSession {
     #[serde(default)]
@@ -427,6 +454,14 @@ pub struct TrayClientSession {
     pub client_name: String,
     #[serde(default)]
     pub highly_suspected_unknown_client: bool,
+    /// Stable identifier of the paired client, when the Core reports one.
+    #[serde(default)]
+    pub client_fingerprint: String,
+    /// App streamed to this client; older Cores only report `TrayState::app_name`.
+    #[serde(default)]
+    pub app_name: String,
+    #[serde(default)]
+    pub paused: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
@@ -608,6 +643,21 @@ mod tray_protocol_tests {
         assert_eq!(config.locale.as_deref(), Some("zh_TW"));
     }
 
+    #[test]
+    fn config_summary_uses_the_typed_accessors() {
+        let config = parse_sunshine_config_content(
+            "port = 48000
+adapter_name = \"Radeon\"
+resolutions = [\n  1920x1080,\n  2560x1440\n]\nfps = [60, bad, 120]\n",
+        );
+
+        assert_eq!(config.port.as_deref(), Some("48000"));
+        assert_eq!(config.adapter_name.as_deref(), Some("Radeon"));
+        assert_eq!(config.output_name, None);
+        assert_eq!(config.resolutions.as_deref(), Some("[1920x1080,2560x1440]"));
+        assert_eq!(config.fps.as_deref(), Some("[60,120]"));
+    }
+
     #[tokio::test]
     async fn latest_locale_wins() {
         let persisted = std::sync::Arc::new(tokio::sync::Mutex::new(None));
@@ -639,127 +689,88 @@ mod tray_protocol_tests {
 
     #[test]
     fn restart_surfaces_definite_request_failures() {
-        let error = handle_restart_action_error(TrayActionRequestError::Definite(
-            "connection refused".to_string(),
-        ))
+        let error = handle_restart_action_error(CoreApiError::Transport {
+            message: "connection refused".to_string(),
+            delivery_unknown: false,
+        })
         .unwrap_err();
 
-        assert_eq!(error, "connection refused");
+        assert!(error.contains("connection refused"));
     }
 
     #[test]
     fn restart_tolerates_an_interrupted_response() {
-        let response = handle_restart_action_error(TrayActionRequestError::DeliveryUnknown(
-            "connection closed".to_string(),
-        ))
+        let response = handle_restart_action_error(CoreApiError::Transport {
+            message: "connection closed".to_string(),
+            delivery_unknown: true,
+        })
         .unwrap();
 
         assert!(response.is_none());
     }
 
     #[test]
-    fn config_save_response_requires_business_success() {
-        assert!(validate_config_save_response(r#"{"status":"true"}"#).is_ok());
-        assert!(validate_config_save_response(r#"{"status":true}"#).is_ok());
-
-        let error = validate_config_save_response(
-            r#"{"status":"false","error":"failed to persist configuration"}"#,
-        )
-        .unwrap_err();
-        assert!(error.contains("failed to persist configuration"));
-    }
-
-    #[test]
-    fn config_save_response_rejects_missing_or_invalid_status() {
-        assert!(validate_config_save_response(r#"{}"#).is_err());
-        assert!(validate_config_save_response("not json").is_err());
-    }
-}
-
-#[derive(Debug)]
-enum TrayActionRequestError {
-    Definite(String),
-    DeliveryUnknown(String),
-}
+    fn sessions_default_missing_fields() {
+        let response: SessionsResponse = serde_json::from_value(serde_json::json!({
+            "success": true,
+            "sessions": [{ "client_name": "Deck", "state": "RUNNING", "bitrate": 20000 }]
+        }))
+        .unwrap();
 
-impl TrayActionRequestError {
-    fn into_message(self) -> String {
-        match self {
-            Self::Definite(message) | Self::DeliveryUnknown(message) => message,
-        }
+        let session = &response.sessions[0];
+        assert_eq!(session.client_name, "Deck");
+        assert_eq!(session.bitrate, 20000);
+        assert_eq!(session.app_name, "");
+        assert_eq!(
+            serde_json::from_value::<SessionInfo>(serde_json::json!({}))
+                .unwrap()
+                .state,
+            "UNKNOWN"
+        );
     }
 }
 
-fn handle_restart_action_error(
-    error: TrayActionRequestError,
-) -> Result<Option<TrayActionResponse>, String> {
+fn handle_restart_action_error(error: CoreApiError) -> Result<Option<TrayActionResponse>, String> {
     match error {
-        TrayActionRequestError::Definite(message) => Err(message),
-        TrayActionRequestError::DeliveryUnknown(message) => {
+        CoreApiError::Transport {
+            message,
+            delivery_unknown: true,
+        } => {
             debug!("Sunshine restart response was interrupted: {}", message);
             Ok(None)
         }
+        error => Err(error.into()),
     }
 }
 
-async fn post_tray_action_request(
-    action: &str,
+#[derive(Debug, Serialize)]
+struct TrayActionRequest<'a> {
+    action: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
     enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     notification_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     operation_id: Option<u64>,
-) -> Result<TrayActionResponse, TrayActionRequestError> {
-    let action_url = local_tray_endpoint("action")
-        .await
-        .map_err(TrayActionRequestError::Definite)?;
-
-    let client = create_https_client().map_err(TrayActionRequestError::Definite)?;
-    let mut body = serde_json::json!({ "action": action });
-    if let Some(enabled) = enabled {
-        body["enabled"] = serde_json::json!(enabled);
-    }
-    if let Some(notification_id) = notification_id {
-        body["notification_id"] = serde_json::json!(notification_id);
-    }
-    if let Some(operation_id) = operation_id {
-        body["operation_id"] = serde_json::json!(operation_id);
-    }
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offer_id: Option<&'a str>,
+}
 
-    let response = client
-        .post(&action_url)
-        .json(&body)
+async fn post_tray_action_request(
+    request: TrayActionRequest<'_>,
+) -> Result<TrayActionResponse, CoreApiError> {
+    let result: TrayActionResponse = CoreClient::local()
+        .await?
+        .post("api/tray/action")
+        .json(&request)?
         .send()
-        .await
-        .map_err(|e| {
-            let message = format!("Post tray action failed: {}", e);
-            if e.is_connect() {
-                TrayActionRequestError::Definite(message)
-            } else {
-                TrayActionRequestError::DeliveryUnknown(message)
-            }
-        })?;
-
-    let status = response.status();
-    let response_text = response.text().await.map_err(|e| {
-        TrayActionRequestError::DeliveryUnknown(format!("Read tray action response failed: {}", e))
-    })?;
-
-    if !status.is_success() {
-        return Err(TrayActionRequestError::Definite(format!(
-            "Tray action failed (status {}): {}",
-            status, response_text
-        )));
-    }
-
-    let result: TrayActionResponse = serde_json::from_str(&response_text).map_err(|e| {
-        TrayActionRequestError::Definite(format!(
-            "Parse tray action failed: {}; body: {}",
-            e, response_text
-        ))
-    })?;
+        .await?;
     let state = result.tray_state.as_ref().ok_or_else(|| {
-        TrayActionRequestError::Definite("Tray action response is missing tray_state".to_string())
+        CoreApiError::Contract("tray action response is missing tray_state".to_string())
     })?;
-    validate_tray_state(state).map_err(TrayActionRequestError::Definite)?;
+    validate_tray_state(state).map_err(CoreApiError::Contract)?;
     Ok(result)
 }
 
@@ -767,13 +778,28 @@ pub async fn post_tray_action(
     action: &str,
     enabled: Option<bool>,
 ) -> Result<TrayActionResponse, String> {
-    post_tray_action_request(action, enabled, None, None)
-        .await
-        .map_err(TrayActionRequestError::into_message)
+    Ok(post_tray_action_request(TrayActionRequest {
+        action,
+        enabled,
+        notification_id: None,
+        operation_id: None,
+        session_id: None,
+        offer_id: None,
+    })
+    .await?)
 }
 
 pub async fn post_tray_restart_action() -> Result<Option<TrayActionResponse>, String> {
-    match post_tray_action_request("restart", None, None, None).await {
+    match post_tray_action_request(TrayActionRequest {
+        action: "restart",
+        enabled: None,
+        notification_id: None,
+        operation_id: None,
+        session_id: None,
+        offer_id: None,
+    })
+    .await
+    {
         Ok(response) => Ok(Some(response)),
         Err(error) => handle_restart_action_error(error),
     }
@@ -782,101 +808,55 @@ pub async fn post_tray_restart_action() -> Result<Option<TrayActionResponse>, St
 pub async fn acknowledge_tray_notification(
     notification_id: u64,
 ) -> Result<TrayActionResponse, String> {
-    post_tray_action_request("notification_ack", None, Some(notification_id), None)
-        .await
-        .map_err(TrayActionRequestError::into_message)
+    Ok(post_tray_action_request(TrayActionRequest {
+        action: "notification_ack",
+        enabled: None,
+        notification_id: Some(notification_id),
+        operation_id: None,
+        session_id: None,
+        offer_id: None,
+    })
+    .await?)
 }
 
 pub async fn confirm_vdd_keep(operation_id: u64, keep: bool) -> Result<TrayActionResponse, String> {
-    post_tray_action_request("vdd_confirm_keep", Some(keep), None, Some(operation_id))
-        .await
-        .map_err(TrayActionRequestError::into_message)
+    Ok(post_tray_action_request(TrayActionRequest {
+        action: "vdd_confirm_keep",
+        enabled: Some(keep),
+        notification_id: None,
+        operation_id: Some(operation_id),
+        session_id: None,
+        offer_id: None,
+    })
+    .await?)
 }
 
-impl SessionInfo {
-    fn from_json(session_obj: &serde_json::Value) -> Self {
-        Self {
-            client_name: session_obj
-                .get("client_name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("Unknown")
-                .to_string(),
-            client_address: session_obj
-                .get("client_address")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string(),
-            state: session_obj
-                .get("state")
-                .and_then(|v| v.as_str())
-                .unwrap_or("UNKNOWN")
-                .to_string(),
-            session_id: session_obj
-                .get("session_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32,
-            width: session_obj
-                .get("width")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as u32,
-            height: session_obj
-                .get("height")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as u32,
-            fps: session_obj.get("fps").and_then(|v| v.as_u64()).unwrap_or(0) as u32,
-            bitrate: session_obj
-                .get("bitrate")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as u32,
-            host_audio: session_obj
-                .get("host_audio")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false),
-            enable_hdr: session_obj
-                .get("enable_hdr")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false),
-            enable_mic: session_obj
-                .get("enable_mic")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false),
-            app_name: session_obj
-                .get("app_name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string(),
-            app_id: session_obj
-                .get("app_id")
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0) as i32,
-        }
-    }
+/// Act on one client session: `session_disconnect`, `session_pause`,
+/// `session_resume`, or `session_send_file` with the id of a registered offer.
+pub async fn post_tray_session_action(
+    action: &str,
+    session_id: u32,
+    offer_id: Option<&str>,
+) -> Result<TrayActionResponse, String> {
+    Ok(post_tray_action_request(TrayActionRequest {
+        action,
+        enabled: None,
+        notification_id: None,
+        operation_id: None,
+        session_id: Some(session_id),
+        offer_id,
+    })
+    .await?)
 }
 
 pub async fn get_tray_state() -> Result<TrayState, String> {
-    let tray_state_url = local_tray_endpoint("state").await?;
-
-    let client = create_https_client()?;
-    let response = client
-        .get(&tray_state_url)
+    let state: TrayState = CoreClient::local()
+        .await?
+        .get("api/tray/state")
         .send()
-        .await
-        .map_err(|e| format!("Request tray state failed: {}", e))?;
-
-    let status = response.status();
-    let response_text = response
-        .text()
-        .await
-        .map_err(|e| format!("Read tray state response failed: {}", e))?;
-
-    if !status.is_success() {
-        return Err(format!(
-            "Tray state request failed (status {}): {}",
-            status, response_text
-        ));
-    }
-
-    parse_tray_state_json(&response_text)
+        .await?;
+    validate_tray_state(&state)?;
+    Ok(state)
 }
 
 #[derive(Debug, Deserialize)]
@@ -890,38 +870,13 @@ pub struct ClientFingerprintRuleInstallResponse {
 pub async fn post_client_fingerprint_rules(
     envelope: &str,
 ) -> Result<ClientFingerprintRuleInstallResponse, String> {
-    let endpoint = local_tray_endpoint("client-fingerprint-rules").await?;
-    let client = create_https_client()?;
-    let response = client
-        .post(&endpoint)
-        .json(&serde_json::json!({ "envelope": envelope }))
+    Ok(CoreClient::local()
+        .await?
+        .post("api/tray/client-fingerprint-rules")
+        .json(&serde_json::json!({ "envelope": envelope }))?
+        .envelope(Envelope::Status)
         .send()
-        .await
-        .map_err(|e| format!("Submit client fingerprint rules failed: {}", e))?;
-
-    let status = response.status();
-    let response_text = response
-        .text()
-        .await
-        .map_err(|e| format!("Read client fingerprint rule response failed: {}", e))?;
-    if !status.is_success() {
-        return Err(format!(
-            "Client fingerprint rules were rejected (status {}): {}",
-            status, response_text
-        ));
-    }
-
-    let result: ClientFingerprintRuleInstallResponse = serde_json::from_str(&response_text)
-        .map_err(|e| {
-            format!(
-                "Parse client fingerprint rule response failed: {}; body: {}",
-                e, response_text
-            )
-        })?;
-    if !result.status {
-        return Err("Client fingerprint rule response reported failure".to_string());
-    }
-    Ok(result)
+        .await?)
 }
 
 pub fn create_https_client() -> Result<reqwest::Client, String> {
@@ -932,171 +887,89 @@ pub fn create_sse_https_client() -> Result<reqwest::Client, String> {
     SSE_HTTPS_CLIENT.as_ref().cloned().map_err(Clone::clone)
 }
 
-fn validate_config_save_response(body: &str) -> Result<(), String> {
-    let response: serde_json::Value = serde_json::from_str(body)
-        .map_err(|error| format!("Sunshine Config API returned invalid JSON: {error}"))?;
-    let succeeded = match response.get("status") {
-        Some(serde_json::Value::Bool(status)) => *status,
-        Some(serde_json::Value::String(status)) => status.eq_ignore_ascii_case("true"),
-        _ => {
-            return Err("Sunshine Config API response is missing a valid status".to_string());
-        }
-    };
-    if succeeded {
-        return Ok(());
-    }
-
-    let detail = response
-        .get("error")
-        .and_then(serde_json::Value::as_str)
-        .filter(|error| !error.trim().is_empty())
-        .unwrap_or("Sunshine rejected the configuration");
-    Err(format!(
-        "Sunshine Config API rejected configuration: {detail}"
-    ))
+/// POST 配置数据到 Sunshine Config API，保留错误分类供调用方区分拒绝与传输失败。
+/// 调用方都以本机 sunshine.conf 为基础构造整份配置，配置快照也只覆盖本机文件，
+/// 因此始终写入本机 Core，不随主窗口切换到其他主机。
+pub async fn save_sunshine_config(
+    config_data: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), CoreApiError> {
+    CoreClient::local()
+        .await?
+        .post("api/config")
+        .json(config_data)?
+        .envelope(Envelope::Status)
+        .max_response_bytes(MAX_CONFIG_SAVE_RESPONSE_BYTES)
+        .send_value()
+        .await?;
+    Ok(())
 }
 
 /// POST 配置数据到 Sunshine Config API
-/// 封装了获取 URL、创建客户端、POST 请求和错误处理的完整流程
 pub async fn post_sunshine_config(
     config_data: &serde_json::Map<String, serde_json::Value>,
 ) -> Result<(), String> {
-    let sunshine_url = get_sunshine_url()
-        .await
-        .map_err(|e| format!("Cannot get Sunshine URL: {}", e))?;
-    let config_url = format!("{}/api/config", sunshine_url.trim_end_matches('/'));
-
-    let client = create_https_client()?;
-    let mut response = client
-        .post(&config_url)
-        .json(config_data)
-        .send()
-        .await
-        .map_err(|e| format!("调用 Sunshine Config API 失败: {}", e))?;
+    Ok(save_sunshine_config(config_data).await?)
+}
 
-    let status = response.status();
-    if response
-        .content_length()
-        .is_some_and(|size| size > MAX_CONFIG_SAVE_RESPONSE_BYTES as u64)
-    {
-        return Err("Sunshine Config API response exceeds 64 KiB".to_string());
-    }
-    let mut response_bytes = Vec::with_capacity(
-        response
-            .content_length()
-            .unwrap_or_default()
-            .min(MAX_CONFIG_SAVE_RESPONSE_BYTES as u64) as usize,
-    );
-    while let Some(chunk) = response
-        .chunk()
+async fn fetch_sessions(client: CoreClient) -> Result<Vec<SessionInfo>, CoreApiError> {
+    match client
+        .get("api/runtime/sessions")
+        .envelope(Envelope::Success)
+        .send::<SessionsResponse>()
         .await
-        .map_err(|error| format!("无法读取 Sunshine Config API 响应: {error}"))?
     {
-        if response_bytes.len().saturating_add(chunk.len()) > MAX_CONFIG_SAVE_RESPONSE_BYTES {
-            return Err("Sunshine Config API response exceeds 64 KiB".to_string());
+        Ok(response) => Ok(response.sessions),
+        // 没有会话或 Core 不支持该接口时返回空列表
+        Err(CoreApiError::NotFound(_)) => {
+            debug!("⚠️ 没有活动会话 (404 或 XML 响应)");
+            Ok(Vec::new())
         }
-        response_bytes.extend_from_slice(&chunk);
-    }
-    let response_body = String::from_utf8(response_bytes)
-        .map_err(|error| format!("Sunshine Config API 响应不是有效 UTF-8: {error}"))?;
-    if !status.is_success() {
-        return Err(format!(
-            "Sunshine Config API 返回错误 (状态: {}): {}",
-            status, response_body
-        ));
+        Err(error) => Err(error),
     }
-    validate_config_save_response(&response_body)
+}
+
+/// Sessions on this machine's Core, regardless of which host the main window targets.
+pub async fn get_local_sessions() -> Result<Vec<SessionInfo>, CoreApiError> {
+    fetch_sessions(CoreClient::local().await?).await
 }
 
 #[tauri::command]
 pub async fn get_active_sessions() -> Result<Vec<SessionInfo>, String> {
-    let sunshine_url = get_sunshine_url().await?;
-    let sessions_url = format!(
-        "{}/api/runtime/sessions",
-        sunshine_url.trim_end_matches('/')
-    );
-
-    debug!("📡 获取活动会话: {}", sessions_url);
-
-    let client = create_https_client()?;
-
-    let response = client
-        .get(&sessions_url)
-        .send()
-        .await
-        .map_err(|e| format!("请求会话信息失败: {}", e))?;
-
-    let status = response.status();
-
-    debug!("📡 获取 sessions 响应状态码: {}", status);
+    let result = match CoreClient::target().await {
+        Ok(client) => fetch_sessions(client).await,
+        Err(error) => Err(error),
+    };
+    let sessions = match result {
+        Ok(sessions) => sessions,
+        Err(error) => {
+            error!("❌ 获取会话信息失败: {}", error);
+            return Err(format!("获取会话信息失败: {}", error.detail()));
+        }
+    };
 
-    // 检查 Content-Type
-    let content_type = response
-        .headers()
-        .get("content-type")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("")
-        .to_lowercase();
+    info!("✅ 获取到 {} 个活动会话", sessions.len());
+    Ok(sessions)
+}
 
-    let response_text = response
-        .text()
+async fn request_bitrate(
+    client: &CoreClient,
+    client_name: &str,
+    bitrate: u32,
+) -> Result<(), CoreApiError> {
+    client
+        .get("api/runtime/bitrate")
+        .query("bitrate", bitrate)
+        .query("clientname", client_name)
+        .envelope(Envelope::Success)
+        .send_value()
         .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
-
-    debug!("📡 获取 sessions 响应内容: {}", response_text);
-
-    // 如果是 404 或 XML 响应，返回空数组（没有会话是正常情况）
-    if status == 404
-        || content_type.contains("xml")
-        || response_text.trim_start().starts_with("<?xml")
-    {
-        debug!("⚠️ 没有活动会话 (404 或 XML 响应)");
-        return Ok(Vec::new());
-    }
-
-    // 如果状态码不是成功，但也不是 404，返回错误
-    if !status.is_success() {
-        error!("❌ 错误响应: {}", response_text);
-        return Err(format!(
-            "获取会话信息失败 (状态: {}): {}",
-            status, response_text
-        ));
-    }
-
-    // 尝试解析 JSON
-    let json: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("解析 JSON 失败: {}，响应内容: {}", e, response_text))?;
-
-    debug!("📡 解析后的 JSON: {:#}", json);
-
-    // 检查 API 响应状态
-    if json.get("success").and_then(|v| v.as_bool()) == Some(false) {
-        let error_msg = json
-            .get("status_message")
-            .and_then(|v| v.as_str())
-            .unwrap_or("未知错误");
-        return Err(format!("API 返回错误: {}", error_msg));
-    }
-
-    // 解析会话列表
-    let sessions = json
-        .get("sessions")
-        .and_then(|v| v.as_array())
-        .map(|sessions_array| {
-            debug!("📡 找到 {} 个会话", sessions_array.len());
-            sessions_array
-                .iter()
-                .map(SessionInfo::from_json)
-                .collect::<Vec<_>>()
-        })
-        .unwrap_or_else(|| {
-            warn!("⚠️ 响应中没有 'sessions' 字段或不是数组");
-            debug!("📡 JSON 结构: {:#}", json);
-            Vec::new()
-        });
+        .map(|_| ())
+}
 
-    info!("✅ 获取到 {} 个活动会话", sessions.len());
-    Ok(sessions)
+/// Change a client's bitrate on this machine's Core without the UI messages
+/// `change_bitrate` produces.
+pub async fn change_local_bitrate(client_name: &str, bitrate: u32) -> Result<(), CoreApiError> {
+    request_bitrate(&CoreClient::local().await?, client_name, bitrate).await
 }
 
 #[tauri::command]
@@ -1106,88 +979,28 @@ pub async fn change_bitrate(client_name: String, bitrate: u32) -> Result<String,
         return Err("码率值必须在 1-800000 Kbps 之间".to_string());
     }
 
-    // 构建请求 URL
-    let sunshine_url = get_sunshine_url().await?;
-    let base_url =
-        Url::parse(&sunshine_url).map_err(|e| format!("解析 Sunshine URL 失败: {}", e))?;
-
-    let mut change_bitrate_url = base_url
-        .join("api/runtime/bitrate")
-        .map_err(|e| format!("构建 URL 失败: {}", e))?;
-
-    change_bitrate_url
-        .query_pairs_mut()
-        .append_pair("bitrate", &bitrate.to_string())
-        .append_pair("clientname", &client_name);
-
     info!("📡 调整码率: {} -> {} Kbps", client_name, bitrate);
-    debug!("📡 请求 URL: {}", change_bitrate_url);
-
-    // 发送请求
-    let client = create_https_client()?;
-    let response = client
-        .get(change_bitrate_url.as_str())
-        .send()
-        .await
-        .map_err(|e| format!("请求调整码率失败: {}", e))?;
-
-    let status = response.status();
-    debug!("📡 HTTP 状态码: {}", status);
 
-    // 读取响应内容
-    let response_text = response
-        .text()
-        .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
-
-    // 检查 HTTP 状态码
-    if !status.is_success() {
-        return Err(match status.as_u16() {
-            401 => "身份验证失败，请检查 Sunshine 配置".to_string(),
-            403 => "访问被拒绝，仅允许 localhost 访问".to_string(),
-            _ => format!("HTTP 错误 (状态码: {}): {}", status, response_text),
-        });
-    }
-
-    // 解析 JSON 响应
-    let json: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("解析 JSON 失败: {}，响应内容: {}", e, response_text))?;
-
-    debug!("📡 解析后的 JSON: {:#}", json);
-
-    // 检查响应状态
-    match json.get("success").and_then(|v| v.as_bool()) {
-        Some(true) => {
+    let result = match CoreClient::target().await {
+        Ok(client) => request_bitrate(&client, &client_name, bitrate).await,
+        Err(error) => Err(error),
+    };
+    match result {
+        Ok(_) => {
             info!("✅ 码率调整成功");
             Ok(format!("码率已调整为 {} Kbps", bitrate))
         }
-        Some(false) => {
-            let error_msg = json
-                .get("status_message")
-                .and_then(|v| v.as_str())
-                .unwrap_or("未知错误");
-            let status_code = json
-                .get("status_code")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0);
-
-            error!("❌ 码率调整失败: {} (状态码: {})", error_msg, status_code);
-
-            // 根据状态码提供详细提示
-            let error_message = if status_code == 404 {
-                format!(
+        Err(error) => {
+            error!("❌ 码率调整失败: {}", error);
+            Err(match error {
+                CoreApiError::Auth(_) => "身份验证失败，请检查 Sunshine 配置".to_string(),
+                CoreApiError::Forbidden(_) => "访问被拒绝，仅允许 localhost 访问".to_string(),
+                CoreApiError::NotFound(detail) => format!(
                     "码率调整失败: {}\n\n提示：请确认客户端名称是否正确，或会话是否处于 RUNNING 状态",
-                    error_msg
-                )
-            } else {
-                format!("码率调整失败: {}", error_msg)
-            };
-
-            Err(error_message)
-        }
-        None => {
-            warn!("⚠️ 响应格式无效，无法解析 success 字段");
-            Err("无效的响应格式".to_string())
+                    detail
+                ),
+                error => format!("码率调整失败: {}", error.detail()),
+            })
         }
     }
 }
@@ -1234,6 +1047,32 @@ pub(crate) fn is_sunshine_running_in_user_mode_impl() -> Result<bool, String> {
     }
 }
 
+/// 检查 SunshineService 是否处于已停止状态（服务被有意停止，而不是卡死）
+/// 内部实现：执行 sc 阻塞调用，必须在 spawn_blocking 中调用
+pub(crate) fn is_sunshine_service_stopped_impl() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        use std::process::Command;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        Command::new("sc")
+            .args(["query", "SunshineService"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .is_ok_and(|result| {
+                String::from_utf8_lossy(&result.stdout)
+                    .to_uppercase()
+                    .contains("STOPPED")
+            })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        false
+    }
+}
+
 /// 检查 Sunshine 是否以用户模式运行（Tauri 命令，在后台线程执行阻塞逻辑，避免卡住 WebView）
 #[tauri::command]
 pub async fn is_sunshine_running_in_user_mode() -> Result<bool, String> {
@@ -1268,11 +1107,21 @@ fn build_start_service_command(sunshine_path: &std::path::Path) -> String {
     )
 }
 
+/// 构建以用户模式启动的命令片段
+#[cfg(target_os = "windows")]
+fn build_start_user_mode_command(sunshine_path: &std::path::Path) -> String {
+    format!(
+        "Set-Location '{}'; Start-Process -FilePath '.\\sunshine.exe' -Verb RunAs -WindowStyle Hidden",
+        sunshine_path.display()
+    )
+}
+
 /// 切换 Sunshine 运行模式（用户模式 ↔ 服务模式）
 #[tauri::command]
 pub async fn toggle_sunshine_mode() -> Result<String, String> {
     #[cfg(target_os = "windows")]
     {
+        crate::core_watchdog::hold_off();
         let is_user_mode = tokio::task::spawn_blocking(is_sunshine_running_in_user_mode_impl)
             .await
             .ok()
@@ -1293,10 +1142,7 @@ pub async fn toggle_sunshine_mode() -> Result<String, String> {
             ("服务模式", format!("{}; {}", stop_cmd, start_cmd))
         } else {
             info!("🔄 切换 Sunshine 模式：服务模式 → 用户模式");
-            let start_cmd = format!(
-                "Set-Location '{}'; Start-Process -FilePath '.\\sunshine.exe' -Verb RunAs -WindowStyle Hidden",
-                sunshine_path.display()
-            );
+            let start_cmd = build_start_user_mode_command(&sunshine_path);
             ("用户模式", format!("{}; {}", stop_cmd, start_cmd))
         };
 
@@ -1316,6 +1162,7 @@ pub async fn toggle_sunshine_mode() -> Result<String, String> {
 pub async fn restart_sunshine_service() -> Result<String, String> {
     #[cfg(target_os = "windows")]
     {
+        crate::core_watchdog::hold_off();
         info!("🔄 开始重启 Sunshine 服务...");
 
         let sunshine_path = get_sunshine_path();
@@ -1335,6 +1182,104 @@ pub async fn restart_sunshine_service() -> Result<String, String> {
     }
 }
 
+/// Why an unattended restart did not happen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnattendedRestartError {
+    /// The Core runs with more privileges than the GUI. Only an elevated
+    /// restart or, for the service, the SCM's recovery actions can bring it
+    /// back.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    NotPermitted(String),
+    Failed(String),
+}
+
+impl std::fmt::Display for UnattendedRestartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotPermitted(message) | Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Exit code of the user-mode restart script when `sunshine.exe` survives
+/// `Stop-Process`, i.e. it runs elevated (`ERROR_ACCESS_DENIED`).
+#[cfg(target_os = "windows")]
+const RESTART_ACCESS_DENIED: i32 = 5;
+
+/// 看门狗自动重启 Sunshine：不请求提权，因此不会弹出 UAC。
+/// 服务只在 GUI 以管理员身份运行时重启，否则交给服务的故障恢复选项；
+/// 以管理员权限运行的用户模式进程无法被结束，此时报告 `NotPermitted`。
+pub async fn restart_sunshine_unattended(user_mode: bool) -> Result<(), UnattendedRestartError> {
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+        let command = if user_mode {
+            info!("🔄 看门狗以用户模式重启 Sunshine（不提权）...");
+            format!(
+                "$core = Get-Process -Name sunshine -ErrorAction SilentlyContinue; \
+                 if ($core) {{ \
+                     $core | Stop-Process -Force -ErrorAction SilentlyContinue; \
+                     Start-Sleep -Seconds 1; \
+                     if (Get-Process -Name sunshine -ErrorAction SilentlyContinue) {{ exit {} }} \
+                 }}; \
+                 Set-Location '{}'; \
+                 Start-Process -FilePath '.\\sunshine.exe' -WindowStyle Hidden -ErrorAction Stop",
+                RESTART_ACCESS_DENIED,
+                get_sunshine_path()
+                    .display()
+                    .to_string()
+                    .replace('\'', "''")
+            )
+        } else {
+            let is_admin = tokio::task::spawn_blocking(crate::utils::is_running_as_admin)
+                .await
+                .ok()
+                .and_then(|r| r.ok())
+                .unwrap_or(false);
+            if !is_admin {
+                return Err(UnattendedRestartError::NotPermitted(
+                    "看门狗无权重启 Sunshine 服务，由服务的故障恢复选项负责重启".to_string(),
+                ));
+            }
+            info!("🔄 看门狗重启 Sunshine 服务...");
+            "Restart-Service -Name 'SunshineService' -Force -ErrorAction Stop".to_string()
+        };
+        let output = tokio::process::Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                command.as_str(),
+            ])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .await
+            .map_err(|e| UnattendedRestartError::Failed(format!("启动重启命令失败: {}", e)))?;
+        if output.status.success() {
+            return Ok(());
+        }
+        if output.status.code() == Some(RESTART_ACCESS_DENIED) {
+            return Err(UnattendedRestartError::NotPermitted(
+                "看门狗无法重启以管理员权限运行的 Sunshine".to_string(),
+            ));
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(UnattendedRestartError::Failed(format!(
+            "重启 Sunshine 失败（未提权）: {}",
+            stderr.trim()
+        )))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = user_mode;
+        Err(UnattendedRestartError::Failed(
+            "此功能仅支持 Windows".to_string(),
+        ))
+    }
+}
+
 /// 以用户模式重启 Sunshine（非服务模式，但需要管理员权限）
 /// @deprecated 使用 sunshine::toggle_sunshine_mode 代替
 #[tauri::command]
@@ -1345,29 +1290,24 @@ pub async fn restart_sunshine_in_user_mode() -> Result<String, String> {
 /// 获取 Sunshine 配置中的 locale 设置（通过 API 获取实时值）
 #[tauri::command]
 pub async fn get_sunshine_locale() -> Result<String, String> {
-    let sunshine_url = get_sunshine_url()
-        .await
-        .map_err(|e| format!("Cannot get Sunshine URL: {}", e))?;
-
-    let locale_url = format!("{}/api/configLocale", sunshine_url.trim_end_matches('/'));
-    let client = create_https_client()?;
-
-    let response = client
-        .get(&locale_url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to get locale from Sunshine API: {}", e))?;
+    let result = match CoreClient::target().await {
+        Ok(client) => {
+            client
+                .get("api/configLocale")
+                .send::<LocaleResponse>()
+                .await
+        }
+        Err(error) => Err(error),
+    };
 
-    if response.status().is_success() {
-        let json: serde_json::Value = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse locale response: {}", e))?;
-        Ok(json["locale"].as_str().unwrap_or("en").to_string())
-    } else {
-        // API 不可达时回退到本地配置文件
-        let config = parse_sunshine_config().await?;
-        Ok(config.locale.unwrap_or_else(|| "en".to_string()))
+    match result {
+        Ok(response) => Ok(response.locale.unwrap_or_else(|| "en".to_string())),
+        Err(error) => {
+            // API 不可达时回退到本地配置文件
+            debug!("Reading locale from Sunshine API failed: {}", error);
+            let config = parse_sunshine_config().await?;
+            Ok(config.locale.unwrap_or_else(|| "en".to_string()))
+        }
     }
 }
 
@@ -1386,6 +1326,10 @@ pub async fn set_sunshine_locale(locale: String) -> Result<String, String> {
             .unwrap_or_default();
         config_data.insert("locale".to_string(), serde_json::json!(locale));
 
+        crate::config_snapshots::snapshot_before_write(
+            crate::config_snapshots::SnapshotReason::LocaleChange,
+            Some(locale.clone()),
+        )?;
         post_sunshine_config(&config_data).await?;
         info!("✅ Locale updated to '{}' via Sunshine API", locale);
         Ok("success".to_string())
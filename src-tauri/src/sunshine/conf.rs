@@ -0,0 +1,488 @@
+//! Lossless reader and writer for `sunshine.conf`.
+//!
+//! The file is kept as a list of items that remember their original text, so
+//! rendering an unmodified document reproduces the input byte for byte and
+//! line numbers of invalid lines can be reported. Editing a key only rewrites
+//! the lines of that entry; comments, blank lines and ordering around it stay
+//! untouched, and the file keeps its line ending style. Writes normally go
+//! through the Core's Config API; the file is only edited while the Core is
+//! not running.
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    key: String,
+    /// Trimmed value. Multi-line `[...]` arrays keep one trimmed line per
+    /// element joined with `\n`, the same shape the Config API accepts.
+    value: String,
+    /// Original lines without line terminators. `None` once edited, in which
+    /// case the entry is rendered as `key = value`.
+    raw: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    /// Blank lines, comments and any other line that is not `key = value`.
+    Verbatim(String),
+    Entry(Entry),
+}
+
+/// A line that is neither blank, a comment nor a `key = value` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidLine {
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SunshineConf {
+    items: Vec<Item>,
+    newline: &'static str,
+    trailing_newline: bool,
+}
+
+impl Default for SunshineConf {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            newline: default_newline(),
+            trailing_newline: true,
+        }
+    }
+}
+
+fn default_newline() -> &'static str {
+    if cfg!(windows) { "\r\n" } else { "\n" }
+}
+
+fn is_verbatim(trimmed: &str) -> bool {
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.trim().split_once('=')?;
+    let key = key.trim();
+    (!key.is_empty()).then(|| (key, value.trim()))
+}
+
+impl SunshineConf {
+    /// Parse config text. Parsing never fails: lines that are not entries
+    /// are preserved verbatim and reported by [`Self::invalid_lines`].
+    pub fn parse(content: &str) -> Self {
+        let newline = if content.contains("\r\n") {
+            "\r\n"
+        } else if content.contains('\n') {
+            "\n"
+        } else {
+            default_newline()
+        };
+        let trailing_newline = content.is_empty() || content.ends_with('\n');
+
+        let lines: Vec<&str> = content
+            .lines()
+            .map(|line| line.strip_suffix('\r').unwrap_or(line))
+            .collect();
+        let mut items = Vec::new();
+        let mut index = 0;
+
+        while index < lines.len() {
+            let line = lines[index];
+            let entry = if is_verbatim(line.trim()) {
+                None
+            } else {
+                split_entry(line)
+            };
+            let Some((key, first_value)) = entry else {
+                items.push(Item::Verbatim(line.to_string()));
+                index += 1;
+                continue;
+            };
+
+            let mut value = first_value.to_string();
+            let mut raw = vec![line.to_string()];
+            if value.starts_with('[') && !value.ends_with(']') {
+                while index + 1 < lines.len() {
+                    index += 1;
+                    let next = lines[index].trim();
+                    value.push('\n');
+                    value.push_str(next);
+                    raw.push(lines[index].to_string());
+                    if next.ends_with(']') {
+                        break;
+                    }
+                }
+            }
+
+            items.push(Item::Entry(Entry {
+                key: key.to_string(),
+                value,
+                raw: Some(raw),
+            }));
+            index += 1;
+        }
+
+        Self {
+            items,
+            newline,
+            trailing_newline,
+        }
+    }
+
+    /// Read a config file. A missing file yields an empty document.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Self::parse(&content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("read {} failed: {}", path.display(), e)),
+        }
+    }
+
+    /// Write the document through a temporary file so readers never see a
+    /// partially written config.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let temp_path = path.with_extension("conf.tmp");
+        fs::write(&temp_path, self.to_string())
+            .map_err(|e| format!("write {} failed: {}", temp_path.display(), e))?;
+        fs::rename(&temp_path, path).map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            format!("replace {} failed: {}", path.display(), e)
+        })
+    }
+
+    fn entries_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Entry> {
+        self.items.iter_mut().filter_map(|item| match item {
+            Item::Entry(entry) => Some(entry),
+            Item::Verbatim(_) => None,
+        })
+    }
+
+    fn all_entries(&self) -> impl DoubleEndedIterator<Item = &Entry> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Entry(entry) => Some(entry),
+            Item::Verbatim(_) => None,
+        })
+    }
+
+    /// Value of `key`. When a key repeats, the last occurrence wins, as in
+    /// Sunshine itself.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.all_entries()
+            .rev()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.value.as_str())
+    }
+
+    /// Effective entries in file order, one per key.
+    pub fn entries(&self) -> Vec<(&str, &str)> {
+        let mut seen = std::collections::HashSet::new();
+        let mut entries: Vec<(&str, &str)> = self
+            .all_entries()
+            .rev()
+            .filter(|entry| seen.insert(entry.key.as_str()))
+            .map(|entry| (entry.key.as_str(), entry.value.as_str()))
+            .collect();
+        entries.reverse();
+        entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.all_entries().next().is_none()
+    }
+
+    /// Set `key`, rewriting only its effective entry, or append it at the end.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        if let Some(entry) = self.entries_mut().rev().find(|entry| entry.key == key) {
+            if entry.value != value {
+                entry.value = value.to_string();
+                entry.raw = None;
+            }
+            return;
+        }
+        self.items.push(Item::Entry(Entry {
+            key: key.to_string(),
+            value: value.to_string(),
+            raw: None,
+        }));
+    }
+
+    /// Remove every occurrence of `key` and return the effective value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let value = self.get(key).map(str::to_string);
+        self.items
+            .retain(|item| !matches!(item, Item::Entry(entry) if entry.key == key));
+        value
+    }
+
+    /// Lines that Sunshine would not understand.
+    pub fn invalid_lines(&self) -> Vec<InvalidLine> {
+        let mut invalid = Vec::new();
+        let mut line = 1;
+        for item in &self.items {
+            match item {
+                Item::Verbatim(text) => {
+                    if !is_verbatim(text.trim()) {
+                        invalid.push(InvalidLine {
+                            line,
+                            text: text.clone(),
+                        });
+                    }
+                    line += 1;
+                }
+                Item::Entry(entry) => {
+                    line += entry.raw.as_ref().map_or(1, Vec::len);
+                }
+            }
+        }
+        invalid
+    }
+
+    /// Effective entries as the string map the Config API expects.
+    pub fn to_map(&self) -> serde_json::Map<String, serde_json::Value> {
+        self.entries()
+            .into_iter()
+            .map(|(key, value)| {
+                (
+                    key.to_string(),
+                    serde_json::Value::String(value.to_string()),
+                )
+            })
+            .collect()
+    }
+
+    fn get_trimmed(&self, key: &str) -> Option<&str> {
+        self.get(key)
+            .map(|value| value.trim_matches('"'))
+            .filter(|value| !value.is_empty())
+    }
+
+    /// Parse a value with `FromStr`, ignoring values that do not parse.
+    pub fn get_parsed<T: std::str::FromStr>(&self, key: &str) -> Option<T> {
+        self.get_trimmed(key)?.parse().ok()
+    }
+
+    /// Sunshine accepts `true/false`, `enabled/disabled`, `on/off`, `yes/no`
+    /// and `1/0` for boolean options.
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get_trimmed(key)?.to_ascii_lowercase().as_str() {
+            "true" | "enabled" | "on" | "yes" | "1" => Some(true),
+            "false" | "disabled" | "off" | "no" | "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Elements of an `[a, b]` array value, one per comma or line.
+    pub fn get_list(&self, key: &str) -> Option<Vec<String>> {
+        let value = self.get(key)?.trim();
+        let inner = value.strip_prefix('[')?.strip_suffix(']')?;
+        Some(
+            inner
+                .split([',', '\n'])
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    /// Base port; the Web UI listens on `port + 1`.
+    pub fn port(&self) -> Option<u16> {
+        self.get_parsed("port")
+    }
+
+    pub fn locale(&self) -> Option<&str> {
+        self.get_trimmed("locale")
+    }
+
+    pub fn adapter_name(&self) -> Option<&str> {
+        self.get_trimmed("adapter_name")
+    }
+
+    pub fn output_name(&self) -> Option<&str> {
+        self.get_trimmed("output_name")
+    }
+
+    /// Capture backend such as `wgc` or `ddx`; `None` means automatic.
+    pub fn capture(&self) -> Option<&str> {
+        self.get_trimmed("capture")
+    }
+
+    pub fn gamepad(&self) -> Option<&str> {
+        self.get_trimmed("gamepad")
+    }
+
+    pub fn resolutions(&self) -> Option<Vec<String>> {
+        self.get_list("resolutions")
+    }
+
+    pub fn fps(&self) -> Option<Vec<u32>> {
+        self.get_list("fps")
+            .map(|items| items.iter().filter_map(|fps| fps.parse().ok()).collect())
+    }
+}
+
+impl fmt::Display for SunshineConf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines: Vec<String> = Vec::new();
+        for item in &self.items {
+            match item {
+                Item::Verbatim(text) => lines.push(text.clone()),
+                Item::Entry(Entry { raw: Some(raw), .. }) => lines.extend(raw.iter().cloned()),
+                Item::Entry(Entry {
+                    key,
+                    value,
+                    raw: None,
+                }) => {
+                    let mut value_lines = value.split('\n');
+                    lines.push(format!("{} = {}", key, value_lines.next().unwrap_or("")));
+                    lines.extend(value_lines.map(str::to_string));
+                }
+            }
+        }
+
+        f.write_str(&lines.join(self.newline))?;
+        if self.trailing_newline && !lines.is_empty() {
+            f.write_str(self.newline)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "# Sunshine config\r\n\r\nport = 47989\r\nresolutions = [\r\n    1920x1080,\r\n    2560x1440\r\n]\r\n  # indented comment\r\nlocale=zh_TW\r\n";
+
+    #[test]
+    fn unmodified_documents_round_trip_exactly() {
+        for content in [SAMPLE, "", "a = 1", "a = 1\n\n\n", "garbage line\nb = 2\n"] {
+            assert_eq!(SunshineConf::parse(content).to_string(), content);
+        }
+    }
+
+    #[test]
+    fn multi_line_arrays_are_single_entries() {
+        let conf = SunshineConf::parse(SAMPLE);
+        assert_eq!(conf.get("resolutions"), Some("[\n1920x1080,\n2560x1440\n]"));
+        assert_eq!(
+            conf.resolutions(),
+            Some(vec!["1920x1080".to_string(), "2560x1440".to_string()])
+        );
+        assert_eq!(conf.locale(), Some("zh_TW"));
+        assert_eq!(conf.port(), Some(47989));
+        assert_eq!(
+            conf.entries(),
+            [
+                ("port", "47989"),
+                ("resolutions", "[\n1920x1080,\n2560x1440\n]"),
+                ("locale", "zh_TW")
+            ]
+        );
+    }
+
+    #[test]
+    fn edits_only_touch_the_affected_lines() {
+        let mut conf = SunshineConf::parse(SAMPLE);
+        conf.set("port", "48000");
+        conf.set("locale", "zh_TW");
+        conf.set("capture", "wgc");
+
+        assert_eq!(
+            conf.to_string(),
+            "# Sunshine config\r\n\r\nport = 48000\r\nresolutions = [\r\n    1920x1080,\r\n    2560x1440\r\n]\r\n  # indented comment\r\nlocale=zh_TW\r\ncapture = wgc\r\n"
+        );
+
+        conf.remove("resolutions");
+        assert!(!conf.to_string().contains("1920x1080"));
+        assert!(conf.to_string().contains("# indented comment"));
+    }
+
+    #[test]
+    fn edited_documents_round_trip() {
+        let mut conf = SunshineConf::parse(SAMPLE);
+        conf.set("resolutions", "[\n1280x720,\n1920x1080\n]");
+        conf.set("output_name", "\\\\.\\DISPLAY2");
+        conf.remove("port");
+
+        let rendered = conf.to_string();
+        assert_eq!(
+            rendered,
+            "# Sunshine config\r\n\r\nresolutions = [\r\n1280x720,\r\n1920x1080\r\n]\r\n  # indented comment\r\nlocale=zh_TW\r\noutput_name = \\\\.\\DISPLAY2\r\n"
+        );
+        let reparsed = SunshineConf::parse(&rendered);
+        assert_eq!(reparsed.entries(), conf.entries());
+        assert_eq!(reparsed.to_string(), rendered);
+        assert_eq!(
+            reparsed.resolutions(),
+            Some(vec!["1280x720".to_string(), "1920x1080".to_string()])
+        );
+        assert_eq!(reparsed.output_name(), Some("\\\\.\\DISPLAY2"));
+        assert_eq!(reparsed.port(), None);
+    }
+
+    #[test]
+    fn last_duplicate_wins_and_is_the_one_edited() {
+        let mut conf = SunshineConf::parse("fps = 30\nfps = [60, 120]\n");
+        assert_eq!(conf.fps(), Some(vec![60, 120]));
+
+        conf.set("fps", "[90]");
+        assert_eq!(conf.to_string(), "fps = 30\nfps = [90]\n");
+        assert_eq!(conf.remove("fps").as_deref(), Some("[90]"));
+        assert!(conf.is_empty());
+    }
+
+    #[test]
+    fn typed_accessors_tolerate_quotes_and_bad_values() {
+        let conf = SunshineConf::parse(
+            "capture = \"wgc\"\ngamepad = \"\"\nport = not-a-number\nkeyboard = disabled\nmouse = On\nds5 = maybe\nadapter_name = \"Radeon\"\n",
+        );
+        assert_eq!(conf.capture(), Some("wgc"));
+        assert_eq!(conf.gamepad(), None);
+        assert_eq!(conf.adapter_name(), Some("Radeon"));
+        assert_eq!(conf.port(), None);
+        assert_eq!(conf.get_bool("ds5"), None);
+        assert_eq!(conf.get_bool("keyboard"), Some(false));
+        assert_eq!(conf.get_bool("mouse"), Some(true));
+        assert_eq!(conf.get_bool("missing"), None);
+    }
+
+    #[test]
+    fn invalid_lines_report_their_line_numbers() {
+        let conf = SunshineConf::parse("a = [\n1,\n2]\nno equals here\n = orphan\n");
+        let invalid = conf.invalid_lines();
+        assert_eq!(
+            invalid,
+            [
+                InvalidLine {
+                    line: 4,
+                    text: "no equals here".to_string()
+                },
+                InvalidLine {
+                    line: 5,
+                    text: " = orphan".to_string()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn save_replaces_the_file_atomically() {
+        let dir = std::env::temp_dir().join(format!("sunshine-conf-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sunshine.conf");
+
+        let mut conf = SunshineConf::load(&path).unwrap();
+        assert!(conf.is_empty());
+        conf.set("locale", "en");
+        conf.save(&path).unwrap();
+
+        assert_eq!(SunshineConf::load(&path).unwrap().locale(), Some("en"));
+        assert!(!path.with_extension("conf.tmp").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
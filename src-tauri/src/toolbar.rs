@@ -290,7 +290,8 @@ pub fn create_tool_window_internal<R: Runtime>(app: &AppHandle<R>, tool_type: &s
             .shadow(false)
             .always_on_top(true)
             .skip_taskbar(true)
-            .visible(false);
+            .visible(false)
+            .initialization_script(crate::proxy_server::webview_access_script());
 
     if is_performance_monitor {
         builder = builder
@@ -444,6 +445,7 @@ pub fn create_toolbar_window_internal<R: Runtime>(app: &AppHandle<R>) -> Result<
     .always_on_top(true)
     .skip_taskbar(true)
     .visible(false) // 先隐藏，等设置好位置再显示
+    .initialization_script(crate::proxy_server::webview_access_script())
     .build()
     {
         Ok(win) => {
@@ -18,15 +18,19 @@ use crate::desktop_settings;
 use crate::{proxy_server, sunshine, toolbar, tray_config, update, utils, windows};
 
 mod actions;
+mod apps_menu;
 mod events;
+mod i18n;
 mod icons;
 mod main_panel;
 mod menu;
 mod notifications;
+mod sessions_menu;
 #[cfg(target_os = "windows")]
 mod vdd_confirmation;
 
 pub use actions::{cleanup_prevent_sleep, handle_tray_menu_event};
+use i18n::TrayStrings;
 use menu::{build_tray_menu, tray_status_label};
 #[cfg(test)]
 use menu::{compact_menu_text, tray_notification_label};
@@ -41,7 +45,7 @@ static PREVENT_SLEEP_STATE: Mutex<bool> = Mutex::new(false);
 #[cfg(target_os = "windows")]
 static SUNSHINE_USER_MODE_STATE: Mutex<bool> = Mutex::new(false);
 
-// 当前语言状态管理 ("zh" 或 "en")
+// 当前语言状态管理 (i18n::LOCALES 中的语言代码)
 static CURRENT_LOCALE: Mutex<Option<String>> = Mutex::new(None);
 static LOCALE_CHANGE_REVISION: AtomicU64 = AtomicU64::new(0);
 static LOCALE_PERSIST_LOCK: Lazy<tokio::sync::Mutex<()>> =
@@ -78,9 +82,9 @@ fn emit_tray_locale_changed<R: Runtime>(
         .map_err(|error| format!("Failed to broadcast locale change: {error}"))
 }
 
-// Last icon name applied from the Sunshine core state. This avoids repeatedly
-// decoding the same .ico file during the polling loop.
-static CURRENT_CORE_ICON: Mutex<Option<String>> = Mutex::new(None);
+// Last icon state applied from the Sunshine core state. This avoids repeatedly
+// handing the same image to the tray during the polling loop.
+static CURRENT_CORE_ICON: Mutex<Option<icons::TrayIconState>> = Mutex::new(None);
 static MONITORED_STATE_RECEIPT: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -220,302 +224,9 @@ pub(crate) fn emit_message<R: Runtime>(app: &AppHandle<R>, msg_type: &str, messa
     }
 }
 
-/// 托盘菜单翻译结构
-struct TrayStrings {
-    status_idle: &'static str,
-    status_streaming: &'static str,
-    status_paused: &'static str,
-    status_pairing: &'static str,
-    status_notification: &'static str,
-    status_connecting: &'static str,
-    status_disconnected: &'static str,
-    notification: &'static str,
-    client_connected: &'static str,
-    client_connected_detail: &'static str,
-    client_connected_named: &'static str,
-    suspicious_client_warning: &'static str,
-    client_disconnected: &'static str,
-    client_disconnected_detail: &'static str,
-    client_disconnected_named: &'static str,
-    incoming_pairing: &'static str,
-    pairing_instruction: &'static str,
-    pairing_menu_instruction: &'static str,
-    complete_pairing: &'static str,
-    open_main_panel: &'static str,
-    interfaces_menu: &'static str,
-    display_menu: &'static str,
-    tools_menu: &'static str,
-    settings_menu: &'static str,
-    advanced_menu: &'static str,
-    help_menu: &'static str,
-    open_sunshine: &'static str,
-    vdd_settings: &'static str,
-    vdd_create: &'static str,
-    vdd_create_confirm: &'static str,
-    vdd_close: &'static str,
-    vdd_keep: &'static str,
-    vdd_keep_confirm: &'static str,
-    vdd_headless: &'static str,
-    vdd_headless_confirm: &'static str,
-    import_config: &'static str,
-    export_config: &'static str,
-    reset_config: &'static str,
-    reset_config_confirm: &'static str,
-    clear_cache: &'static str,
-    clear_cache_confirm: &'static str,
-    reset_display: &'static str,
-    reset_display_confirm: &'static str,
-    restart_user_mode: &'static str,
-    show_toolbar: &'static str,
-    prevent_sleep: &'static str,
-    rtss_control: &'static str,
-    host_performance: &'static str,
-    log_console: &'static str,
-    open_desktop: &'static str,
-    #[cfg(target_os = "windows")]
-    auto_start: &'static str,
-    #[cfg(any(debug_assertions, feature = "beta"))]
-    web_stream: &'static str,
-    #[cfg(debug_assertions)]
-    debug_page: &'static str,
-    check_update: &'static str,
-    about: &'static str,
-    shutdown: &'static str,
-    shutdown_message: &'static str,
-    language: &'static str,
-    star_project: &'static str,
-    visit_project_sunshine: &'static str,
-    visit_project_moonlight: &'static str,
-    restart: &'static str,
-    recover_service: &'static str,
-    recovery_timeout: &'static str,
-    tooltip: &'static str,
-    tooltip_admin: &'static str,
-}
-
-const ZH_STRINGS: TrayStrings = TrayStrings {
-    status_idle: "空闲",
-    status_streaming: "串流中",
-    status_paused: "串流已暂停",
-    status_pairing: "等待配对",
-    status_notification: "有新通知",
-    status_connecting: "正在连接",
-    status_disconnected: "未连接",
-    notification: "通知",
-    client_connected: "客户端已连接",
-    client_connected_detail: "已建立新的串流连接。",
-    client_connected_named: "「{name}」已连接到这台电脑。",
-    suspicious_client_warning: "警告：您很可能正在受到未知侵权客户端的侵害。该判断基于客户端网络特征，可能存在误判。",
-    client_disconnected: "客户端已断开",
-    client_disconnected_detail: "串流连接已结束。",
-    client_disconnected_named: "「{name}」已从这台电脑断开。",
-    incoming_pairing: "Moonlight 配对请求",
-    pairing_instruction: "点击此通知，输入 Moonlight 上显示的 4 位 PIN。",
-    pairing_menu_instruction: "打开 Sunshine 托盘菜单中的“完成配对”，输入 Moonlight 上显示的 4 位 PIN。",
-    complete_pairing: "完成配对",
-    open_main_panel: "打开主面板",
-    interfaces_menu: "其他界面",
-    display_menu: "显示",
-    tools_menu: "工具",
-    settings_menu: "设置",
-    advanced_menu: "高级",
-    help_menu: "帮助",
-    open_sunshine: "Web 管理界面",
-    vdd_settings: "基地显示器设置（ZakoVDD）",
-    vdd_create: "创建基地显示器",
-    vdd_create_confirm: "创建基地显示器可能会更改 Windows 显示布局。是否继续？",
-    vdd_close: "关闭基地显示器",
-    vdd_keep: "串流结束后保留",
-    vdd_keep_confirm: "启用后，串流结束时将保留基地显示器，并可能更改 Windows 显示布局。是否继续？",
-    vdd_headless: "无头模式自动创建",
-    vdd_headless_confirm: "这是实验性恢复功能：未检测到显示器时会自动创建基地显示器，并可能更改当前显示布局。是否继续？",
-    import_config: "导入配置",
-    export_config: "导出配置",
-    reset_config: "重置配置",
-    reset_config_confirm: "这将清空 sunshine.conf，并让 Sunshine 恢复默认配置。是否继续？",
-    clear_cache: "清理缓存",
-    clear_cache_confirm: "这将终止当前运行的应用，以便重建缓存状态。是否继续？",
-    reset_display: "重置显示",
-    reset_display_confirm: "这将重置已保存的显示设备状态。是否继续？",
-    restart_user_mode: "以用户模式运行 Sunshine",
-    show_toolbar: "显示桌宠",
-    prevent_sleep: "阻止系统休眠",
-    rtss_control: "RTSS 控制",
-    host_performance: "主机性能",
-    log_console: "日志控制台",
-    open_desktop: "大屏模式",
-    #[cfg(target_os = "windows")]
-    auto_start: "开机运行",
-    #[cfg(any(debug_assertions, feature = "beta"))]
-    web_stream: "Web 串流服务",
-    #[cfg(debug_assertions)]
-    debug_page: "调试页面",
-    check_update: "检查更新",
-    about: "关于 Sunshine",
-    shutdown: "退出 Sunshine",
-    shutdown_message: "这将停止 Sunshine 服务、GUI 和托盘。确定要退出吗？",
-    language: "语言",
-    star_project: "项目主页",
-    visit_project_sunshine: "Sunshine 源代码",
-    visit_project_moonlight: "Moonlight 源代码",
-    restart: "重启 Sunshine",
-    recover_service: "重新启动 Sunshine 服务",
-    recovery_timeout: "Sunshine 服务未能在等待时间内恢复，请检查服务日志后重试。",
-    tooltip: "Sunshine GUI",
-    tooltip_admin: "Sunshine GUI (管理员)",
-};
-
-const EN_STRINGS: TrayStrings = TrayStrings {
-    status_idle: "Idle",
-    status_streaming: "Streaming",
-    status_paused: "Stream paused",
-    status_pairing: "Pairing",
-    status_notification: "New notification",
-    status_connecting: "Connecting",
-    status_disconnected: "Disconnected",
-    notification: "Notification",
-    client_connected: "Client connected",
-    client_connected_detail: "A new streaming connection is ready.",
-    client_connected_named: "“{name}” connected to this computer.",
-    suspicious_client_warning: "Warning: You are very likely being affected by an unknown infringing client. This heuristic assessment may be inaccurate.",
-    client_disconnected: "Client disconnected",
-    client_disconnected_detail: "The streaming connection has ended.",
-    client_disconnected_named: "“{name}” disconnected from this computer.",
-    incoming_pairing: "Moonlight pairing request",
-    pairing_instruction: "Click this notification to enter the 4-digit PIN shown in Moonlight.",
-    pairing_menu_instruction: "Open Complete Pairing from the Sunshine tray menu and enter the 4-digit PIN shown in Moonlight.",
-    complete_pairing: "Complete pairing",
-    open_main_panel: "Open Main Panel",
-    interfaces_menu: "Other Interfaces",
-    display_menu: "Display",
-    tools_menu: "Tools",
-    settings_menu: "Settings",
-    advanced_menu: "Advanced",
-    help_menu: "Help",
-    open_sunshine: "Web Management UI",
-    vdd_settings: "Virtual Display Settings",
-    vdd_create: "Create Virtual Display",
-    vdd_create_confirm: "This creates a virtual display and may change the Windows display layout. Continue?",
-    vdd_close: "Close Virtual Display",
-    vdd_keep: "Keep After Streaming",
-    vdd_keep_confirm: "Keep mode leaves the virtual display enabled after streaming ends and may change the Windows display layout. Continue?",
-    vdd_headless: "Auto-create When Headless",
-    vdd_headless_confirm: "This experimental recovery feature creates a virtual display when no display is detected and may change the active display layout. Continue?",
-    import_config: "Import Config",
-    export_config: "Export Config",
-    reset_config: "Reset Config",
-    reset_config_confirm: "This clears sunshine.conf and restores Sunshine defaults. Continue?",
-    clear_cache: "Clear Cache",
-    clear_cache_confirm: "This terminates the running application so cached state can be rebuilt. Continue?",
-    reset_display: "Reset Display",
-    reset_display_confirm: "This resets persisted display-device state. Continue?",
-    restart_user_mode: "Run Sunshine in User Mode",
-    show_toolbar: "Show Desktop Pet",
-    prevent_sleep: "Prevent System Sleep",
-    rtss_control: "RTSS Control",
-    host_performance: "Host Performance",
-    log_console: "Log Console",
-    open_desktop: "Large Screen Mode",
-    #[cfg(target_os = "windows")]
-    auto_start: "Run at Startup",
-    #[cfg(any(debug_assertions, feature = "beta"))]
-    web_stream: "Web Streaming",
-    #[cfg(debug_assertions)]
-    debug_page: "Debug Page",
-    check_update: "Check for Updates",
-    about: "About Sunshine",
-    shutdown: "Exit Sunshine",
-    shutdown_message: "This will stop the Sunshine service, GUI, and tray. Continue?",
-    language: "Language",
-    star_project: "Project Website",
-    visit_project_sunshine: "Sunshine Source Code",
-    visit_project_moonlight: "Moonlight Source Code",
-    restart: "Restart Sunshine",
-    recover_service: "Restart Sunshine Service",
-    recovery_timeout: "The Sunshine service did not recover in time. Check the service log and try again.",
-    tooltip: "Sunshine GUI",
-    tooltip_admin: "Sunshine GUI (Admin)",
-};
-
-const JA_STRINGS: TrayStrings = TrayStrings {
-    status_idle: "待機中",
-    status_streaming: "ストリーミング中",
-    status_paused: "ストリーム一時停止",
-    status_pairing: "ペアリング待機中",
-    status_notification: "新しい通知",
-    status_connecting: "接続中",
-    status_disconnected: "未接続",
-    notification: "通知",
-    client_connected: "クライアントが接続しました",
-    client_connected_detail: "新しいストリーミング接続が確立されました。",
-    client_connected_named: "「{name}」がこのコンピューターに接続しました。",
-    suspicious_client_warning: "警告：不明な権利侵害クライアントが使用されている可能性が非常に高いです。この判定はヒューリスティックであり、誤判定の可能性があります。",
-    client_disconnected: "クライアントが切断されました",
-    client_disconnected_detail: "ストリーミング接続が終了しました。",
-    client_disconnected_named: "「{name}」がこのコンピューターから切断されました。",
-    incoming_pairing: "Moonlight ペアリング要求",
-    pairing_instruction: "この通知をクリックし、Moonlight に表示された 4 桁の PIN を入力してください。",
-    pairing_menu_instruction: "Sunshine のトレイメニューから「ペアリングを完了」を開き、Moonlight に表示された 4 桁の PIN を入力してください。",
-    complete_pairing: "ペアリングを完了",
-    open_main_panel: "メインパネルを開く",
-    interfaces_menu: "その他のインターフェース",
-    display_menu: "ディスプレイ",
-    tools_menu: "ツール",
-    settings_menu: "設定",
-    advanced_menu: "詳細設定",
-    help_menu: "ヘルプ",
-    open_sunshine: "Web 管理画面",
-    vdd_settings: "仮想ディスプレイ設定",
-    vdd_create: "仮想ディスプレイを作成",
-    vdd_create_confirm: "仮想ディスプレイを作成すると、Windows のディスプレイ配置が変更される場合があります。続行しますか？",
-    vdd_close: "仮想ディスプレイを閉じる",
-    vdd_keep: "ストリーミング後も保持",
-    vdd_keep_confirm: "有効にすると、ストリーミング終了後も仮想ディスプレイが保持され、Windows のディスプレイ配置が変更される場合があります。続行しますか？",
-    vdd_headless: "ヘッドレス時に自動作成",
-    vdd_headless_confirm: "これは実験的な復旧機能です。ディスプレイが検出されない場合に仮想ディスプレイを作成し、現在の配置が変更される場合があります。続行しますか？",
-    import_config: "設定をインポート",
-    export_config: "設定をエクスポート",
-    reset_config: "設定をリセット",
-    reset_config_confirm: "sunshine.conf を消去し、Sunshine の既定設定に戻します。続行しますか？",
-    clear_cache: "キャッシュを消去",
-    clear_cache_confirm: "キャッシュ状態を再構築するため、実行中のアプリケーションを終了します。続行しますか？",
-    reset_display: "ディスプレイをリセット",
-    reset_display_confirm: "保存されているディスプレイデバイスの状態をリセットします。続行しますか？",
-    restart_user_mode: "ユーザーモードで Sunshine を実行",
-    show_toolbar: "デスクトップペットを表示",
-    prevent_sleep: "システムのスリープを防止",
-    rtss_control: "RTSS コントロール",
-    host_performance: "ホストパフォーマンス",
-    log_console: "ログコンソール",
-    open_desktop: "大画面モード",
-    #[cfg(target_os = "windows")]
-    auto_start: "起動時に実行",
-    #[cfg(any(debug_assertions, feature = "beta"))]
-    web_stream: "Web ストリーミング",
-    #[cfg(debug_assertions)]
-    debug_page: "デバッグページ",
-    check_update: "更新を確認",
-    about: "Sunshine について",
-    shutdown: "Sunshine を終了",
-    shutdown_message: "Sunshine サービス、GUI、トレイを停止します。続行しますか？",
-    language: "言語",
-    star_project: "プロジェクトサイト",
-    visit_project_sunshine: "Sunshine ソースコード",
-    visit_project_moonlight: "Moonlight ソースコード",
-    restart: "Sunshine を再起動",
-    recover_service: "Sunshine サービスを再起動",
-    recovery_timeout: "Sunshine サービスが時間内に復旧しませんでした。サービスログを確認して再試行してください。",
-    tooltip: "Sunshine GUI",
-    tooltip_admin: "Sunshine GUI (管理者)",
-};
-
 fn get_tray_strings() -> &'static TrayStrings {
     let locale = CURRENT_LOCALE.lock().unwrap();
-    match locale.as_deref() {
-        Some("zh") => &ZH_STRINGS,
-        Some("ja") => &JA_STRINGS,
-        _ => &EN_STRINGS,
-    }
+    i18n::strings(locale.as_deref().unwrap_or(i18n::FALLBACK_LOCALE))
 }
 
 fn get_current_locale() -> String {
@@ -523,7 +234,7 @@ fn get_current_locale() -> String {
         .lock()
         .unwrap()
         .clone()
-        .unwrap_or_else(|| "en".to_string())
+        .unwrap_or_else(|| i18n::FALLBACK_LOCALE.to_string())
 }
 
 #[cfg(target_os = "windows")]
@@ -565,29 +276,25 @@ pub fn create_system_tray<R: Runtime + 'static>(app: &AppHandle<R>) -> tauri::Re
     if let Err(error) = notifications::initialize(app) {
         warn!("{}", error);
     }
+    // Load the rules now, so the old desktop-setting switches are migrated
+    // before the next settings save drops them.
+    notifications::rules::current();
 
     initialize_tray_locale();
     build_owned_system_tray(app)?;
+    icons::start_bitrate_poller(app);
     events::start_tray_state_monitoring(app);
+    apps_menu::start_watcher(app);
 
     Ok(())
 }
 
 fn try_normalize_tray_locale(locale: &str) -> Option<&'static str> {
-    let locale = locale.trim().to_ascii_lowercase().replace('_', "-");
-    if locale == "zh" || locale.starts_with("zh-") {
-        Some("zh")
-    } else if locale == "ja" || locale.starts_with("ja-") {
-        Some("ja")
-    } else if locale == "en" || locale.starts_with("en-") {
-        Some("en")
-    } else {
-        None
-    }
+    i18n::match_locale(locale)
 }
 
 fn normalize_tray_locale(locale: &str) -> &'static str {
-    try_normalize_tray_locale(locale).unwrap_or("en")
+    try_normalize_tray_locale(locale).unwrap_or(i18n::FALLBACK_LOCALE)
 }
 
 fn resolve_initial_tray_locale(
@@ -599,17 +306,23 @@ fn resolve_initial_tray_locale(
         .filter(|locale| !locale.trim().is_empty())
         .map(normalize_tray_locale)
         .or_else(|| system_locale.and_then(try_normalize_tray_locale))
-        .unwrap_or("en")
+        .unwrap_or(i18n::FALLBACK_LOCALE)
         .to_string()
 }
 
 #[cfg(target_os = "windows")]
 fn locale_from_windows_ui_language(language_id: u16) -> Option<&'static str> {
-    // Match the primary language portion of the Windows LANGID.
+    // Match the primary language portion of the Windows LANGID; Chinese also
+    // needs the sublanguage to tell Taiwan, Hong Kong and Macau apart.
     match language_id & 0x03ff {
+        0x0004 if matches!(language_id >> 10, 0x01 | 0x03 | 0x05) => Some("zh_TW"),
         0x0004 => Some("zh"),
         0x0011 => Some("ja"),
         0x0009 => Some("en"),
+        0x0012 => Some("ko"),
+        0x0007 => Some("de"),
+        0x000c => Some("fr"),
+        0x0019 => Some("ru"),
         _ => None,
     }
 }
@@ -833,7 +546,7 @@ mod tests {
         state.notification.action = "open_pin".to_string();
 
         assert_eq!(
-            tray_notification_label(&ZH_STRINGS, &state),
+            tray_notification_label(i18n::strings("zh"), &state),
             "完成配对: Moonlight Client"
         );
     }
@@ -844,7 +557,7 @@ mod tests {
         state.app_name = "Desktop".to_string();
 
         assert_eq!(
-            tray_status_label(&ZH_STRINGS, Some(&state), CoreConnectionState::Connected),
+            tray_status_label(i18n::strings("zh"), Some(&state), CoreConnectionState::Connected),
             "Sunshine · 串流中: Desktop"
         );
     }
@@ -852,7 +565,7 @@ mod tests {
     #[test]
     fn menu_status_uses_connecting_without_core_state() {
         assert_eq!(
-            tray_status_label(&EN_STRINGS, None, CoreConnectionState::Connecting),
+            tray_status_label(i18n::strings("en"), None, CoreConnectionState::Connecting),
             "Sunshine · Connecting"
         );
     }
@@ -860,7 +573,7 @@ mod tests {
     #[test]
     fn menu_status_distinguishes_disconnected_core() {
         assert_eq!(
-            tray_status_label(&EN_STRINGS, None, CoreConnectionState::Disconnected),
+            tray_status_label(i18n::strings("en"), None, CoreConnectionState::Disconnected),
             "Sunshine · Disconnected"
         );
     }
@@ -870,7 +583,9 @@ mod tests {
         assert_eq!(normalize_tray_locale("zh-CN"), "zh");
         assert_eq!(normalize_tray_locale("ja-JP"), "ja");
         assert_eq!(normalize_tray_locale("en-US"), "en");
-        assert_eq!(normalize_tray_locale("fr-FR"), "en");
+        assert_eq!(normalize_tray_locale("zh-Hant-TW"), "zh_TW");
+        assert_eq!(normalize_tray_locale("fr-FR"), "fr");
+        assert_eq!(normalize_tray_locale("pt-BR"), "en");
     }
 
     #[test]
@@ -894,7 +609,7 @@ mod tests {
         };
         assert_eq!(
             resolve_initial_tray_locale(Some(&config), Some("en-US")),
-            "zh"
+            "zh_TW"
         );
 
         assert_eq!(resolve_initial_tray_locale(None, Some("ja-JP")), "ja");
@@ -907,13 +622,16 @@ mod tests {
         assert_eq!(locale_from_windows_ui_language(0x0804), Some("zh"));
         assert_eq!(locale_from_windows_ui_language(0x0411), Some("ja"));
         assert_eq!(locale_from_windows_ui_language(0x0409), Some("en"));
-        assert_eq!(locale_from_windows_ui_language(0x040c), None);
+        assert_eq!(locale_from_windows_ui_language(0x0404), Some("zh_TW"));
+        assert_eq!(locale_from_windows_ui_language(0x0c04), Some("zh_TW"));
+        assert_eq!(locale_from_windows_ui_language(0x040c), Some("fr"));
+        assert_eq!(locale_from_windows_ui_language(0x0415), None);
     }
 
     #[test]
     fn unsupported_persisted_ui_locale_maps_to_english() {
         let config = sunshine::SunshineConfig {
-            locale: Some("fr".to_string()),
+            locale: Some("pl".to_string()),
             ..Default::default()
         };
 
@@ -925,25 +643,25 @@ mod tests {
 
     #[test]
     fn primary_desktop_actions_have_clear_localized_labels() {
-        assert_eq!(ZH_STRINGS.open_desktop, "大屏模式");
-        assert_eq!(ZH_STRINGS.show_toolbar, "显示桌宠");
+        assert_eq!(i18n::strings("zh").open_desktop, "大屏模式");
+        assert_eq!(i18n::strings("zh").show_toolbar, "显示桌宠");
         #[cfg(target_os = "windows")]
-        assert_eq!(ZH_STRINGS.auto_start, "开机运行");
-        assert_eq!(EN_STRINGS.open_desktop, "Large Screen Mode");
+        assert_eq!(i18n::strings("zh").auto_start, "开机运行");
+        assert_eq!(i18n::strings("en").open_desktop, "Large Screen Mode");
         #[cfg(target_os = "windows")]
-        assert_eq!(EN_STRINGS.auto_start, "Run at Startup");
-        assert_eq!(ZH_STRINGS.shutdown, "退出 Sunshine");
-        assert_eq!(EN_STRINGS.shutdown, "Exit Sunshine");
+        assert_eq!(i18n::strings("en").auto_start, "Run at Startup");
+        assert_eq!(i18n::strings("zh").shutdown, "退出 Sunshine");
+        assert_eq!(i18n::strings("en").shutdown, "Exit Sunshine");
     }
 
     #[test]
     fn native_confirmations_use_localized_tray_text() {
-        assert!(ZH_STRINGS.vdd_create_confirm.contains("基地显示器"));
-        assert!(ZH_STRINGS.vdd_keep_confirm.contains("串流结束"));
-        assert!(ZH_STRINGS.vdd_headless_confirm.contains("实验性"));
-        assert!(ZH_STRINGS.reset_config_confirm.contains("默认配置"));
-        assert!(ZH_STRINGS.clear_cache_confirm.contains("缓存状态"));
-        assert!(ZH_STRINGS.reset_display_confirm.contains("显示设备"));
+        assert!(i18n::strings("zh").vdd_create_confirm.contains("基地显示器"));
+        assert!(i18n::strings("zh").vdd_keep_confirm.contains("串流结束"));
+        assert!(i18n::strings("zh").vdd_headless_confirm.contains("实验性"));
+        assert!(i18n::strings("zh").reset_config_confirm.contains("默认配置"));
+        assert!(i18n::strings("zh").clear_cache_confirm.contains("缓存状态"));
+        assert!(i18n::strings("zh").reset_display_confirm.contains("显示设备"));
     }
 
     #[test]
@@ -1018,6 +736,22 @@ fn apply_tray_state<R: Runtime + 'static>(
         return;
     };
 
+    // Profiles follow sessions regardless of which process owns the tray. A new
+    // Core instance ends every session the old one had.
+    crate::client_profiles::observe_core_instance(&state.instance_id);
+    for change in notifications::connection_changes(previous_state.as_ref(), state) {
+        match change {
+            notifications::ConnectionChange::Connected {
+                client_name,
+                client_fingerprint,
+                ..
+            } => crate::client_profiles::on_client_connected(client_name, client_fingerprint),
+            notifications::ConnectionChange::Disconnected(client_name) => {
+                crate::client_profiles::on_client_disconnected(client_name)
+            }
+        }
+    }
+
     if state.owner != "gui" {
         if app.remove_tray_by_id(TRAY_ID).is_some() {
             info!(
@@ -1048,11 +782,33 @@ fn apply_tray_state<R: Runtime + 'static>(
             debug!("Failed to update tray tooltip from core state: {}", e);
         }
     }
-    icons::apply_tray_icon(app, state.icon.as_str());
+    icons::apply_tray_icon(app, state);
 }
 
 fn apply_core_disconnected<R: Runtime + 'static>(app: &AppHandle<R>) {
-    let should_rebuild_menu = TRAY_RUNTIME_STATE.lock().unwrap().mark_disconnected();
+    let (should_rebuild_menu, lost_unannounced) = {
+        let mut runtime = TRAY_RUNTIME_STATE.lock().unwrap();
+        // The Core announces its own restarts and shutdowns before it goes.
+        let lost_unannounced = runtime.connection == CoreConnectionState::Connected
+            && !runtime.tray_state.as_ref().is_some_and(|state| {
+                matches!(state.operation.action.as_str(), "restart" | "shutdown")
+            });
+        (runtime.mark_disconnected(), lost_unannounced)
+    };
+    if lost_unannounced {
+        let notify_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if crate::core_watchdog::core_stop_expected().await {
+                return;
+            }
+            let app_handle = notify_handle.clone();
+            if let Err(e) = notify_handle.run_on_main_thread(move || {
+                notify_core_crash(&app_handle);
+            }) {
+                debug!("Failed to schedule core crash notification: {}", e);
+            }
+        });
+    }
 
     if let Err(e) = build_owned_system_tray(app) {
         error!(
@@ -1256,3 +1012,131 @@ pub async fn set_locale_preferences(
 pub fn get_tray_locale() -> Option<String> {
     CURRENT_LOCALE.lock().unwrap().clone()
 }
+
+#[tauri::command]
+pub fn get_tray_apps_settings() -> apps_menu::TrayAppsSettings {
+    apps_menu::current_settings()
+}
+
+#[tauri::command]
+pub fn save_tray_apps_settings(
+    app: AppHandle,
+    settings: apps_menu::TrayAppsSettings,
+) -> Result<apps_menu::TrayAppsSettings, String> {
+    let settings = apps_menu::save_settings(settings)?;
+    info!(
+        "🎮 托盘应用菜单设置已保存: {} 个收藏",
+        settings.favorites.len()
+    );
+    rebuild_tray_menu(&app);
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn get_notification_rules() -> notifications::rules::NotificationRules {
+    notifications::rules::current()
+}
+
+#[tauri::command]
+pub fn save_notification_rules(
+    rules: notifications::rules::NotificationRules,
+) -> Result<notifications::rules::NotificationRules, String> {
+    let rules = notifications::rules::save(rules)?;
+    info!("🔔 通知规则已保存: {} 条规则", rules.rules.len());
+    Ok(rules)
+}
+
+#[tauri::command]
+pub fn get_notification_history() -> Vec<notifications::history::NotificationRecord> {
+    notifications::history::list()
+}
+
+/// Mark history entries read; every entry when `ids` is omitted.
+#[tauri::command]
+pub fn acknowledge_notifications(app: AppHandle, ids: Option<Vec<u64>>) -> Result<usize, String> {
+    notifications::history::acknowledge(&app, ids)
+}
+
+#[tauri::command]
+pub fn clear_notification_history(app: AppHandle) -> Result<usize, String> {
+    notifications::history::clear(&app)
+}
+
+/// Whether the notification rules let an update announcement through now.
+pub(crate) fn update_notification_allowed() -> bool {
+    notifications::rules::allows(
+        notifications::rules::NotificationEvent::UpdateAvailable,
+        None,
+    )
+}
+
+/// Show or hide the bitrate bar on the tray icon.
+pub(crate) fn set_bitrate_bar<R: Runtime>(app: &AppHandle<R>, enabled: bool) {
+    icons::set_bitrate_bar(app, enabled);
+}
+
+/// Client sessions in the most recent tray state, empty while the Core is away.
+pub(crate) fn current_client_sessions() -> Vec<sunshine::TrayClientSession> {
+    TRAY_RUNTIME_STATE
+        .lock()
+        .unwrap()
+        .tray_state
+        .as_ref()
+        .map(|state| state.sessions.clone())
+        .unwrap_or_default()
+}
+
+pub(crate) fn is_core_connected() -> bool {
+    TRAY_RUNTIME_STATE.lock().unwrap().connection == CoreConnectionState::Connected
+}
+
+/// A manual service recovery from the tray is waiting for the Core.
+pub(crate) fn is_core_recovery_in_progress() -> bool {
+    TRAY_RUNTIME_STATE.lock().unwrap().recovery.is_in_progress()
+}
+
+/// Tell the user the Core went away without anyone asking it to.
+fn notify_core_crash<R: Runtime>(app: &AppHandle<R>) {
+    if !notifications::rules::allows(notifications::rules::NotificationEvent::CoreCrash, None) {
+        return;
+    }
+    let strings = get_tray_strings();
+    match notifications::show(app, strings.core_crashed, strings.core_crashed_detail, None) {
+        Ok(()) => notifications::history::record(
+            app,
+            strings.core_crashed,
+            strings.core_crashed_detail,
+            Some(notifications::rules::NotificationEvent::CoreCrash),
+            "",
+            None,
+        ),
+        Err(e) => warn!("{}", e),
+    }
+}
+
+/// Tell the user the watchdog stopped restarting a crash-looping Core.
+pub(crate) fn notify_core_crash_loop<R: Runtime>(
+    app: &AppHandle<R>,
+    restarts: usize,
+    minutes: u64,
+) {
+    if !notifications::rules::allows(notifications::rules::NotificationEvent::CoreCrash, None) {
+        return;
+    }
+    let strings = get_tray_strings();
+    let body = strings
+        .watchdog_crash_loop_detail
+        .replace("{count}", &restarts.to_string())
+        .replace("{minutes}", &minutes.to_string());
+    match notifications::show(app, strings.watchdog_crash_loop, &body, None) {
+        Ok(()) => notifications::history::record(
+            app,
+            strings.watchdog_crash_loop,
+            &body,
+            Some(notifications::rules::NotificationEvent::CoreCrash),
+            "",
+            None,
+        ),
+        Err(e) => warn!("{}", e),
+    }
+}
@@ -72,9 +72,6 @@ pub fn handle_tray_menu_event<R: Runtime + 'static>(app: &AppHandle<R>, menu_id:
             let _ = windows::open_about_window(app);
         }
         "shutdown" => shutdown_sunshine(app),
-        "lang_zh" => switch_tray_locale(app, "zh"),
-        "lang_en" => switch_tray_locale(app, "en"),
-        "lang_ja" => switch_tray_locale(app, "ja"),
         "star_project" => utils::open_url_in_browser("https://www.alkaidlab.com/"),
         "visit_project_sunshine" => {
             utils::open_url_in_browser("https://github.com/AlkaidLab/foundation-sunshine")
@@ -83,7 +80,17 @@ pub fn handle_tray_menu_event<R: Runtime + 'static>(app: &AppHandle<R>, menu_id:
             utils::open_url_in_browser("https://github.com/qiin2333/moonlight-vplus")
         }
         "restart" => restart_sunshine(app),
-        _ => warn!("⚠️ 未知的托盘菜单事件: {}", menu_id),
+        _ => {
+            if let Some(locale) = menu_id.strip_prefix("lang_") {
+                switch_tray_locale(app, locale);
+            } else if let Some(name) = menu_id.strip_prefix(apps_menu::LAUNCH_PREFIX) {
+                apps_menu::launch(app, name);
+            } else if !sessions_menu::handle_menu_event(app, menu_id)
+                && !notifications::history::handle_menu_event(app, menu_id)
+            {
+                warn!("⚠️ 未知的托盘菜单事件: {}", menu_id);
+            }
+        }
     }
 }
 
@@ -137,23 +144,26 @@ fn handle_tray_notification_action<R: Runtime>(app: &AppHandle<R>) {
     }
 }
 
+/// Pairing requests come from this machine's Core, so the proxy points at it
+/// while the PIN window is open and returns to the registry's active host once
+/// the window closes.
 pub(super) fn open_pairing_window<R: Runtime>(app: &AppHandle<R>) {
     let app_handle = app.clone();
     tauri::async_runtime::spawn(async move {
         match sunshine::get_local_sunshine_url().await {
             Ok(url) => {
                 proxy_server::set_sunshine_target(url);
+                proxy_server::set_sunshine_target_credentials(None);
                 proxy_server::ensure_started();
                 let pin_handle = app_handle.clone();
-                if let Err(e) = app_handle.run_on_main_thread(move || {
-                    if let Err(e) = windows::open_pin_window(&pin_handle) {
-                        error!("Failed to open PIN window from tray notification: {}", e);
-                    }
-                }) {
+                if let Err(e) =
+                    app_handle.run_on_main_thread(move || open_local_pin_window(&pin_handle))
+                {
                     error!(
                         "Failed to schedule PIN window from tray notification: {}",
                         e
                     );
+                    crate::host_registry::restore_active_host();
                 }
             }
             Err(e) => {
@@ -164,6 +174,16 @@ pub(super) fn open_pairing_window<R: Runtime>(app: &AppHandle<R>) {
     });
 }
 
+fn open_local_pin_window<R: Runtime>(app: &AppHandle<R>) {
+    match windows::open_pin_window(app) {
+        Ok(()) => windows::on_pin_window_closed(app, crate::host_registry::restore_active_host),
+        Err(e) => {
+            error!("Failed to open PIN window from tray notification: {}", e);
+            crate::host_registry::restore_active_host();
+        }
+    }
+}
+
 fn acknowledge_notification<R: Runtime>(app: &AppHandle<R>, notification_id: u64) {
     let app_handle = app.clone();
     tauri::async_runtime::spawn(async move {
@@ -261,35 +281,46 @@ fn run_tray_action<R: Runtime>(app: &AppHandle<R>, action: &'static str, enabled
     let app_handle = app.clone();
 
     tauri::async_runtime::spawn(async move {
-        match sunshine::post_tray_action(action, enabled).await {
-            Ok(response) => {
-                if let Some(state) = response.tray_state {
-                    apply_tray_state_on_main_thread(&app_handle, state);
-                }
+        let result = sunshine::post_tray_action(action, enabled).await;
+        report_tray_action_result(&app_handle, action, result);
+    });
+}
 
-                if response.status {
-                    if !response.message.is_empty() {
-                        emit_message(&app_handle, "success", &response.message);
-                    }
-                } else {
-                    let message = if response.error.is_empty() {
-                        "Tray action failed".to_string()
-                    } else {
-                        response.error
-                    };
-                    error!("Tray action '{}' failed: {}", action, message);
-                    emit_message(&app_handle, "error", &message);
-                }
+/// Apply the state a tray action returned and surface its outcome.
+pub(super) fn report_tray_action_result<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    action: &str,
+    result: Result<sunshine::TrayActionResponse, String>,
+) {
+    match result {
+        Ok(response) => {
+            if let Some(state) = response.tray_state {
+                apply_tray_state_on_main_thread(app_handle, state);
             }
-            Err(e) => {
-                debug!("Tray action '{}' skipped: {}", action, e);
-                emit_message(&app_handle, "error", &e);
+
+            if response.status {
+                if !response.message.is_empty() {
+                    emit_message(app_handle, "success", &response.message);
+                }
+            } else {
+                let message = if response.error.is_empty() {
+                    "Tray action failed".to_string()
+                } else {
+                    response.error
+                };
+                error!("Tray action '{}' failed: {}", action, message);
+                emit_message(app_handle, "error", &message);
             }
         }
-    });
+        Err(e) => {
+            debug!("Tray action '{}' skipped: {}", action, e);
+            emit_message(app_handle, "error", &e);
+        }
+    }
 }
 
 fn restart_sunshine<R: Runtime>(app: &AppHandle<R>) {
+    crate::core_watchdog::hold_off();
     let app_handle = app.clone();
 
     tauri::async_runtime::spawn(async move {
@@ -0,0 +1,337 @@
+//! "Apps" submenu that launches `apps.json` entries from the tray.
+//!
+//! Favourites come first, then the remaining apps in `apps.json` order up to
+//! `maxItems` entries in total; anything beyond that goes into a "More"
+//! submenu. Entries are identified by name so a menu built before an edit
+//! never launches the wrong app. `apps.json` is polled for changes and the
+//! menu rebuilt when it moves. Settings live in
+//! `<config>/Sunshine GUI/tray-apps.json`.
+
+use super::*;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use tauri::menu::{IsMenuItem, MenuItem, PredefinedMenuItem, Submenu};
+
+pub(super) const LAUNCH_PREFIX: &str = "launch_app:";
+const SETTINGS_FILE: &str = "tray-apps.json";
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_LABEL_CHARS: usize = 40;
+const MAX_OVERFLOW_ITEMS: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TrayAppsSettings {
+    pub enabled: bool,
+    /// Entries shown directly in the submenu, favourites included.
+    pub max_items: usize,
+    /// App names pinned to the top, in this order.
+    pub favorites: Vec<String>,
+}
+
+impl Default for TrayAppsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_items: 10,
+            favorites: Vec::new(),
+        }
+    }
+}
+
+impl TrayAppsSettings {
+    fn validated(mut self) -> Result<Self, String> {
+        if !(1..=30).contains(&self.max_items) {
+            return Err("maxItems must be between 1 and 30".to_string());
+        }
+        let mut seen = HashSet::new();
+        self.favorites = self
+            .favorites
+            .into_iter()
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty() && seen.insert(name.clone()))
+            .collect();
+        Ok(self)
+    }
+}
+
+struct AppsSnapshot {
+    modified: Option<SystemTime>,
+    names: Vec<String>,
+}
+
+static SETTINGS: Lazy<Mutex<TrayAppsSettings>> = Lazy::new(|| Mutex::new(load_settings()));
+static SNAPSHOT: Lazy<Mutex<AppsSnapshot>> = Lazy::new(|| Mutex::new(read_snapshot()));
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(SETTINGS_FILE))
+}
+
+fn load_settings() -> TrayAppsSettings {
+    let Ok(path) = settings_path() else {
+        return TrayAppsSettings::default();
+    };
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return TrayAppsSettings::default();
+    };
+    serde_json::from_str::<TrayAppsSettings>(&text)
+        .map_err(|e| e.to_string())
+        .and_then(TrayAppsSettings::validated)
+        .unwrap_or_else(|e| {
+            warn!("Invalid tray apps settings, using defaults: {}", e);
+            TrayAppsSettings::default()
+        })
+}
+
+pub(super) fn current_settings() -> TrayAppsSettings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+pub(super) fn save_settings(settings: TrayAppsSettings) -> Result<TrayAppsSettings, String> {
+    let settings = settings.validated()?;
+    let path = settings_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())?;
+    *SETTINGS.lock().unwrap() = settings.clone();
+    Ok(settings)
+}
+
+fn apps_modified() -> Option<SystemTime> {
+    std::fs::metadata(crate::apps::apps_path())
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn read_snapshot() -> AppsSnapshot {
+    let modified = apps_modified();
+    let names = match crate::apps::load_apps_file(&crate::apps::apps_path()) {
+        Ok(file) => file.apps.into_iter().map(|app| app.name).collect(),
+        Err(e) => {
+            warn!("托盘应用菜单: {}", e);
+            Vec::new()
+        }
+    };
+    AppsSnapshot { modified, names }
+}
+
+/// Rebuild the menu whenever `apps.json` is written, by the GUI or the WebUI.
+pub(super) fn start_watcher<R: Runtime + 'static>(app: &AppHandle<R>) {
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let modified = tokio::task::spawn_blocking(apps_modified)
+                .await
+                .ok()
+                .flatten();
+            if SNAPSHOT.lock().unwrap().modified == modified {
+                continue;
+            }
+            let Ok(snapshot) = tokio::task::spawn_blocking(read_snapshot).await else {
+                continue;
+            };
+            let changed = {
+                let mut current = SNAPSHOT.lock().unwrap();
+                let changed = current.names != snapshot.names;
+                *current = snapshot;
+                changed
+            };
+            if changed && current_settings().enabled {
+                debug!("apps.json changed, rebuilding tray apps menu");
+                let rebuild_handle = app_handle.clone();
+                let _ = app_handle.run_on_main_thread(move || {
+                    rebuild_tray_menu(&rebuild_handle);
+                });
+            }
+        }
+    });
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct AppsMenuPlan {
+    favorites: Vec<String>,
+    others: Vec<String>,
+    overflow: Vec<String>,
+}
+
+fn plan_menu(names: &[String], settings: &TrayAppsSettings) -> AppsMenuPlan {
+    let mut seen = HashSet::new();
+    let unique: Vec<&String> = names
+        .iter()
+        .filter(|name| !name.trim().is_empty() && seen.insert(name.as_str()))
+        .collect();
+
+    let favorites: Vec<String> = settings
+        .favorites
+        .iter()
+        .filter(|favorite| seen.contains(favorite.as_str()))
+        .take(settings.max_items)
+        .cloned()
+        .collect();
+    let mut rest = unique
+        .into_iter()
+        .filter(|name| !favorites.contains(*name))
+        .cloned();
+    let others = rest
+        .by_ref()
+        .take(settings.max_items.saturating_sub(favorites.len()))
+        .collect();
+    let overflow = rest.take(MAX_OVERFLOW_ITEMS).collect();
+    AppsMenuPlan {
+        favorites,
+        others,
+        overflow,
+    }
+}
+
+fn launch_item<R: Runtime>(app: &AppHandle<R>, name: &str) -> tauri::Result<MenuItem<R>> {
+    MenuItem::with_id(
+        app,
+        format!("{LAUNCH_PREFIX}{name}"),
+        menu::compact_menu_text(name, MAX_LABEL_CHARS),
+        true,
+        None::<&str>,
+    )
+}
+
+/// The Apps submenu, or `None` when it is turned off.
+pub(super) fn build_submenu<R: Runtime>(
+    app: &AppHandle<R>,
+    s: &TrayStrings,
+) -> tauri::Result<Option<Submenu<R>>> {
+    let settings = current_settings();
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let plan = plan_menu(&SNAPSHOT.lock().unwrap().names, &settings);
+
+    let favorites = plan
+        .favorites
+        .iter()
+        .map(|name| launch_item(app, name))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let others = plan
+        .others
+        .iter()
+        .map(|name| launch_item(app, name))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let overflow = plan
+        .overflow
+        .iter()
+        .map(|name| launch_item(app, name))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let overflow_refs: Vec<&dyn IsMenuItem<R>> = overflow
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<R>)
+        .collect();
+    let more = Submenu::with_id_and_items(app, "apps_more", s.apps_more, true, &overflow_refs)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let empty = MenuItem::with_id(app, "apps_empty", s.apps_empty, false, None::<&str>)?;
+
+    let mut items: Vec<&dyn IsMenuItem<R>> = Vec::new();
+    items.extend(favorites.iter().map(|item| item as &dyn IsMenuItem<R>));
+    if !favorites.is_empty() && !others.is_empty() {
+        items.push(&separator);
+    }
+    items.extend(others.iter().map(|item| item as &dyn IsMenuItem<R>));
+    if !overflow.is_empty() {
+        items.push(&more);
+    }
+    if items.is_empty() {
+        items.push(&empty);
+    }
+    Submenu::with_id_and_items(app, "apps", s.apps_menu, true, &items).map(Some)
+}
+
+/// Launch an app on this computer, looking it up again by name.
+pub(super) fn launch<R: Runtime>(app: &AppHandle<R>, name: &str) {
+    info!("🎮 托盘菜单：启动应用 {}", name);
+    let name = name.to_string();
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let lookup = name.clone();
+        let result = async move {
+            let apps = crate::apps::list_apps().await?;
+            let entry = apps
+                .into_iter()
+                .find(|app| app.name == lookup)
+                .ok_or_else(|| format!("{} is no longer in the Core's app list", lookup))?;
+            crate::commands::launch_app(None, None, None, Some(entry)).await
+        }
+        .await;
+        if let Err(error) = result {
+            warn!("托盘启动应用失败: {}", error);
+            let message = get_tray_strings()
+                .app_launch_failed
+                .replace("{name}", &name)
+                .replace("{error}", &error);
+            emit_message(&app_handle, "error", &message);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn favourites_lead_and_the_rest_overflow() {
+        let settings = TrayAppsSettings {
+            max_items: 3,
+            favorites: names(&["Steam", "Missing"]),
+            ..TrayAppsSettings::default()
+        };
+        let plan = plan_menu(
+            &names(&["Desktop", "Steam", "Playnite", "Desktop", "Emulator", ""]),
+            &settings,
+        );
+        assert_eq!(
+            plan,
+            AppsMenuPlan {
+                favorites: names(&["Steam"]),
+                others: names(&["Desktop", "Playnite"]),
+                overflow: names(&["Emulator"]),
+            }
+        );
+    }
+
+    #[test]
+    fn favourites_are_capped_by_max_items() {
+        let settings = TrayAppsSettings {
+            max_items: 1,
+            favorites: names(&["B", "A"]),
+            ..TrayAppsSettings::default()
+        };
+        let plan = plan_menu(&names(&["A", "B", "C"]), &settings);
+        assert_eq!(plan.favorites, names(&["B"]));
+        assert!(plan.others.is_empty());
+        assert_eq!(plan.overflow, names(&["A", "C"]));
+    }
+
+    #[test]
+    fn settings_are_validated() {
+        let settings = TrayAppsSettings {
+            favorites: names(&[" Steam ", "Steam", ""]),
+            ..TrayAppsSettings::default()
+        }
+        .validated()
+        .unwrap();
+        assert_eq!(settings.favorites, names(&["Steam"]));
+        assert!(
+            TrayAppsSettings {
+                max_items: 0,
+                ..TrayAppsSettings::default()
+            }
+            .validated()
+            .is_err()
+        );
+    }
+}
@@ -114,6 +114,10 @@ fn apply_if_new<R: Runtime + 'static>(
         state.revision,
         force_reconcile,
     ) {
+        crate::session_history::observe_tray_state(&state);
+        crate::event_hooks::observe_tray_state(&state);
+        crate::core_watchdog::observe_tray_state(&state);
+        crate::client_blocklist::enforce(&state);
         #[cfg(target_os = "windows")]
         if state.vdd.awaiting_confirmation && state.vdd.confirmation_operation_id != 0 {
             vdd_confirmation::show(app, state.vdd.confirmation_operation_id);
@@ -142,6 +146,9 @@ fn accept_state_revision(
 }
 
 fn mark_core_disconnected<R: Runtime + 'static>(app: &AppHandle<R>) {
+    crate::session_history::observe_core_disconnected();
+    crate::event_hooks::observe_core_disconnected();
+    crate::client_profiles::observe_core_disconnected();
     let disconnect_handle = app.clone();
     if let Err(e) = app.run_on_main_thread(move || {
         apply_core_disconnected(&disconnect_handle);
@@ -0,0 +1,327 @@
+//! Tray menu and notification strings.
+//!
+//! Translations live next to the frontend locales in `src/i18n/tray/<code>.json`
+//! as flat `key: text` objects and are embedded at build time. English is the
+//! reference catalog: a key that is missing from a translation, or whose
+//! `{placeholder}` set differs from the English text, falls back to English on
+//! its own instead of discarding the whole language. `build.rs` picks up every
+//! JSON file in that directory, so adding a language only needs a new file
+//! with a `language_name` entry.
+
+use log::warn;
+use once_cell::sync::Lazy;
+use std::collections::{BTreeSet, HashMap};
+
+pub(super) const FALLBACK_LOCALE: &str = "en";
+
+pub(super) struct TrayLocale {
+    /// Same code as the frontend and the Sunshine `locale` setting.
+    pub code: &'static str,
+    source: &'static str,
+}
+
+/// Every `src/i18n/tray/<code>.json`, sorted by code; generated by `build.rs`.
+pub(super) const LOCALES: &[TrayLocale] = include!(concat!(env!("OUT_DIR"), "/tray_locales.rs"));
+
+macro_rules! tray_strings {
+    ($($(#[$meta:meta])* $key:ident,)*) => {
+        /// 托盘菜单翻译结构
+        pub(super) struct TrayStrings {
+            $($(#[$meta])* pub(super) $key: &'static str,)*
+        }
+
+        impl TrayStrings {
+            fn from_lookup(mut lookup: impl FnMut(&'static str) -> &'static str) -> Self {
+                Self {
+                    $($(#[$meta])* $key: lookup(stringify!($key)),)*
+                }
+            }
+        }
+
+        /// Every catalog key, including platform- and build-specific ones.
+        const KEYS: &[&str] = &[$(stringify!($key),)*];
+    };
+}
+
+tray_strings! {
+    status_idle,
+    status_streaming,
+    status_paused,
+    status_pairing,
+    status_notification,
+    status_connecting,
+    status_disconnected,
+    notification,
+    client_connected,
+    client_connected_detail,
+    client_connected_named,
+    suspicious_client_warning,
+    client_disconnected,
+    client_disconnected_detail,
+    client_disconnected_named,
+    incoming_pairing,
+    pairing_instruction,
+    pairing_menu_instruction,
+    complete_pairing,
+    open_main_panel,
+    interfaces_menu,
+    display_menu,
+    tools_menu,
+    settings_menu,
+    advanced_menu,
+    help_menu,
+    open_sunshine,
+    vdd_settings,
+    vdd_create,
+    vdd_create_confirm,
+    vdd_close,
+    vdd_keep,
+    vdd_keep_confirm,
+    vdd_headless,
+    vdd_headless_confirm,
+    import_config,
+    export_config,
+    reset_config,
+    reset_config_confirm,
+    clear_cache,
+    clear_cache_confirm,
+    reset_display,
+    reset_display_confirm,
+    restart_user_mode,
+    show_toolbar,
+    prevent_sleep,
+    rtss_control,
+    host_performance,
+    log_console,
+    open_desktop,
+    apps_menu,
+    apps_more,
+    apps_empty,
+    app_launch_failed,
+    sessions_menu,
+    session_pause,
+    session_resume,
+    session_bitrate,
+    session_send_file,
+    session_disconnect,
+    session_unpair,
+    session_block,
+    session_bitrate_failed,
+    session_unpair_failed,
+    session_block_failed,
+    notification_history,
+    notification_history_unread,
+    notification_history_empty,
+    notification_mark_all_read,
+    notification_clear,
+    #[cfg(target_os = "windows")]
+    auto_start,
+    #[cfg(any(debug_assertions, feature = "beta"))]
+    web_stream,
+    #[cfg(debug_assertions)]
+    debug_page,
+    check_update,
+    about,
+    shutdown,
+    shutdown_message,
+    language,
+    /// Shown in the language submenu, always in the language itself.
+    language_name,
+    star_project,
+    visit_project_sunshine,
+    visit_project_moonlight,
+    restart,
+    recover_service,
+    recovery_timeout,
+    watchdog_crash_loop,
+    watchdog_crash_loop_detail,
+    core_crashed,
+    core_crashed_detail,
+    tooltip,
+    tooltip_admin,
+}
+
+type Catalog = HashMap<String, String>;
+
+/// Catalogs are resolved once per language and live for the whole process.
+static RESOLVED: Lazy<HashMap<&'static str, &'static TrayStrings>> = Lazy::new(|| {
+    let reference = parse_catalog(FALLBACK_LOCALE);
+    LOCALES
+        .iter()
+        .map(|locale| {
+            let (resolved, problems) = resolve_catalog(&reference, &parse_catalog(locale.code));
+            for problem in problems {
+                warn!("Tray locale '{}': {}", locale.code, problem);
+            }
+            (locale.code, leak_strings(resolved))
+        })
+        .collect()
+});
+
+fn parse_catalog(code: &str) -> Catalog {
+    let Some(locale) = LOCALES.iter().find(|locale| locale.code == code) else {
+        return Catalog::new();
+    };
+    serde_json::from_str(locale.source).unwrap_or_else(|e| {
+        warn!("Invalid tray locale file '{}': {}", code, e);
+        Catalog::new()
+    })
+}
+
+fn placeholders(text: &str) -> BTreeSet<&str> {
+    text.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .filter(|name| {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+        .collect()
+}
+
+/// Pick the text for every key, falling back to the reference per key. Returns
+/// the problems found so they can be logged or asserted on.
+fn resolve_catalog(
+    reference: &Catalog,
+    translated: &Catalog,
+) -> (HashMap<&'static str, String>, Vec<String>) {
+    let mut problems = Vec::new();
+    let mut resolved = HashMap::new();
+    for key in KEYS {
+        let fallback = reference.get(*key);
+        let text = match (translated.get(*key), fallback) {
+            (Some(text), Some(fallback)) if placeholders(text) != placeholders(fallback) => {
+                problems.push(format!(
+                    "'{}' must use the placeholders {:?}",
+                    key,
+                    placeholders(fallback)
+                ));
+                fallback.clone()
+            }
+            (Some(text), _) if !text.trim().is_empty() => text.clone(),
+            (_, Some(fallback)) => {
+                problems.push(format!("'{}' is missing", key));
+                fallback.clone()
+            }
+            (_, None) => {
+                problems.push(format!("'{}' is missing from the reference catalog", key));
+                key.to_string()
+            }
+        };
+        resolved.insert(*key, text);
+    }
+    for key in translated.keys() {
+        if !KEYS.contains(&key.as_str()) {
+            problems.push(format!("unknown key '{}'", key));
+        }
+    }
+    (resolved, problems)
+}
+
+fn leak_strings(mut resolved: HashMap<&'static str, String>) -> &'static TrayStrings {
+    let strings = TrayStrings::from_lookup(|key| {
+        resolved
+            .remove(key)
+            .map(|text| &*text.leak())
+            .unwrap_or(key)
+    });
+    Box::leak(Box::new(strings))
+}
+
+/// Strings for a supported locale code, English for anything else.
+pub(super) fn strings(code: &str) -> &'static TrayStrings {
+    RESOLVED
+        .get(code)
+        .or_else(|| RESOLVED.get(FALLBACK_LOCALE))
+        .copied()
+        .expect("the fallback tray locale is bundled")
+}
+
+/// Map a UI or system locale (`zh-Hant-TW`, `de_DE.UTF-8`, …) to a bundled code.
+pub(super) fn match_locale(locale: &str) -> Option<&'static str> {
+    let locale = locale.trim().to_ascii_lowercase().replace('_', "-");
+    let mut subtags = locale.split(['-', '.', '@']);
+    let language = subtags.next()?;
+    if language == "zh" {
+        let traditional = subtags.any(|subtag| matches!(subtag, "tw" | "hk" | "mo" | "hant"));
+        return Some(if traditional { "zh_TW" } else { "zh" });
+    }
+    LOCALES
+        .iter()
+        .map(|locale| locale.code)
+        .find(|code| *code == language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog(entries: &[(&str, &str)]) -> Catalog {
+        entries
+            .iter()
+            .map(|(key, text)| (key.to_string(), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn bundled_catalogs_are_complete_and_consistent() {
+        let reference = parse_catalog(FALLBACK_LOCALE);
+        for locale in LOCALES {
+            let translated: Catalog = serde_json::from_str(locale.source)
+                .unwrap_or_else(|e| panic!("{} does not parse: {}", locale.code, e));
+            let (_, problems) = resolve_catalog(&reference, &translated);
+            assert!(problems.is_empty(), "{}: {:?}", locale.code, problems);
+        }
+    }
+
+    #[test]
+    fn missing_and_mismatched_keys_fall_back_individually() {
+        let reference = parse_catalog(FALLBACK_LOCALE);
+        let translated = catalog(&[
+            ("status_idle", "Leerlauf"),
+            ("client_connected_named", "{client} ist verbunden"),
+            ("no_such_key", "?"),
+        ]);
+        let (resolved, problems) = resolve_catalog(&reference, &translated);
+
+        assert_eq!(resolved["status_idle"], "Leerlauf");
+        assert_eq!(
+            resolved["client_connected_named"],
+            reference["client_connected_named"]
+        );
+        assert_eq!(resolved["shutdown"], "Exit Sunshine");
+        assert!(
+            problems
+                .iter()
+                .any(|p| p.contains("client_connected_named"))
+        );
+        assert!(
+            problems
+                .iter()
+                .any(|p| p.contains("unknown key 'no_such_key'"))
+        );
+    }
+
+    #[test]
+    fn placeholders_ignore_unrelated_braces() {
+        assert_eq!(
+            placeholders("{count} in {minutes} min {not a placeholder}"),
+            BTreeSet::from(["count", "minutes"])
+        );
+        assert!(placeholders("no placeholders {").is_empty());
+    }
+
+    #[test]
+    fn locales_match_bundled_codes() {
+        assert_eq!(match_locale("zh-CN"), Some("zh"));
+        assert_eq!(match_locale("zh_TW"), Some("zh_TW"));
+        assert_eq!(match_locale("zh-Hant-HK"), Some("zh_TW"));
+        assert_eq!(match_locale("zh_TW.UTF-8"), Some("zh_TW"));
+        assert_eq!(match_locale("de_DE.UTF-8"), Some("de"));
+        assert_eq!(match_locale("ko-KR"), Some("ko"));
+        assert_eq!(match_locale("pl-PL"), None);
+        assert_eq!(match_locale("C"), None);
+        assert_eq!(strings("pl").shutdown, "Exit Sunshine");
+        assert_eq!(strings("ru").language, "Язык");
+        assert_eq!(strings("ja").language_name, "日本語");
+    }
+}
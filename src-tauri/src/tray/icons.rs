@@ -1,83 +1,256 @@
+//! Tray icon rendering.
+//!
+//! The base icon comes from the Core's assets (or the bundled copy) and gets
+//! overlays drawn at runtime: a streaming/paused dot, a warning marker for
+//! suspected unknown clients, a session-count badge and, when enabled in the
+//! desktop settings, a bitrate bar. The tray state carries no bitrate, so while
+//! the bar is on and clients are connected `/api/runtime/sessions` is polled for
+//! it. Rendered images are cached per state.
+
+use image::{Rgba, RgbaImage};
 use log::debug;
-use std::{io::Cursor, path::PathBuf};
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+use std::{collections::HashMap, io::Cursor, path::PathBuf, sync::Mutex};
 use tauri::{AppHandle, Runtime, image::Image};
 
-use super::{CURRENT_CORE_ICON, TRAY_ID};
+use super::{CURRENT_CORE_ICON, TRAY_ID, TRAY_RUNTIME_STATE};
 use crate::sunshine;
 
-pub(super) fn load_initial_tray_icon<R: Runtime>(app: &AppHandle<R>) -> Image<'_> {
-    load_tray_icon(&core_tray_icon_path("sunshine.ico"), "sunshine.ico").unwrap_or_else(|e| {
-        debug!(
-            "Failed to load initial C++ tray icon, using app icon: {}",
-            e
-        );
-        app.default_window_icon().unwrap().clone()
-    })
+/// Session counts above this show as "+".
+const MAX_BADGE_COUNT: usize = 9;
+/// Total bitrate that fills the bar, in Kbps.
+const FULL_BAR_KBPS: u32 = 150_000;
+const BAR_STEPS: u8 = 8;
+const MAX_CACHED_ICONS: usize = 64;
+const BITRATE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const STREAMING_COLOR: Rgba<u8> = Rgba([229, 57, 53, 255]);
+const PAUSED_COLOR: Rgba<u8> = Rgba([255, 160, 0, 255]);
+const WARNING_COLOR: Rgba<u8> = Rgba([255, 202, 40, 255]);
+const BADGE_COLOR: Rgba<u8> = Rgba([30, 136, 229, 255]);
+const BAR_COLOR: Rgba<u8> = Rgba([67, 160, 71, 255]);
+const BAR_TRACK_COLOR: Rgba<u8> = Rgba([33, 33, 33, 160]);
+const OUTLINE_COLOR: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const MARK_COLOR: Rgba<u8> = Rgba([33, 33, 33, 255]);
+
+/// 3x5 glyphs, one bit per pixel with the most significant of three bits on
+/// the left.
+const DIGIT_GLYPHS: [[u8; 5]; 9] = [
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const PLUS_GLYPH: [u8; 5] = [0b000, 0b010, 0b111, 0b010, 0b000];
+
+static RENDERED_ICONS: Lazy<Mutex<HashMap<TrayIconState, RgbaImage>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+/// The desktop setting, cached so icon updates never read it from disk.
+static BITRATE_BAR: AtomicBool = AtomicBool::new(false);
+/// Total bitrate of the running sessions in Kbps, 0 when unknown.
+static SESSIONS_KBPS: AtomicU32 = AtomicU32::new(0);
+static BITRATE_POLLER_STARTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+enum Activity {
+    #[default]
+    Idle,
+    Streaming,
+    Paused,
 }
 
-pub(super) fn apply_tray_icon<R: Runtime>(app: &AppHandle<R>, icon: &str) {
-    let normalized_icon = if icon.is_empty() { "default" } else { icon };
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+struct Badges {
+    /// Connected sessions, capped at `MAX_BADGE_COUNT + 1`.
+    sessions: usize,
+    activity: Activity,
+    warning: bool,
+    /// Filled bar steps out of `BAR_STEPS`; `None` hides the bar.
+    bitrate_steps: Option<u8>,
+}
 
-    {
-        let current_icon = CURRENT_CORE_ICON.lock().unwrap();
-        if current_icon.as_deref() == Some(normalized_icon) {
-            return;
+/// Everything a rendered tray icon depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct TrayIconState {
+    file_name: &'static str,
+    grayscale: bool,
+    badges: Badges,
+}
+
+impl TrayIconState {
+    const DISCONNECTED: Self = Self {
+        file_name: "sunshine.ico",
+        grayscale: true,
+        badges: Badges {
+            sessions: 0,
+            activity: Activity::Idle,
+            warning: false,
+            bitrate_steps: None,
+        },
+    };
+
+    /// `bitrate_kbps` is `None` while the bar is off.
+    fn from_tray_state(state: &sunshine::TrayState, bitrate_kbps: Option<u32>) -> Self {
+        let activity = match state.status.as_str() {
+            "streaming" => Activity::Streaming,
+            "paused" => Activity::Paused,
+            _ => Activity::Idle,
+        };
+        Self {
+            file_name: tray_icon_file_name(&state.icon),
+            grayscale: false,
+            badges: Badges {
+                sessions: state.sessions.len().min(MAX_BADGE_COUNT + 1),
+                activity,
+                warning: state
+                    .sessions
+                    .iter()
+                    .any(|session| session.highly_suspected_unknown_client),
+                bitrate_steps: bitrate_kbps
+                    .filter(|&kbps| kbps > 0 && !state.sessions.is_empty())
+                    .map(bitrate_steps),
+            },
         }
     }
+}
 
-    let Some(tray) = app.tray_by_id(TRAY_ID) else {
-        return;
-    };
-    let file_name = tray_icon_file_name(normalized_icon);
-    let icon_path = core_tray_icon_path(file_name);
+pub(super) fn load_initial_tray_icon<R: Runtime>(app: &AppHandle<R>) -> Image<'_> {
+    load_tray_image(&core_tray_icon_path("sunshine.ico"), "sunshine.ico")
+        .map(rgba_to_tauri_image)
+        .unwrap_or_else(|e| {
+            debug!(
+                "Failed to load initial C++ tray icon, using app icon: {}",
+                e
+            );
+            app.default_window_icon().unwrap().clone()
+        })
+}
 
-    match load_tray_icon(&icon_path, file_name) {
-        Ok(image) => {
-            if let Err(e) = tray.set_icon(Some(image)) {
-                debug!("Failed to apply tray icon '{}': {}", file_name, e);
-                return;
+pub(super) fn apply_tray_icon<R: Runtime>(app: &AppHandle<R>, state: &sunshine::TrayState) {
+    let bitrate_kbps = BITRATE_BAR
+        .load(Ordering::Acquire)
+        .then(|| SESSIONS_KBPS.load(Ordering::Acquire));
+    set_tray_icon(app, TrayIconState::from_tray_state(state, bitrate_kbps));
+}
+
+/// Redraw the icon for the last tray state, e.g. after the bitrate changed.
+fn refresh_tray_icon<R: Runtime>(app: &AppHandle<R>) {
+    let state = TRAY_RUNTIME_STATE.lock().unwrap().tray_state.clone();
+    if let Some(state) = state.filter(|state| state.owner == "gui") {
+        apply_tray_icon(app, &state);
+    }
+}
+
+fn schedule_refresh<R: Runtime>(app: &AppHandle<R>) {
+    let refresh_handle = app.clone();
+    if let Err(e) = app.run_on_main_thread(move || refresh_tray_icon(&refresh_handle)) {
+        debug!("Failed to schedule tray icon refresh: {}", e);
+    }
+}
+
+/// Show or hide the bitrate bar; called when the desktop settings change.
+pub(super) fn set_bitrate_bar<R: Runtime>(app: &AppHandle<R>, enabled: bool) {
+    if BITRATE_BAR.swap(enabled, Ordering::AcqRel) != enabled {
+        schedule_refresh(app);
+    }
+}
+
+/// Poll session bitrates while the bar is on and clients are connected.
+pub(super) fn start_bitrate_poller<R: Runtime + 'static>(app: &AppHandle<R>) {
+    if BITRATE_POLLER_STARTED.swap(true, Ordering::AcqRel) {
+        return;
+    }
+    BITRATE_BAR.store(
+        crate::desktop_settings::load_desktop_settings_from_disk().tray_bitrate_bar,
+        Ordering::Release,
+    );
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(BITRATE_POLL_INTERVAL).await;
+            let kbps = if BITRATE_BAR.load(Ordering::Acquire)
+                && !super::current_client_sessions().is_empty()
+            {
+                match sunshine::get_local_sessions().await {
+                    Ok(sessions) => sessions
+                        .iter()
+                        .fold(0u32, |total, session| total.saturating_add(session.bitrate)),
+                    Err(e) => {
+                        debug!("Session bitrates unavailable: {}", e);
+                        0
+                    }
+                }
+            } else {
+                0
+            };
+            if SESSIONS_KBPS.swap(kbps, Ordering::AcqRel) != kbps {
+                schedule_refresh(&app);
             }
-            *CURRENT_CORE_ICON.lock().unwrap() = Some(normalized_icon.to_string());
         }
-        Err(e) => debug!("Failed to load tray icon '{}': {}", file_name, e),
-    }
+    });
 }
 
 pub(super) fn apply_disconnected_tray_icon<R: Runtime>(app: &AppHandle<R>) {
-    const ICON_STATE: &str = "disconnected";
-    {
-        let current_icon = CURRENT_CORE_ICON.lock().unwrap();
-        if current_icon.as_deref() == Some(ICON_STATE) {
-            return;
-        }
-    }
+    set_tray_icon(app, TrayIconState::DISCONNECTED);
+}
 
+fn set_tray_icon<R: Runtime>(app: &AppHandle<R>, icon_state: TrayIconState) {
+    if CURRENT_CORE_ICON.lock().unwrap().as_ref() == Some(&icon_state) {
+        return;
+    }
     let Some(tray) = app.tray_by_id(TRAY_ID) else {
         return;
     };
-    let file_name = "sunshine.ico";
-    let icon_path = core_tray_icon_path(file_name);
-    match load_disconnected_icon(&icon_path, file_name) {
-        Ok(mut image) => {
-            grayscale(&mut image);
+
+    match rendered_icon(&icon_state) {
+        Ok(image) => {
             if let Err(e) = tray.set_icon(Some(rgba_to_tauri_image(image))) {
-                debug!("Failed to apply disconnected tray icon: {}", e);
+                debug!("Failed to apply tray icon {:?}: {}", icon_state, e);
                 return;
             }
-            *CURRENT_CORE_ICON.lock().unwrap() = Some(ICON_STATE.to_string());
+            *CURRENT_CORE_ICON.lock().unwrap() = Some(icon_state);
         }
-        Err(e) => debug!("Failed to load disconnected tray icon: {}", e),
+        Err(e) => debug!("Failed to render tray icon {:?}: {}", icon_state, e),
     }
 }
 
-fn load_disconnected_icon(path: &PathBuf, file_name: &str) -> Result<image::RgbaImage, String> {
+fn rendered_icon(icon_state: &TrayIconState) -> Result<RgbaImage, String> {
+    if let Some(image) = RENDERED_ICONS.lock().unwrap().get(icon_state) {
+        return Ok(image.clone());
+    }
+
+    let mut image = load_tray_image(
+        &core_tray_icon_path(icon_state.file_name),
+        icon_state.file_name,
+    )?;
+    if icon_state.grayscale {
+        grayscale(&mut image);
+    }
+    draw_badges(&mut image, &icon_state.badges);
+
+    let mut cache = RENDERED_ICONS.lock().unwrap();
+    if cache.len() >= MAX_CACHED_ICONS {
+        cache.clear();
+    }
+    cache.insert(*icon_state, image.clone());
+    Ok(image)
+}
+
+fn load_tray_image(path: &PathBuf, file_name: &str) -> Result<RgbaImage, String> {
     if path.exists() {
         match std::fs::read(path).map_err(|error| error.to_string()) {
             Ok(bytes) => {
                 return decode_tray_icon_with_fallback(&bytes, bundled_tray_icon_bytes(file_name));
             }
             Err(error) => debug!(
-                "Failed to load disconnected core tray icon '{}': {}",
+                "Failed to load core tray icon '{}': {}",
                 path.display(),
                 error
             ),
@@ -86,10 +259,7 @@ fn load_disconnected_icon(path: &PathBuf, file_name: &str) -> Result<image::Rgba
     decode_tray_icon(bundled_tray_icon_bytes(file_name))
 }
 
-fn decode_tray_icon_with_fallback(
-    bytes: &[u8],
-    fallback: &[u8],
-) -> Result<image::RgbaImage, String> {
+fn decode_tray_icon_with_fallback(bytes: &[u8], fallback: &[u8]) -> Result<RgbaImage, String> {
     decode_tray_icon(bytes).or_else(|error| {
         debug!("Failed to decode custom tray icon: {}", error);
         decode_tray_icon(fallback)
@@ -121,26 +291,7 @@ fn bundled_tray_icon_bytes(file_name: &str) -> &'static [u8] {
     }
 }
 
-fn load_tray_icon(path: &PathBuf, file_name: &str) -> Result<Image<'static>, String> {
-    if path.exists() {
-        match load_tray_icon_from_path(path) {
-            Ok(image) => return Ok(image),
-            Err(e) => debug!("Failed to load core tray icon '{}': {}", path.display(), e),
-        }
-    }
-    load_tray_icon_from_bytes(bundled_tray_icon_bytes(file_name))
-}
-
-fn load_tray_icon_from_path(path: &PathBuf) -> Result<Image<'static>, String> {
-    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
-    decode_tray_icon(&bytes).map(rgba_to_tauri_image)
-}
-
-fn load_tray_icon_from_bytes(bytes: &'static [u8]) -> Result<Image<'static>, String> {
-    decode_tray_icon(bytes).map(rgba_to_tauri_image)
-}
-
-fn decode_tray_icon(bytes: &[u8]) -> Result<image::RgbaImage, String> {
+fn decode_tray_icon(bytes: &[u8]) -> Result<RgbaImage, String> {
     let preferred_frame = select_small_ico_frame(bytes).and_then(|selected_ico| {
         image::ImageReader::with_format(Cursor::new(selected_ico), image::ImageFormat::Ico)
             .decode()
@@ -161,7 +312,7 @@ fn decode_tray_icon(bytes: &[u8]) -> Result<image::RgbaImage, String> {
     }
 }
 
-fn grayscale(image: &mut image::RgbaImage) {
+fn grayscale(image: &mut RgbaImage) {
     for pixel in image.pixels_mut() {
         let [red, green, blue, alpha] = pixel.0;
         let luminance =
@@ -170,6 +321,127 @@ fn grayscale(image: &mut image::RgbaImage) {
     }
 }
 
+fn bitrate_steps(kbps: u32) -> u8 {
+    let filled = u64::from(kbps.min(FULL_BAR_KBPS)) * u64::from(BAR_STEPS);
+    filled.div_ceil(u64::from(FULL_BAR_KBPS)) as u8
+}
+
+fn draw_badges(image: &mut RgbaImage, badges: &Badges) {
+    let size = image.width().min(image.height()) as i32;
+    // Nothing legible fits on smaller frames.
+    if size < 16 {
+        return;
+    }
+    let unit = size / 16;
+
+    if let Some(steps) = badges.bitrate_steps {
+        let top = size - 2 * unit;
+        fill_rect(image, 0, top, size, 2 * unit, BAR_TRACK_COLOR);
+        let filled = size * i32::from(steps) / i32::from(BAR_STEPS);
+        fill_rect(image, 0, top, filled, 2 * unit, BAR_COLOR);
+    }
+
+    let dot_color = match badges.activity {
+        Activity::Streaming => Some(STREAMING_COLOR),
+        Activity::Paused => Some(PAUSED_COLOR),
+        Activity::Idle => None,
+    };
+    if let Some(color) = dot_color {
+        let radius = size * 3 / 16;
+        fill_circle(image, radius, radius, radius, OUTLINE_COLOR);
+        fill_circle(image, radius, radius, radius - unit, color);
+    }
+
+    if badges.warning {
+        draw_warning(image, size, unit);
+    }
+
+    if badges.sessions > 0 {
+        let radius = size * 5 / 16;
+        let center = size - 1 - radius;
+        fill_circle(image, center, center, radius, OUTLINE_COLOR);
+        fill_circle(image, center, center, radius - unit, BADGE_COLOR);
+        let glyph = if badges.sessions > MAX_BADGE_COUNT {
+            &PLUS_GLYPH
+        } else {
+            &DIGIT_GLYPHS[badges.sessions - 1]
+        };
+        draw_glyph(
+            image,
+            glyph,
+            center - 3 * unit / 2,
+            center - 5 * unit / 2,
+            unit,
+        );
+    }
+}
+
+/// A "!" triangle in the top-right corner.
+fn draw_warning(image: &mut RgbaImage, size: i32, unit: i32) {
+    let extent = size * 7 / 16;
+    let center = size - extent / 2 - 1;
+    for row in 0..extent {
+        let half = (row + 1) * extent / (2 * extent);
+        fill_rect(image, center - half, row, 2 * half + 1, 1, WARNING_COLOR);
+    }
+    let left = center - unit / 2;
+    let bar_top = extent / 4;
+    fill_rect(
+        image,
+        left,
+        bar_top,
+        unit,
+        extent - 3 * unit - bar_top,
+        MARK_COLOR,
+    );
+    fill_rect(image, left, extent - 2 * unit, unit, unit, MARK_COLOR);
+}
+
+fn draw_glyph(image: &mut RgbaImage, glyph: &[u8; 5], left: i32, top: i32, scale: i32) {
+    for (row, bits) in glyph.iter().enumerate() {
+        for column in 0..3 {
+            if bits & (0b100 >> column) != 0 {
+                fill_rect(
+                    image,
+                    left + column * scale,
+                    top + row as i32 * scale,
+                    scale,
+                    scale,
+                    OUTLINE_COLOR,
+                );
+            }
+        }
+    }
+}
+
+fn fill_circle(image: &mut RgbaImage, center_x: i32, center_y: i32, radius: i32, color: Rgba<u8>) {
+    for y in center_y - radius..=center_y + radius {
+        for x in center_x - radius..=center_x + radius {
+            let (dx, dy) = (x - center_x, y - center_y);
+            if dx * dx + dy * dy <= radius * radius {
+                put_pixel(image, x, y, color);
+            }
+        }
+    }
+}
+
+fn fill_rect(image: &mut RgbaImage, left: i32, top: i32, width: i32, height: i32, color: Rgba<u8>) {
+    for y in top..top + height {
+        for x in left..left + width {
+            put_pixel(image, x, y, color);
+        }
+    }
+}
+
+fn put_pixel(image: &mut RgbaImage, x: i32, y: i32, color: Rgba<u8>) {
+    let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+        return;
+    };
+    if x < image.width() && y < image.height() {
+        image.put_pixel(x, y, color);
+    }
+}
+
 fn select_small_ico_frame(bytes: &[u8]) -> Result<Vec<u8>, String> {
     const ICO_HEADER_LEN: usize = 6;
     const ICO_ENTRY_LEN: usize = 16;
@@ -254,7 +526,7 @@ fn ico_dimension(value: u8) -> u16 {
     if value == 0 { 256 } else { value as u16 }
 }
 
-fn rgba_to_tauri_image(image: image::RgbaImage) -> Image<'static> {
+fn rgba_to_tauri_image(image: RgbaImage) -> Image<'static> {
     let (width, height) = image.dimensions();
     Image::new_owned(image.into_raw(), width, height)
 }
@@ -265,7 +537,7 @@ mod tests {
 
     #[test]
     fn grayscale_preserves_alpha_and_equalizes_color_channels() {
-        let mut image = image::RgbaImage::from_pixel(1, 1, image::Rgba([240, 80, 20, 123]));
+        let mut image = RgbaImage::from_pixel(1, 1, Rgba([240, 80, 20, 123]));
 
         grayscale(&mut image);
 
@@ -284,4 +556,141 @@ mod tests {
         assert!(image.width() > 0);
         assert!(image.height() > 0);
     }
+
+    fn tray_state(status: &str, sessions: &[bool]) -> sunshine::TrayState {
+        sunshine::TrayState {
+            status: status.to_string(),
+            icon: "playing".to_string(),
+            sessions: sessions
+                .iter()
+                .enumerate()
+                .map(|(index, &suspicious)| sunshine::TrayClientSession {
+                    id: index as u32 + 1,
+                    highly_suspected_unknown_client: suspicious,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn render(badges: Badges) -> RgbaImage {
+        let mut image = RgbaImage::new(32, 32);
+        draw_badges(&mut image, &badges);
+        image
+    }
+
+    #[test]
+    fn badges_follow_the_tray_state() {
+        let state = tray_state("streaming", &[false, true, false]);
+
+        let icon = TrayIconState::from_tray_state(&state, None);
+        assert_eq!(icon.file_name, "sunshine-playing.ico");
+        assert_eq!(
+            icon.badges,
+            Badges {
+                sessions: 3,
+                activity: Activity::Streaming,
+                warning: true,
+                bitrate_steps: None,
+            }
+        );
+        assert_eq!(
+            TrayIconState::from_tray_state(&state, Some(60_000))
+                .badges
+                .bitrate_steps,
+            Some(4)
+        );
+
+        let crowded = tray_state("paused", &[false; 12]);
+        let badges = TrayIconState::from_tray_state(&crowded, Some(0)).badges;
+        assert_eq!(badges.sessions, MAX_BADGE_COUNT + 1);
+        assert_eq!(badges.activity, Activity::Paused);
+        assert_eq!(badges.bitrate_steps, None);
+
+        let idle = TrayIconState::from_tray_state(&tray_state("idle", &[]), Some(20_000));
+        assert_eq!(idle.badges.bitrate_steps, None);
+    }
+
+    #[test]
+    fn bitrate_steps_round_up_and_saturate() {
+        assert_eq!(bitrate_steps(1), 1);
+        assert_eq!(bitrate_steps(FULL_BAR_KBPS / 2), BAR_STEPS / 2);
+        assert_eq!(bitrate_steps(FULL_BAR_KBPS * 3), BAR_STEPS);
+    }
+
+    #[test]
+    fn overlays_land_in_their_corners() {
+        assert_eq!(render(Badges::default()), RgbaImage::new(32, 32));
+
+        let image = render(Badges {
+            sessions: 2,
+            activity: Activity::Streaming,
+            warning: true,
+            bitrate_steps: Some(4),
+        });
+        // Dot top-left, warning top-right, badge bottom-right.
+        assert_eq!(*image.get_pixel(6, 6), STREAMING_COLOR);
+        assert_eq!(*image.get_pixel(24, 13), WARNING_COLOR);
+        assert_eq!(*image.get_pixel(24, 6), MARK_COLOR);
+        assert_eq!(*image.get_pixel(12, 21), BADGE_COLOR);
+        // The bar fills half the bottom edge.
+        assert_eq!(*image.get_pixel(0, 31), BAR_COLOR);
+        assert_eq!(*image.get_pixel(15, 31), BAR_COLOR);
+        assert_eq!(*image.get_pixel(16, 31), BAR_TRACK_COLOR);
+        assert_eq!(*image.get_pixel(3, 16), Rgba([0, 0, 0, 0]));
+
+        let paused = render(Badges {
+            activity: Activity::Paused,
+            ..Badges::default()
+        });
+        assert_eq!(*paused.get_pixel(6, 6), PAUSED_COLOR);
+    }
+
+    #[test]
+    fn session_badges_show_the_count() {
+        let one = render(Badges {
+            sessions: 1,
+            ..Badges::default()
+        });
+        let two = render(Badges {
+            sessions: 2,
+            ..Badges::default()
+        });
+        let many = render(Badges {
+            sessions: MAX_BADGE_COUNT + 1,
+            ..Badges::default()
+        });
+        assert_ne!(one, two);
+        assert_ne!(two, many);
+        // Glyphs are white on the badge.
+        assert!(two.pixels().any(|pixel| *pixel == OUTLINE_COLOR));
+        assert_eq!(*two.get_pixel(0, 0), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn tiny_icons_are_left_alone() {
+        let mut image = RgbaImage::new(8, 8);
+        draw_badges(
+            &mut image,
+            &Badges {
+                sessions: 3,
+                activity: Activity::Streaming,
+                warning: true,
+                bitrate_steps: Some(8),
+            },
+        );
+        assert_eq!(image, RgbaImage::new(8, 8));
+    }
+
+    #[test]
+    fn rendered_icons_are_cached_per_state() {
+        let state = TrayIconState::from_tray_state(&tray_state("streaming", &[true]), None);
+        let first = rendered_icon(&state).expect("bundled icon should render");
+        assert!(RENDERED_ICONS.lock().unwrap().contains_key(&state));
+        assert_eq!(rendered_icon(&state).unwrap(), first);
+
+        let disconnected = rendered_icon(&TrayIconState::DISCONNECTED).unwrap();
+        assert_ne!(disconnected, first);
+    }
 }
@@ -164,6 +164,10 @@ pub(super) fn build_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<M
     let primary_separator = PredefinedMenuItem::separator(app)?;
 
     let open_desktop = MenuItem::with_id(app, "open_desktop", s.open_desktop, true, None::<&str>)?;
+    let apps_submenu = apps_menu::build_submenu(app, s)?;
+    let sessions_submenu =
+        sessions_menu::build_submenu(app, s, tray_state.as_ref().filter(|_| core_connected))?;
+    let history_submenu = notifications::history::build_submenu(app, s)?;
     #[cfg(target_os = "windows")]
     let auto_start = CheckMenuItem::with_id(
         app,
@@ -270,37 +274,25 @@ pub(super) fn build_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<M
     tools_items.push(&debug_page);
     let tools_submenu = Submenu::with_id_and_items(app, "tools", s.tools_menu, true, &tools_items)?;
 
-    let lang_zh = CheckMenuItem::with_id(
-        app,
-        "lang_zh",
-        "中文",
-        true,
-        current_locale == "zh",
-        None::<&str>,
-    )?;
-    let lang_en = CheckMenuItem::with_id(
-        app,
-        "lang_en",
-        "English",
-        true,
-        current_locale == "en",
-        None::<&str>,
-    )?;
-    let lang_ja = CheckMenuItem::with_id(
-        app,
-        "lang_ja",
-        "日本語",
-        true,
-        current_locale == "ja",
-        None::<&str>,
-    )?;
-    let lang_submenu = Submenu::with_id_and_items(
-        app,
-        "language",
-        s.language,
-        true,
-        &[&lang_zh, &lang_en, &lang_ja],
-    )?;
+    let language_items = i18n::LOCALES
+        .iter()
+        .map(|locale| {
+            CheckMenuItem::with_id(
+                app,
+                format!("lang_{}", locale.code),
+                i18n::strings(locale.code).language_name,
+                true,
+                current_locale == locale.code,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let language_refs: Vec<&dyn tauri::menu::IsMenuItem<R>> = language_items
+        .iter()
+        .map(|item| item as &dyn tauri::menu::IsMenuItem<R>)
+        .collect();
+    let lang_submenu =
+        Submenu::with_id_and_items(app, "language", s.language, true, &language_refs)?;
 
     #[cfg(target_os = "windows")]
     let prevent_sleep = CheckMenuItem::with_id(
@@ -426,9 +418,16 @@ pub(super) fn build_tray_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<M
     if active_notification.is_some() {
         items.push(&notification_item);
     }
+    if let Some(sessions_submenu) = &sessions_submenu {
+        items.push(sessions_submenu);
+    }
+    items.push(&history_submenu);
     items.push(&primary_separator);
     items.push(&open_main_panel);
     items.push(&open_desktop);
+    if let Some(apps_submenu) = &apps_submenu {
+        items.push(apps_submenu);
+    }
     #[cfg(target_os = "windows")]
     items.push(&auto_start);
     items.push(&interfaces_submenu);
@@ -7,6 +7,11 @@ use tauri_plugin_notification::NotificationExt;
 #[cfg(target_os = "windows")]
 use std::{path::PathBuf, sync::OnceLock};
 
+pub(super) mod history;
+pub(super) mod rules;
+
+use rules::NotificationEvent;
+
 #[cfg(target_os = "windows")]
 static WINDOWS_AUMID_REGISTRATION: OnceLock<Result<(), String>> = OnceLock::new();
 #[cfg(target_os = "windows")]
@@ -19,9 +24,12 @@ const WINDOWS_NOTIFICATION_ICON_BYTES: &[u8] = include_bytes!("../../icons/tray/
 const WINDOWS_NOTIFICATION_ICON_FILE: &str = "notification-icon-v2.ico";
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-enum ConnectionChange {
+pub(super) enum ConnectionChange {
     Connected {
+        /// `None` for Cores without session snapshots.
+        session_id: Option<u32>,
         client_name: Option<String>,
+        client_fingerprint: Option<String>,
         highly_suspected_unknown_client: bool,
     },
     Disconnected(Option<String>),
@@ -36,6 +44,8 @@ struct NotificationContent {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub(super) enum NotificationAction {
     OpenPin,
+    /// Disconnect, unpair and block buttons for a suspicious session.
+    SuspiciousClient(u32),
 }
 
 #[cfg(target_os = "windows")]
@@ -106,11 +116,6 @@ fn is_enabled() -> bool {
     crate::desktop_settings::load_desktop_settings_from_disk().notifications
 }
 
-fn connection_is_enabled() -> bool {
-    let settings = crate::desktop_settings::load_desktop_settings_from_disk();
-    settings.notifications && settings.connection_notify
-}
-
 const fn supports_actions() -> bool {
     cfg!(target_os = "windows")
 }
@@ -122,7 +127,7 @@ fn supports_session_snapshots(state: &crate::sunshine::TrayState) -> bool {
         .any(|capability| capability == "sessions-v1")
 }
 
-fn connection_changes(
+pub(super) fn connection_changes(
     previous: Option<&crate::sunshine::TrayState>,
     current: &crate::sunshine::TrayState,
 ) -> Vec<ConnectionChange> {
@@ -142,8 +147,11 @@ fn connection_changes(
                 .any(|previous_session| previous_session.id == session.id)
             {
                 changes.push(ConnectionChange::Connected {
+                    session_id: Some(session.id),
                     client_name: (!session.client_name.trim().is_empty())
                         .then(|| session.client_name.trim().to_string()),
+                    client_fingerprint: (!session.client_fingerprint.trim().is_empty())
+                        .then(|| session.client_fingerprint.trim().to_string()),
                     highly_suspected_unknown_client: session.highly_suspected_unknown_client,
                 });
             }
@@ -164,7 +172,9 @@ fn connection_changes(
     } else if matches!(previous.status.as_str(), "idle" | "paused") && current.status == "streaming"
     {
         vec![ConnectionChange::Connected {
+            session_id: None,
             client_name: None,
+            client_fingerprint: None,
             highly_suspected_unknown_client: false,
         }]
     } else if previous.status == "streaming" && matches!(current.status.as_str(), "idle" | "paused")
@@ -175,6 +185,46 @@ fn connection_changes(
     }
 }
 
+fn connection_event(change: &ConnectionChange) -> (NotificationEvent, Option<&str>) {
+    match change {
+        ConnectionChange::Connected {
+            client_name,
+            highly_suspected_unknown_client,
+            ..
+        } => (
+            if *highly_suspected_unknown_client {
+                NotificationEvent::SuspiciousClient
+            } else {
+                NotificationEvent::ClientConnected
+            },
+            client_name.as_deref(),
+        ),
+        ConnectionChange::Disconnected(client_name) => (
+            NotificationEvent::ClientDisconnected,
+            client_name.as_deref(),
+        ),
+    }
+}
+
+/// Toast buttons for a suspicious client; the tray Sessions menu offers the
+/// same actions where notifications have no buttons. Both need a Core that
+/// can disconnect sessions.
+fn connection_action(
+    change: &ConnectionChange,
+    state: &crate::sunshine::TrayState,
+) -> Option<NotificationAction> {
+    match change {
+        ConnectionChange::Connected {
+            session_id: Some(session_id),
+            highly_suspected_unknown_client: true,
+            ..
+        } if supports_actions() && crate::client_blocklist::can_enforce(state) => {
+            Some(NotificationAction::SuspiciousClient(*session_id))
+        }
+        _ => None,
+    }
+}
+
 fn connection_content(
     strings: &super::TrayStrings,
     change: ConnectionChange,
@@ -183,6 +233,7 @@ fn connection_content(
         ConnectionChange::Connected {
             client_name,
             highly_suspected_unknown_client,
+            ..
         } => {
             let mut body = client_name
                 .map(|name| {
@@ -256,15 +307,41 @@ pub(super) fn show_connection_change_if_any<R: Runtime>(
     previous: Option<&crate::sunshine::TrayState>,
     current: &crate::sunshine::TrayState,
 ) {
-    if !connection_is_enabled() {
+    if !is_enabled() {
         return;
     }
 
     let strings = super::get_tray_strings();
     for change in connection_changes(previous, current) {
+        // Blocked clients are disconnected and logged by the blocklist; a Core
+        // that cannot disconnect them still lets the user know they are here.
+        let blocked = matches!(
+            &change,
+            ConnectionChange::Connected {
+                client_fingerprint: Some(fingerprint),
+                ..
+            } if crate::client_blocklist::is_blocked(fingerprint)
+                && crate::client_blocklist::can_enforce(current)
+        );
+        if blocked {
+            continue;
+        }
+        let (event, client_name) = connection_event(&change);
+        if !rules::allows(event, client_name) {
+            continue;
+        }
+        let action = connection_action(&change, current);
         let content = connection_content(strings, change);
-        if let Err(error) = show(app, &content.title, &content.body, None) {
-            warn!("{}", error);
+        match show(app, &content.title, &content.body, action) {
+            Ok(()) => history::record(
+                app,
+                &content.title,
+                &content.body,
+                Some(event),
+                "",
+                Some(current),
+            ),
+            Err(error) => warn!("{}", error),
         }
     }
 }
@@ -286,14 +363,36 @@ pub(super) fn show_core_notification_if_new<R: Runtime>(
         }
     }
 
+    let is_pairing = notification.action == "open_pin";
+    let (event, client) = if is_pairing {
+        (
+            NotificationEvent::PairingRequest,
+            Some(state.pairing_client_name.as_str()),
+        )
+    } else {
+        (NotificationEvent::CoreMessage, None)
+    };
+    if !rules::allows(event, client) {
+        // Remember it anyway so the next state update does not ask again.
+        *LAST_SHOWN_NOTIFICATION.lock().unwrap() = Some(key);
+        return;
+    }
+
     let supports_actions = supports_actions();
     let content = core_notification_content(super::get_tray_strings(), state, supports_actions);
-    let action = (notification.action == "open_pin" && supports_actions)
-        .then_some(NotificationAction::OpenPin);
+    let action = (is_pairing && supports_actions).then_some(NotificationAction::OpenPin);
     match show(app, &content.title, &content.body, action) {
         Ok(()) => {
             *LAST_SHOWN_NOTIFICATION.lock().unwrap() = Some(key);
             debug!("Queued native tray notification {}", notification.id);
+            history::record(
+                app,
+                &content.title,
+                &content.body,
+                Some(event),
+                &notification.action,
+                Some(state),
+            );
         }
         Err(error) => warn!("{}", error),
     }
@@ -351,6 +450,7 @@ fn show_windows<R: Runtime>(
     body: &str,
     action: Option<NotificationAction>,
 ) -> Result<(), String> {
+    use super::sessions_menu::SessionAction;
     use std::sync::mpsc;
     use tauri_winrt_notification::{IconCrop, Sound, Toast};
 
@@ -369,16 +469,28 @@ fn show_windows<R: Runtime>(
             .map_err(|error| format!("Failed to show native tray notification: {error:?}"))?;
         return Ok(());
     };
+    if let NotificationAction::SuspiciousClient(session_id) = action {
+        // Button arguments are Sessions menu ids, handled like a menu click.
+        let strings = super::get_tray_strings();
+        for (session_action, label) in [
+            (SessionAction::Disconnect, strings.session_disconnect),
+            (SessionAction::Unpair, strings.session_unpair),
+            (SessionAction::Block, strings.session_block),
+        ] {
+            toast = toast.add_button(label, &session_action.menu_id(session_id));
+        }
+    }
 
-    let (sender, receiver) = mpsc::channel();
+    // `Some("")` for a click on the toast body, `None` when dismissed.
+    let (sender, receiver) = mpsc::channel::<Option<String>>();
     let activated_sender = sender.clone();
     toast = toast
         .on_activated(move |selected_action| {
-            let _ = activated_sender.send(selected_action.is_none());
+            let _ = activated_sender.send(Some(selected_action.unwrap_or_default()));
             Ok(())
         })
         .on_dismissed(move |_| {
-            let _ = sender.send(false);
+            let _ = sender.send(None);
             Ok(())
         });
     toast
@@ -389,12 +501,17 @@ fn show_windows<R: Runtime>(
     std::thread::Builder::new()
         .name("sunshine-notification-action".to_string())
         .spawn(move || {
-            if receiver.recv().unwrap_or(false) {
-                match action {
-                    NotificationAction::OpenPin => {
-                        super::actions::open_pairing_window(&app_handle);
-                    }
+            let Ok(Some(selected)) = receiver.recv() else {
+                return;
+            };
+            match action {
+                NotificationAction::OpenPin if selected.is_empty() => {
+                    super::actions::open_pairing_window(&app_handle);
                 }
+                NotificationAction::SuspiciousClient(_) if !selected.is_empty() => {
+                    super::sessions_menu::handle_menu_event(&app_handle, &selected);
+                }
+                _ => {}
             }
         })
         .map_err(|error| format!("Failed to start notification action listener: {error}"))?;
@@ -423,13 +540,16 @@ mod tests {
         connected.sessions.push(crate::sunshine::TrayClientSession {
             id: 7,
             client_name: "Living Room TV".to_string(),
-            highly_suspected_unknown_client: false,
+            client_fingerprint: "fp-tv".to_string(),
+            ..Default::default()
         });
 
         assert_eq!(
             connection_changes(Some(&idle), &connected),
             vec![ConnectionChange::Connected {
+                session_id: Some(7),
                 client_name: Some("Living Room TV".to_string()),
+                client_fingerprint: Some("fp-tv".to_string()),
                 highly_suspected_unknown_client: false,
             }]
         );
@@ -451,7 +571,9 @@ mod tests {
         assert_eq!(
             connection_changes(Some(&idle), &streaming),
             vec![ConnectionChange::Connected {
+                session_id: None,
                 client_name: None,
+                client_fingerprint: None,
                 highly_suspected_unknown_client: false,
             }]
         );
@@ -462,6 +584,31 @@ mod tests {
         assert!(connection_changes(Some(&streaming), &notification).is_empty());
     }
 
+    #[test]
+    fn suspicious_connections_use_their_own_rule() {
+        let suspicious = ConnectionChange::Connected {
+            session_id: Some(4),
+            client_name: Some("Unknown".to_string()),
+            client_fingerprint: None,
+            highly_suspected_unknown_client: true,
+        };
+        assert_eq!(
+            connection_event(&suspicious),
+            (NotificationEvent::SuspiciousClient, Some("Unknown"))
+        );
+        let mut state = crate::sunshine::TrayState::default();
+        assert_eq!(connection_action(&suspicious, &state), None);
+        state.capabilities = vec!["session-actions-v1".to_string()];
+        assert_eq!(
+            connection_action(&suspicious, &state),
+            supports_actions().then_some(NotificationAction::SuspiciousClient(4))
+        );
+        assert_eq!(
+            connection_event(&ConnectionChange::Disconnected(None)),
+            (NotificationEvent::ClientDisconnected, None)
+        );
+    }
+
     #[test]
     fn connection_changes_ignore_initial_state_and_core_restart() {
         let streaming = tray_state("streaming", "core-instance");
@@ -475,9 +622,11 @@ mod tests {
     fn connection_copy_has_a_clear_title_and_named_detail() {
         assert_eq!(
             connection_content(
-                &super::super::ZH_STRINGS,
+                super::super::i18n::strings("zh"),
                 ConnectionChange::Connected {
+                    session_id: Some(1),
                     client_name: Some("客厅电视".to_string()),
+                    client_fingerprint: None,
                     highly_suspected_unknown_client: false,
                 }
             ),
@@ -488,7 +637,7 @@ mod tests {
         );
         assert_eq!(
             connection_content(
-                &super::super::EN_STRINGS,
+                super::super::i18n::strings("en"),
                 ConnectionChange::Disconnected(None)
             ),
             NotificationContent {
@@ -502,9 +651,11 @@ mod tests {
     fn suspicious_client_warning_is_appended_to_connection_notification() {
         assert_eq!(
             connection_content(
-                &super::super::ZH_STRINGS,
+                super::super::i18n::strings("zh"),
                 ConnectionChange::Connected {
+                    session_id: Some(1),
                     client_name: Some("可疑客户端".to_string()),
+                    client_fingerprint: None,
                     highly_suspected_unknown_client: true,
                 }
             ),
@@ -527,7 +678,7 @@ mod tests {
         state.notification.action = "open_pin".to_string();
 
         assert_eq!(
-            core_notification_content(&super::super::EN_STRINGS, &state, true),
+            core_notification_content(super::super::i18n::strings("en"), &state, true),
             NotificationContent {
                 title: "Moonlight pairing request · Living Room TV".to_string(),
                 body: "Click this notification to enter the 4-digit PIN shown in Moonlight."
@@ -0,0 +1,365 @@
+//! Bounded history of the native notifications shown by the tray.
+//!
+//! Every toast is kept with the action it offered, the tray state revision it
+//! came from and whether the user has read it. The newest `MAX_ENTRIES`
+//! survive restarts in `<config>/Sunshine GUI/notification-history.json`, and
+//! the tray menu shows the unread count with the latest entries.
+
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::menu::{IsMenuItem, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Emitter, Runtime};
+
+use super::rules::NotificationEvent;
+use crate::sunshine;
+use crate::tray::{TrayStrings, menu::compact_menu_text, rebuild_tray_menu};
+
+const HISTORY_FILE: &str = "notification-history.json";
+const MAX_ENTRIES: usize = 100;
+const MENU_ENTRIES: usize = 10;
+const MAX_LABEL_CHARS: usize = 48;
+const ENTRY_PREFIX: &str = "notification_history:";
+const READ_ALL_ID: &str = "notification_history_read_all";
+const CLEAR_ID: &str = "notification_history_clear";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRecord {
+    pub id: u64,
+    pub title: String,
+    pub body: String,
+    /// Rule that let it through; `None` in records written by older versions.
+    #[serde(default)]
+    pub event: Option<NotificationEvent>,
+    /// Action the notification offered, such as `open_pin`; empty for none.
+    #[serde(default)]
+    pub action: String,
+    /// Core instance and tray state revision that raised it; empty and 0 for
+    /// notifications raised by the GUI itself.
+    #[serde(default)]
+    pub instance_id: String,
+    #[serde(default)]
+    pub revision: u64,
+    pub created_at: String,
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct History {
+    next_id: u64,
+    /// Oldest first.
+    entries: VecDeque<NotificationRecord>,
+}
+
+impl History {
+    fn push(&mut self, mut record: NotificationRecord) {
+        let newest = self.entries.back().map_or(0, |entry| entry.id);
+        record.id = self.next_id.max(newest + 1);
+        self.next_id = record.id + 1;
+        self.entries.push_back(record);
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    fn unread(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| !entry.acknowledged)
+            .count()
+    }
+
+    /// Mark entries read, every entry when `ids` is `None`; returns how many
+    /// changed.
+    fn acknowledge(&mut self, ids: Option<&[u64]>) -> usize {
+        let mut changed = 0;
+        for entry in &mut self.entries {
+            if !entry.acknowledged && ids.is_none_or(|ids| ids.contains(&entry.id)) {
+                entry.acknowledged = true;
+                changed += 1;
+            }
+        }
+        changed
+    }
+
+    fn newest_first(&self) -> Vec<NotificationRecord> {
+        self.entries.iter().rev().cloned().collect()
+    }
+}
+
+static HISTORY: Lazy<Mutex<History>> = Lazy::new(|| Mutex::new(load()));
+
+fn history_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(HISTORY_FILE))
+}
+
+fn load() -> History {
+    let Ok(path) = history_path() else {
+        return History::default();
+    };
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return History::default();
+    };
+    serde_json::from_str(&text).unwrap_or_else(|e| {
+        warn!("Invalid notification history, starting empty: {}", e);
+        History::default()
+    })
+}
+
+fn save(history: &History) -> Result<(), String> {
+    let path = history_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(history).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Apply `change` to the history, persist it and refresh the tray and the
+/// frontend. Must run on the main thread, like every tray menu rebuild.
+fn update<R: Runtime, T>(
+    app: &AppHandle<R>,
+    change: impl FnOnce(&mut History) -> T,
+) -> Result<T, String> {
+    let (result, unread) = {
+        let mut history = HISTORY.lock().unwrap();
+        let result = change(&mut history);
+        save(&history)?;
+        (result, history.unread())
+    };
+    let _ = app.emit(
+        "notification-history-changed",
+        serde_json::json!({ "unread": unread }),
+    );
+    rebuild_tray_menu(app);
+    Ok(result)
+}
+
+/// Keep a notification that was just shown.
+pub(in crate::tray) fn record<R: Runtime>(
+    app: &AppHandle<R>,
+    title: &str,
+    body: &str,
+    event: Option<NotificationEvent>,
+    action: &str,
+    state: Option<&sunshine::TrayState>,
+) {
+    let record = NotificationRecord {
+        id: 0,
+        title: title.to_string(),
+        body: body.to_string(),
+        event,
+        action: action.to_string(),
+        instance_id: state
+            .map(|state| state.instance_id.clone())
+            .unwrap_or_default(),
+        revision: state.map_or(0, |state| state.revision),
+        created_at: chrono::Local::now().to_rfc3339(),
+        acknowledged: false,
+    };
+    if let Err(e) = update(app, |history| history.push(record)) {
+        warn!("Failed to save notification history: {}", e);
+    }
+}
+
+pub(in crate::tray) fn list() -> Vec<NotificationRecord> {
+    HISTORY.lock().unwrap().newest_first()
+}
+
+pub(in crate::tray) fn acknowledge<R: Runtime>(
+    app: &AppHandle<R>,
+    ids: Option<Vec<u64>>,
+) -> Result<usize, String> {
+    update(app, |history| history.acknowledge(ids.as_deref()))
+}
+
+pub(in crate::tray) fn clear<R: Runtime>(app: &AppHandle<R>) -> Result<usize, String> {
+    update(app, |history| {
+        let removed = history.entries.len();
+        history.entries.clear();
+        removed
+    })
+}
+
+/// The notification history submenu, titled with the unread count.
+pub(in crate::tray) fn build_submenu<R: Runtime>(
+    app: &AppHandle<R>,
+    s: &TrayStrings,
+) -> tauri::Result<Submenu<R>> {
+    let (entries, unread) = {
+        let history = HISTORY.lock().unwrap();
+        let entries: Vec<_> = history
+            .entries
+            .iter()
+            .rev()
+            .take(MENU_ENTRIES)
+            .cloned()
+            .collect();
+        (entries, history.unread())
+    };
+
+    let mut items = entries
+        .iter()
+        .map(|entry| {
+            let title = compact_menu_text(&entry.title, MAX_LABEL_CHARS);
+            let label = if entry.acknowledged {
+                title
+            } else {
+                format!("● {title}")
+            };
+            MenuItem::with_id(
+                app,
+                format!("{ENTRY_PREFIX}{}", entry.id),
+                label,
+                true,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    if items.is_empty() {
+        items.push(MenuItem::with_id(
+            app,
+            "notification_history_empty",
+            s.notification_history_empty,
+            false,
+            None::<&str>,
+        )?);
+    }
+    let separator = PredefinedMenuItem::separator(app)?;
+    let read_all = MenuItem::with_id(
+        app,
+        READ_ALL_ID,
+        s.notification_mark_all_read,
+        unread > 0,
+        None::<&str>,
+    )?;
+    let clear = MenuItem::with_id(
+        app,
+        CLEAR_ID,
+        s.notification_clear,
+        !entries.is_empty(),
+        None::<&str>,
+    )?;
+
+    let mut refs: Vec<&dyn IsMenuItem<R>> = items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<R>)
+        .collect();
+    refs.extend([
+        &separator as &dyn IsMenuItem<R>,
+        &read_all as &dyn IsMenuItem<R>,
+        &clear as &dyn IsMenuItem<R>,
+    ]);
+    let title = if unread > 0 {
+        s.notification_history_unread
+            .replace("{count}", &unread.to_string())
+    } else {
+        s.notification_history.to_string()
+    };
+    Submenu::with_id_and_items(app, "notification_history", title, true, &refs)
+}
+
+/// Handle a notification history menu id; returns `false` for ids that are
+/// not ours.
+pub(in crate::tray) fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, menu_id: &str) -> bool {
+    let result = match menu_id {
+        READ_ALL_ID => acknowledge(app, None),
+        CLEAR_ID => clear(app).inspect(|removed| info!("🔔 已清空 {} 条通知记录", removed)),
+        _ => {
+            let Some(id) = menu_id
+                .strip_prefix(ENTRY_PREFIX)
+                .and_then(|id| id.parse::<u64>().ok())
+            else {
+                return false;
+            };
+            let action = HISTORY
+                .lock()
+                .unwrap()
+                .entries
+                .iter()
+                .find(|entry| entry.id == id)
+                .map(|entry| entry.action.clone());
+            if action.as_deref() == Some("open_pin") {
+                crate::tray::actions::open_pairing_window(app);
+            }
+            acknowledge(app, Some(vec![id]))
+        }
+    };
+    match result {
+        Ok(changed) => debug!(
+            "Notification history menu '{}': {} changed",
+            menu_id, changed
+        ),
+        Err(e) => warn!("Failed to update notification history: {}", e),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(title: &str) -> NotificationRecord {
+        NotificationRecord {
+            id: 0,
+            title: title.to_string(),
+            body: String::new(),
+            event: None,
+            action: String::new(),
+            instance_id: String::new(),
+            revision: 0,
+            created_at: String::new(),
+            acknowledged: false,
+        }
+    }
+
+    #[test]
+    fn history_is_bounded_and_ids_keep_growing() {
+        let mut history = History::default();
+        for index in 0..MAX_ENTRIES + 5 {
+            history.push(record(&index.to_string()));
+        }
+
+        assert_eq!(history.entries.len(), MAX_ENTRIES);
+        assert_eq!(history.entries.front().unwrap().title, "5");
+        let newest = history.newest_first();
+        assert_eq!(newest[0].id, (MAX_ENTRIES + 5) as u64);
+        assert_eq!(newest[0].title, (MAX_ENTRIES + 4).to_string());
+
+        history.entries.clear();
+        history.push(record("after clear"));
+        assert_eq!(history.entries[0].id, (MAX_ENTRIES + 6) as u64);
+    }
+
+    #[test]
+    fn acknowledging_counts_only_unread_entries() {
+        let mut history = History::default();
+        for title in ["a", "b", "c"] {
+            history.push(record(title));
+        }
+        assert_eq!(history.unread(), 3);
+
+        assert_eq!(history.acknowledge(Some(&[2, 42])), 1);
+        assert_eq!(history.acknowledge(Some(&[2])), 0);
+        assert_eq!(history.unread(), 2);
+        assert_eq!(history.acknowledge(None), 2);
+        assert_eq!(history.unread(), 0);
+    }
+
+    #[test]
+    fn saved_history_without_next_id_does_not_reuse_ids() {
+        let mut saved = record("old");
+        saved.id = 7;
+        let text = serde_json::json!({ "entries": [saved] }).to_string();
+        let mut history: History = serde_json::from_str(&text).unwrap();
+
+        history.push(record("new"));
+        assert_eq!(history.entries[1].id, 8);
+    }
+}
@@ -0,0 +1,609 @@
+//! User-configurable notification rules.
+//!
+//! Every notification the GUI raises has a rule, optionally narrowed to one
+//! client by name; a client-specific rule wins over the generic one for the
+//! same event. Rules can be switched off, throttled, or marked urgent so they
+//! still fire during quiet hours and while a fullscreen game runs. The VDD
+//! keep-display confirmation is a dialog rather than a notice and has no rule.
+//! The global `notifications` desktop setting still gates everything. Rules
+//! live in `<config>/Sunshine GUI/notification-rules.json`; the first load
+//! takes over the connection and update switches that used to live in the
+//! desktop settings.
+
+use chrono::Timelike;
+use log::{debug, info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::desktop_settings::LegacyNotificationSwitches;
+
+const SETTINGS_FILE: &str = "notification-rules.json";
+const MAX_THROTTLE_SECS: u64 = 24 * 60 * 60;
+/// Events older rule files may still list; their rules are dropped on load.
+const RETIRED_EVENTS: &[&str] = &["vddConfirmation"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationEvent {
+    #[default]
+    ClientConnected,
+    ClientDisconnected,
+    PairingRequest,
+    SuspiciousClient,
+    /// The Core stopped without announcing a shutdown, or the watchdog gave
+    /// up restarting it.
+    CoreCrash,
+    UpdateAvailable,
+    /// Any other notice the Core raises through its tray state.
+    CoreMessage,
+}
+
+impl NotificationEvent {
+    const ALL: [Self; 7] = [
+        Self::ClientConnected,
+        Self::ClientDisconnected,
+        Self::PairingRequest,
+        Self::SuspiciousClient,
+        Self::CoreCrash,
+        Self::UpdateAvailable,
+        Self::CoreMessage,
+    ];
+
+    fn names_client(self) -> bool {
+        matches!(
+            self,
+            Self::ClientConnected
+                | Self::ClientDisconnected
+                | Self::PairingRequest
+                | Self::SuspiciousClient
+        )
+    }
+
+    /// Events that interrupt quiet hours unless the user says otherwise.
+    fn urgent_by_default(self) -> bool {
+        matches!(
+            self,
+            Self::PairingRequest | Self::SuspiciousClient | Self::CoreCrash
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationRule {
+    pub event: NotificationEvent,
+    /// Client name this rule is limited to; empty matches every client.
+    pub client: String,
+    pub enabled: bool,
+    /// Minimum seconds between two notifications from this rule, 0 for none.
+    pub throttle_secs: u64,
+    /// Shown during quiet hours and while a fullscreen game runs.
+    pub urgent: bool,
+}
+
+impl Default for NotificationRule {
+    fn default() -> Self {
+        Self::generic(NotificationEvent::default())
+    }
+}
+
+impl NotificationRule {
+    fn generic(event: NotificationEvent) -> Self {
+        Self {
+            event,
+            client: String::new(),
+            enabled: true,
+            throttle_secs: 0,
+            urgent: event.urgent_by_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// Local time as `HH:MM`; a window that ends before it starts spans midnight.
+    pub start: String,
+    pub end: String,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+        }
+    }
+}
+
+impl QuietHours {
+    fn contains(&self, minute_of_day: u32) -> bool {
+        let (Some(start), Some(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            (start..end).contains(&minute_of_day)
+        } else {
+            minute_of_day >= start || minute_of_day < end
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationRules {
+    pub rules: Vec<NotificationRule>,
+    pub quiet_hours: QuietHours,
+    /// Hold back non-urgent notifications while a fullscreen app is in front.
+    pub mute_during_fullscreen: bool,
+}
+
+impl Default for NotificationRules {
+    fn default() -> Self {
+        Self {
+            rules: NotificationEvent::ALL
+                .into_iter()
+                .map(NotificationRule::generic)
+                .collect(),
+            quiet_hours: QuietHours::default(),
+            mute_during_fullscreen: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Show,
+    Disabled,
+    QuietHours,
+    Fullscreen,
+    Throttled,
+}
+
+struct Context {
+    minute_of_day: u32,
+    fullscreen: bool,
+    now: Instant,
+}
+
+type ThrottleKey = (NotificationEvent, String);
+
+impl NotificationRules {
+    fn validated(mut self) -> Result<Self, String> {
+        let mut seen = HashSet::new();
+        for rule in &mut self.rules {
+            rule.client = rule.client.trim().to_string();
+            if !rule.client.is_empty() && !rule.event.names_client() {
+                return Err(format!("{:?} rules cannot name a client", rule.event));
+            }
+            if rule.throttle_secs > MAX_THROTTLE_SECS {
+                return Err(format!(
+                    "throttleSecs must not exceed {}",
+                    MAX_THROTTLE_SECS
+                ));
+            }
+            if !seen.insert((rule.event, rule.client.to_lowercase())) {
+                return Err(format!(
+                    "duplicate {:?} rule for '{}'",
+                    rule.event, rule.client
+                ));
+            }
+        }
+        for time in [&self.quiet_hours.start, &self.quiet_hours.end] {
+            if parse_time(time).is_none() {
+                return Err(format!(
+                    "invalid quiet hours time '{}', expected HH:MM",
+                    time
+                ));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Carry the old desktop-setting switches over to the generic rules.
+    fn with_legacy_switches(mut self, legacy: &LegacyNotificationSwitches) -> Self {
+        for rule in self.rules.iter_mut().filter(|rule| rule.client.is_empty()) {
+            let switch = match rule.event {
+                NotificationEvent::ClientConnected | NotificationEvent::ClientDisconnected => {
+                    legacy.connection_notify
+                }
+                NotificationEvent::UpdateAvailable => legacy.update_notify,
+                _ => None,
+            };
+            if let Some(enabled) = switch {
+                rule.enabled = enabled;
+            }
+        }
+        self
+    }
+
+    fn rule_for(
+        &self,
+        event: NotificationEvent,
+        client: Option<&str>,
+    ) -> Option<&NotificationRule> {
+        let client = client.map(str::trim).filter(|client| !client.is_empty());
+        let rules = || self.rules.iter().filter(move |rule| rule.event == event);
+        client
+            .and_then(|client| {
+                rules().find(|rule| {
+                    !rule.client.is_empty() && rule.client.to_lowercase() == client.to_lowercase()
+                })
+            })
+            .or_else(|| rules().find(|rule| rule.client.is_empty()))
+    }
+
+    fn evaluate(
+        &self,
+        event: NotificationEvent,
+        client: Option<&str>,
+        context: &Context,
+        last_fired: &mut HashMap<ThrottleKey, Instant>,
+    ) -> Verdict {
+        // Events without a rule keep the pre-rules behaviour.
+        let Some(rule) = self.rule_for(event, client) else {
+            return Verdict::Show;
+        };
+        if !rule.enabled {
+            return Verdict::Disabled;
+        }
+        if !rule.urgent {
+            if self.quiet_hours.enabled && self.quiet_hours.contains(context.minute_of_day) {
+                return Verdict::QuietHours;
+            }
+            if self.mute_during_fullscreen && context.fullscreen {
+                return Verdict::Fullscreen;
+            }
+        }
+        if rule.throttle_secs > 0 {
+            let key = (event, rule.client.to_lowercase());
+            let throttle = Duration::from_secs(rule.throttle_secs);
+            if last_fired
+                .get(&key)
+                .is_some_and(|last| context.now.duration_since(*last) < throttle)
+            {
+                return Verdict::Throttled;
+            }
+            last_fired.insert(key, context.now);
+        }
+        Verdict::Show
+    }
+}
+
+static RULES: Lazy<Mutex<NotificationRules>> = Lazy::new(|| Mutex::new(load()));
+static LAST_FIRED: Lazy<Mutex<HashMap<ThrottleKey, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(SETTINGS_FILE))
+}
+
+fn load() -> NotificationRules {
+    let Ok(path) = settings_path() else {
+        return NotificationRules::default();
+    };
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return migrate_legacy(&path),
+        Err(_) => return NotificationRules::default(),
+    };
+    parse(&text).unwrap_or_else(|e| {
+        warn!("Invalid notification rules, using defaults: {}", e);
+        NotificationRules::default()
+    })
+}
+
+fn parse(text: &str) -> Result<NotificationRules, String> {
+    let mut value: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if let Some(rules) = value
+        .get_mut("rules")
+        .and_then(|rules| rules.as_array_mut())
+    {
+        rules.retain(|rule| {
+            let event = rule.get("event").and_then(|event| event.as_str());
+            !event.is_some_and(|event| RETIRED_EVENTS.contains(&event))
+        });
+    }
+    serde_json::from_value::<NotificationRules>(value)
+        .map_err(|e| e.to_string())
+        .and_then(NotificationRules::validated)
+}
+
+/// Start from the switches older versions kept in the desktop settings and
+/// write them out as rules, so they are read only once.
+fn migrate_legacy(path: &Path) -> NotificationRules {
+    let legacy = crate::desktop_settings::legacy_notification_switches();
+    let rules = NotificationRules::default().with_legacy_switches(&legacy);
+    if legacy.connection_notify.is_some() || legacy.update_notify.is_some() {
+        match write(path, &rules) {
+            Ok(()) => info!("🔔 已将旧的通知开关迁移为通知规则"),
+            Err(e) => warn!("Failed to write migrated notification rules: {}", e),
+        }
+    }
+    rules
+}
+
+fn write(path: &Path, rules: &NotificationRules) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(rules).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+pub(in crate::tray) fn current() -> NotificationRules {
+    RULES.lock().unwrap().clone()
+}
+
+pub(in crate::tray) fn save(rules: NotificationRules) -> Result<NotificationRules, String> {
+    let rules = rules.validated()?;
+    write(&settings_path()?, &rules)?;
+    *RULES.lock().unwrap() = rules.clone();
+    LAST_FIRED.lock().unwrap().clear();
+    Ok(rules)
+}
+
+/// Whether a notification for `event` may be shown right now. Passing counts
+/// towards the rule's throttle, so call this only right before showing.
+pub(in crate::tray) fn allows(event: NotificationEvent, client: Option<&str>) -> bool {
+    let rules = RULES.lock().unwrap();
+    let needs_fullscreen = rules.mute_during_fullscreen;
+    let context = Context {
+        minute_of_day: {
+            let now = chrono::Local::now();
+            now.hour() * 60 + now.minute()
+        },
+        fullscreen: needs_fullscreen && fullscreen_app_running(),
+        now: Instant::now(),
+    };
+    let verdict = rules.evaluate(event, client, &context, &mut LAST_FIRED.lock().unwrap());
+    if verdict != Verdict::Show {
+        debug!(
+            "Notification {:?} ({}) held back: {:?}",
+            event,
+            client.unwrap_or("-"),
+            verdict
+        );
+    }
+    verdict == Verdict::Show
+}
+
+#[cfg(target_os = "windows")]
+fn fullscreen_app_running() -> bool {
+    use ::windows::Win32::UI::Shell::{
+        QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+        SHQueryUserNotificationState,
+    };
+
+    match unsafe { SHQueryUserNotificationState() } {
+        Ok(state) => [
+            QUNS_BUSY,
+            QUNS_RUNNING_D3D_FULL_SCREEN,
+            QUNS_PRESENTATION_MODE,
+        ]
+        .contains(&state),
+        Err(e) => {
+            debug!("SHQueryUserNotificationState failed: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn fullscreen_app_running() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(minute_of_day: u32, fullscreen: bool) -> Context {
+        Context {
+            minute_of_day,
+            fullscreen,
+            now: Instant::now(),
+        }
+    }
+
+    fn evaluate(
+        rules: &NotificationRules,
+        event: NotificationEvent,
+        client: Option<&str>,
+        context: &Context,
+    ) -> Verdict {
+        rules.evaluate(event, client, context, &mut HashMap::new())
+    }
+
+    #[test]
+    fn defaults_cover_every_event_and_validate() {
+        let rules = NotificationRules::default().validated().unwrap();
+        for event in NotificationEvent::ALL {
+            let rule = rules.rule_for(event, None).expect("generic rule");
+            assert!(rule.enabled);
+            assert_eq!(rule.urgent, event.urgent_by_default());
+        }
+        let parsed: NotificationRules = serde_json::from_str("{}").unwrap();
+        assert_eq!(parsed, NotificationRules::default());
+    }
+
+    #[test]
+    fn client_rules_override_the_generic_rule() {
+        let mut rules = NotificationRules::default();
+        rules.rules.push(NotificationRule {
+            client: "Living Room TV".to_string(),
+            enabled: false,
+            ..NotificationRule::generic(NotificationEvent::ClientConnected)
+        });
+        let now = context(12 * 60, false);
+
+        assert_eq!(
+            evaluate(
+                &rules,
+                NotificationEvent::ClientConnected,
+                Some("living room tv"),
+                &now
+            ),
+            Verdict::Disabled
+        );
+        assert_eq!(
+            evaluate(
+                &rules,
+                NotificationEvent::ClientConnected,
+                Some("Steam Deck"),
+                &now
+            ),
+            Verdict::Show
+        );
+        assert_eq!(
+            evaluate(
+                &rules,
+                NotificationEvent::ClientDisconnected,
+                Some("Living Room TV"),
+                &now
+            ),
+            Verdict::Show
+        );
+    }
+
+    #[test]
+    fn quiet_hours_and_fullscreen_spare_urgent_rules() {
+        let mut rules = NotificationRules::default();
+        rules.quiet_hours.enabled = true;
+
+        let night = context(23 * 60, false);
+        let morning = context(6 * 60 + 59, false);
+        let day = context(7 * 60, false);
+        assert_eq!(
+            evaluate(&rules, NotificationEvent::ClientConnected, None, &night),
+            Verdict::QuietHours
+        );
+        assert_eq!(
+            evaluate(&rules, NotificationEvent::ClientConnected, None, &morning),
+            Verdict::QuietHours
+        );
+        assert_eq!(
+            evaluate(&rules, NotificationEvent::ClientConnected, None, &day),
+            Verdict::Show
+        );
+        assert_eq!(
+            evaluate(&rules, NotificationEvent::SuspiciousClient, None, &night),
+            Verdict::Show
+        );
+
+        let gaming = context(12 * 60, true);
+        assert_eq!(
+            evaluate(&rules, NotificationEvent::UpdateAvailable, None, &gaming),
+            Verdict::Show
+        );
+        rules.mute_during_fullscreen = true;
+        assert_eq!(
+            evaluate(&rules, NotificationEvent::UpdateAvailable, None, &gaming),
+            Verdict::Fullscreen
+        );
+        assert_eq!(
+            evaluate(&rules, NotificationEvent::CoreCrash, None, &gaming),
+            Verdict::Show
+        );
+    }
+
+    #[test]
+    fn legacy_switches_and_retired_events_are_migrated() {
+        let legacy = LegacyNotificationSwitches {
+            connection_notify: Some(false),
+            update_notify: None,
+        };
+        let rules = NotificationRules::default().with_legacy_switches(&legacy);
+        let enabled = |event| rules.rule_for(event, None).unwrap().enabled;
+        assert!(!enabled(NotificationEvent::ClientConnected));
+        assert!(!enabled(NotificationEvent::ClientDisconnected));
+        assert!(enabled(NotificationEvent::UpdateAvailable));
+        assert!(enabled(NotificationEvent::CoreCrash));
+
+        let parsed = parse(
+            r#"{"rules": [
+                {"event": "vddConfirmation", "enabled": true},
+                {"event": "coreCrash", "enabled": false}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.rules.len(), 1);
+        assert!(!parsed.rules[0].enabled);
+    }
+
+    #[test]
+    fn throttling_is_per_rule() {
+        let mut rules = NotificationRules::default();
+        rules.rules[0].throttle_secs = 60;
+        let mut last_fired = HashMap::new();
+        let start = context(12 * 60, false);
+        let soon = Context {
+            now: start.now + Duration::from_secs(30),
+            ..context(12 * 60, false)
+        };
+        let later = Context {
+            now: start.now + Duration::from_secs(61),
+            ..context(12 * 60, false)
+        };
+        let connected = NotificationEvent::ClientConnected;
+
+        assert_eq!(
+            rules.evaluate(connected, Some("TV"), &start, &mut last_fired),
+            Verdict::Show
+        );
+        assert_eq!(
+            rules.evaluate(connected, Some("Deck"), &soon, &mut last_fired),
+            Verdict::Throttled
+        );
+        assert_eq!(
+            rules.evaluate(
+                NotificationEvent::ClientDisconnected,
+                Some("TV"),
+                &soon,
+                &mut last_fired
+            ),
+            Verdict::Show
+        );
+        assert_eq!(
+            rules.evaluate(connected, Some("TV"), &later, &mut last_fired),
+            Verdict::Show
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let mut rules = NotificationRules::default();
+        rules.quiet_hours.start = "25:00".to_string();
+        assert!(rules.validated().is_err());
+
+        let mut rules = NotificationRules::default();
+        rules.rules.push(NotificationRule {
+            client: "TV".to_string(),
+            ..NotificationRule::generic(NotificationEvent::CoreCrash)
+        });
+        assert!(rules.validated().is_err());
+
+        let mut rules = NotificationRules::default();
+        rules.rules.push(NotificationRule::generic(
+            NotificationEvent::ClientConnected,
+        ));
+        assert!(rules.validated().is_err());
+
+        let mut rules = NotificationRules::default();
+        rules.rules[0].throttle_secs = MAX_THROTTLE_SECS + 1;
+        assert!(rules.validated().is_err());
+    }
+}
@@ -0,0 +1,333 @@
+//! "Sessions" submenu with per-client controls.
+//!
+//! Disconnect, pause/resume and send-file go through the Core's tray actions
+//! so the Core decides what happens to a session; bitrate presets use the
+//! runtime bitrate API. Sessions flagged as suspicious also offer unpairing
+//! and blocking their fingerprint, which disconnect them as well and so need
+//! the same Core support as the other actions. Menu ids carry the session id,
+//! and the session is looked up again on click so a stale menu never acts on
+//! the wrong client.
+
+use super::*;
+use tauri::menu::{IsMenuItem, MenuItem, PredefinedMenuItem, Submenu};
+
+const ID_PREFIX: &str = "session:";
+const ACTIONS_CAPABILITY: &str = "session-actions-v1";
+const MAX_LABEL_CHARS: usize = 40;
+const BITRATE_PRESETS_KBPS: &[u32] = &[5_000, 10_000, 20_000, 40_000, 80_000, 150_000];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SessionAction {
+    Disconnect,
+    Pause,
+    Resume,
+    Bitrate(u32),
+    SendFile,
+    Unpair,
+    Block,
+}
+
+impl SessionAction {
+    pub(super) fn menu_id(self, session_id: u32) -> String {
+        let action = match self {
+            Self::Disconnect => "disconnect".to_string(),
+            Self::Pause => "pause".to_string(),
+            Self::Resume => "resume".to_string(),
+            Self::Bitrate(kbps) => format!("bitrate:{kbps}"),
+            Self::SendFile => "send_file".to_string(),
+            Self::Unpair => "unpair".to_string(),
+            Self::Block => "block".to_string(),
+        };
+        format!("{ID_PREFIX}{session_id}:{action}")
+    }
+
+    fn tray_action(self) -> Option<&'static str> {
+        match self {
+            Self::Disconnect => Some("session_disconnect"),
+            Self::Pause => Some("session_pause"),
+            Self::Resume => Some("session_resume"),
+            Self::Bitrate(_) | Self::SendFile | Self::Unpair | Self::Block => None,
+        }
+    }
+}
+
+fn parse_menu_id(menu_id: &str) -> Option<(u32, SessionAction)> {
+    let (session_id, action) = menu_id.strip_prefix(ID_PREFIX)?.split_once(':')?;
+    let action = match action {
+        "disconnect" => SessionAction::Disconnect,
+        "pause" => SessionAction::Pause,
+        "resume" => SessionAction::Resume,
+        "send_file" => SessionAction::SendFile,
+        "unpair" => SessionAction::Unpair,
+        "block" => SessionAction::Block,
+        other => SessionAction::Bitrate(other.strip_prefix("bitrate:")?.parse().ok()?),
+    };
+    Some((session_id.parse().ok()?, action))
+}
+
+fn session_label(session: &sunshine::TrayClientSession, state: &sunshine::TrayState) -> String {
+    let app_name = if session.app_name.is_empty() {
+        state.app_name.as_str()
+    } else {
+        session.app_name.as_str()
+    };
+    let client_name = if session.client_name.is_empty() {
+        format!("#{}", session.id)
+    } else {
+        session.client_name.clone()
+    };
+    let mut label = if app_name.is_empty() {
+        client_name
+    } else {
+        format!("{client_name} · {app_name}")
+    };
+    if session.highly_suspected_unknown_client {
+        label.insert_str(0, "⚠ ");
+    }
+    menu::compact_menu_text(&label, MAX_LABEL_CHARS)
+}
+
+fn bitrate_label(kbps: u32) -> String {
+    format!("{} Mbps", kbps / 1000)
+}
+
+fn session_submenu<R: Runtime>(
+    app: &AppHandle<R>,
+    s: &TrayStrings,
+    state: &sunshine::TrayState,
+    session: &sunshine::TrayClientSession,
+    actions_supported: bool,
+) -> tauri::Result<Submenu<R>> {
+    let item = |action: SessionAction, label: &str, enabled: bool| {
+        MenuItem::with_id(
+            app,
+            action.menu_id(session.id),
+            label,
+            enabled,
+            None::<&str>,
+        )
+    };
+    let pause = if session.paused {
+        item(SessionAction::Resume, s.session_resume, actions_supported)?
+    } else {
+        item(SessionAction::Pause, s.session_pause, actions_supported)?
+    };
+    let presets = BITRATE_PRESETS_KBPS
+        .iter()
+        .map(|&kbps| item(SessionAction::Bitrate(kbps), &bitrate_label(kbps), true))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let preset_refs: Vec<&dyn IsMenuItem<R>> = presets
+        .iter()
+        .map(|preset| preset as &dyn IsMenuItem<R>)
+        .collect();
+    let bitrate = Submenu::with_id_and_items(
+        app,
+        format!("{ID_PREFIX}{}:bitrate", session.id),
+        s.session_bitrate,
+        !session.client_name.is_empty(),
+        &preset_refs,
+    )?;
+    let send_file = item(
+        SessionAction::SendFile,
+        s.session_send_file,
+        actions_supported,
+    )?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let disconnect = item(
+        SessionAction::Disconnect,
+        s.session_disconnect,
+        actions_supported,
+    )?;
+
+    let can_remove = actions_supported && !session.client_fingerprint.trim().is_empty();
+    let unpair = item(SessionAction::Unpair, s.session_unpair, can_remove)?;
+    let block = item(SessionAction::Block, s.session_block, can_remove)?;
+    let mut items: Vec<&dyn IsMenuItem<R>> =
+        vec![&pause, &bitrate, &send_file, &separator, &disconnect];
+    if session.highly_suspected_unknown_client {
+        items.extend([&unpair as &dyn IsMenuItem<R>, &block as &dyn IsMenuItem<R>]);
+    }
+
+    Submenu::with_id_and_items(
+        app,
+        format!("{ID_PREFIX}{}", session.id),
+        session_label(session, state),
+        true,
+        &items,
+    )
+}
+
+/// The Sessions submenu, or `None` while no client is connected.
+pub(super) fn build_submenu<R: Runtime>(
+    app: &AppHandle<R>,
+    s: &TrayStrings,
+    state: Option<&sunshine::TrayState>,
+) -> tauri::Result<Option<Submenu<R>>> {
+    let Some(state) = state.filter(|state| !state.sessions.is_empty()) else {
+        return Ok(None);
+    };
+    let actions_supported = state
+        .capabilities
+        .iter()
+        .any(|capability| capability == ACTIONS_CAPABILITY);
+    let sessions = state
+        .sessions
+        .iter()
+        .map(|session| session_submenu(app, s, state, session, actions_supported))
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let session_refs: Vec<&dyn IsMenuItem<R>> = sessions
+        .iter()
+        .map(|session| session as &dyn IsMenuItem<R>)
+        .collect();
+    Submenu::with_id_and_items(app, "sessions", s.sessions_menu, true, &session_refs).map(Some)
+}
+
+/// Handle a `session:` menu id, also used for suspicious-client notification
+/// buttons; returns `false` for ids that are not ours.
+pub(super) fn handle_menu_event<R: Runtime + 'static>(app: &AppHandle<R>, menu_id: &str) -> bool {
+    let Some((session_id, action)) = parse_menu_id(menu_id) else {
+        return false;
+    };
+    let Some(session) = current_client_sessions()
+        .into_iter()
+        .find(|session| session.id == session_id)
+    else {
+        warn!("托盘会话菜单: 会话 {} 已结束", session_id);
+        return true;
+    };
+    info!(
+        "🎮 托盘菜单：会话 {} ({}) {:?}",
+        session_id, session.client_name, action
+    );
+
+    let app_handle = app.clone();
+    match action {
+        SessionAction::Bitrate(kbps) => {
+            tauri::async_runtime::spawn(async move {
+                match sunshine::change_local_bitrate(&session.client_name, kbps).await {
+                    Ok(()) => info!("✅ {} 码率已调整为 {} Kbps", session.client_name, kbps),
+                    Err(error) => {
+                        let message = get_tray_strings()
+                            .session_bitrate_failed
+                            .replace("{error}", error.detail());
+                        error!("❌ {}", message);
+                        emit_message(&app_handle, "error", &message);
+                    }
+                }
+            });
+        }
+        SessionAction::SendFile => {
+            tauri::async_runtime::spawn(async move {
+                let Some(path) = pick_file(&app_handle).await else {
+                    return;
+                };
+                let result = crate::file_transfer::send_file_to_session(session_id, &path).await;
+                actions::report_tray_action_result(&app_handle, "session_send_file", result);
+            });
+        }
+        SessionAction::Unpair => {
+            tauri::async_runtime::spawn(async move {
+                if let Err(error) = crate::paired_clients::unpair_session_client(&session).await {
+                    let message = get_tray_strings()
+                        .session_unpair_failed
+                        .replace("{error}", &error);
+                    error!("❌ {}", message);
+                    emit_message(&app_handle, "error", &message);
+                    return;
+                }
+                let result =
+                    sunshine::post_tray_session_action("session_disconnect", session_id, None)
+                        .await;
+                actions::report_tray_action_result(&app_handle, "session_disconnect", result);
+            });
+        }
+        SessionAction::Block => {
+            if let Err(error) = crate::client_blocklist::block_session(&session) {
+                let message = get_tray_strings()
+                    .session_block_failed
+                    .replace("{error}", &error);
+                error!("❌ {}", message);
+                emit_message(app, "error", &message);
+                return true;
+            }
+            tauri::async_runtime::spawn(async move {
+                let result = crate::client_blocklist::disconnect_session(session_id).await;
+                actions::report_tray_action_result(&app_handle, "session_disconnect", result);
+            });
+        }
+        action => {
+            let Some(tray_action) = action.tray_action() else {
+                return true;
+            };
+            tauri::async_runtime::spawn(async move {
+                let result =
+                    sunshine::post_tray_session_action(tray_action, session_id, None).await;
+                actions::report_tray_action_result(&app_handle, tray_action, result);
+            });
+        }
+    }
+    true
+}
+
+async fn pick_file<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    use tauri_plugin_dialog::DialogExt;
+    use tokio::sync::oneshot;
+
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title(get_tray_strings().session_send_file)
+        .pick_file(move |file_path| {
+            let _ = tx.send(file_path);
+        });
+    rx.await.ok().flatten().map(|path| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn menu_ids_round_trip() {
+        for action in [
+            SessionAction::Disconnect,
+            SessionAction::Pause,
+            SessionAction::Resume,
+            SessionAction::Bitrate(20_000),
+            SessionAction::SendFile,
+            SessionAction::Unpair,
+            SessionAction::Block,
+        ] {
+            assert_eq!(parse_menu_id(&action.menu_id(7)), Some((7, action)));
+        }
+        assert_eq!(parse_menu_id("session:7"), None);
+        assert_eq!(parse_menu_id("session:7:bitrate"), None);
+        assert_eq!(parse_menu_id("session:x:pause"), None);
+        assert_eq!(parse_menu_id("launch_app:Desktop"), None);
+    }
+
+    #[test]
+    fn labels_name_the_client_and_its_app() {
+        let state = sunshine::TrayState {
+            app_name: "Desktop".to_string(),
+            ..Default::default()
+        };
+        let mut session = sunshine::TrayClientSession {
+            id: 3,
+            client_name: "Steam Deck".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(session_label(&session, &state), "Steam Deck · Desktop");
+
+        session.app_name = "Cyberpunk 2077".to_string();
+        session.highly_suspected_unknown_client = true;
+        assert_eq!(
+            session_label(&session, &state),
+            "⚠ Steam Deck · Cyberpunk 2077"
+        );
+
+        session.client_name.clear();
+        assert_eq!(session_label(&session, &state), "⚠ #3 · Cyberpunk 2077");
+        assert_eq!(bitrate_label(150_000), "150 Mbps");
+    }
+}
@@ -9,6 +9,8 @@ pub(super) fn show<R: Runtime>(app: &AppHandle<R>, operation_id: u64) {
         return;
     }
 
+    // This is a dialog, not a notification: rules never hold it back, so only
+    // the user (or the dialog's own timeout) decides.
     let app_handle = app.clone();
     tauri::async_runtime::spawn(async move {
         let keep = tauri::async_runtime::spawn_blocking(show_timed_confirmation)
@@ -1,23 +1,89 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
     fs,
     path::{Path, PathBuf},
 };
 
-use log::error;
+use log::{error, info, warn};
+use serde::Serialize;
 use tauri::{AppHandle, Runtime};
 
-use crate::{sunshine, tray::emit_message};
+use crate::{
+    config_snapshots::{self, SnapshotReason},
+    core_api::{CoreApiError, CoreClient},
+    sunshine::{self, conf::SunshineConf},
+    tray::emit_message,
+};
+
+mod schema;
+
+/// Keys `/api/config` reports that are not settings.
+const CONFIG_METADATA_KEYS: &[&str] = &[
+    "status",
+    "platform",
+    "version",
+    "display_devices",
+    "adapters",
+    "pair_name",
+];
+const MAX_SUMMARY_KEYS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigChangeKind {
+    Added,
+    Changed,
+    Removed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigKeyChange {
+    pub key: String,
+    pub kind: ConfigChangeKind,
+    pub current: Option<String>,
+    pub imported: Option<String>,
+    /// False for keys the GUI schema does not describe.
+    pub known: bool,
+    /// Why the imported value fails schema validation.
+    pub issue: Option<String>,
+    /// Whether the change is applied when the user does not cherry-pick.
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportPreview {
+    pub path: String,
+    pub changes: Vec<ConfigKeyChange>,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigKeyRejection {
+    pub key: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigImportResult {
+    pub applied: Vec<String>,
+    pub rejected: Vec<ConfigKeyRejection>,
+}
 
 pub fn import_config<R: Runtime>(app: &AppHandle<R>) {
     let app_handle = app.clone();
 
     tauri::async_runtime::spawn(async move {
         match import_config_impl(&app_handle).await {
-            Ok(()) => emit_message(
+            Ok(result) if result.rejected.is_empty() => emit_message(
                 &app_handle,
                 "success",
                 "Config imported. Restart Sunshine to apply every setting.",
             ),
+            Ok(result) => emit_message(&app_handle, "warning", &rejection_summary(&result)),
             Err(e) if e == "cancelled" => {}
             Err(e) => {
                 error!("Config import failed: {}", e);
@@ -67,7 +133,11 @@ fn reset_config_after_confirm<R: Runtime>(app: &AppHandle<R>) {
 
     tauri::async_runtime::spawn(async move {
         let config_data = serde_json::Map::new();
-        match sunshine::post_sunshine_config(&config_data).await {
+        let reset = match config_snapshots::snapshot_before_write(SnapshotReason::Reset, None) {
+            Ok(()) => sunshine::post_sunshine_config(&config_data).await,
+            Err(e) => Err(e),
+        };
+        match reset {
             Ok(()) => emit_message(
                 &app_handle,
                 "success",
@@ -81,12 +151,341 @@ fn reset_config_after_confirm<R: Runtime>(app: &AppHandle<R>) {
     });
 }
 
-async fn import_config_impl<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+async fn import_config_impl<R: Runtime>(app: &AppHandle<R>) -> Result<ConfigImportResult, String> {
     let path = pick_config_file(app).await?;
-    validate_config_path(&path)?;
-    let content = fs::read_to_string(&path).map_err(|e| format!("read config failed: {}", e))?;
-    let config_data = parse_config_content(&content)?;
-    sunshine::post_sunshine_config(&config_data).await
+    let preview = build_import_preview(&path).await?;
+    if preview.changes.is_empty() {
+        return Err("The imported config matches the current settings.".to_string());
+    }
+    if !confirm_import(app, preview_summary(&preview)).await {
+        return Err("cancelled".to_string());
+    }
+
+    let keys = preview
+        .changes
+        .iter()
+        .filter(|change| change.selected)
+        .map(|change| change.key.clone())
+        .collect::<Vec<_>>();
+    apply_import(&path, &keys).await
+}
+
+async fn confirm_import<R: Runtime>(app: &AppHandle<R>, summary: String) -> bool {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+    use tokio::sync::oneshot;
+
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .message(summary)
+        .title("Import Sunshine Config")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Import".to_string(),
+            "Cancel".to_string(),
+        ))
+        .show(move |confirmed| {
+            let _ = tx.send(confirmed);
+        });
+    rx.await.unwrap_or(false)
+}
+
+fn read_import_file(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    validate_config_path(path)?;
+    let content = fs::read_to_string(path).map_err(|e| format!("read config failed: {}", e))?;
+    Ok(parse_config_content(&content)?
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, config_value_text(&value)?)))
+        .collect())
+}
+
+/// Settings currently in effect, from this machine's Core when reachable,
+/// otherwise from the local `sunshine.conf`.
+async fn current_config() -> Result<BTreeMap<String, String>, String> {
+    let from_core = match CoreClient::local().await {
+        Ok(client) => client.get("api/config").send_value().await,
+        Err(error) => Err(error),
+    };
+    let map = match from_core {
+        Ok(json) => return Ok(config_json_entries(&json)?.into_iter().collect()),
+        Err(error) => {
+            warn!("Config import compares against sunshine.conf: {}", error);
+            crate::vdd::read_full_sunshine_config().await?
+        }
+    };
+    Ok(map
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, config_value_text(&value)?)))
+        .collect())
+}
+
+fn diff_config(
+    current: &BTreeMap<String, String>,
+    imported: &BTreeMap<String, String>,
+) -> (Vec<ConfigKeyChange>, usize) {
+    let mut changes = Vec::new();
+    let mut unchanged = 0;
+    let keys: BTreeSet<&String> = current.keys().chain(imported.keys()).collect();
+
+    for key in keys {
+        let current_value = current.get(key);
+        let imported_value = imported.get(key);
+        let kind = match (current_value, imported_value) {
+            (None, Some(_)) => ConfigChangeKind::Added,
+            (Some(_), None) => ConfigChangeKind::Removed,
+            (Some(a), Some(b)) if a.trim() != b.trim() => ConfigChangeKind::Changed,
+            _ => {
+                unchanged += 1;
+                continue;
+            }
+        };
+        let known = schema::kind_of(key).is_some();
+        let issue = imported_value.and_then(|value| schema::validate(key, value).err());
+        changes.push(ConfigKeyChange {
+            key: key.clone(),
+            kind,
+            current: current_value.cloned(),
+            imported: imported_value.cloned(),
+            known,
+            selected: known && issue.is_none(),
+            issue,
+        });
+    }
+
+    (changes, unchanged)
+}
+
+async fn build_import_preview(path: &Path) -> Result<ConfigImportPreview, String> {
+    let imported = read_import_file(path)?;
+    let current = current_config().await?;
+    let (changes, unchanged) = diff_config(&current, &imported);
+    Ok(ConfigImportPreview {
+        path: path.display().to_string(),
+        changes,
+        unchanged,
+    })
+}
+
+fn merged_config(
+    base: &BTreeMap<String, String>,
+    changes: &[&ConfigKeyChange],
+) -> BTreeMap<String, String> {
+    let mut merged = base.clone();
+    for change in changes {
+        match &change.imported {
+            Some(value) => merged.insert(change.key.clone(), value.clone()),
+            None => merged.remove(&change.key),
+        };
+    }
+    merged
+}
+
+fn to_config_map(config: &BTreeMap<String, String>) -> serde_json::Map<String, serde_json::Value> {
+    config
+        .iter()
+        .map(|(key, value)| (key.clone(), serde_json::Value::String(value.clone())))
+        .collect()
+}
+
+/// A rejection that applies to the request as a whole rather than to a value.
+fn is_request_level_failure(error: &CoreApiError) -> bool {
+    matches!(
+        error,
+        CoreApiError::Auth(_) | CoreApiError::Forbidden(_) | CoreApiError::Transport { .. }
+    )
+}
+
+/// Apply the chosen changes in one request. When the Core rejects the batch,
+/// apply them one at a time so each rejection is attributed to its key.
+async fn apply_changes(
+    current: &BTreeMap<String, String>,
+    changes: Vec<&ConfigKeyChange>,
+) -> Result<ConfigImportResult, String> {
+    let mut result = ConfigImportResult::default();
+    if changes.is_empty() {
+        return Ok(result);
+    }
+
+    let batch = merged_config(current, &changes);
+    let error = match sunshine::save_sunshine_config(&to_config_map(&batch)).await {
+        Ok(()) => {
+            result.applied = changes.iter().map(|change| change.key.clone()).collect();
+            return Ok(result);
+        }
+        // The request never reached the Core; a stopped Core reads the file
+        // when it starts.
+        Err(CoreApiError::Transport {
+            message,
+            delivery_unknown: false,
+        }) => {
+            warn!(
+                "Sunshine is not running, importing into sunshine.conf: {}",
+                message
+            );
+            apply_changes_to_file(&sunshine::config_dir().join("sunshine.conf"), &changes)?;
+            result.applied = changes.iter().map(|change| change.key.clone()).collect();
+            return Ok(result);
+        }
+        Err(error) if is_request_level_failure(&error) => return Err(error.into()),
+        Err(error) => error,
+    };
+    if changes.len() == 1 {
+        result.rejected.push(ConfigKeyRejection {
+            key: changes[0].key.clone(),
+            reason: error.detail().to_string(),
+        });
+        return Ok(result);
+    }
+
+    warn!(
+        "Sunshine rejected the import batch, retrying per key: {}",
+        error
+    );
+    let mut accepted = current.clone();
+    for change in changes {
+        let candidate = merged_config(&accepted, &[change]);
+        match sunshine::save_sunshine_config(&to_config_map(&candidate)).await {
+            Ok(()) => {
+                accepted = candidate;
+                result.applied.push(change.key.clone());
+            }
+            Err(error) if is_request_level_failure(&error) => return Err(error.into()),
+            Err(error) => result.rejected.push(ConfigKeyRejection {
+                key: change.key.clone(),
+                reason: error.detail().to_string(),
+            }),
+        }
+    }
+    Ok(result)
+}
+
+/// Edit only the imported keys' lines, keeping comments and the rest of the
+/// file as they are.
+fn apply_changes_to_file(path: &Path, changes: &[&ConfigKeyChange]) -> Result<(), String> {
+    let mut conf = SunshineConf::load(path)?;
+    for change in changes {
+        match &change.imported {
+            Some(value) => conf.set(&change.key, value),
+            None => {
+                conf.remove(&change.key);
+            }
+        }
+    }
+    conf.save(path)
+}
+
+async fn apply_import(path: &Path, keys: &[String]) -> Result<ConfigImportResult, String> {
+    let imported = read_import_file(path)?;
+    let current = current_config().await?;
+    let (changes, _) = diff_config(&current, &imported);
+    let chosen: Vec<&ConfigKeyChange> = changes
+        .iter()
+        .filter(|change| keys.contains(&change.key))
+        .collect();
+
+    let (invalid, valid): (Vec<_>, Vec<_>) = chosen
+        .into_iter()
+        .partition(|change| change.issue.is_some());
+    if !valid.is_empty() {
+        config_snapshots::snapshot_before_write(
+            SnapshotReason::Import,
+            Some(path.display().to_string()),
+        )?;
+    }
+    let mut result = apply_changes(&current, valid).await?;
+    result
+        .rejected
+        .extend(invalid.into_iter().map(|change| ConfigKeyRejection {
+            key: change.key.clone(),
+            reason: change.issue.clone().unwrap_or_default(),
+        }));
+    info!(
+        "Config import applied {} key(s), rejected {}",
+        result.applied.len(),
+        result.rejected.len()
+    );
+    Ok(result)
+}
+
+fn key_list<'a>(keys: impl Iterator<Item = &'a str>) -> String {
+    let keys: Vec<&str> = keys.collect();
+    let mut text = keys
+        .iter()
+        .take(MAX_SUMMARY_KEYS)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if keys.len() > MAX_SUMMARY_KEYS {
+        text.push_str(&format!(" and {} more", keys.len() - MAX_SUMMARY_KEYS));
+    }
+    text
+}
+
+fn preview_summary(preview: &ConfigImportPreview) -> String {
+    let mut lines = vec![format!(
+        "{} setting(s) differ from the current config ({} unchanged).",
+        preview.changes.len(),
+        preview.unchanged
+    )];
+    let groups = [
+        ("Added", ConfigChangeKind::Added),
+        ("Changed", ConfigChangeKind::Changed),
+        ("Removed", ConfigChangeKind::Removed),
+    ];
+    for (label, kind) in groups {
+        let keys = preview
+            .changes
+            .iter()
+            .filter(|change| change.kind == kind && change.selected)
+            .map(|change| change.key.as_str());
+        let keys = key_list(keys);
+        if !keys.is_empty() {
+            lines.push(format!("{label}: {keys}"));
+        }
+    }
+
+    let unknown = key_list(
+        preview
+            .changes
+            .iter()
+            .filter(|change| !change.known)
+            .map(|change| change.key.as_str()),
+    );
+    if !unknown.is_empty() {
+        lines.push(format!("Skipped unknown keys: {unknown}"));
+    }
+    for change in preview.changes.iter().filter(|change| change.known) {
+        if let Some(issue) = &change.issue {
+            lines.push(format!("Skipped {}: {}", change.key, issue));
+        }
+    }
+    lines.join("\n")
+}
+
+fn rejection_summary(result: &ConfigImportResult) -> String {
+    let mut message = format!(
+        "Imported {} setting(s). Sunshine rejected {}:",
+        result.applied.len(),
+        result.rejected.len()
+    );
+    for rejection in &result.rejected {
+        message.push_str(&format!("\n{}: {}", rejection.key, rejection.reason));
+    }
+    message
+}
+
+/// Compare a config file with the current settings without applying it.
+#[tauri::command]
+pub async fn preview_config_import(path: String) -> Result<ConfigImportPreview, String> {
+    build_import_preview(Path::new(&path)).await
+}
+
+/// Apply the listed keys from a previewed config file.
+#[tauri::command]
+pub async fn apply_config_import(
+    path: String,
+    keys: Vec<String>,
+) -> Result<ConfigImportResult, String> {
+    apply_import(Path::new(&path), &keys).await
 }
 
 async fn export_config_impl<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
@@ -184,45 +583,15 @@ fn parse_config_content(
         return Err("config file contains invalid NUL bytes".to_string());
     }
 
-    let lines: Vec<&str> = content.lines().collect();
-    let mut config_map = serde_json::Map::new();
-    let mut i = 0;
-
-    while i < lines.len() {
-        let line = lines[i].trim();
-        if line.starts_with('#') || line.is_empty() {
-            i += 1;
-            continue;
-        }
-
-        if let Some((key, value)) = line.split_once('=') {
-            let key = key.trim();
-            if key.is_empty() {
-                return Err("config contains an empty key".to_string());
-            }
-
-            let mut value = value.trim().to_string();
-            if value.starts_with('[') && !value.ends_with(']') {
-                i += 1;
-                while i < lines.len() {
-                    let next_line = lines[i].trim();
-                    value.push('\n');
-                    value.push_str(next_line);
-
-                    if next_line.ends_with(']') {
-                        break;
-                    }
-                    i += 1;
-                }
-            }
-
-            config_map.insert(key.to_string(), serde_json::json!(value));
-        } else {
-            return Err(format!("invalid config line: {}", line));
-        }
-
-        i += 1;
+    let conf = SunshineConf::parse(content);
+    if let Some(invalid) = conf.invalid_lines().first() {
+        return Err(format!(
+            "invalid config line {}: {}",
+            invalid.line,
+            invalid.text.trim()
+        ));
     }
+    let config_map = conf.to_map();
 
     if config_map.is_empty() {
         return Err("config file contains no settings".to_string());
@@ -232,23 +601,21 @@ fn parse_config_content(
 }
 
 async fn fetch_config_for_export() -> Result<String, String> {
-    let sunshine_url = sunshine::get_sunshine_url().await?;
-    let config_url = format!("{}/api/config", sunshine_url.trim_end_matches('/'));
-    let client = sunshine::create_https_client()?;
-    let response = client
-        .get(&config_url)
-        .send()
-        .await
-        .map_err(|e| format!("fetch config failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return read_local_config_for_export();
-    }
-
-    let json: serde_json::Value = response
-        .json()
+    let json = match CoreClient::local()
+        .await?
+        .get("api/config")
+        .send_value()
         .await
-        .map_err(|e| format!("parse config response failed: {}", e))?;
+    {
+        Ok(json) => json,
+        Err(CoreApiError::Transport { message, .. }) => {
+            return Err(format!("fetch config failed: {}", message));
+        }
+        Err(error) => {
+            warn!("Config export falls back to sunshine.conf: {}", error);
+            return read_local_config_for_export();
+        }
+    };
 
     config_json_to_conf(&json).or_else(|_| read_local_config_for_export())
 }
@@ -258,42 +625,37 @@ fn read_local_config_for_export() -> Result<String, String> {
     fs::read_to_string(&config_path).map_err(|e| format!("read local config failed: {}", e))
 }
 
-fn config_json_to_conf(json: &serde_json::Value) -> Result<String, String> {
+fn config_value_text(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(value) => Some(value.clone()),
+        serde_json::Value::Bool(value) => Some(value.to_string()),
+        serde_json::Value::Number(value) => Some(value.to_string()),
+        serde_json::Value::Null | serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            None
+        }
+    }
+}
+
+/// Scalar settings from a `/api/config` response, sorted by key.
+fn config_json_entries(json: &serde_json::Value) -> Result<Vec<(String, String)>, String> {
     let object = json
         .as_object()
         .ok_or_else(|| "config response is not an object".to_string())?;
-    let metadata_keys = [
-        "status",
-        "platform",
-        "version",
-        "display_devices",
-        "adapters",
-        "pair_name",
-    ];
 
-    let mut keys: Vec<&String> = object
-        .keys()
-        .filter(|key| !metadata_keys.contains(&key.as_str()))
+    let mut entries: Vec<(String, String)> = object
+        .iter()
+        .filter(|(key, _)| !CONFIG_METADATA_KEYS.contains(&key.as_str()))
+        .filter_map(|(key, value)| Some((key.clone(), config_value_text(value)?)))
         .collect();
-    keys.sort();
-
-    let mut lines = Vec::new();
-    for key in keys {
-        let Some(value) = object.get(key) else {
-            continue;
-        };
-
-        let value = match value {
-            serde_json::Value::String(value) => value.clone(),
-            serde_json::Value::Bool(value) => value.to_string(),
-            serde_json::Value::Number(value) => value.to_string(),
-            serde_json::Value::Null
-            | serde_json::Value::Array(_)
-            | serde_json::Value::Object(_) => continue,
-        };
+    entries.sort();
+    Ok(entries)
+}
 
-        lines.push(format!("{} = {}", key, value));
-    }
+fn config_json_to_conf(json: &serde_json::Value) -> Result<String, String> {
+    let mut lines: Vec<String> = config_json_entries(json)?
+        .into_iter()
+        .map(|(key, value)| format!("{} = {}", key, value))
+        .collect();
 
     if lines.is_empty() {
         return Err("config response contains no exportable settings".to_string());
@@ -322,4 +684,98 @@ mod tests {
             "a_setting = 42\nz_setting = true\n"
         );
     }
+
+    #[test]
+    fn config_import_keeps_multi_line_arrays_and_rejects_bad_lines() {
+        let config =
+            parse_config_content("# comment\nfps = [\n  60,\n  120\n]\nlocale = en\n").unwrap();
+        assert_eq!(config["fps"], "[\n60,\n120\n]");
+        assert_eq!(config["locale"], "en");
+
+        let error = parse_config_content("locale = en\nnot a setting\n").unwrap_err();
+        assert!(error.contains("line 2"));
+    }
+
+    fn config(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn config_import_diff_classifies_and_preselects_valid_known_keys() {
+        let current = config(&[("port", "47989"), ("upnp", "enabled"), ("locale", "en")]);
+        let imported = config(&[
+            ("port", "80"),
+            ("upnp", "enabled"),
+            ("fps", "[60]"),
+            ("future_option", "1"),
+        ]);
+
+        let (changes, unchanged) = diff_config(&current, &imported);
+        assert_eq!(unchanged, 1);
+        let find = |key: &str| changes.iter().find(|change| change.key == key).unwrap();
+
+        let port = find("port");
+        assert_eq!(port.kind, ConfigChangeKind::Changed);
+        assert!(port.issue.as_deref().unwrap().contains("outside"));
+        assert!(!port.selected);
+
+        assert_eq!(find("fps").kind, ConfigChangeKind::Added);
+        assert!(find("fps").selected);
+        assert_eq!(find("locale").kind, ConfigChangeKind::Removed);
+        assert!(find("locale").selected);
+
+        let future = find("future_option");
+        assert!(!future.known && future.issue.is_none() && !future.selected);
+    }
+
+    #[test]
+    fn config_import_merges_selected_changes_and_summarizes_skips() {
+        let current = config(&[("port", "47989"), ("locale", "en")]);
+        let imported = config(&[("port", "x"), ("fps", "[60]"), ("future_option", "1")]);
+        let (changes, unchanged) = diff_config(&current, &imported);
+
+        let selected: Vec<&ConfigKeyChange> =
+            changes.iter().filter(|change| change.selected).collect();
+        assert_eq!(
+            merged_config(&current, &selected),
+            config(&[("fps", "[60]"), ("port", "47989")])
+        );
+
+        let summary = preview_summary(&ConfigImportPreview {
+            path: "import.conf".to_string(),
+            changes,
+            unchanged,
+        });
+        assert!(summary.contains("Added: fps"));
+        assert!(summary.contains("Removed: locale"));
+        assert!(summary.contains("Skipped unknown keys: future_option"));
+        assert!(summary.contains("Skipped port: expected an integer"));
+    }
+
+    #[test]
+    fn offline_import_edits_only_the_imported_lines() {
+        let dir = std::env::temp_dir().join(format!("sunshine-import-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sunshine.conf");
+        fs::write(
+            &path,
+            "# tuned by hand\nport = 47989\nlocale = en\nupnp = enabled\n",
+        )
+        .unwrap();
+
+        let current = config(&[("port", "47989"), ("locale", "en"), ("upnp", "enabled")]);
+        let imported = config(&[("port", "48000"), ("upnp", "enabled"), ("fps", "[60]")]);
+        let (changes, _) = diff_config(&current, &imported);
+        let selected: Vec<&ConfigKeyChange> = changes.iter().collect();
+        apply_changes_to_file(&path, &selected).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# tuned by hand\nport = 48000\nupnp = enabled\nfps = [60]\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
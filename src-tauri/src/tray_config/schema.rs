@@ -0,0 +1,203 @@
+//! Types and ranges of the `sunshine.conf` keys the GUI knows about.
+//!
+//! The schema only guards imports: a key missing here is reported as unknown
+//! rather than rejected, because newer Core builds add options first.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ValueKind {
+    Bool,
+    Int {
+        min: i64,
+        max: i64,
+    },
+    Choice(&'static [&'static str]),
+    /// Bracketed list such as `[60, 120]`.
+    List,
+    Text,
+}
+
+const LOG_LEVELS: &[&str] = &[
+    "0", "1", "2", "3", "4", "5", "6", "verbose", "debug", "info", "warning", "error", "fatal",
+    "none",
+];
+const LOCALES: &[&str] = &[
+    "bg", "cs", "de", "en", "en_GB", "en_US", "es", "fr", "it", "ja", "ko", "pl", "pt", "pt_BR",
+    "ru", "sv", "tr", "uk", "zh", "zh_TW",
+];
+
+const SCHEMA: &[(&str, ValueKind)] = &[
+    ("sunshine_name", ValueKind::Text),
+    ("locale", ValueKind::Choice(LOCALES)),
+    ("min_log_level", ValueKind::Choice(LOG_LEVELS)),
+    (
+        "port",
+        ValueKind::Int {
+            min: 1029,
+            max: 65514,
+        },
+    ),
+    ("address_family", ValueKind::Choice(&["ipv4", "both"])),
+    (
+        "origin_web_ui_allowed",
+        ValueKind::Choice(&["pc", "lan", "wan"]),
+    ),
+    ("upnp", ValueKind::Bool),
+    ("lan_encryption_mode", ValueKind::Int { min: 0, max: 2 }),
+    ("wan_encryption_mode", ValueKind::Int { min: 0, max: 2 }),
+    (
+        "ping_timeout",
+        ValueKind::Int {
+            min: 0,
+            max: 3_600_000,
+        },
+    ),
+    ("channels", ValueKind::Int { min: 1, max: 16 }),
+    ("fec_percentage", ValueKind::Int { min: 1, max: 255 }),
+    ("qp", ValueKind::Int { min: 0, max: 51 }),
+    ("min_threads", ValueKind::Int { min: 1, max: 64 }),
+    ("system_tray", ValueKind::Bool),
+    ("notify_pre_releases", ValueKind::Bool),
+    ("file_apps", ValueKind::Text),
+    ("credentials_file", ValueKind::Text),
+    ("log_path", ValueKind::Text),
+    ("pkey", ValueKind::Text),
+    ("cert", ValueKind::Text),
+    ("file_state", ValueKind::Text),
+    ("resolutions", ValueKind::List),
+    ("fps", ValueKind::List),
+    ("adapter_name", ValueKind::Text),
+    ("output_name", ValueKind::Text),
+    (
+        "capture",
+        ValueKind::Choice(&["", "wgc", "ddx", "amd", "nvfbc", "kms", "x11", "wlr"]),
+    ),
+    (
+        "encoder",
+        ValueKind::Choice(&["", "nvenc", "quicksync", "amdvce", "vaapi", "software"]),
+    ),
+    ("hevc_mode", ValueKind::Int { min: 0, max: 3 }),
+    ("av1_mode", ValueKind::Int { min: 0, max: 3 }),
+    ("nvenc_preset", ValueKind::Int { min: 1, max: 7 }),
+    (
+        "nvenc_twopass",
+        ValueKind::Choice(&["disabled", "quarter_res", "full_res"]),
+    ),
+    ("nvenc_spatial_aq", ValueKind::Bool),
+    ("nvenc_realtime_hags", ValueKind::Bool),
+    ("audio_sink", ValueKind::Text),
+    ("virtual_sink", ValueKind::Text),
+    ("stream_audio", ValueKind::Bool),
+    ("install_steam_audio_drivers", ValueKind::Bool),
+    ("keyboard", ValueKind::Bool),
+    ("mouse", ValueKind::Bool),
+    ("controller", ValueKind::Bool),
+    (
+        "gamepad",
+        ValueKind::Choice(&["auto", "x360", "ds4", "ds5", "switch", "xone"]),
+    ),
+    ("motion_as_ds4", ValueKind::Bool),
+    ("touchpad_as_ds4", ValueKind::Bool),
+    ("ds4_back_as_touchpad_click", ValueKind::Bool),
+    (
+        "back_button_timeout",
+        ValueKind::Int {
+            min: -1,
+            max: 60_000,
+        },
+    ),
+    (
+        "key_repeat_delay",
+        ValueKind::Int {
+            min: 0,
+            max: 10_000,
+        },
+    ),
+    (
+        "key_repeat_frequency",
+        ValueKind::Int { min: 0, max: 1_000 },
+    ),
+    ("always_send_scancodes", ValueKind::Bool),
+    ("key_rightalt_to_key_win", ValueKind::Bool),
+    ("high_resolution_scrolling", ValueKind::Bool),
+    ("native_pen_touch", ValueKind::Bool),
+    ("client_fingerprint_remote_rules", ValueKind::Bool),
+    ("client_fingerprint_rules_url", ValueKind::Text),
+    (
+        "client_fingerprint_rules_refresh_hours",
+        ValueKind::Int { min: 1, max: 720 },
+    ),
+];
+
+pub(super) fn kind_of(key: &str) -> Option<ValueKind> {
+    SCHEMA
+        .iter()
+        .find(|(candidate, _)| *candidate == key)
+        .map(|(_, kind)| *kind)
+}
+
+/// Check `value` against the schema entry for `key`. Unknown keys pass.
+pub(super) fn validate(key: &str, value: &str) -> Result<(), String> {
+    let Some(kind) = kind_of(key) else {
+        return Ok(());
+    };
+    let value = value.trim().trim_matches('"');
+    match kind {
+        ValueKind::Bool => match value.to_ascii_lowercase().as_str() {
+            "true" | "false" | "enabled" | "disabled" | "on" | "off" | "yes" | "no" | "1" | "0" => {
+                Ok(())
+            }
+            _ => Err(format!("expected enabled or disabled, got '{value}'")),
+        },
+        ValueKind::Int { min, max } => match value.parse::<i64>() {
+            Ok(number) if (min..=max).contains(&number) => Ok(()),
+            Ok(number) => Err(format!("{number} is outside {min}..={max}")),
+            Err(_) => Err(format!("expected an integer, got '{value}'")),
+        },
+        ValueKind::Choice(choices) => {
+            if choices
+                .iter()
+                .any(|choice| choice.eq_ignore_ascii_case(value))
+            {
+                Ok(())
+            } else {
+                Err(format!(
+                    "expected one of {}, got '{value}'",
+                    choices
+                        .iter()
+                        .map(|choice| if choice.is_empty() { "(auto)" } else { choice })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+        }
+        ValueKind::List => {
+            if value.starts_with('[') && value.ends_with(']') {
+                Ok(())
+            } else {
+                Err("expected a bracketed list such as [a, b]".to_string())
+            }
+        }
+        ValueKind::Text => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_types_and_ranges() {
+        assert!(validate("port", "47989").is_ok());
+        assert!(validate("port", "80").unwrap_err().contains("outside"));
+        assert!(validate("port", "high").is_err());
+        assert!(validate("upnp", "enabled").is_ok());
+        assert!(validate("upnp", "maybe").is_err());
+        assert!(validate("capture", "").is_ok());
+        assert!(validate("capture", "WGC").is_ok());
+        assert!(validate("capture", "dxgi").unwrap_err().contains("(auto)"));
+        assert!(validate("fps", "[\n60,\n120\n]").is_ok());
+        assert!(validate("fps", "60").is_err());
+        assert!(validate("something_new", "anything").is_ok());
+        assert_eq!(kind_of("something_new"), None);
+    }
+}
@@ -1439,8 +1439,8 @@ fn handle_auto_check_result<R: Runtime>(
 ) {
     match result {
         Ok(Some(update_info)) => {
-            if !crate::desktop_settings::load_desktop_settings_from_disk().update_notify {
-                debug!("Update notification disabled by desktop settings");
+            if !crate::tray::update_notification_allowed() {
+                debug!("Update notification held back by notification rules");
                 save_last_check_time(app);
                 return;
             }
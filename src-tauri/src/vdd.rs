@@ -1,6 +1,7 @@
 #[cfg(target_os = "windows")]
 use crate::bat_runner;
 use crate::sunshine;
+use crate::sunshine::conf::SunshineConf;
 #[cfg(target_os = "windows")]
 use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use log::{debug, error, info, warn};
@@ -664,54 +665,14 @@ pub async fn read_full_sunshine_config()
         .join("config")
         .join("sunshine.conf");
 
-    let mut config_map = serde_json::Map::new();
-
     if !config_path.exists() {
         warn!("⚠️  配置文件不存在: {:?}", config_path);
-        return Ok(config_map);
+        return Ok(serde_json::Map::new());
     }
 
-    let content =
-        fs::read_to_string(&config_path).map_err(|e| format!("读取 sunshine.conf 失败: {}", e))?;
-
-    let lines: Vec<&str> = content.lines().collect();
-    let mut i = 0;
-
-    while i < lines.len() {
-        let line = lines[i].trim();
-
-        // 跳过注释和空行
-        if line.starts_with('#') || line.is_empty() {
-            i += 1;
-            continue;
-        }
-
-        // 解析 key = value 格式
-        if let Some((key, value)) = line.split_once('=') {
-            let key = key.trim().to_string();
-            let mut value = value.trim().to_string();
-
-            // 检查是否是多行值（以 [ 开始但不以 ] 结束）
-            if value.starts_with('[') && !value.ends_with(']') {
-                // 读取后续行直到找到 ]
-                i += 1;
-                while i < lines.len() {
-                    let next_line = lines[i].trim();
-                    value.push('\n');
-                    value.push_str(next_line);
-
-                    if next_line.ends_with(']') {
-                        break;
-                    }
-                    i += 1;
-                }
-            }
-
-            config_map.insert(key, serde_json::json!(value));
-        }
-
-        i += 1;
-    }
+    let config_map = SunshineConf::load(&config_path)
+        .map_err(|e| format!("读取 sunshine.conf 失败: {}", e))?
+        .to_map();
 
     debug!("📄 读取到 {} 个配置项", config_map.len());
     Ok(config_map)
@@ -719,10 +680,17 @@ pub async fn read_full_sunshine_config()
 
 /// 调用 Sunshine Config API 保存 VDD 配置
 /// Sunshine 的 saveVddSettings() 会负责写入 vdd_settings.xml 文件
-async fn sync_vdd_config_to_sunshine(settings: &VddSettings) -> Result<(), String> {
+/// `snapshot` 为 false 时不创建配置快照（客户端配置的自动切换）
+async fn sync_vdd_config_to_sunshine(settings: &VddSettings, snapshot: bool) -> Result<(), String> {
     // 读取完整的现有配置，然后更新 VDD 相关的配置项
     // 这样可以避免丢失其他配置
     let mut config_data = read_full_sunshine_config().await?;
+    if snapshot {
+        crate::config_snapshots::snapshot_before_write(
+            crate::config_snapshots::SnapshotReason::VddSync,
+            None,
+        )?;
+    }
 
     debug!("🔄 合并 VDD 配置到现有配置中");
 
@@ -844,7 +812,7 @@ pub struct Resolutions {
     pub resolution: Vec<Resolution>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Resolution {
     pub width: u32,
     pub height: u32,
@@ -1107,14 +1075,25 @@ pub async fn load_vdd_settings() -> Result<VddSettings, String> {
 }
 
 #[tauri::command]
-pub async fn save_vdd_settings(mut settings: VddSettings) -> Result<String, String> {
+pub async fn save_vdd_settings(settings: VddSettings) -> Result<String, String> {
+    write_vdd_settings(settings, true).await
+}
+
+/// Apply VDD settings for a client profile. Profiles switch on every connect
+/// and disconnect, so these writes are not snapshotted: they would push the
+/// user's own snapshots out of the retention limit.
+pub async fn apply_profile_vdd_settings(settings: VddSettings) -> Result<String, String> {
+    write_vdd_settings(settings, false).await
+}
+
+async fn write_vdd_settings(mut settings: VddSettings, snapshot: bool) -> Result<String, String> {
     info!("💾 开始保存 VDD 配置...");
     normalize_hardware_cursor_for_persistence(&mut settings);
     let _operation_guard = VDD_SETTINGS_OPERATION_LOCK.lock().await;
 
     // 步骤1: 调用 Sunshine Config API 保存主要配置（resolutions, fps, adapter_name）
     // C++ 会写入 monitors, gpu, global, resolutions 字段
-    sync_vdd_config_to_sunshine(&settings).await?;
+    sync_vdd_config_to_sunshine(&settings, snapshot).await?;
 
     // 步骤2: 等待 C++ 完成文件写入
     debug!("⏳ 等待 Sunshine API 完成文件写入...");
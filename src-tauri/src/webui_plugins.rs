@@ -0,0 +1,428 @@
+//! User-provided CSS/JS plugins injected into the proxied Core WebUI.
+//!
+//! Each plugin is a folder under `<config>/Sunshine GUI/plugins/<id>/` with a
+//! `plugin.json` manifest. Plugins are disabled until the user enables them,
+//! and enable flags live outside the plugin folder so updating a plugin does
+//! not reset them.
+
+use log::{debug, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+
+const PLUGINS_DIR: &str = "plugins";
+const MANIFEST_FILE: &str = "plugin.json";
+const STATE_FILE: &str = "webui-plugins.json";
+const MAX_PLUGIN_ASSET_BYTES: u64 = 1024 * 1024;
+
+/// Loaded plugins are cached because injection runs for every HTML page.
+/// Listing or toggling plugins rescans the folder and replaces the cache.
+static PLUGIN_CACHE: Lazy<RwLock<Option<Vec<LoadedPlugin>>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct PluginManifest {
+    name: String,
+    description: String,
+    version: String,
+    /// Path patterns such as `/`, `/config` or `/apps*`. `*` matches any run
+    /// of characters. An empty list matches every injected page.
+    matches: Vec<String>,
+    styles: Vec<String>,
+    scripts: Vec<String>,
+    /// Lower values are injected first. Ties are ordered by plugin id.
+    order: i32,
+}
+
+impl Default for PluginManifest {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            description: String::new(),
+            version: String::new(),
+            matches: Vec::new(),
+            styles: Vec::new(),
+            scripts: Vec::new(),
+            order: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PluginState {
+    enabled: BTreeMap<String, bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebUiPluginInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub version: String,
+    pub matches: Vec<String>,
+    pub order: i32,
+    pub enabled: bool,
+    pub path: String,
+    /// Manifest or asset problems. A plugin with errors is never injected.
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebUiPluginList {
+    pub directory: String,
+    pub plugins: Vec<WebUiPluginInfo>,
+}
+
+#[derive(Debug, Clone)]
+struct LoadedPlugin {
+    info: WebUiPluginInfo,
+    styles: Vec<String>,
+    scripts: Vec<String>,
+}
+
+fn plugins_dir() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(PLUGINS_DIR))
+}
+
+fn state_path() -> Result<PathBuf, String> {
+    Ok(crate::desktop_settings::settings_dir()?.join(STATE_FILE))
+}
+
+fn load_state(path: &Path) -> PluginState {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(path: &Path, state: &PluginState) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let text = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// Match a request path against a pattern where `*` spans any characters.
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim();
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == path;
+    };
+    let Some(mut remaining) = path.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts = rest.split('*').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            return remaining.ends_with(part);
+        }
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn read_asset(plugin_dir: &Path, relative: &str) -> Result<String, String> {
+    let relative_path = Path::new(relative);
+    if relative.is_empty()
+        || relative_path
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "{relative}: asset paths must stay inside the plugin folder"
+        ));
+    }
+
+    let path = plugin_dir.join(relative_path);
+    let size = fs::metadata(&path)
+        .map_err(|e| format!("{relative}: {e}"))?
+        .len();
+    if size > MAX_PLUGIN_ASSET_BYTES {
+        return Err(format!("{relative}: asset exceeds 1 MiB"));
+    }
+    fs::read_to_string(&path).map_err(|e| format!("{relative}: {e}"))
+}
+
+fn load_plugin(dir: &Path, state: &PluginState) -> Option<LoadedPlugin> {
+    let id = dir.file_name()?.to_string_lossy().to_string();
+    let mut errors = Vec::new();
+    let manifest = match fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        Ok(text) => serde_json::from_str::<PluginManifest>(&text).unwrap_or_else(|e| {
+            errors.push(format!("{MANIFEST_FILE}: {e}"));
+            PluginManifest::default()
+        }),
+        // Folders without a manifest are not plugins.
+        Err(_) => return None,
+    };
+
+    let mut read_all = |files: &[String]| -> Vec<String> {
+        files
+            .iter()
+            .filter_map(|file| {
+                read_asset(dir, file)
+                    .map_err(|error| errors.push(error))
+                    .ok()
+            })
+            .collect()
+    };
+    let styles = read_all(&manifest.styles);
+    let scripts = read_all(&manifest.scripts);
+
+    Some(LoadedPlugin {
+        info: WebUiPluginInfo {
+            name: if manifest.name.trim().is_empty() {
+                id.clone()
+            } else {
+                manifest.name
+            },
+            enabled: state.enabled.get(&id).copied().unwrap_or(false),
+            id,
+            description: manifest.description,
+            version: manifest.version,
+            matches: manifest.matches,
+            order: manifest.order,
+            path: dir.to_string_lossy().to_string(),
+            errors,
+        },
+        styles,
+        scripts,
+    })
+}
+
+fn scan_plugins(root: &Path, state: &PluginState) -> Vec<LoadedPlugin> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+
+    let mut plugins: Vec<LoadedPlugin> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| load_plugin(&path, state))
+        .collect();
+    plugins.sort_by(|a, b| {
+        a.info
+            .order
+            .cmp(&b.info.order)
+            .then_with(|| a.info.id.cmp(&b.info.id))
+    });
+    for plugin in &plugins {
+        for error in &plugin.info.errors {
+            warn!("WebUI plugin {}: {}", plugin.info.id, error);
+        }
+    }
+    plugins
+}
+
+fn rescan() -> Result<WebUiPluginList, String> {
+    let root = plugins_dir()?;
+    let plugins = scan_plugins(&root, &load_state(&state_path()?));
+    let infos = plugins.iter().map(|plugin| plugin.info.clone()).collect();
+    if let Ok(mut cache) = PLUGIN_CACHE.write() {
+        *cache = Some(plugins);
+    }
+    Ok(WebUiPluginList {
+        directory: root.to_string_lossy().to_string(),
+        plugins: infos,
+    })
+}
+
+/// Keep an inline asset from closing its own element early.
+fn escape_inline(content: &str, tag: &str) -> String {
+    let closing = format!("</{tag}");
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(index) = rest.to_ascii_lowercase().find(&closing) {
+        result.push_str(&rest[..index]);
+        result.push_str("<\\/");
+        rest = &rest[index + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn render_plugins(plugins: &[LoadedPlugin], path: &str) -> String {
+    let mut markup = String::new();
+    for plugin in plugins {
+        let info = &plugin.info;
+        let matches = info.matches.is_empty()
+            || info
+                .matches
+                .iter()
+                .any(|pattern| path_matches(pattern, path));
+        if !info.enabled || !info.errors.is_empty() || !matches {
+            continue;
+        }
+
+        let id = info
+            .id
+            .replace('&', "&amp;")
+            .replace('"', "&quot;")
+            .replace('<', "&lt;");
+        for style in &plugin.styles {
+            markup.push_str(&format!(
+                "<style data-sunshine-plugin=\"{id}\">\n{}\n</style>\n",
+                escape_inline(style, "style")
+            ));
+        }
+        for script in &plugin.scripts {
+            markup.push_str(&format!(
+                "<script data-sunshine-plugin=\"{id}\">\n{}\n</script>\n",
+                escape_inline(script, "script")
+            ));
+        }
+    }
+    markup
+}
+
+/// Markup for enabled plugins that match `path`, in injection order.
+/// It is inserted after the built-in styles and script.
+pub fn injection_markup(path: &str) -> String {
+    let cached = PLUGIN_CACHE
+        .read()
+        .ok()
+        .and_then(|cache| cache.as_ref().map(|plugins| render_plugins(plugins, path)));
+    if let Some(markup) = cached {
+        return markup;
+    }
+
+    match rescan() {
+        Ok(_) => PLUGIN_CACHE
+            .read()
+            .ok()
+            .and_then(|cache| cache.as_ref().map(|plugins| render_plugins(plugins, path)))
+            .unwrap_or_default(),
+        Err(e) => {
+            debug!("WebUI plugins unavailable: {}", e);
+            String::new()
+        }
+    }
+}
+
+/// List plugins from the user plugins folder, rescanning it from disk.
+#[tauri::command]
+pub fn list_webui_plugins() -> Result<WebUiPluginList, String> {
+    // Create the folder so the UI can open it for the user to drop plugins in.
+    fs::create_dir_all(plugins_dir()?).map_err(|e| e.to_string())?;
+    rescan()
+}
+
+/// Enable or disable one plugin. Reload the WebUI page to apply the change.
+#[tauri::command]
+pub fn set_webui_plugin_enabled(id: String, enabled: bool) -> Result<WebUiPluginList, String> {
+    let path = state_path()?;
+    let mut state = load_state(&path);
+    state.enabled.insert(id, enabled);
+    save_state(&path, &state)?;
+    rescan()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("sunshine-webui-plugins-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn write_plugin(root: &Path, id: &str, manifest: &str, files: &[(&str, &str)]) {
+        let dir = root.join(id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), manifest).unwrap();
+        for (name, content) in files {
+            fs::write(dir.join(name), content).unwrap();
+        }
+    }
+
+    fn enabled(ids: &[&str]) -> PluginState {
+        PluginState {
+            enabled: ids.iter().map(|id| (id.to_string(), true)).collect(),
+        }
+    }
+
+    #[test]
+    fn path_patterns_support_wildcards() {
+        assert!(path_matches("/", "/"));
+        assert!(!path_matches("/", "/config"));
+        assert!(path_matches("/apps*", "/apps"));
+        assert!(path_matches("/apps*", "/apps/edit"));
+        assert!(path_matches("*", "/troubleshooting"));
+        assert!(path_matches("/*.html", "/index.html"));
+        assert!(!path_matches("/*.html", "/index.htm"));
+    }
+
+    #[test]
+    fn enabled_plugins_are_injected_in_order_for_matching_pages() {
+        let root = temp_root();
+        write_plugin(
+            &root,
+            "late",
+            r#"{"order": 20, "scripts": ["late.js"]}"#,
+            &[("late.js", "console.log('late')")],
+        );
+        write_plugin(
+            &root,
+            "early",
+            r#"{"order": 10, "matches": ["/config"], "styles": ["early.css"]}"#,
+            &[("early.css", "body { color: red; }")],
+        );
+        write_plugin(
+            &root,
+            "disabled",
+            r#"{"scripts": ["off.js"]}"#,
+            &[("off.js", "console.log('off')")],
+        );
+
+        let plugins = scan_plugins(&root, &enabled(&["late", "early"]));
+        let ids: Vec<_> = plugins.iter().map(|p| p.info.id.as_str()).collect();
+        assert_eq!(ids, ["early", "late", "disabled"]);
+
+        let config = render_plugins(&plugins, "/config");
+        let early = config.find("color: red").unwrap();
+        let late = config.find("console.log('late')").unwrap();
+        assert!(early < late);
+        assert!(!config.contains("off"));
+
+        let apps = render_plugins(&plugins, "/apps");
+        assert!(!apps.contains("color: red"));
+        assert!(apps.contains("console.log('late')"));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn plugins_with_invalid_assets_are_reported_and_skipped() {
+        let root = temp_root();
+        write_plugin(&root, "escape", r#"{"scripts": ["../secret.js"]}"#, &[]);
+        write_plugin(&root, "missing", r#"{"styles": ["nope.css"]}"#, &[]);
+
+        let plugins = scan_plugins(&root, &enabled(&["escape", "missing"]));
+        assert!(plugins.iter().all(|plugin| !plugin.info.errors.is_empty()));
+        assert!(render_plugins(&plugins, "/").is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn inline_assets_cannot_close_their_element() {
+        assert_eq!(
+            escape_inline("a = '</SCRIPT><b>'", "script"),
+            "a = '<\\/SCRIPT><b>'"
+        );
+        assert_eq!(escape_inline("x</style>", "style"), "x<\\/style>");
+    }
+}
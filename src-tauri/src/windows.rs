@@ -666,6 +666,8 @@ pub fn open_pin_window<R: Runtime>(app: &AppHandle<R>) -> Result<(), Box<dyn std
     .always_on_top(true)
     .skip_taskbar(true)
     .visible(false)
+    // PIN 提交和配置读取都经过本地代理，需要携带访问令牌
+    .initialization_script(proxy_server::webview_access_script())
     .build()
     .map_err(|e| format!("创建 PIN 窗口失败: {}", e))?;
 
@@ -684,6 +686,17 @@ pub fn open_pin_window<R: Runtime>(app: &AppHandle<R>) -> Result<(), Box<dyn std
     Ok(())
 }
 
+/// PIN 窗口关闭后执行 `callback`；窗口不存在时什么也不做
+pub fn on_pin_window_closed<R: Runtime>(app: &AppHandle<R>, callback: fn()) {
+    if let Some(window) = app.get_webview_window(PIN_WINDOW_ID) {
+        window.on_window_event(move |event| {
+            if matches!(event, tauri::WindowEvent::Destroyed) {
+                callback();
+            }
+        });
+    }
+}
+
 /// 打开调试页面窗口（仅开发环境）
 #[cfg(debug_assertions)]
 pub fn open_debug_page<R: Runtime>(app: &AppHandle<R>) {
@@ -755,6 +768,7 @@ fn create_main_window_internal<R: Runtime>(
     .visible(false)
     .disable_drag_drop_handler()
     .initialization_script(WEBVIEW_VISIBILITY_INIT_SCRIPT)
+    .initialization_script(proxy_server::webview_access_script())
     .on_page_load(move |window, payload| {
         if visible && payload.event() == PageLoadEvent::Finished {
             tauri::async_runtime::spawn(async move {
@@ -822,6 +836,7 @@ fn create_desktop_window_internal<R: Runtime>(
     .fullscreen(true)
     .disable_drag_drop_handler()
     .initialization_script(WEBVIEW_VISIBILITY_INIT_SCRIPT)
+    .initialization_script(proxy_server::webview_access_script())
     .build()
     .map_err(|e| {
         end_webview_heartbeat(DESKTOP_WINDOW_ID);
@@ -945,7 +960,7 @@ fn navigate_to_url(window: &WebviewWindow, url: &str) {
         return;
     }
 
-    let proxy_url = proxy_server::get_proxy_url();
+    let proxy_url = proxy_server::get_frame_url();
     let script = format!(
         r#"(function(){{ const iframe = document.querySelector('.sunshine-iframe'); if (iframe) iframe.src = '{}{}'; }})();"#,
         proxy_url, path
@@ -11,8 +11,17 @@ export const defaultDesktopSettings = {
   notifications: true,
   connectionNotify: true,
   updateNotify: true,
+  trayBitrateBar: false,
   devMode: false,
   logLevel: 'info',
+  fileLogLevel: 'info',
+  logFileRetention: 7,
+}
+
+// These toggles are views on the generic notification rules, which decide delivery.
+const RULE_TOGGLES = {
+  connectionNotify: ['clientConnected', 'clientDisconnected'],
+  updateNotify: ['updateAvailable'],
 }
 
 export const desktopSettings = ref({ ...defaultDesktopSettings })
@@ -25,6 +34,9 @@ function normalize(settings = {}) {
     logLevel: ['error', 'warn', 'info', 'debug', 'trace'].includes(settings.logLevel)
       ? settings.logLevel
       : defaultDesktopSettings.logLevel,
+    fileLogLevel: ['off', 'error', 'warn', 'info', 'debug', 'trace'].includes(settings.fileLogLevel)
+      ? settings.fileLogLevel
+      : defaultDesktopSettings.fileLogLevel,
   }
 }
 
@@ -45,10 +57,52 @@ function loadLocal() {
   }
 }
 
+function isGenericRule(rule, events) {
+  return events.includes(rule.event) && !rule.client
+}
+
+function togglesFromRules(notificationRules) {
+  const rules = notificationRules?.rules || []
+  return Object.fromEntries(
+    Object.entries(RULE_TOGGLES).map(([key, events]) => [
+      key,
+      rules.filter((rule) => isGenericRule(rule, events)).every((rule) => rule.enabled),
+    ])
+  )
+}
+
+function rulesWithToggles(notificationRules, settings) {
+  const rules = (notificationRules.rules || []).map((rule) => {
+    const key = Object.keys(RULE_TOGGLES).find((name) => isGenericRule(rule, RULE_TOGGLES[name]))
+    return key ? { ...rule, enabled: settings[key] } : rule
+  })
+  for (const [key, events] of Object.entries(RULE_TOGGLES)) {
+    for (const event of events) {
+      if (!rules.some((rule) => isGenericRule(rule, [event]))) {
+        rules.push({ event, client: '', enabled: settings[key], throttleSecs: 0, urgent: false })
+      }
+    }
+  }
+  return { ...notificationRules, rules }
+}
+
+async function loadRuleToggles() {
+  return togglesFromRules(await tauriInvoke('get_notification_rules'))
+}
+
+async function saveRuleToggles(settings) {
+  const current = await tauriInvoke('get_notification_rules')
+  const toggles = togglesFromRules(current)
+  if (Object.keys(RULE_TOGGLES).every((key) => toggles[key] === settings[key])) return toggles
+  return togglesFromRules(
+    await tauriInvoke('save_notification_rules', { rules: rulesWithToggles(current, settings) })
+  )
+}
+
 export async function loadDesktopSettings() {
   try {
     const response = await tauriInvoke('get_desktop_settings')
-    desktopSettings.value = normalize(response.settings)
+    desktopSettings.value = normalize({ ...response.settings, ...(await loadRuleToggles()) })
     desktopSettingsStatus.value = response.status || null
     persistLocal(desktopSettings.value)
   } catch {
@@ -70,7 +124,7 @@ export async function saveDesktopSettings(nextSettings) {
   let response = null
   try {
     response = await tauriInvoke('save_desktop_settings', { settings: normalized })
-    desktopSettings.value = normalize(response.settings)
+    desktopSettings.value = normalize({ ...response.settings, ...(await saveRuleToggles(normalized)) })
     desktopSettingsStatus.value = response.status || null
   } catch (e) {
     desktopSettings.value = normalized
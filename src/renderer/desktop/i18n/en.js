@@ -173,6 +173,9 @@ export const en = {
     connectionNotifyDesc: 'Show the device name when a client connects or disconnects',
     updateNotify: 'Update Notifications',
     updateNotifyDesc: 'Notify when new version is available',
+    trayIcon: 'Tray Icon',
+    trayBitrateBar: 'Tray Bitrate Bar',
+    trayBitrateBarDesc: 'Show the current streaming bitrate as a bar on the tray icon',
     notificationTitle: 'Sunshine',
     updateAvailable: 'Update available',
     updateAvailableVersion: 'Update available: {version}',
@@ -186,7 +189,13 @@ export const en = {
       warn: 'Warnings and Above',
       info: 'Info and Above',
       debug: 'Debug (All)',
+      off: 'Off',
     },
+    fileLogLevel: 'Log File Level',
+    fileLogLevelDesc: 'Level written to the rotating log files, independent of the console level',
+    logFileRetention: 'Log File Retention',
+    logFileRetentionDesc: 'How many rotated log files to keep',
+    logFileRetentionCount: '{count} files',
     pet: 'Desktop Pet',
     deskObserve: 'Desktop Observation',
     deskObserveDesc: 'Mita will periodically peek at your desktop and make comments (requires AI enabled with vision-capable model like GPT-4o)',
@@ -696,6 +705,7 @@ Output only one short sentence, no explanation. Reply in English.`,
     refresh: 'Refresh',
     exportTxt: 'Export TXT',
     exportJson: 'Export JSON',
+    exportZip: 'Export with Log Files',
     clear: 'Clear',
     filterLevel: 'Filter Level',
     error: 'Error',
@@ -173,6 +173,9 @@ export const zh = {
     connectionNotifyDesc: '客户端连入或断开时显示通知，并包含设备名称',
     updateNotify: '更新通知',
     updateNotifyDesc: '有新版本时通知',
+    trayIcon: '托盘图标',
+    trayBitrateBar: '托盘码率条',
+    trayBitrateBarDesc: '在托盘图标上以进度条显示当前串流码率',
     notificationTitle: 'Sunshine',
     updateAvailable: '有可用更新',
     updateAvailableVersion: '有可用更新：{version}',
@@ -186,7 +189,13 @@ export const zh = {
       warn: 'WARN 及以上',
       info: 'INFO 及以上',
       debug: 'DEBUG（全部）',
+      off: '关闭',
     },
+    fileLogLevel: '日志文件级别',
+    fileLogLevelDesc: '写入滚动日志文件的级别，与控制台级别相互独立',
+    logFileRetention: '日志文件保留数',
+    logFileRetentionDesc: '保留多少个已轮转的日志文件',
+    logFileRetentionCount: '{count} 个文件',
     pet: '桌宠',
     deskObserve: '桌面观察',
     deskObserveDesc: '米塔会定时偷看你的桌面并发表评论（需要启用 AI 并配置支持视觉的模型如 GPT-4o）',
@@ -696,6 +705,7 @@ export const zh = {
     refresh: '刷新',
     exportTxt: '导出 TXT',
     exportJson: '导出 JSON',
+    exportZip: '导出（含日志文件）',
     clear: '清空',
     filterLevel: 'Log Level',
     error: 'ERROR',
@@ -19,3 +19,17 @@ export async function checkLocalProxyHealth(
     clearTimeout(timer)
   }
 }
+
+// The loopback proxy requires a per-session token. The first iframe
+// navigation carries it in the query; the proxy answers with a cookie and a
+// redirect to the clean URL, so later navigations need nothing extra.
+export function withProxyAccess(url, access = globalThis.__SUNSHINE_PROXY_ACCESS__) {
+  if (!access?.query || !access?.token) return url
+  try {
+    const target = new URL(url)
+    target.searchParams.set(access.query, access.token)
+    return target.toString()
+  } catch {
+    return url
+  }
+}
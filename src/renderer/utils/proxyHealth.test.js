@@ -1,7 +1,7 @@
 import assert from 'node:assert/strict'
 import test from 'node:test'
 
-import { checkLocalProxyHealth } from './proxyHealth.js'
+import { checkLocalProxyHealth, withProxyAccess } from './proxyHealth.js'
 
 const check = {
   url: 'http://127.0.0.1:48081/__foundation_proxy_health?token=expected',
@@ -39,3 +39,13 @@ test('stops waiting when the local proxy does not respond', async () => {
 
   assert.equal(result, false)
 })
+
+test('adds the proxy access token only when the webview provides one', () => {
+  const access = { query: '__gui_token', token: 'secret' }
+
+  assert.equal(
+    withProxyAccess('http://127.0.0.1:48081/config?tab=av', access),
+    'http://127.0.0.1:48081/config?tab=av&__gui_token=secret',
+  )
+  assert.equal(withProxyAccess('http://127.0.0.1:48081/', undefined), 'http://127.0.0.1:48081/')
+})